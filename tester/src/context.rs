@@ -381,6 +381,28 @@ impl Context {
         }
     }
 
+    /// Changes the configuration of the test subject app.
+    ///
+    /// This function must be called before [Context::run]. The test subject process is started
+    /// with the default test configuration, and `f` is applied to it before the app is run.
+    pub fn configure_app(&self, f: impl FnOnce(AppBuilder) -> AppBuilder) -> anyhow::Result<()> {
+        match &mut *self.0.lock().unwrap() {
+            ContextInner::Check(ctx) => {
+                if ctx.pid.is_some() {
+                    bail!("cannot configure the app after it has been started");
+                }
+                Ok(())
+            }
+            ContextInner::Run(app) => {
+                let builder = app
+                    .take()
+                    .context("cannot configure the app after it has been started")?;
+                *app = Some(f(builder));
+                Ok(())
+            }
+        }
+    }
+
     /// Runs a test subject program in a new process.
     ///
     /// The `init` function will be called to initialize the app.
@@ -4,7 +4,7 @@ use {
         widgets::{Button, Window},
         Widget, WidgetBaseOf, WidgetInitializer,
     },
-    widgem_tester::Context,
+    widgem_tester::{Context, Key},
 };
 
 pub struct RootWidget {
@@ -37,3 +37,26 @@ pub fn main(ctx: &mut Context) -> anyhow::Result<()> {
     window.close()?;
     Ok(())
 }
+
+#[widgem_tester::test]
+pub fn checked(ctx: &mut Context) -> anyhow::Result<()> {
+    ctx.run(|root| {
+        let mut contents = root
+            .set_main_content(Window::init(module_path!().into()))?
+            .contents_mut();
+        contents
+            .set_next_item(Button::init("Unchecked".into()))?
+            .set_checkable(true);
+        contents
+            .set_next_item(Button::init("Checked".into()))?
+            .set_checkable(true)
+            .set_checked(true);
+        Ok(())
+    })?;
+    let window = ctx.wait_for_window_by_pid()?;
+    window.snapshot("checkable buttons")?;
+    ctx.input_key(Key::Space)?;
+    window.snapshot("toggled focused button")?;
+    window.close()?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use {
+    anyhow::Context as _,
+    widgem::widgets::{Button, Window},
+    widgem_tester::{Context, Key},
+};
+
+#[widgem_tester::test]
+pub fn main(ctx: &mut Context) -> anyhow::Result<()> {
+    ctx.configure_app(|app| app.with_inspector(true))?;
+    ctx.run(|root| {
+        root.set_main_content(Window::init(module_path!().into()))?
+            .set_main_content(Button::init("Test".into()))?;
+        Ok(())
+    })?;
+
+    let main_window = ctx.wait_for_window_by_pid()?;
+    main_window.snapshot("main window")?;
+    let modifier = if cfg!(target_os = "macos") {
+        Key::Meta
+    } else {
+        Key::Control
+    };
+    ctx.input_key_combination(&[modifier, Key::Shift, Key::Unicode('i')])?;
+    let inspector_window = ctx
+        .wait_for_windows_by_pid(2)?
+        .into_iter()
+        .find(|w| w.id().ok() != main_window.id().ok())
+        .context("no inspector window")?;
+    inspector_window.snapshot("inspector")?;
+    inspector_window.close()?;
+    main_window.close()?;
+    Ok(())
+}
@@ -1,4 +1,5 @@
 mod button_tests;
+mod inspector_tests;
 mod label_tests;
 mod menu_tests;
 mod scroll_area_tests;
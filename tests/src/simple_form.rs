@@ -1,6 +1,6 @@
 use {
     widgem::{
-        layout::Layout,
+        layout::{Layout, LayoutDirection},
         types::Axis,
         widgets::{Button, Label, RootWidget, ScrollBar, TextArea, TextInput},
         WidgetExt, Window,
@@ -76,6 +76,20 @@ fn main(ctx: &mut Context) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[widgem_tester::test]
+fn right_to_left(ctx: &mut Context) -> anyhow::Result<()> {
+    ctx.run(|root| {
+        root.set_layout_direction(Some(LayoutDirection::RightToLeft));
+        init_form(root)
+    })?;
+
+    let window = ctx.wait_for_window_by_pid()?;
+    ctx.set_blinking_expected(true);
+    window.snapshot("mirrored form")?;
+    window.close()?;
+    Ok(())
+}
+
 #[cfg(target_os = "windows")]
 mod windows {
     use {
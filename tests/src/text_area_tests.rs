@@ -1,6 +1,7 @@
 use {
+    itertools::Itertools,
     widgem::widgets::{TextArea, Window},
-    widgem_tester::Context,
+    widgem_tester::{Context, Key},
 };
 
 #[widgem_tester::test]
@@ -17,3 +18,77 @@ pub fn main(ctx: &mut Context) -> anyhow::Result<()> {
     window.close()?;
     Ok(())
 }
+
+#[widgem_tester::test]
+pub fn line_numbers(ctx: &mut Context) -> anyhow::Result<()> {
+    ctx.run(|root| {
+        let window = root.set_main_content(Window::init(module_path!().into()))?;
+        window
+            .set_main_content(TextArea::init())?
+            .set_line_numbers_visible(true)
+            .set_current_line_highlighted(true)
+            .set_text((1..=9).map(|i| format!("line {i}")).join("\n"));
+        Ok(())
+    })?;
+    ctx.set_blinking_expected(true);
+    let window = ctx.wait_for_window_by_pid()?;
+    window.snapshot("line numbers")?;
+    ctx.input_key(Key::DownArrow)?;
+    window.snapshot("second line is current")?;
+    ctx.input_key(Key::Return)?;
+    window.snapshot("added tenth line")?;
+    window.close()?;
+    Ok(())
+}
+
+#[widgem_tester::test]
+pub fn find_bar(ctx: &mut Context) -> anyhow::Result<()> {
+    ctx.run(|root| {
+        let window = root.set_main_content(Window::init(module_path!().into()))?;
+        window
+            .set_main_content(TextArea::init())?
+            .set_find_bar_enabled(true)
+            .set_text("one two one\nthree one");
+        Ok(())
+    })?;
+    ctx.set_blinking_expected(true);
+    let window = ctx.wait_for_window_by_pid()?;
+    window.snapshot("text area")?;
+    let modifier = if cfg!(target_os = "macos") {
+        Key::Meta
+    } else {
+        Key::Control
+    };
+    ctx.input_key_combination(&[modifier, Key::Unicode('f')])?;
+    window.snapshot("find bar shown")?;
+    ctx.input_text("one")?;
+    window.snapshot("matches of one")?;
+    ctx.input_key(Key::F3)?;
+    window.snapshot("second match selected")?;
+    ctx.input_key(Key::Escape)?;
+    window.snapshot("find bar closed")?;
+    window.close()?;
+    Ok(())
+}
+
+#[widgem_tester::test]
+pub fn read_only_append(ctx: &mut Context) -> anyhow::Result<()> {
+    ctx.run(|root| {
+        let window = root.set_main_content(Window::init(module_path!().into()))?;
+        let text_area = window.set_main_content(TextArea::init())?;
+        text_area.set_read_only(true).set_text("line 1");
+        text_area.append("\nline 2").append("\nline 3");
+        anyhow::ensure!(text_area.text() == "line 1\nline 2\nline 3");
+        Ok(())
+    })?;
+    ctx.set_blinking_expected(true);
+    let window = ctx.wait_for_window_by_pid()?;
+    window.snapshot("appended lines")?;
+    // Typing doesn't modify a read-only text area.
+    ctx.set_changing_expected(false);
+    ctx.input_text("x")?;
+    ctx.input_key(Key::Return)?;
+    window.snapshot("typing ignored")?;
+    window.close()?;
+    Ok(())
+}
@@ -69,6 +69,11 @@ impl AccessibilityNodes {
         self.root
     }
 
+    /// Returns the parent of the node, or `None` for the root and unknown nodes.
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.direct_parents.get(&id).copied()
+    }
+
     pub fn add_node(&mut self, parent: Option<NodeId>, child: NodeId, key_in_parent: ChildKey) {
         if let Some(parent) = parent {
             if !self.nodes.contains_key(&parent) {
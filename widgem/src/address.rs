@@ -34,6 +34,15 @@ impl WidgetAddress {
             None
         }
     }
+    pub fn parent(&self) -> Option<WidgetAddress> {
+        if self.path.len() > 1 {
+            Some(Self {
+                path: self.path[..self.path.len() - 1].into(),
+            })
+        } else {
+            None
+        }
+    }
     pub fn strip_prefix(&self, parent: RawWidgetId) -> Option<&[(ChildKey, RawWidgetId)]> {
         if let Some(index) = self.path.iter().position(|(_index, id)| *id == parent) {
             Some(&self.path[index + 1..])
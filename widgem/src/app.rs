@@ -7,24 +7,25 @@ use std::{
 };
 
 use anyhow::{anyhow, Context as _};
-use cosmic_text::{FontSystem, SwashCache};
+use cosmic_text::{AttrsOwned, FontSystem, SwashCache};
 use tracing::warn;
-use winit::{event_loop::EventLoopProxy, monitor::MonitorHandle};
+use winit::{event_loop::EventLoopProxy, monitor::MonitorHandle, window::Theme};
 
 use crate::{
     app_builder::AppBuilder,
     callback::{Callback, CallbackId, WidgetCallbackData},
     event::{FocusReason, KeyboardInputEvent},
     event_loop::{with_active_event_loop, UserEvent},
+    layout::LayoutDirection,
     shared_window::{
         ScrollToRectRequest, SetFocusRequest, SharedWindow, WindowId, WindowInfo, WindowRequest,
     },
     shortcut::{Shortcut, ShortcutId},
-    style::Style,
+    style::{defaults::style_for_theme, icons::IconProvider, FontStyle, Style},
     system::{LayoutState, SharedSystemDataInner, SystemConfig},
     timer::{Timer, TimerId},
-    types::Rect,
-    RawWidgetId, Widget, WidgetAddress, WidgetId,
+    types::{LogicalPixels, Rect},
+    Pixmap, RawWidgetId, Widget, WidgetAddress, WidgetId,
 };
 
 #[cfg(all(
@@ -265,11 +266,88 @@ impl App {
         data.config.clone()
     }
 
+    /// Returns the default layout direction of the app.
+    ///
+    /// See [AppBuilder::with_layout_direction].
+    pub fn layout_direction(&self) -> LayoutDirection {
+        self.config().layout_direction
+    }
+
     pub(crate) fn style(&self) -> Style {
         let data = self.data.borrow();
         data.style.clone()
     }
 
+    /// Replaces the style of the app.
+    ///
+    /// All widgets will receive a [StyleChangeEvent](crate::event::StyleChangeEvent), and all windows
+    /// will be laid out and redrawn.
+    ///
+    /// The app will no longer switch styles when the system theme changes
+    /// (see [AppBuilder::with_follow_system_theme](crate::AppBuilder::with_follow_system_theme)).
+    pub fn set_style(&self, style: Style) {
+        self.replace_style(style, false);
+    }
+
+    fn replace_style(&self, mut style: Style, is_builtin: bool) {
+        style.clear_cache();
+        let icon_provider = self.data.borrow().icon_provider.clone();
+        style.set_icon_provider(icon_provider);
+        {
+            let mut data = self.data.borrow_mut();
+            data.style = style;
+            data.is_builtin_style = is_builtin;
+        }
+        for window in self.windows().into_values() {
+            if let Some(address) = self.address(window.root_widget_id) {
+                window.shared_window.request_style_change_event(address);
+            }
+            window.shared_window.request_redraw();
+        }
+    }
+
+    /// Returns the provider used to look up named icons.
+    ///
+    /// See [AppBuilder::with_icon_provider].
+    pub fn icon_provider(&self) -> Rc<dyn IconProvider> {
+        self.data.borrow().icon_provider.clone()
+    }
+
+    /// Replaces the provider used to look up named icons.
+    ///
+    /// The current style is reapplied to all widgets so that the icons are reloaded.
+    pub fn set_icon_provider(&self, provider: Rc<dyn IconProvider>) {
+        self.data.borrow_mut().icon_provider = provider;
+        let is_builtin = self.data.borrow().is_builtin_style;
+        self.replace_style(self.style(), is_builtin);
+    }
+
+    /// Loads a named icon (e.g. `edit-copy`) using the current [icon provider](Self::icon_provider)
+    /// and renders it to fit into a square with the side of `size` at the specified scale.
+    ///
+    /// Symbolic icons are returned as is (black); use [Pixmap::tinted] to colorize them.
+    pub fn load_icon(&self, name: &str, size: LogicalPixels, scale: f32) -> anyhow::Result<Pixmap> {
+        let style = self.style();
+        Ok(style.load_named_icon(name, size, scale)?.0)
+    }
+
+    /// Returns the light/dark preference of the OS, as reported by the windowing system.
+    ///
+    /// Returns `None` if no windows were created yet or the platform doesn't report the theme.
+    pub fn system_theme(&self) -> Option<Theme> {
+        self.data.borrow().system_theme
+    }
+
+    pub(crate) fn system_theme_changed(&self, theme: Theme) {
+        if self.data.borrow().system_theme == Some(theme) {
+            return;
+        }
+        self.data.borrow_mut().system_theme = Some(theme);
+        if self.config().follow_system_theme && self.data.borrow().is_builtin_style {
+            self.replace_style(style_for_theme(theme), true);
+        }
+    }
+
     pub(crate) fn with_font_system<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut FontSystem) -> R,
@@ -278,6 +356,19 @@ impl App {
         f(&mut data.font_system)
     }
 
+    /// Returns text attributes corresponding to `font`.
+    ///
+    /// Font families are resolved once for each list of families.
+    pub(crate) fn font_attrs(&self, font: &FontStyle) -> AttrsOwned {
+        let data = &mut *self.data.borrow_mut();
+        let family = data
+            .resolved_font_families
+            .entry(font.families.clone())
+            .or_insert_with(|| font.resolve_family(&data.font_system))
+            .clone();
+        font.to_attrs_with_family(family)
+    }
+
     pub(crate) fn with_font_system_and_swash_cache<F, R>(&self, f: F) -> R
     where
         F: FnOnce(&mut FontSystem, &mut SwashCache) -> R,
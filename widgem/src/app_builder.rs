@@ -1,16 +1,28 @@
-use std::{path::PathBuf, time::Duration};
+use std::{path::PathBuf, rc::Rc, time::Duration};
 
 use winit::event_loop::EventLoop;
 
 use crate::{
     event_loop::{self, UserEvent},
+    layout::LayoutDirection,
+    style::{icons::IconProvider, Style},
     widgets::RootWidget,
 };
 
+const DEBUG_LAYOUT_ENV_VAR: &str = "WIDGEM_DEBUG_LAYOUT";
+const INSPECTOR_ENV_VAR: &str = "WIDGEM_INSPECTOR";
+
 pub struct AppBuilder {
     pub(crate) system_fonts: bool,
     pub(crate) custom_font_paths: Vec<PathBuf>,
     pub(crate) fixed_scale: Option<f32>,
+    pub(crate) layout_direction: LayoutDirection,
+    pub(crate) debug_layout: bool,
+    pub(crate) inspector: bool,
+    pub(crate) style: Option<Style>,
+    pub(crate) style_hot_reload: bool,
+    pub(crate) icon_provider: Option<Rc<dyn IconProvider>>,
+    pub(crate) follow_system_theme: bool,
     pub(crate) auto_repeat_delay: Option<Duration>,
     pub(crate) auto_repeat_interval: Option<Duration>,
 }
@@ -27,6 +39,13 @@ impl AppBuilder {
             system_fonts: true,
             custom_font_paths: vec![],
             fixed_scale: None,
+            layout_direction: LayoutDirection::default(),
+            debug_layout: std::env::var_os(DEBUG_LAYOUT_ENV_VAR).is_some_and(|value| value != "0"),
+            inspector: std::env::var_os(INSPECTOR_ENV_VAR).is_some_and(|value| value != "0"),
+            style: None,
+            style_hot_reload: false,
+            icon_provider: None,
+            follow_system_theme: false,
             auto_repeat_delay: None,
             auto_repeat_interval: None,
         }
@@ -37,6 +56,9 @@ impl AppBuilder {
         self
     }
 
+    /// Load an additional font file.
+    ///
+    /// The font can be used in styles by its family name, e.g. `font-family: "My Font", sans-serif;`.
     pub fn with_font(mut self, path: PathBuf) -> AppBuilder {
         self.custom_font_paths.push(path);
         self
@@ -47,6 +69,90 @@ impl AppBuilder {
         self
     }
 
+    /// Set the default horizontal direction of the layout.
+    ///
+    /// Use [LayoutDirection::RightToLeft] for right-to-left languages. The direction can be
+    /// overridden for individual widgets with
+    /// [WidgetExt::set_layout_direction](crate::WidgetExt::set_layout_direction).
+    /// The current value is available via [App::layout_direction](crate::App::layout_direction).
+    pub fn with_layout_direction(mut self, direction: LayoutDirection) -> AppBuilder {
+        self.layout_direction = direction;
+        self
+    }
+
+    /// Enable or disable the layout debug overlay.
+    ///
+    /// When enabled, outlines of all widgets are drawn on top of the UI, along with their
+    /// padding (green) and spacing between rows and columns (orange).
+    ///
+    /// The overlay can also be enabled by setting `WIDGEM_DEBUG_LAYOUT=1` environment variable.
+    /// See also [dump_layout](crate::layout::dump_layout).
+    pub fn with_layout_debug(mut self, enable: bool) -> AppBuilder {
+        self.debug_layout = enable;
+        self
+    }
+
+    /// Enable or disable the widget inspector.
+    ///
+    /// When enabled, pressing `Ctrl+Shift+I` (`Cmd+Shift+I` on macOS) opens a separate window
+    /// that shows the widget tree of the window that was focused at the time. The inspector
+    /// highlights the hovered and the selected widget and displays the selected widget's classes,
+    /// pseudo-classes, computed style, geometry and accessibility node. Inline style of the
+    /// selected widget can be edited live. Pressing the shortcut again closes the inspector.
+    ///
+    /// The inspector can also be enabled by setting `WIDGEM_INSPECTOR=1` environment variable.
+    pub fn with_inspector(mut self, enable: bool) -> AppBuilder {
+        self.inspector = enable;
+        self
+    }
+
+    /// Use `style` instead of the default style.
+    ///
+    /// The style can be changed later with [App::set_style](crate::App::set_style).
+    pub fn with_style(mut self, style: Style) -> AppBuilder {
+        self.style = Some(style);
+        self
+    }
+
+    /// Enable or disable automatic reloading of the style file.
+    ///
+    /// When enabled and the current style was loaded with [Style::load_from_file],
+    /// the file is checked for changes periodically. If the file is modified, the style is
+    /// reloaded and applied to all widgets. If the new version of the file fails to load,
+    /// a warning is logged and the previous style stays in use.
+    pub fn with_style_hot_reload(mut self, enable: bool) -> AppBuilder {
+        self.style_hot_reload = enable;
+        self
+    }
+
+    /// Enable or disable following the light/dark preference of the OS.
+    ///
+    /// When enabled, the app switches between the bundled
+    /// [default_style](crate::style::defaults::default_style) and
+    /// [dark_style](crate::style::defaults::dark_style) automatically, based on the theme
+    /// reported by the windowing system when a window is created and whenever it changes.
+    ///
+    /// The system theme is only followed while one of the bundled themes is in use. It has no effect
+    /// if the style is set with [with_style](Self::with_style) or
+    /// [App::set_style](crate::App::set_style).
+    ///
+    /// The current system theme is available via [App::system_theme](crate::App::system_theme).
+    pub fn with_follow_system_theme(mut self, enable: bool) -> AppBuilder {
+        self.follow_system_theme = enable;
+        self
+    }
+
+    /// Use `provider` to look up named icons instead of the
+    /// [default provider](crate::style::icons::default_icon_provider).
+    ///
+    /// The provider is used for `icon: theme("name")` in styles and for
+    /// [App::load_icon](crate::App::load_icon). It can be changed later with
+    /// [App::set_icon_provider](crate::App::set_icon_provider).
+    pub fn with_icon_provider(mut self, provider: Rc<dyn IconProvider>) -> AppBuilder {
+        self.icon_provider = Some(provider);
+        self
+    }
+
     pub fn with_auto_repeat_delay(mut self, delay: Duration) -> AppBuilder {
         self.auto_repeat_delay = Some(delay);
         self
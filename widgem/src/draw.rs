@@ -1,15 +1,19 @@
 use {
     crate::{
         style::{
-            common::{ComputedBackground, ComputedBorderStyle},
+            common::{
+                ComputedBackground, ComputedBorderRadius, ComputedBorderStyle, ComputedBoxShadow,
+                ComputedOutline,
+            },
             RelativeOffset,
         },
-        types::{Point, PpxSuffix, Rect},
+        types::{PhysicalPixels, Point, PpxSuffix, Rect},
     },
     std::{cell::RefCell, rc::Rc},
     tiny_skia::{
-        BlendMode, Color, FillRule, FilterQuality, LinearGradient, Mask, Paint, Path, PathBuilder,
-        Pattern, Pixmap, PixmapPaint, PixmapRef, Shader, SpreadMode, Stroke, Transform,
+        BlendMode, Color, ColorU8, FillRule, FilterQuality, LinearGradient, Mask, Paint, Path,
+        PathBuilder, Pattern, Pixmap, PixmapPaint, PixmapRef, Shader, SpreadMode, Stroke,
+        Transform,
     },
     tracing::warn,
 };
@@ -29,6 +33,97 @@ fn rounded_line_in_square_corner(
     );
 }
 
+fn radii(radius: &ComputedBorderRadius) -> [f32; 4] {
+    [
+        radius.top_left,
+        radius.top_right,
+        radius.bottom_right,
+        radius.bottom_left,
+    ]
+    .map(|radius| radius.to_i32() as f32)
+}
+
+fn background_shader(rect: &Rect, background: &ComputedBackground) -> Shader<'static> {
+    match background {
+        ComputedBackground::Solid { color } => Shader::SolidColor(*color),
+        ComputedBackground::LinearGradient(gradient) => LinearGradient::new(
+            relative_pos(rect, gradient.start),
+            relative_pos(rect, gradient.end),
+            gradient.stops.clone(),
+            gradient.mode,
+            Transform::default(),
+        )
+        .unwrap_or_else(|| {
+            warn!("failed to create gradient");
+            Shader::SolidColor(Color::TRANSPARENT)
+        }),
+    }
+}
+
+/// Applies a horizontal and a vertical box blur to a pixmap with premultiplied colors.
+fn box_blur(pixmap: &mut Pixmap, radius: usize) {
+    let width = pixmap.width() as usize;
+    let height = pixmap.height() as usize;
+    let data = pixmap.data_mut();
+    let mut line = Vec::new();
+    let window = (2 * radius + 1) as u32;
+    for (len, step, lines, line_step) in
+        [(width, 4, height, width * 4), (height, width * 4, width, 4)]
+    {
+        for line_index in 0..lines {
+            let start = line_index * line_step;
+            line.clear();
+            line.extend((0..len).map(|i| {
+                let offset = start + i * step;
+                [
+                    data[offset],
+                    data[offset + 1],
+                    data[offset + 2],
+                    data[offset + 3],
+                ]
+            }));
+            let mut sum = [0u32; 4];
+            for pixel in line.iter().take(radius) {
+                for channel in 0..4 {
+                    sum[channel] += u32::from(pixel[channel]);
+                }
+            }
+            for i in 0..len {
+                if let Some(pixel) = line.get(i + radius) {
+                    for channel in 0..4 {
+                        sum[channel] += u32::from(pixel[channel]);
+                    }
+                }
+                if i > radius {
+                    let pixel = line[i - radius - 1];
+                    for channel in 0..4 {
+                        sum[channel] -= u32::from(pixel[channel]);
+                    }
+                }
+                let offset = start + i * step;
+                for channel in 0..4 {
+                    data[offset + channel] = (sum[channel] / window) as u8;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct BoxShadowKey {
+    size: (i32, i32),
+    radius: [i32; 4],
+    blur: i32,
+    color: ColorU8,
+}
+
+// A rendered box shadow kept by `DrawEvent::draw_box_shadow` between draws.
+#[derive(Debug, Clone)]
+pub(crate) struct CachedBoxShadow {
+    key: BoxShadowKey,
+    pixmap: Pixmap,
+}
+
 #[derive(Debug, Clone)]
 pub struct DrawEvent {
     top_left: Point,
@@ -36,6 +131,8 @@ pub struct DrawEvent {
     mask: Rc<Mask>,
     transform: Transform,
     mask_rect: Rect,
+    // Area available for drawing box shadows and outlines.
+    overflow_mask_rect: Rect,
 }
 
 fn relative_pos(rect: &Rect, offset: RelativeOffset) -> tiny_skia::Point {
@@ -46,15 +143,26 @@ fn relative_pos(rect: &Rect, offset: RelativeOffset) -> tiny_skia::Point {
 
 impl DrawEvent {
     pub fn new(pixmap: Rc<RefCell<Pixmap>>, top_left: Point, mask_rect: Rect) -> Self {
+        Self::with_overflow_mask_rect(pixmap, top_left, mask_rect, mask_rect)
+    }
+
+    fn with_overflow_mask_rect(
+        pixmap: Rc<RefCell<Pixmap>>,
+        top_left: Point,
+        mask_rect: Rect,
+        overflow_mask_rect: Rect,
+    ) -> Self {
         let mut mask = Mask::new(pixmap.borrow().width(), pixmap.borrow().height()).unwrap();
-        mask.fill_path(
-            &PathBuilder::from_rect(
-                tiny_skia::Rect::try_from(mask_rect).expect("invalid rect"), // TODO: handle error?
-            ),
-            FillRule::default(),
-            false,
-            Transform::default(),
-        );
+        if !mask_rect.is_empty() {
+            mask.fill_path(
+                &PathBuilder::from_rect(
+                    tiny_skia::Rect::try_from(mask_rect).expect("invalid rect"), // TODO: handle error?
+                ),
+                FillRule::default(),
+                false,
+                Transform::default(),
+            );
+        }
 
         Self {
             top_left,
@@ -65,6 +173,7 @@ impl DrawEvent {
                 top_left.y().to_i32() as f32,
             ),
             mask_rect,
+            overflow_mask_rect,
         }
     }
 
@@ -113,14 +222,19 @@ impl DrawEvent {
         );
     }
 
-    fn rounded_rect_path(&self, rect: Rect, mut radius: f32, width: f32) -> Path {
-        if radius > (rect.size_x().to_i32() as f32 / 2.0)
-            || radius > (rect.size_y().to_i32() as f32 / 2.0)
-        {
-            //TODO do something here, log some error
-            warn!("radius is bigger than fits in rectangle");
-            radius = 0.0;
-        }
+    fn rounded_rect_path(&self, rect: Rect, radius: [f32; 4], width: f32) -> Path {
+        let [top_left_radius, top_right_radius, bottom_right_radius, bottom_left_radius] = radius
+            .map(|radius| {
+                if radius > (rect.size_x().to_i32() as f32 / 2.0)
+                    || radius > (rect.size_y().to_i32() as f32 / 2.0)
+                {
+                    //TODO do something here, log some error
+                    warn!("radius is bigger than fits in rectangle");
+                    0.0
+                } else {
+                    radius
+                }
+            });
         let top_left_point = rect.top_left();
         let top_left = tiny_skia::Point {
             x: top_left_point.x().to_i32() as f32 + width / 2.0,
@@ -131,37 +245,40 @@ impl DrawEvent {
             y: rect.size_y().to_i32() as f32 - width,
         };
         let mut path_builder = PathBuilder::new();
-        path_builder.move_to(top_left.x + radius, top_left.y);
-        path_builder.line_to(top_left.x + size.x - radius, top_left.y);
+        path_builder.move_to(top_left.x + top_left_radius, top_left.y);
+        path_builder.line_to(top_left.x + size.x - top_right_radius, top_left.y);
         rounded_line_in_square_corner(
             &mut path_builder,
             top_left.x + size.x,
             top_left.y,
             top_left.x + size.x,
-            top_left.y + radius,
+            top_left.y + top_right_radius,
+        );
+        path_builder.line_to(
+            top_left.x + size.x,
+            top_left.y + size.y - bottom_right_radius,
         );
-        path_builder.line_to(top_left.x + size.x, top_left.y + size.y - radius);
         rounded_line_in_square_corner(
             &mut path_builder,
             top_left.x + size.x,
             top_left.y + size.y,
-            top_left.x + size.x - radius,
+            top_left.x + size.x - bottom_right_radius,
             top_left.y + size.y,
         );
-        path_builder.line_to(top_left.x + radius, top_left.y + size.y);
+        path_builder.line_to(top_left.x + bottom_left_radius, top_left.y + size.y);
         rounded_line_in_square_corner(
             &mut path_builder,
             top_left.x,
             top_left.y + size.y,
             top_left.x,
-            top_left.y + size.y - radius,
+            top_left.y + size.y - bottom_left_radius,
         );
-        path_builder.line_to(top_left.x, top_left.y + radius);
+        path_builder.line_to(top_left.x, top_left.y + top_left_radius);
         rounded_line_in_square_corner(
             &mut path_builder,
             top_left.x,
             top_left.y,
-            top_left.x + radius,
+            top_left.x + top_left_radius,
             top_left.y,
         );
         path_builder.finish().unwrap()
@@ -204,35 +321,255 @@ impl DrawEvent {
         border: &ComputedBorderStyle,
         background: Option<&ComputedBackground>,
     ) {
-        let path = self.rounded_rect_path(
-            rect,
-            border.radius.to_i32() as f32,
-            border.width.to_i32() as f32,
-        );
-        if let Some(background) = background {
-            let shader = match background {
-                ComputedBackground::Solid { color } => Shader::SolidColor(*color),
-                ComputedBackground::LinearGradient(gradient) => LinearGradient::new(
-                    relative_pos(&rect, gradient.start),
-                    relative_pos(&rect, gradient.end),
-                    gradient.stops.clone(),
-                    gradient.mode,
-                    Transform::default(),
-                )
-                .unwrap_or_else(|| {
-                    warn!("failed to create gradient");
-                    Shader::SolidColor(Color::TRANSPARENT)
-                }),
+        let radius = radii(&border.radius);
+        if let Some(side) = border.uniform_side() {
+            let path = self.rounded_rect_path(rect, radius, side.width.to_i32() as f32);
+            if let Some(background) = background {
+                self.fill_path(&path, background_shader(&rect, background));
+            }
+            if side.width > 0.ppx() {
+                self.stroke_path(&path, side.color, side.width.to_i32() as f32);
+            }
+        } else {
+            let path = self.rounded_rect_path(rect, radius, 0.0);
+            if let Some(background) = background {
+                self.fill_path(&path, background_shader(&rect, background));
+            }
+            self.fill_border_sides(rect, border, &path);
+        }
+    }
+
+    /// Draws each side of a border with different widths or colors as a separate trapezoid,
+    /// clipped by the outer rounded shape.
+    fn fill_border_sides(&self, rect: Rect, border: &ComputedBorderStyle, outer_path: &Path) {
+        let mut mask = (*self.mask).clone();
+        mask.intersect_path(outer_path, FillRule::Winding, true, self.transform);
+
+        let x0 = rect.left().to_i32() as f32;
+        let y0 = rect.top().to_i32() as f32;
+        let x1 = rect.right().to_i32() as f32;
+        let y1 = rect.bottom().to_i32() as f32;
+        let top = border.top.width.to_i32() as f32;
+        let right = border.right.width.to_i32() as f32;
+        let bottom = border.bottom.width.to_i32() as f32;
+        let left = border.left.width.to_i32() as f32;
+        let sides = [
+            (
+                &border.top,
+                [
+                    (x0, y0),
+                    (x1, y0),
+                    (x1 - right, y0 + top),
+                    (x0 + left, y0 + top),
+                ],
+            ),
+            (
+                &border.right,
+                [
+                    (x1, y0),
+                    (x1, y1),
+                    (x1 - right, y1 - bottom),
+                    (x1 - right, y0 + top),
+                ],
+            ),
+            (
+                &border.bottom,
+                [
+                    (x1, y1),
+                    (x0, y1),
+                    (x0 + left, y1 - bottom),
+                    (x1 - right, y1 - bottom),
+                ],
+            ),
+            (
+                &border.left,
+                [
+                    (x0, y1),
+                    (x0, y0),
+                    (x0 + left, y0 + top),
+                    (x0 + left, y1 - bottom),
+                ],
+            ),
+        ];
+        for (side, points) in sides {
+            if side.width <= 0.ppx() || side.color.alpha() == 0.0 {
+                continue;
+            }
+            let mut path_builder = PathBuilder::new();
+            path_builder.move_to(points[0].0, points[0].1);
+            for (x, y) in &points[1..] {
+                path_builder.line_to(*x, *y);
+            }
+            path_builder.close();
+            let Some(path) = path_builder.finish() else {
+                continue;
             };
-            self.fill_path(&path, shader);
+            self.pixmap.borrow_mut().fill_path(
+                &path,
+                &Paint {
+                    shader: Shader::SolidColor(side.color),
+                    ..Paint::default()
+                },
+                FillRule::default(),
+                self.transform,
+                Some(&mask),
+            );
         }
-        if border.width > 0.ppx() {
-            self.stroke_path(&path, border.color, border.width.to_i32() as f32);
+    }
+
+    /// Creates a mask that allows drawing outside of the widget's rect, within the bounds
+    /// allowed by the parent.
+    fn overflow_mask(&self) -> Option<Mask> {
+        let mut mask = Mask::new(self.mask.width(), self.mask.height())?;
+        let rect = tiny_skia::Rect::try_from(self.overflow_mask_rect).ok()?;
+        mask.fill_path(
+            &PathBuilder::from_rect(rect),
+            FillRule::default(),
+            false,
+            Transform::default(),
+        );
+        Some(mask)
+    }
+
+    /// Draws a box shadow of a widget occupying `rect` with the specified border radius.
+    ///
+    /// The shadow is not drawn inside `rect`.
+    ///
+    /// The blurred shadow is stored in `cache` and reused by subsequent calls
+    /// until the size, the radius, the blur or the color of the shadow changes.
+    pub(crate) fn draw_box_shadow(
+        &self,
+        rect: Rect,
+        radius: &ComputedBorderRadius,
+        shadow: &ComputedBoxShadow,
+        cache: &mut Option<CachedBoxShadow>,
+    ) {
+        let Some(mut mask) = self.overflow_mask() else {
+            return;
+        };
+        if let Some(mut box_mask) = Mask::new(mask.width(), mask.height()) {
+            box_mask.fill_path(
+                &self.rounded_rect_path(rect, radii(radius), 0.0),
+                FillRule::default(),
+                true,
+                self.transform,
+            );
+            for (value, box_value) in mask.data_mut().iter_mut().zip(box_mask.data()) {
+                *value = (u16::from(*value) * u16::from(255 - box_value) / 255) as u8;
+            }
+        }
+
+        let shadow_rect = rect.translate(shadow.offset).expand(shadow.spread);
+        if shadow_rect.is_empty() {
+            return;
+        }
+        let shadow_radius = radii(&radius.expand(shadow.spread));
+        let blur = shadow.blur.to_i32().max(0);
+        let key = BoxShadowKey {
+            size: (shadow_rect.size_x().to_i32(), shadow_rect.size_y().to_i32()),
+            radius: shadow_radius.map(|radius| radius as i32),
+            blur,
+            color: shadow.color.to_color_u8(),
+        };
+        let cached = match cache {
+            Some(cached) if cached.key == key => cached,
+            _ => {
+                let Some(pixmap) =
+                    self.box_shadow_pixmap(shadow_rect, shadow_radius, blur, shadow.color)
+                else {
+                    return;
+                };
+                cache.insert(CachedBoxShadow { key, pixmap })
+            }
+        };
+        self.pixmap.borrow_mut().draw_pixmap(
+            shadow_rect.left().to_i32() - blur,
+            shadow_rect.top().to_i32() - blur,
+            cached.pixmap.as_ref(),
+            &PixmapPaint::default(),
+            self.transform,
+            Some(&mask),
+        );
+    }
+
+    // Renders a blurred box shadow with `blur` pixels of transparent margin on each side.
+    fn box_shadow_pixmap(
+        &self,
+        shadow_rect: Rect,
+        shadow_radius: [f32; 4],
+        blur: i32,
+        color: Color,
+    ) -> Option<Pixmap> {
+        let Some(mut pixmap) = Pixmap::new(
+            (shadow_rect.size_x().to_i32() + 2 * blur) as u32,
+            (shadow_rect.size_y().to_i32() + 2 * blur) as u32,
+        ) else {
+            warn!("failed to create pixmap for box shadow");
+            return None;
+        };
+        let shape_rect =
+            Rect::from_pos_size(Point::new(blur.ppx(), blur.ppx()), shadow_rect.size());
+        pixmap.fill_path(
+            &self.rounded_rect_path(shape_rect, shadow_radius, 0.0),
+            &Paint {
+                shader: Shader::SolidColor(color),
+                ..Paint::default()
+            },
+            FillRule::default(),
+            Transform::default(),
+            None,
+        );
+        if blur > 0 {
+            // Blur radius in CSS is twice the standard deviation of the gaussian blur.
+            // Three box blurs approximate the gaussian blur.
+            let box_radius = ((blur as f32 / 2.0).round() as usize).max(1);
+            for _ in 0..3 {
+                box_blur(&mut pixmap, box_radius);
+            }
+        }
+        Some(pixmap)
+    }
+
+    /// Draws an outline around a widget occupying `rect` with the specified border radius.
+    pub fn draw_outline(
+        &self,
+        rect: Rect,
+        radius: &ComputedBorderRadius,
+        outline: &ComputedOutline,
+    ) {
+        if outline.width <= 0.ppx() {
+            return;
         }
+        let Some(mask) = self.overflow_mask() else {
+            return;
+        };
+        let expand = outline.offset + outline.width;
+        let outline_rect = rect.expand(expand);
+        if outline_rect.is_empty() {
+            return;
+        }
+        let path = self.rounded_rect_path(
+            outline_rect,
+            radii(&radius.expand(expand)),
+            outline.width.to_i32() as f32,
+        );
+        self.pixmap.borrow_mut().stroke_path(
+            &path,
+            &Paint {
+                shader: Shader::SolidColor(outline.color),
+                ..Paint::default()
+            },
+            &Stroke {
+                width: outline.width.to_i32() as f32,
+                ..Stroke::default()
+            },
+            self.transform,
+            Some(&mask),
+        );
     }
 
     pub fn fill_rounded_rect(&self, rect: Rect, radius: f32, width: f32, shader: Shader) {
-        let path = self.rounded_rect_path(rect, radius, width);
+        let path = self.rounded_rect_path(rect, [radius; 4], width);
         self.fill_path(&path, shader);
     }
 
@@ -241,7 +578,7 @@ impl DrawEvent {
         if width == 0.0 {
             return;
         }
-        let path = self.rounded_rect_path(rect, radius, width);
+        let path = self.rounded_rect_path(rect, [radius; 4], width);
         self.stroke_path(&path, color, width);
     }
 
@@ -273,16 +610,32 @@ impl DrawEvent {
     }
 
     pub fn map_to_child(&self, rect_in_parent: Rect) -> Option<Self> {
+        self.map_to_child_with_overflow(rect_in_parent, 0.ppx())
+    }
+
+    /// Creates a draw event for a child that may draw up to `overflow` pixels outside of
+    /// its rect (e.g. box shadows and outlines).
+    pub fn map_to_child_with_overflow(
+        &self,
+        rect_in_parent: Rect,
+        overflow: PhysicalPixels,
+    ) -> Option<Self> {
         let rect = rect_in_parent.translate(self.top_left);
         let mask_rect = self.mask_rect.intersect(rect);
-        if mask_rect.is_empty() {
+        let overflow_mask_rect = if overflow > 0.ppx() {
+            self.mask_rect.intersect(rect.expand(overflow))
+        } else {
+            mask_rect
+        };
+        if overflow_mask_rect.is_empty() {
             return None;
         }
 
-        Some(Self::new(
+        Some(Self::with_overflow_mask_rect(
             Rc::clone(&self.pixmap),
             rect.top_left(),
             mask_rect,
+            overflow_mask_rect,
         ))
     }
 }
@@ -7,7 +7,7 @@ use {
         types::{Point, Rect},
         WidgetBase, WidgetGeometry,
     },
-    accesskit::{Action, ActionData},
+    accesskit::{Action, ActionData, NodeId},
     derive_more::From,
     winit::{
         dpi::PhysicalPosition,
@@ -283,11 +283,19 @@ impl WindowFocusChangeEvent {
 
 #[derive(Debug, Clone)]
 pub struct AccessibilityActionEvent {
+    pub(crate) target: NodeId,
     pub(crate) action: Action,
     pub(crate) data: Option<ActionData>,
 }
 
 impl AccessibilityActionEvent {
+    /// Returns the accessibility node the action was requested for.
+    ///
+    /// It can be the widget's own node or one of the nodes it added as its children.
+    pub fn target(&self) -> NodeId {
+        self.target
+    }
+
     pub fn action(&self) -> Action {
         self.action
     }
@@ -2,8 +2,9 @@ use {
     crate::{
         app_builder::AppBuilder,
         callback::{CallbackId, InvokeCallbackEvent},
+        inspector,
         shared_window::{WindowId, WindowRequest},
-        style::defaults::default_style,
+        style::{defaults::default_style, icons::default_icon_provider},
         system::{OrWarn, SharedSystemDataInner, SystemConfig},
         timer::Timers,
         widgets::{get_widget_by_address_mut, get_widget_by_id_mut, RootWidget},
@@ -175,6 +176,14 @@ impl ApplicationHandler<UserEvent> for Handler {
             let font_system =
                 FontSystem::new_with_locale_and_db(FontSystem::new().locale().to_string(), db);
 
+            let icon_provider = self
+                .app_builder
+                .icon_provider
+                .take()
+                .unwrap_or_else(default_icon_provider);
+            let is_builtin_style = self.app_builder.style.is_none();
+            let mut style = self.app_builder.style.take().unwrap_or_else(default_style);
+            style.set_icon_provider(icon_provider.clone());
             let shared_system_data = SharedSystemDataInner {
                 config: Rc::new(SystemConfig {
                     exit_after_last_window_closes: true,
@@ -188,13 +197,22 @@ impl ApplicationHandler<UserEvent> for Handler {
                         .auto_repeat_interval
                         .unwrap_or(DEFAULT_AUTO_REPEAT_INTERVAL),
                     fixed_scale: self.app_builder.fixed_scale,
+                    layout_direction: self.app_builder.layout_direction,
+                    debug_layout: self.app_builder.debug_layout,
+                    inspector: self.app_builder.inspector,
+                    style_hot_reload: self.app_builder.style_hot_reload,
+                    follow_system_theme: self.app_builder.follow_system_theme,
                 }),
                 address_book: HashMap::new(),
                 font_system,
+                resolved_font_families: HashMap::new(),
                 swash_cache: SwashCache::new(),
                 event_loop_proxy: self.event_loop_proxy.take().expect("only happens once"),
                 // TODO: how to detect monitor scale change?
-                style: default_style(),
+                style,
+                is_builtin_style,
+                icon_provider,
+                system_theme: None,
                 timers: Timers::new(),
                 clipboard: Clipboard::new().expect("failed to initialize clipboard"),
                 had_any_windows: false,
@@ -207,6 +225,12 @@ impl ApplicationHandler<UserEvent> for Handler {
             };
             let app = App::init(shared_system_data);
             let mut root_widget = RootWidget::new(WidgetBase::new_root(app));
+            if root_widget.base().app().config().inspector {
+                inspector::install(&mut root_widget);
+            }
+            if root_widget.base().app().config().style_hot_reload {
+                root_widget.watch_style_file();
+            }
             self.init.take().expect("double init")(&mut root_widget).or_warn();
             self.root_widget = Some(Box::new(root_widget));
 
@@ -0,0 +1,439 @@
+//! Live widget inspector.
+//!
+//! The inspector is enabled with [AppBuilder::with_inspector](crate::AppBuilder::with_inspector).
+//! It's displayed in a separate window owned by the [RootWidget]. All of its actions are
+//! callbacks of the root widget because the inspector needs access to the widgets of other windows.
+
+use {
+    crate::{
+        callback::Callback,
+        impl_widget_base,
+        layout::Layout,
+        shortcut::{KeyCombinations, Shortcut, ShortcutScope},
+        style::css::PseudoClass,
+        system::OrWarn,
+        timer::TimerId,
+        widget_initializer::{self, WidgetInitializer},
+        widgets::{get_widget_by_id_mut, Button, Column, Label, RootWidget, ScrollArea, TextArea},
+        App, ChildKey, RawWidgetId, Widget, WidgetBaseOf, WidgetExt, WidgetId, Window,
+    },
+    anyhow::Result,
+    itertools::Itertools,
+    std::{borrow::Cow, time::Duration},
+    tracing::warn,
+};
+
+const INSPECTOR_KEY: &str = "__inspector";
+const SHORTCUT: &str = "CtrlOrMacCmd+Shift+I";
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+pub(crate) struct InspectorState {
+    // Root widget of the inspected window.
+    target_window_root: RawWidgetId,
+    timer: TimerId,
+    // IDs of the widgets displayed in the tree, in display order.
+    rows: Vec<RawWidgetId>,
+    row_texts: Vec<String>,
+    selected: Option<RawWidgetId>,
+    highlighted: Vec<RawWidgetId>,
+    details: String,
+}
+
+/// Registers the application shortcut that toggles the inspector window.
+pub(crate) fn install(root: &mut RootWidget) {
+    let key_combinations =
+        KeyCombinations::from_str_portable(SHORTCUT).expect("invalid inspector shortcut");
+    let callback = root_callback(root, |root, ()| toggle(root));
+    root.base_mut().add_shortcut(Shortcut::new(
+        key_combinations,
+        ShortcutScope::Application,
+        callback,
+    ));
+}
+
+fn root_callback<E, F>(root: &RootWidget, func: F) -> Callback<E>
+where
+    F: Fn(&mut RootWidget, E) -> Result<()> + 'static,
+    E: 'static,
+{
+    root.base()
+        .app()
+        .create_widget_callback(WidgetId::new(root.base().id()), func)
+}
+
+fn toggle(root: &mut RootWidget) -> Result<()> {
+    if root.inspector_state().is_some() || root.base().has_child(INSPECTOR_KEY) {
+        close(root);
+        return Ok(());
+    }
+    let Some(target_window_root) = target_window_root(root.base().app()) else {
+        warn!("inspector: there is no window to inspect");
+        return Ok(());
+    };
+
+    let apply_style_callback = root_callback(root, |root, ()| apply_style(root));
+    let refresh_callback = root_callback(root, |root, _| refresh(root));
+    root.base_mut()
+        .set_child(INSPECTOR_KEY, Window::init("widgem inspector".into()))?
+        .set_main_content(Inspector::init())?
+        .on_apply_style(apply_style_callback);
+
+    let timer = root
+        .base()
+        .app()
+        .add_interval(REFRESH_INTERVAL, refresh_callback);
+    root.set_inspector_state(Some(InspectorState {
+        target_window_root,
+        timer,
+        rows: Vec::new(),
+        row_texts: Vec::new(),
+        selected: None,
+        highlighted: Vec::new(),
+        details: String::new(),
+    }));
+    refresh(root)
+}
+
+// Prefers the focused window. Falls back to the window created first.
+fn target_window_root(app: &App) -> Option<RawWidgetId> {
+    let windows = app.windows();
+    windows
+        .values()
+        .find(|info| info.shared_window.is_focused())
+        .or_else(|| windows.values().min_by_key(|info| info.id))
+        .map(|info| info.root_widget_id)
+}
+
+fn close(root: &mut RootWidget) {
+    if let Some(state) = root.inspector_state() {
+        root.base().app().cancel_timer(state.timer);
+        let highlighted = state.highlighted.clone();
+        set_highlighted(root, &highlighted, &[]);
+    }
+    root.set_inspector_state(None);
+    if root.base().has_child(INSPECTOR_KEY) {
+        root.base_mut().remove_child(INSPECTOR_KEY).or_warn();
+    }
+}
+
+fn inspector_widget_mut(root: &mut RootWidget) -> Result<&mut Inspector> {
+    root.base_mut()
+        .get_child_mut::<Window>(INSPECTOR_KEY)?
+        .base_mut()
+        .get_child_mut::<Inspector>(ChildKey::main())
+}
+
+fn refresh(root: &mut RootWidget) -> Result<()> {
+    let Some(state) = root.inspector_state() else {
+        return Ok(());
+    };
+    if !root.base().has_child(INSPECTOR_KEY) {
+        // The inspector window has been closed by the user.
+        close(root);
+        return Ok(());
+    }
+    let target_window_root = state.target_window_root;
+    let selected = state.selected;
+
+    let mut rows = Vec::new();
+    let mut hovered = None;
+    if let Ok(target) = get_widget_by_id_mut(root, target_window_root) {
+        collect_rows(target, 0, &mut rows, &mut hovered);
+    }
+    let details = match selected.map(|id| get_widget_by_id_mut(root, id)) {
+        Some(Ok(widget)) => describe(widget),
+        Some(Err(_)) => "The selected widget has been deleted.".into(),
+        None if rows.is_empty() => "The inspected window has been closed.".into(),
+        None => "Select a widget in the tree.".into(),
+    };
+    let row_texts = rows
+        .iter()
+        .map(|(id, depth, summary)| {
+            let marker = if Some(*id) == selected { "* " } else { "" };
+            let suffix = if Some(*id) == hovered {
+                "  (hovered)"
+            } else {
+                ""
+            };
+            format!("{}{marker}{summary}{suffix}", "    ".repeat(*depth))
+        })
+        .collect_vec();
+    let highlighted = hovered.into_iter().chain(selected).unique().collect_vec();
+
+    let state = root.inspector_state().expect("checked above");
+    let old_highlighted = state.highlighted.clone();
+    let old_row_texts = state.row_texts.clone();
+    let details_changed = state.details != details;
+
+    let num_existing_rows = inspector_widget_mut(root)?.num_rows()?;
+    let new_row_callbacks = (num_existing_rows..row_texts.len())
+        .map(|index| root_callback(root, move |root, ()| select_row(root, index)))
+        .collect_vec();
+    let inspector = inspector_widget_mut(root)?;
+    inspector.set_rows(&old_row_texts, &row_texts, new_row_callbacks)?;
+    if details_changed {
+        inspector.set_details(&details)?;
+    }
+    set_highlighted(root, &old_highlighted, &highlighted);
+
+    let state = root.inspector_state_mut().expect("checked above");
+    state.rows = rows.into_iter().map(|(id, _, _)| id).collect();
+    state.row_texts = row_texts;
+    state.highlighted = highlighted;
+    state.details = details;
+    Ok(())
+}
+
+fn collect_rows(
+    widget: &mut dyn Widget,
+    depth: usize,
+    rows: &mut Vec<(RawWidgetId, usize, String)>,
+    hovered: &mut Option<RawWidgetId>,
+) {
+    let base = widget.base();
+    if base.is_under_mouse() {
+        // Rows are collected in pre-order, so the deepest widget under mouse wins.
+        *hovered = Some(base.id());
+    }
+    let mut summary = base.style_selector().tag().to_string();
+    for class in base.style_selector().classes() {
+        summary.push('.');
+        summary.push_str(class);
+    }
+    if let Some((key, _)) = base.address().path.last() {
+        summary.push_str(&format!("  [{key:?}]"));
+    }
+    if !base.is_visible() {
+        summary.push_str("  (hidden)");
+    }
+    rows.push((base.id(), depth, summary));
+    for child in widget.base_mut().children_mut() {
+        collect_rows(child, depth + 1, rows, hovered);
+    }
+}
+
+fn pseudo_class_name(class: &PseudoClass) -> Cow<'_, str> {
+    match class {
+        PseudoClass::Hover => "hover".into(),
+        PseudoClass::Focus => "focus".into(),
+        PseudoClass::Active => "active".into(),
+        PseudoClass::Enabled => "enabled".into(),
+        PseudoClass::Disabled => "disabled".into(),
+        PseudoClass::Current => "current".into(),
+        PseudoClass::Checked => "checked".into(),
+        PseudoClass::FocusVisible => "focus-visible".into(),
+        PseudoClass::ReadOnly => "read-only".into(),
+        PseudoClass::PlaceholderShown => "placeholder-shown".into(),
+        PseudoClass::Invalid => "invalid".into(),
+        PseudoClass::Custom(name) => name.as_ref().into(),
+    }
+}
+
+fn describe(widget: &mut dyn Widget) -> String {
+    let size_hint_x = widget.size_hint_x(None);
+    let size_x = widget
+        .base()
+        .size()
+        .map_or(size_hint_x.preferred(), |size| size.x());
+    let size_hint_y = widget.size_hint_y(size_x);
+    let accessibility_node = if widget.base().is_accessibility_node_enabled() {
+        widget
+            .handle_accessibility_node_request()
+            .or_warn()
+            .flatten()
+    } else {
+        None
+    };
+
+    let base = widget.base();
+    let selector = base.style_selector();
+    let mut lines = vec![
+        format!("type: {}", base.type_name()),
+        format!(
+            "address: {}",
+            base.address()
+                .path
+                .iter()
+                .map(|(key, _)| format!("{key:?}"))
+                .join(" / ")
+        ),
+        format!(
+            "classes: {}",
+            selector
+                .classes()
+                .iter()
+                .map(|class| format!(".{class}"))
+                .join(" ")
+        ),
+        format!(
+            "pseudo-classes: {}",
+            selector
+                .pseudo_classes()
+                .iter()
+                .map(|class| format!(":{}", pseudo_class_name(class)))
+                .join(" ")
+        ),
+        format!(
+            "visible: {}, enabled: {}, focused: {}",
+            base.is_visible(),
+            base.is_enabled(),
+            base.is_focused()
+        ),
+    ];
+    match base.geometry() {
+        Some(geometry) => {
+            lines.push(format!("rect in window: {:?}", geometry.rect_in_window()));
+            lines.push(format!("rect in parent: {:?}", geometry.rect_in_parent()));
+        }
+        None => lines.push("geometry: none".into()),
+    }
+    lines.push(format!("size hint x: {size_hint_x:?}"));
+    lines.push(format!("size hint y: {size_hint_y:?}"));
+    lines.push(format!("layout: {:?}", base.layout()));
+    lines.push(format!("scale: {}", base.scale()));
+    lines.push(format!("computed style: {:#?}", base.base_style()));
+    match &accessibility_node {
+        Some(node) => lines.push(format!("accessibility node: {node:#?}")),
+        None => lines.push("accessibility node: none".into()),
+    }
+    lines.join("\n")
+}
+
+fn set_highlighted(root: &mut RootWidget, old: &[RawWidgetId], new: &[RawWidgetId]) {
+    for id in old {
+        if !new.contains(id) {
+            if let Ok(widget) = get_widget_by_id_mut(root, *id) {
+                widget.base_mut().set_inspector_highlighted(false);
+            }
+        }
+    }
+    for id in new {
+        if let Ok(widget) = get_widget_by_id_mut(root, *id) {
+            widget.base_mut().set_inspector_highlighted(true);
+        }
+    }
+}
+
+fn select_row(root: &mut RootWidget, index: usize) -> Result<()> {
+    let Some(state) = root.inspector_state_mut() else {
+        return Ok(());
+    };
+    let Some(&id) = state.rows.get(index) else {
+        return Ok(());
+    };
+    state.selected = Some(id);
+    let inline_style = get_widget_by_id_mut(root, id)?
+        .base()
+        .style()
+        .unwrap_or_default()
+        .to_string();
+    inspector_widget_mut(root)?.set_style_text(&inline_style)?;
+    refresh(root)
+}
+
+fn apply_style(root: &mut RootWidget) -> Result<()> {
+    let Some(selected) = root.inspector_state().and_then(|state| state.selected) else {
+        return Ok(());
+    };
+    let style = inspector_widget_mut(root)?.style_text()?;
+    get_widget_by_id_mut(root, selected)?
+        .base_mut()
+        .set_style(&style);
+    refresh(root)
+}
+
+struct Inspector {
+    base: WidgetBaseOf<Self>,
+}
+
+impl Inspector {
+    fn new(mut base: WidgetBaseOf<Self>) -> Result<Self> {
+        base.set_layout(Layout::ExplicitGrid);
+        base.set_child("tree_title", Label::init("Widgets".into()))?
+            .set_grid_cell(0, 0);
+        base.set_child("tree", ScrollArea::init())?
+            .set_grid_cell_span(0, 1, 1, 4)
+            .set_content(Column::init())?;
+        base.set_child("details_title", Label::init("Details".into()))?
+            .set_grid_cell(1, 0);
+        base.set_child("details", ScrollArea::init())?
+            .set_grid_cell(1, 1)
+            .set_content(Label::init(String::new()))?;
+        base.set_child("style", TextArea::init())?
+            .set_grid_cell(1, 2);
+        base.set_child("apply_style", Button::init("Apply inline style".into()))?
+            .set_grid_cell(1, 3);
+        Ok(Self { base })
+    }
+
+    fn init() -> impl WidgetInitializer<Output = Self> {
+        widget_initializer::from_fallible_new(Self::new)
+    }
+
+    fn tree_mut(&mut self) -> Result<&mut Column> {
+        self.base
+            .get_child_mut::<ScrollArea>("tree")?
+            .content_mut::<Column>()
+    }
+
+    fn num_rows(&mut self) -> Result<usize> {
+        Ok(self.tree_mut()?.base().children().count())
+    }
+
+    fn set_rows(
+        &mut self,
+        old_texts: &[String],
+        texts: &[String],
+        new_row_callbacks: Vec<Callback<()>>,
+    ) -> Result<()> {
+        let tree = self.tree_mut()?.base_mut();
+        let num_existing_rows = tree.children().count();
+        for (index, text) in texts.iter().enumerate().take(num_existing_rows) {
+            if old_texts.get(index) != Some(text) {
+                tree.get_child_mut::<Button>(index as u32)?.set_text(text);
+            }
+        }
+        for ((index, text), callback) in texts
+            .iter()
+            .enumerate()
+            .skip(num_existing_rows)
+            .zip(new_row_callbacks)
+        {
+            tree.set_child(index as u32, Button::init(text.clone()))?
+                .add_class("inspector_row".into())
+                .on_triggered(callback);
+        }
+        for index in texts.len()..num_existing_rows {
+            tree.remove_child(index as u32)?;
+        }
+        Ok(())
+    }
+
+    fn set_details(&mut self, text: &str) -> Result<()> {
+        self.base
+            .get_child_mut::<ScrollArea>("details")?
+            .content_mut::<Label>()?
+            .set_text(text);
+        Ok(())
+    }
+
+    fn style_text(&self) -> Result<String> {
+        Ok(self.base.get_child::<TextArea>("style")?.text())
+    }
+
+    fn set_style_text(&mut self, text: &str) -> Result<()> {
+        self.base.get_child_mut::<TextArea>("style")?.set_text(text);
+        Ok(())
+    }
+
+    fn on_apply_style(&mut self, callback: Callback<()>) -> &mut Self {
+        if let Some(button) = self.base.get_child_mut::<Button>("apply_style").or_warn() {
+            button.on_triggered(callback);
+        }
+        self
+    }
+}
+
+impl Widget for Inspector {
+    impl_widget_base!();
+}
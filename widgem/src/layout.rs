@@ -1,12 +1,13 @@
 use {
     crate::{
-        types::{PhysicalPixels, PpxSuffix, Rect, Size},
+        types::{LogicalPixels, PhysicalPixels, PpxSuffix, Rect, Size},
         RawWidgetId, Widget, WidgetExt, WidgetGeometry,
     },
     itertools::Itertools,
+    serde_json::json,
     std::{
         cmp::{max, min},
-        collections::{hash_map, BTreeMap, HashMap},
+        collections::{BTreeMap, HashMap, HashSet},
         ops::RangeInclusive,
     },
     tracing::warn,
@@ -24,15 +25,44 @@ pub enum Layout {
     /// Child widgets are layed out from top to bottom. This is the default for most widgets.
     #[default]
     VerticalFirst,
-    // TODO: it should depend on writing direction
-    /// Child widgets are layed out from left to right.
+    /// Child widgets are layed out from left to right
+    /// (or from right to left if [LayoutDirection::RightToLeft] is in use).
     HorizontalFirst,
     /// Child widgets are positioned in a grid according to the row and column settings
     /// of each child.
+    ///
+    /// Columns are numbered from right to left if [LayoutDirection::RightToLeft] is in use.
     ExplicitGrid,
+    /// Child widgets are positioned in a grid with `columns` columns. Each row is filled
+    /// from left to right (or right to left, depending on [LayoutDirection]) in the order
+    /// the children were declared.
+    ///
+    /// Children with an explicitly assigned grid cell
+    /// (see [WidgetBase::set_grid_cell](crate::WidgetBase::set_grid_cell)) keep their position,
+    /// and the remaining children fill the free cells around them.
+    /// Use [WidgetBase::set_grid_span](crate::WidgetBase::set_grid_span) to make an automatically placed child
+    /// span multiple cells.
+    AutoGrid { columns: i32 },
     // TODO: other layout types? disabled variant?
 }
 
+/// Horizontal direction of the layout.
+///
+/// The default direction is set using
+/// [AppBuilder::with_layout_direction](crate::AppBuilder::with_layout_direction)
+/// and can be overridden for any widget using
+/// [WidgetExt::set_layout_direction](crate::WidgetExt::set_layout_direction).
+/// The direction propagates to child widgets.
+///
+/// With [LayoutDirection::RightToLeft], the default layout is mirrored horizontally:
+/// the first column is the rightmost one, and [Alignment::Start] aligns items to the right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LayoutDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct SizeHint {
     min: PhysicalPixels,
@@ -133,6 +163,20 @@ impl LayoutItemOptions {
         self.y.grid_cell = Some(y..=y);
     }
 
+    /// Assign a cell that starts at column `x` and row `y` and spans over
+    /// `span_x` columns and `span_y` rows.
+    pub fn set_grid_cell_span(&mut self, x: i32, y: i32, span_x: i32, span_y: i32) {
+        self.x.grid_cell = Some(x..=x + span_x - 1);
+        self.y.grid_cell = Some(y..=y + span_y - 1);
+    }
+
+    /// Set the number of columns and rows occupied by the item when its cell is assigned
+    /// automatically.
+    pub fn set_grid_span(&mut self, span_x: i32, span_y: i32) {
+        self.x.grid_span = Some(span_x);
+        self.y.grid_span = Some(span_y);
+    }
+
     pub fn unset_grid_cell(&mut self) {
         self.x.grid_cell = None;
         self.y.grid_cell = None;
@@ -159,6 +203,8 @@ impl LayoutItemOptions {
 pub struct LayoutItemAxisOptions {
     // row or column
     grid_cell: Option<RangeInclusive<i32>>,
+    // number of rows or columns if the cell is assigned automatically
+    grid_span: Option<i32>,
     alignment: Option<Alignment>,
     is_fixed: Option<bool>,
     // TODO: alignment, priority, stretch, etc.
@@ -169,6 +215,10 @@ impl LayoutItemAxisOptions {
         self.grid_cell.clone()
     }
 
+    pub fn grid_span(&self) -> Option<i32> {
+        self.grid_span
+    }
+
     pub fn alignment(&self) -> Option<Alignment> {
         self.alignment
     }
@@ -181,6 +231,10 @@ impl LayoutItemAxisOptions {
         self.grid_cell = pos_in_grid;
     }
 
+    pub fn set_grid_span(&mut self, span: Option<i32>) {
+        self.grid_span = span;
+    }
+
     pub fn set_alignment(&mut self, alignment: Option<Alignment>) {
         self.alignment = alignment;
     }
@@ -196,7 +250,143 @@ pub enum Alignment {
     Start,
     Middle,
     End,
-    // TODO: justify?
+    /// Stretch the item to fill its cell.
+    Justify,
+    /// Align items in the same row by their first text baseline.
+    ///
+    /// Only applies to the Y axis. Items that don't report a baseline and
+    /// items spanning multiple rows are aligned to the start.
+    Baseline,
+}
+
+// Alignment of items that don't have an explicit alignment.
+// Justify and Baseline set on the parent apply to all its items.
+fn default_item_alignment(grid_alignment: Alignment) -> Option<Alignment> {
+    match grid_alignment {
+        Alignment::Justify | Alignment::Baseline => Some(grid_alignment),
+        Alignment::Start | Alignment::Middle | Alignment::End => None,
+    }
+}
+
+fn offset_in_cell(
+    alignment: Alignment,
+    cell_size: PhysicalPixels,
+    item_size: PhysicalPixels,
+) -> PhysicalPixels {
+    let offset = match alignment {
+        Alignment::Start | Alignment::Justify | Alignment::Baseline => 0.ppx(),
+        Alignment::Middle => (cell_size - item_size) / 2,
+        Alignment::End => cell_size - item_size,
+    };
+    max(offset, 0.ppx())
+}
+
+/// Configuration of the grid formed by the children of a widget.
+///
+/// Set it using [WidgetBase::set_grid_config](crate::WidgetBase::set_grid_config).
+///
+/// These values are applied on top of the values provided by the widget's style.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GridConfig {
+    x: GridAxisConfig,
+    y: GridAxisConfig,
+}
+
+impl GridConfig {
+    /// Configuration of columns.
+    pub fn x(&self) -> &GridAxisConfig {
+        &self.x
+    }
+
+    /// Configuration of rows.
+    pub fn y(&self) -> &GridAxisConfig {
+        &self.y
+    }
+
+    pub fn set_x(&mut self, x: GridAxisConfig) {
+        self.x = x;
+    }
+
+    pub fn set_y(&mut self, y: GridAxisConfig) {
+        self.y = y;
+    }
+
+    /// Override spacing between columns. `None` means the value from the style is used.
+    pub fn set_column_spacing(&mut self, spacing: Option<LogicalPixels>) {
+        self.x.spacing = spacing;
+    }
+
+    /// Override spacing between rows. `None` means the value from the style is used.
+    pub fn set_row_spacing(&mut self, spacing: Option<LogicalPixels>) {
+        self.y.spacing = spacing;
+    }
+
+    pub fn set_column_min_size(&mut self, column: i32, min_size: Option<LogicalPixels>) {
+        self.x.set_min_size(column, min_size);
+    }
+
+    pub fn set_row_min_size(&mut self, row: i32, min_size: Option<LogicalPixels>) {
+        self.y.set_min_size(row, min_size);
+    }
+
+    pub fn set_column_stretch(&mut self, column: i32, stretch: u32) {
+        self.x.set_stretch(column, stretch);
+    }
+
+    pub fn set_row_stretch(&mut self, row: i32, stretch: u32) {
+        self.y.set_stretch(row, stretch);
+    }
+}
+
+/// Configuration of rows or columns of a grid.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GridAxisConfig {
+    spacing: Option<LogicalPixels>,
+    min_sizes: BTreeMap<i32, LogicalPixels>,
+    stretch: BTreeMap<i32, u32>,
+}
+
+impl GridAxisConfig {
+    /// Spacing between rows or columns, or `None` if the value from the style is used.
+    pub fn spacing(&self) -> Option<LogicalPixels> {
+        self.spacing
+    }
+
+    pub fn set_spacing(&mut self, spacing: Option<LogicalPixels>) {
+        self.spacing = spacing;
+    }
+
+    /// Minimal size of the row or column at `pos`.
+    ///
+    /// The row or column will not be smaller than this value even if all its items have smaller size hints.
+    pub fn min_size(&self, pos: i32) -> Option<LogicalPixels> {
+        self.min_sizes.get(&pos).copied()
+    }
+
+    pub fn set_min_size(&mut self, pos: i32, min_size: Option<LogicalPixels>) {
+        if let Some(min_size) = min_size {
+            self.min_sizes.insert(pos, min_size);
+        } else {
+            self.min_sizes.remove(&pos);
+        }
+    }
+
+    /// Stretch factor of the row or column at `pos`. Default is 0.
+    ///
+    /// If there is more space available than preferred by the items, the extra space is
+    /// distributed between rows or columns proportionally to their stretch factors. If all stretch
+    /// factors are 0, the extra space is split evenly between rows or columns that are not fixed.
+    pub fn stretch(&self, pos: i32) -> u32 {
+        self.stretch.get(&pos).copied().unwrap_or(0)
+    }
+
+    pub fn set_stretch(&mut self, pos: i32, stretch: u32) {
+        if stretch == 0 {
+            self.stretch.remove(&pos);
+        } else {
+            self.stretch.insert(pos, stretch);
+        }
+    }
 }
 
 pub(crate) fn fair_split(count: i32, total: PhysicalPixels) -> Vec<PhysicalPixels> {
@@ -214,9 +404,30 @@ pub(crate) fn fair_split(count: i32, total: PhysicalPixels) -> Vec<PhysicalPixel
     results
 }
 
+// Splits `total` into parts proportional to `weights`.
+fn weighted_split(weights: &[u32], total: PhysicalPixels) -> Vec<PhysicalPixels> {
+    let total_weight: u32 = weights.iter().sum();
+    if total_weight == 0 {
+        return vec![0.ppx(); weights.len()];
+    }
+    let mut cumulative_weight = 0;
+    let mut prev = 0.ppx();
+    let mut results = Vec::new();
+    for weight in weights {
+        cumulative_weight += weight;
+        let next = PhysicalPixels::from_i32(
+            (total.to_i32() as f32 * cumulative_weight as f32 / total_weight as f32).round() as i32,
+        );
+        results.push(next - prev);
+        prev = next;
+    }
+    results
+}
+
 #[derive(Debug)]
 pub(crate) struct LayoutItem {
     pub(crate) size_hints: SizeHint,
+    pub(crate) stretch: u32,
     // TODO: params
 }
 
@@ -227,8 +438,16 @@ pub(crate) struct SolveLayoutOutput {
     pub(crate) spacing: PhysicalPixels,
 }
 
+// Fixed items never grow, so their stretch factors are ignored.
+fn stretch(item: &LayoutItem) -> u32 {
+    if item.size_hints.is_fixed {
+        0
+    } else {
+        item.stretch
+    }
+}
+
 // TODO: chose min/preferred spacing and padding
-// TODO: support spanned items
 pub(crate) fn solve_layout(
     items: &[LayoutItem],
     total: PhysicalPixels,
@@ -293,6 +512,23 @@ pub(crate) fn solve_layout(
                 break;
             }
         }
+    } else if items.iter().any(|item| stretch(item) > 0) {
+        // Available size is more than the preferred size. Distributing extra space according to
+        // stretch factors.
+        let mut remaining =
+            total - output.padding * 2 - output.spacing * items.len().saturating_sub(1) as i32;
+        let extras = weighted_split(
+            &items.iter().map(stretch).collect_vec(),
+            total - total_preferred,
+        );
+        for (item, extra) in items.iter().zip(extras) {
+            let item_size = min(item.size_hints.preferred + extra, remaining);
+            output.sizes.push(item_size);
+            remaining -= item_size;
+            if remaining == 0.ppx() {
+                break;
+            }
+        }
     } else if total_preferred < total {
         let num_flexible = items
             .iter()
@@ -363,8 +599,15 @@ pub(crate) struct GridAxisOptions {
 fn size_hint(
     items: &[(RangeInclusive<i32>, PhysicalPixels)],
     options: &GridAxisOptions,
+    config: &GridAxisConfig,
+    scale: f32,
     mode: SizeHintMode,
 ) -> PhysicalPixels {
+    let (padding, spacing) = match mode {
+        SizeHintMode::Min => (options.min_padding, options.min_spacing),
+        SizeHintMode::Preferred => (options.preferred_padding, options.preferred_spacing),
+    };
+    let spacing = max(0.ppx(), spacing - options.border_collapse);
     let mut max_per_column = BTreeMap::new();
     let mut spanned = Vec::new();
     for (pos, hint) in items {
@@ -379,6 +622,8 @@ fn size_hint(
         }
     }
     for (range, hint) in spanned {
+        // Spacing between the spanned columns is also available to the item.
+        let hint = hint - spacing * (*range.end() - *range.start());
         let current: PhysicalPixels = range
             .clone()
             .map(|pos| max_per_column.get(&pos).copied().unwrap_or(0.ppx()))
@@ -390,20 +635,119 @@ fn size_hint(
             }
         }
     }
-    let (padding, spacing) = match mode {
-        SizeHintMode::Min => (options.min_padding, options.min_spacing),
-        SizeHintMode::Preferred => (options.preferred_padding, options.preferred_spacing),
-    };
+    for (pos, value) in &mut max_per_column {
+        if let Some(min_size) = config.min_size(*pos) {
+            *value = max(*value, min_size.to_physical(scale));
+        }
+    }
     max_per_column.values().sum::<PhysicalPixels>()
         + 2 * padding
-        + max_per_column.len().saturating_sub(1) as i32 * (spacing - options.border_collapse)
+        + max_per_column.len().saturating_sub(1) as i32 * spacing
+}
+
+// Merges size hints of items into size hints of rows or columns.
+// An item spanning multiple rows or columns only adds the part of its size hint
+// that doesn't fit into them and the spacing between them, split evenly between them.
+fn cell_hints(
+    items: Vec<(RangeInclusive<i32>, SizeHint)>,
+    options: &GridAxisOptions,
+    config: &GridAxisConfig,
+    scale: f32,
+) -> BTreeMap<i32, SizeHint> {
+    let min_spacing = max(0.ppx(), options.min_spacing - options.border_collapse);
+    let preferred_spacing = max(0.ppx(), options.preferred_spacing - options.border_collapse);
+    let mut hints_per_cell = BTreeMap::<i32, SizeHint>::new();
+    let mut spanned = Vec::new();
+    for (pos, hints) in items {
+        if pos.start() == pos.end() {
+            let cell_hints = hints_per_cell.entry(*pos.start()).or_insert(hints);
+            cell_hints.min = max(cell_hints.min, hints.min);
+            cell_hints.preferred = max(cell_hints.preferred, hints.preferred);
+            cell_hints.is_fixed = cell_hints.is_fixed && hints.is_fixed;
+        } else if pos.start() > pos.end() {
+            warn!("invalid pos_in_grid range");
+        } else {
+            spanned.push((pos, hints));
+        }
+    }
+    for (range, hints) in spanned {
+        for pos in range.clone() {
+            hints_per_cell
+                .entry(pos)
+                .or_insert(SizeHint::new(0.ppx(), 0.ppx(), hints.is_fixed));
+        }
+        let count = *range.end() - *range.start() + 1;
+        let hint_min = hints.min - min_spacing * (count - 1);
+        let hint_preferred = hints.preferred - preferred_spacing * (count - 1);
+        let current_min: PhysicalPixels = range.clone().map(|pos| hints_per_cell[&pos].min).sum();
+        if hint_min > current_min {
+            for (pos, extra) in range.clone().zip(fair_split(count, hint_min - current_min)) {
+                let cell_hints = hints_per_cell.get_mut(&pos).expect("inserted above");
+                cell_hints.min += extra;
+                cell_hints.preferred = max(cell_hints.preferred, cell_hints.min);
+            }
+        }
+        let current_preferred: PhysicalPixels = range
+            .clone()
+            .map(|pos| hints_per_cell[&pos].preferred)
+            .sum();
+        if hint_preferred > current_preferred {
+            for (pos, extra) in range
+                .clone()
+                .zip(fair_split(count, hint_preferred - current_preferred))
+            {
+                hints_per_cell
+                    .get_mut(&pos)
+                    .expect("inserted above")
+                    .preferred += extra;
+            }
+        }
+    }
+    for (pos, cell_hints) in &mut hints_per_cell {
+        if let Some(min_size) = config.min_size(*pos) {
+            let min_size = min_size.to_physical(scale);
+            cell_hints.min = max(cell_hints.min, min_size);
+            cell_hints.preferred = max(cell_hints.preferred, min_size);
+        }
+    }
+    hints_per_cell
+}
+
+// Returns total size of the rows or columns covered by `range`, including spacing between them.
+fn span_size(
+    sizes: &BTreeMap<i32, PhysicalPixels>,
+    range: &RangeInclusive<i32>,
+    spacing: PhysicalPixels,
+) -> Option<PhysicalPixels> {
+    let mut total = 0.ppx();
+    for pos in range.clone() {
+        total += *sizes.get(&pos)?;
+    }
+    Some(total + spacing * (*range.end() - *range.start()))
+}
+
+// Returns grid options from the widget's style with overrides
+// from the widget's grid config applied.
+fn grid_options(widget: &(impl Widget + ?Sized)) -> GridOptions {
+    let base = widget.base();
+    let mut options = base.base_style().grid.clone();
+    let config = base.grid_config();
+    if let Some(spacing) = config.x().spacing() {
+        options.x.min_spacing = spacing.to_physical(base.scale());
+        options.x.preferred_spacing = options.x.min_spacing;
+    }
+    if let Some(spacing) = config.y().spacing() {
+        options.y.min_spacing = spacing.to_physical(base.scale());
+        options.y.preferred_spacing = options.y.min_spacing;
+    }
+    options
 }
 
 pub fn default_size_hint_x(
     widget: &mut (impl Widget + ?Sized),
     size_y: Option<PhysicalPixels>,
 ) -> SizeHint {
-    let options = widget.base().base_style().grid.clone();
+    let options = grid_options(widget);
     let rows_and_columns = assign_rows_and_columns(widget);
     size_hint_x(widget, size_y, &options, &rows_and_columns)
 }
@@ -423,6 +767,8 @@ pub(crate) fn size_hint_x(
         None,
     );
 
+    let config = widget.base().grid_config().x().clone();
+    let scale = widget.base().scale();
     let mut min_items = Vec::new();
     let mut preferred_items = Vec::new();
     let mut all_fixed = true;
@@ -451,8 +797,14 @@ pub(crate) fn size_hint_x(
         }
     }
     SizeHint {
-        min: size_hint(&min_items, &options.x, SizeHintMode::Min),
-        preferred: size_hint(&preferred_items, &options.x, SizeHintMode::Preferred),
+        min: size_hint(&min_items, &options.x, &config, scale, SizeHintMode::Min),
+        preferred: size_hint(
+            &preferred_items,
+            &options.x,
+            &config,
+            scale,
+            SizeHintMode::Preferred,
+        ),
         is_fixed: all_fixed,
     }
 }
@@ -461,7 +813,7 @@ pub fn default_size_hint_y(
     widget: &mut (impl Widget + ?Sized),
     size_x: PhysicalPixels,
 ) -> SizeHint {
-    let options = widget.base().base_style().grid.clone();
+    let options = grid_options(widget);
     let rows_and_columns = assign_rows_and_columns(widget);
     size_hint_y(widget, &options, size_x, &rows_and_columns)
 }
@@ -473,6 +825,8 @@ pub(crate) fn size_hint_y(
     rows_and_columns: &RowsAndColumns,
 ) -> SizeHint {
     let x_layout = x_layout(widget, rows_and_columns, &options.x, size_x, None);
+    let config = widget.base().grid_config().y().clone();
+    let scale = widget.base().scale();
     let mut min_items = Vec::new();
     let mut preferred_items = Vec::new();
     let mut all_fixed = true;
@@ -499,13 +853,87 @@ pub(crate) fn size_hint_y(
             all_fixed = false;
         }
     }
+    let extents = baseline_extents(
+        widget,
+        rows_and_columns,
+        &x_layout.child_sizes,
+        default_item_alignment(options.y.alignment),
+    );
+    for (row, (above, below)) in extents {
+        preferred_items.push((row..=row, above + below));
+    }
     SizeHint {
-        min: size_hint(&min_items, &options.y, SizeHintMode::Min),
-        preferred: size_hint(&preferred_items, &options.y, SizeHintMode::Preferred),
+        min: size_hint(&min_items, &options.y, &config, scale, SizeHintMode::Min),
+        preferred: size_hint(
+            &preferred_items,
+            &options.y,
+            &config,
+            scale,
+            SizeHintMode::Preferred,
+        ),
         is_fixed: all_fixed,
     }
 }
 
+// Calculates the space required above and below the baseline in each row
+// by the items aligned by baseline.
+fn baseline_extents(
+    widget: &mut (impl Widget + ?Sized),
+    rows_and_columns: &RowsAndColumns,
+    child_sizes_x: &HashMap<RawWidgetId, PhysicalPixels>,
+    default_alignment: Option<Alignment>,
+) -> BTreeMap<i32, (PhysicalPixels, PhysicalPixels)> {
+    let mut output = BTreeMap::new();
+    for item in widget.base_mut().children_mut() {
+        let alignment = item
+            .base()
+            .layout_item_options()
+            .y
+            .alignment
+            .or(default_alignment);
+        if alignment != Some(Alignment::Baseline) {
+            continue;
+        }
+        let Some(pos) = rows_and_columns.id_to_y.get(&item.base().id()).cloned() else {
+            continue;
+        };
+        if pos.start() != pos.end() {
+            continue;
+        }
+        let Some(size_x) = child_sizes_x.get(&item.base().id()).copied() else {
+            continue;
+        };
+        let size_y = item.size_hint_y(size_x).preferred;
+        let Some(baseline) = item.baseline(Size::new(size_x, size_y)) else {
+            continue;
+        };
+        add_baseline_extent(&mut output, *pos.start(), size_y, baseline);
+    }
+    output
+}
+
+fn add_baseline_extent(
+    extents: &mut BTreeMap<i32, (PhysicalPixels, PhysicalPixels)>,
+    row: i32,
+    size_y: PhysicalPixels,
+    baseline: PhysicalPixels,
+) {
+    let (above, below) = extents.entry(row).or_insert((0.ppx(), 0.ppx()));
+    *above = max(*above, baseline);
+    *below = max(*below, size_y - baseline);
+}
+
+// Offset of a baseline-aligned item within its row. `above` is the space required
+// above the baseline in the row.
+fn baseline_offset(
+    above: PhysicalPixels,
+    baseline: PhysicalPixels,
+    row_size: PhysicalPixels,
+    size_y: PhysicalPixels,
+) -> PhysicalPixels {
+    (above - baseline).clamp(0.ppx(), max(row_size - size_y, 0.ppx()))
+}
+
 struct XLayout {
     padding: PhysicalPixels,
     spacing: PhysicalPixels,
@@ -520,16 +948,13 @@ fn x_layout(
     size_x: PhysicalPixels,
     first_pass: Option<HashMap<RawWidgetId, Option<WidgetGeometry>>>,
 ) -> XLayout {
-    let mut hints_per_column = BTreeMap::new();
+    let config = widget.base().grid_config().x().clone();
+    let scale = widget.base().scale();
+    let mut items = Vec::new();
     for item in widget.base_mut().children_mut() {
         let Some(pos) = rows_and_columns.id_to_x.get(&item.base().id()).cloned() else {
             continue;
         };
-        if pos.start() != pos.end() {
-            warn!("spanned items are not supported yet");
-        }
-        let pos = *pos.start();
-
         let first_pass_size_y = first_pass
             .as_ref()
             .and_then(|first_pass| first_pass.get(&item.base().id()))
@@ -539,26 +964,25 @@ fn x_layout(
         if let Some(is_fixed) = item.base().layout_item_options().x.is_fixed {
             hints.is_fixed = is_fixed;
         }
-        let column_hints = hints_per_column.entry(pos).or_insert(hints);
-        column_hints.min = max(column_hints.min, hints.min);
-        column_hints.preferred = max(column_hints.preferred, hints.preferred);
-        column_hints.is_fixed = column_hints.is_fixed && hints.is_fixed;
+        items.push((pos, hints));
     }
+    let hints_per_column = cell_hints(items, options, &config, scale);
     let layout_items = hints_per_column
-        .values()
-        .map(|hints| LayoutItem { size_hints: *hints })
+        .iter()
+        .map(|(pos, hints)| LayoutItem {
+            size_hints: *hints,
+            stretch: config.stretch(*pos),
+        })
         .collect_vec();
     let output = solve_layout(&layout_items, size_x, options);
     let column_sizes: BTreeMap<_, _> = hints_per_column.keys().copied().zip(output.sizes).collect();
+    let default_alignment = default_item_alignment(options.alignment);
     let mut child_sizes = HashMap::new();
     for item in widget.base_mut().children_mut() {
         let Some(pos) = rows_and_columns.id_to_x.get(&item.base().id()).cloned() else {
             continue;
         };
-        if pos.start() != pos.end() {
-            warn!("spanned items are not supported yet");
-        }
-        let Some(column_size) = column_sizes.get(pos.start()) else {
+        let Some(column_size) = span_size(&column_sizes, &pos, output.spacing) else {
             warn!("missing column data for existing child");
             continue;
         };
@@ -567,7 +991,15 @@ fn x_layout(
             .and_then(|first_pass| first_pass.get(&item.base().id()))
             .and_then(|x| x.as_ref())
             .map(|g| g.size_y());
-        let child_size = if item
+        let alignment = item
+            .base()
+            .layout_item_options()
+            .x
+            .alignment
+            .or(default_alignment);
+        let child_size = if alignment == Some(Alignment::Justify) {
+            column_size
+        } else if item
             .base()
             .layout_item_options()
             .x
@@ -575,9 +1007,9 @@ fn x_layout(
             .unwrap_or_else(|| item.size_hint_x(first_pass_size_y).is_fixed)
         {
             let hint = item.size_hint_x(first_pass_size_y).preferred;
-            min(hint, *column_size)
+            min(hint, column_size)
         } else {
-            *column_size
+            column_size
         };
         child_sizes.insert(item.base().id(), child_size);
     }
@@ -593,7 +1025,58 @@ fn x_layout(
 pub(crate) struct RowsAndColumns {
     id_to_x: HashMap<RawWidgetId, RangeInclusive<i32>>,
     id_to_y: HashMap<RawWidgetId, RangeInclusive<i32>>,
-    x_y_to_id: HashMap<(i32, i32), RawWidgetId>,
+    occupied: HashSet<(i32, i32)>,
+}
+
+impl RowsAndColumns {
+    fn is_free(&self, x: &RangeInclusive<i32>, y: &RangeInclusive<i32>) -> bool {
+        x.clone()
+            .cartesian_product(y.clone())
+            .all(|cell| !self.occupied.contains(&cell))
+    }
+
+    fn insert(&mut self, id: RawWidgetId, x: RangeInclusive<i32>, y: RangeInclusive<i32>) {
+        self.occupied.extend(x.clone().cartesian_product(y.clone()));
+        self.id_to_x.insert(id, x);
+        self.id_to_y.insert(id, y);
+    }
+
+    // Places the items with the specified spans into the free cells of `Layout::AutoGrid`,
+    // row by row, in the order the widgets were declared (rather than the order of their keys).
+    fn auto_place(&mut self, mut items: Vec<(RawWidgetId, i32, i32)>, columns: i32) {
+        // Widget IDs are allocated in creation order.
+        items.sort_by_key(|(id, _, _)| *id);
+        let columns = if columns < 1 {
+            warn!("invalid column count in Layout::AutoGrid: {columns}");
+            1
+        } else {
+            columns
+        };
+        let mut current_x = 0;
+        let mut current_y = 0;
+        for (id, span_x, span_y) in items {
+            if span_x < 1 || span_y < 1 || span_x > columns {
+                warn!("invalid grid span ({span_x}, {span_y}) for {columns} columns");
+            }
+            let span_x = span_x.clamp(1, columns);
+            let span_y = max(span_y, 1);
+            loop {
+                if current_x + span_x > columns {
+                    current_x = 0;
+                    current_y += 1;
+                    continue;
+                }
+                let x = current_x..=current_x + span_x - 1;
+                let y = current_y..=current_y + span_y - 1;
+                if self.is_free(&x, &y) {
+                    self.insert(id, x, y);
+                    current_x += span_x;
+                    break;
+                }
+                current_x += 1;
+            }
+        }
+    }
 }
 
 // TODO: refresh only when relevant things have changed
@@ -602,6 +1085,7 @@ pub(crate) fn assign_rows_and_columns<W: Widget + ?Sized>(widget: &W) -> RowsAnd
     let mut current_x = 0;
     let mut current_y = 0;
     let layout = widget.base().layout();
+    let mut auto_placed = Vec::new();
     // TODO: impl sorting key and sort by (sorting_key, key) here.
     for child in widget.base().children() {
         if child.base().is_window_root() || !child.base().is_self_visible() {
@@ -648,23 +1132,39 @@ pub(crate) fn assign_rows_and_columns<W: Widget + ?Sized>(widget: &W) -> RowsAnd
                     }
                 }
             }
-        }
-        match output.x_y_to_id.entry((*x.start(), *y.start())) {
-            hash_map::Entry::Occupied(_) => {
-                warn!(
-                    "assigned same grid pos ({}, {}) to multiple widgets, ignoring duplicate",
-                    x.start(),
-                    y.start()
-                );
-                continue;
-            }
-            hash_map::Entry::Vacant(entry) => {
-                entry.insert(id);
+            Layout::AutoGrid { .. } => {
+                match (options.x.grid_cell.clone(), options.y.grid_cell.clone()) {
+                    (Some(x_conf), Some(y_conf)) => {
+                        x = x_conf;
+                        y = y_conf;
+                    }
+                    (x_conf, y_conf) => {
+                        if x_conf.is_some() || y_conf.is_some() {
+                            warn!("only one of row and column is set, placing item automatically");
+                        }
+                        auto_placed.push((
+                            id,
+                            options.x.grid_span.unwrap_or(1),
+                            options.y.grid_span.unwrap_or(1),
+                        ));
+                        continue;
+                    }
+                }
             }
         }
+        if !output.is_free(&x, &y) {
+            warn!(
+                "assigned same grid pos ({}, {}) to multiple widgets, ignoring duplicate",
+                x.start(),
+                y.start()
+            );
+            continue;
+        }
+        output.insert(id, x, y);
+    }
 
-        output.id_to_x.insert(id, x);
-        output.id_to_y.insert(id, y);
+    if let Layout::AutoGrid { columns } = layout {
+        output.auto_place(auto_placed, columns);
     }
 
     output
@@ -684,7 +1184,7 @@ fn default_layout_pass<W: Widget + ?Sized>(
         warn!("missing geometry or for_size in layout pass");
         return Default::default();
     };
-    let options = widget.base().base_style().grid.clone();
+    let options = grid_options(widget);
     let x_layout = x_layout(
         widget,
         rows_and_columns,
@@ -692,33 +1192,41 @@ fn default_layout_pass<W: Widget + ?Sized>(
         geometry.size_x(),
         first_pass,
     );
-    let mut hints_per_row = BTreeMap::new();
+    let config = widget.base().grid_config().y().clone();
+    let scale = widget.base().scale();
+    let mut items = Vec::new();
     for item in widget.base_mut().children_mut() {
         // TODO: problem with is_self_visible
         let Some(pos) = rows_and_columns.id_to_y.get(&item.base().id()).cloned() else {
             continue;
         };
-
-        if pos.start() != pos.end() {
-            warn!("spanned items are not supported yet");
-        }
         let Some(item_size_x) = x_layout.child_sizes.get(&item.base().id()) else {
             continue;
         };
-        let pos = *pos.start();
         let mut hints = item.size_hint_y(*item_size_x);
         if let Some(is_fixed) = item.base().layout_item_options().y.is_fixed {
             hints.is_fixed = is_fixed;
         }
-        let row_hints = hints_per_row.entry(pos).or_insert(hints);
-        row_hints.min = max(row_hints.min, hints.min);
-        row_hints.preferred = max(row_hints.preferred, hints.preferred);
-        row_hints.is_fixed = row_hints.is_fixed && hints.is_fixed;
-        // TODO: deduplicate
+        items.push((pos, hints));
     }
+    let default_alignment_x = default_item_alignment(options.x.alignment);
+    let default_alignment_y = default_item_alignment(options.y.alignment);
+    let extents = baseline_extents(
+        widget,
+        rows_and_columns,
+        &x_layout.child_sizes,
+        default_alignment_y,
+    );
+    for (row, (above, below)) in &extents {
+        items.push((*row..=*row, SizeHint::new(0.ppx(), *above + *below, true)));
+    }
+    let hints_per_row = cell_hints(items, &options.y, &config, scale);
     let layout_items = hints_per_row
-        .values()
-        .map(|hints| LayoutItem { size_hints: *hints })
+        .iter()
+        .map(|(pos, hints)| LayoutItem {
+            size_hints: *hints,
+            stretch: config.stretch(*pos),
+        })
         .collect_vec();
     let output_y = solve_layout(&layout_items, geometry.size_y(), &options.y);
     let row_sizes: BTreeMap<_, _> = hints_per_row.keys().copied().zip(output_y.sizes).collect();
@@ -736,6 +1244,7 @@ fn default_layout_pass<W: Widget + ?Sized>(
         geometry.size_y(),
         options.y.alignment,
     );
+    let is_mirrored = widget.base().layout_direction() == LayoutDirection::RightToLeft;
     let mut output = HashMap::new();
     for item in widget.base_mut().children_mut() {
         if item.base().is_window_root() {
@@ -762,48 +1271,257 @@ fn default_layout_pass<W: Widget + ?Sized>(
             warn!("missing item in x_layout.child_sizes");
             continue;
         };
-        let Some(row_size) = row_sizes.get(pos_y.start()) else {
+        let Some(row_size) = span_size(&row_sizes, &pos_y, output_y.spacing) else {
             warn!("missing item in row_sizes");
             continue;
         };
+        let alignment_x = item
+            .base()
+            .layout_item_options()
+            .x
+            .alignment
+            .or(default_alignment_x)
+            .unwrap_or(Alignment::Start);
+        let alignment_y = item
+            .base()
+            .layout_item_options()
+            .y
+            .alignment
+            .or(default_alignment_y)
+            .unwrap_or(Alignment::Start);
         let size_hint_y = item.size_hint_y(*size_x);
-        let size_y = if item
+        let size_y = if alignment_y == Alignment::Justify {
+            row_size
+        } else if item
             .base()
             .layout_item_options()
             .y
             .is_fixed
             .unwrap_or(size_hint_y.is_fixed)
         {
-            min(*row_size, size_hint_y.preferred)
+            min(row_size, size_hint_y.preferred)
         } else {
-            *row_size
+            row_size
         };
+        let offset_x = span_size(&x_layout.column_sizes, &pos_x, x_layout.spacing)
+            .map_or(0.ppx(), |column_size| {
+                offset_in_cell(alignment_x, column_size, *size_x)
+            });
+        let mut offset_y = offset_in_cell(alignment_y, row_size, size_y);
+        if alignment_y == Alignment::Baseline {
+            if let Some((above, _)) = extents.get(pos_y.start()) {
+                if let Some(baseline) = item.baseline(Size::new(*size_x, size_y)) {
+                    offset_y = baseline_offset(*above, baseline, row_size, size_y);
+                }
+            }
+        }
+        let mut pos_x = *cell_pos_x + offset_x;
+        if is_mirrored {
+            pos_x = geometry.size_x() - pos_x - *size_x;
+        }
         output.insert(
             item.base().id(),
             Some(WidgetGeometry::new(
                 &geometry,
-                Rect::from_xywh(*cell_pos_x, *cell_pos_y, *size_x, size_y),
+                Rect::from_xywh(pos_x, *cell_pos_y + offset_y, *size_x, size_y),
             )),
         );
     }
+    if for_size.is_none() && widget.base().app().config().debug_layout {
+        let content_rect = Rect::from_x1y1x2y2(
+            x_layout.padding,
+            output_y.padding,
+            geometry.size_x() - x_layout.padding,
+            geometry.size_y() - output_y.padding,
+        );
+        let mut gaps = Vec::new();
+        for ((pos1, size1), pos2) in positions_x
+            .values()
+            .zip(x_layout.column_sizes.values())
+            .tuple_windows()
+            .map(|((pos1, size1), (pos2, _))| ((pos1, size1), pos2))
+        {
+            let (mut x1, mut x2) = (*pos1 + *size1, *pos2);
+            if is_mirrored {
+                (x1, x2) = (geometry.size_x() - x2, geometry.size_x() - x1);
+            }
+            gaps.push(Rect::from_x1y1x2y2(
+                x1,
+                content_rect.top(),
+                x2,
+                content_rect.bottom(),
+            ));
+        }
+        for ((pos1, size1), pos2) in positions_y
+            .values()
+            .zip(row_sizes.values())
+            .tuple_windows()
+            .map(|((pos1, size1), (pos2, _))| ((pos1, size1), pos2))
+        {
+            gaps.push(Rect::from_x1y1x2y2(
+                content_rect.left(),
+                *pos1 + *size1,
+                content_rect.right(),
+                *pos2,
+            ));
+        }
+        widget
+            .base()
+            .set_layout_debug_info(LayoutDebugInfo { content_rect, gaps });
+    }
     output
 }
 
+// Areas of the default layout displayed by the layout debug overlay.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LayoutDebugInfo {
+    // Rect of the widget without padding.
+    pub(crate) content_rect: Rect,
+    // Spacing between rows and columns.
+    pub(crate) gaps: Vec<Rect>,
+}
+
+/// Returns a JSON representation of the widget tree starting from `widget`.
+///
+/// Each node contains the widget's type name, address, child key, size hints
+/// and geometry, as well as the nodes of its children. This can be used to diagnose
+/// layout issues or to compare layouts in tests.
+pub fn dump_layout(widget: &mut (impl Widget + ?Sized)) -> serde_json::Value {
+    let size_hint_x = widget.size_hint_x(None);
+    let size_x = widget
+        .base()
+        .size()
+        .map_or(size_hint_x.preferred, |size| size.x());
+    let size_hint_y = widget.size_hint_y(size_x);
+    let base = widget.base();
+    let address = base
+        .address()
+        .path
+        .iter()
+        .map(|(key, _)| format!("{key:?}"))
+        .collect_vec();
+    let mut output = layout_node_json(
+        base.type_name(),
+        address,
+        base.is_self_visible(),
+        size_hint_x,
+        size_hint_y,
+        base.geometry(),
+    );
+    let children = widget
+        .base_mut()
+        .children_mut()
+        .map(dump_layout)
+        .collect_vec();
+    output["children"] = children.into();
+    output
+}
+
+// Output of `dump_layout` for a single widget, without its children.
+fn layout_node_json(
+    type_name: &str,
+    address: Vec<String>,
+    is_visible: bool,
+    size_hint_x: SizeHint,
+    size_hint_y: SizeHint,
+    geometry: Option<&WidgetGeometry>,
+) -> serde_json::Value {
+    fn rect_to_json(rect: Rect) -> serde_json::Value {
+        json!({
+            "x": rect.left().to_i32(),
+            "y": rect.top().to_i32(),
+            "size_x": rect.size_x().to_i32(),
+            "size_y": rect.size_y().to_i32(),
+        })
+    }
+
+    fn size_hint_to_json(hint: SizeHint) -> serde_json::Value {
+        json!({
+            "min": hint.min.to_i32(),
+            "preferred": hint.preferred.to_i32(),
+            "is_fixed": hint.is_fixed,
+        })
+    }
+
+    let geometry = geometry.map(|geometry| {
+        json!({
+            "rect_in_parent": rect_to_json(geometry.rect_in_parent()),
+            "rect_in_window": rect_to_json(geometry.rect_in_window()),
+        })
+    });
+    json!({
+        "type_name": type_name,
+        "key": address.last(),
+        "address": address,
+        "is_visible": is_visible,
+        "size_hint_x": size_hint_to_json(size_hint_x),
+        "size_hint_y": size_hint_to_json(size_hint_y),
+        "geometry": geometry,
+    })
+}
+
 pub fn default_layout<W: Widget + ?Sized>(widget: &mut W) {
-    if widget.base().geometry().is_none() {
+    let Some(geometry) = widget.base().geometry().cloned() else {
         for child in widget.base_mut().children_mut() {
             child.set_geometry(None);
         }
         return;
+    };
+    // The cache is bypassed to collect the debug info.
+    let rects = if widget.base().app().config().debug_layout {
+        layout_rects(widget, None)
+    } else {
+        cached_layout_rects(widget, geometry.size())
+    };
+    for child in widget.base_mut().children_mut() {
+        if let Some(rect) = rects.get(&child.base().id()) {
+            child.set_geometry(rect.map(|rect| WidgetGeometry::new(&geometry, rect)));
+        }
     }
+}
+
+// Runs both layout passes and returns the resulting rects of the children.
+fn layout_rects(
+    widget: &mut (impl Widget + ?Sized),
+    for_size: Option<Size>,
+) -> HashMap<RawWidgetId, Option<Rect>> {
     let rows_and_columns = assign_rows_and_columns(widget);
-    let first_pass = default_layout_pass(widget, None, &rows_and_columns, None);
-    let mut second_pass = default_layout_pass(widget, None, &rows_and_columns, Some(first_pass));
+    let first_pass = default_layout_pass(widget, for_size, &rows_and_columns, None);
+    default_layout_pass(widget, for_size, &rows_and_columns, Some(first_pass))
+        .into_iter()
+        .map(|(id, geometry)| (id, geometry.map(|geometry| geometry.rect_in_parent())))
+        .collect()
+}
+
+// Same as `layout_rects` but reuses the result computed earlier for the same size,
+// either by the layout or by a baseline request.
+fn cached_layout_rects(
+    widget: &mut (impl Widget + ?Sized),
+    size: Size,
+) -> HashMap<RawWidgetId, Option<Rect>> {
+    if let Some(rects) = widget.base().layout_cache(size) {
+        return rects;
+    }
+    let rects = layout_rects(widget, Some(size));
+    widget.base().set_layout_cache(size, rects.clone());
+    rects
+}
+
+/// Calculates the first text baseline of the widget from the baselines of its children.
+///
+/// Children are laid out within `size` using the default grid layout, and the baseline of
+/// the first child (in key order) that reports a baseline is used.
+pub fn default_baseline(widget: &mut (impl Widget + ?Sized), size: Size) -> Option<PhysicalPixels> {
+    let rects = cached_layout_rects(widget, size);
     for child in widget.base_mut().children_mut() {
-        if let Some(geometry) = second_pass.remove(&child.base().id()) {
-            child.set_geometry(geometry);
+        let Some(Some(rect)) = rects.get(&child.base().id()) else {
+            continue;
+        };
+        if let Some(baseline) = child.baseline(rect.size()) {
+            return Some(rect.top() + baseline);
         }
     }
+    None
 }
 
 fn positions(
@@ -825,6 +1543,8 @@ fn positions(
         Alignment::End => {
             pos += available_for_items - total_taken;
         }
+        // These only affect the alignment of items within their cells.
+        Alignment::Justify | Alignment::Baseline => {}
     }
     let mut result = BTreeMap::new();
     for (num, size) in sizes {
@@ -833,3 +1553,194 @@ fn positions(
     }
     result
 }
+
+#[test]
+fn auto_grid() {
+    let ids = (0..6).map(|_| RawWidgetId::new_unique()).collect_vec();
+    let mut grid = RowsAndColumns::default();
+    // An explicitly placed item in the middle of the first row.
+    grid.insert(ids[0], 1..=1, 0..=0);
+    grid.auto_place(
+        vec![
+            (ids[1], 1, 1),
+            // Doesn't fit into the rest of the first row.
+            (ids[2], 2, 1),
+            (ids[3], 1, 2),
+            (ids[4], 1, 1),
+            // The span is clamped to the number of columns.
+            (ids[5], 5, 1),
+        ],
+        3,
+    );
+    let cells = ids
+        .iter()
+        .map(|id| {
+            let x = &grid.id_to_x[id];
+            let y = &grid.id_to_y[id];
+            ((*x.start(), *x.end()), (*y.start(), *y.end()))
+        })
+        .collect_vec();
+    assert_eq!(
+        cells,
+        [
+            ((1, 1), (0, 0)),
+            ((0, 0), (0, 0)),
+            ((0, 1), (1, 1)),
+            ((2, 2), (1, 2)),
+            ((0, 0), (2, 2)),
+            ((0, 2), (3, 3)),
+        ]
+    );
+
+    // Items are placed in declaration order, not in the order of their keys.
+    let mut grid = RowsAndColumns::default();
+    grid.auto_place(vec![(ids[1], 1, 1), (ids[0], 1, 1)], 2);
+    assert_eq!(grid.id_to_x[&ids[0]], 0..=0);
+    assert_eq!(grid.id_to_x[&ids[1]], 1..=1);
+}
+
+#[test]
+fn spanned_items() {
+    let options = GridAxisOptions {
+        min_padding: 0.ppx(),
+        min_spacing: 10.ppx(),
+        preferred_padding: 0.ppx(),
+        preferred_spacing: 10.ppx(),
+        border_collapse: 0.ppx(),
+        alignment: Alignment::Start,
+    };
+    let config = GridAxisConfig::default();
+    let items = vec![
+        (0..=0, SizeHint::new_expanding(20.ppx(), 50.ppx())),
+        (1..=1, SizeHint::new_expanding(20.ppx(), 50.ppx())),
+        // The minimal size fits into both columns and the spacing between them.
+        (0..=1, SizeHint::new_expanding(50.ppx(), 130.ppx())),
+    ];
+    let hints = cell_hints(items.clone(), &options, &config, 1.0);
+    assert_eq!(hints[&0], SizeHint::new_expanding(20.ppx(), 60.ppx()));
+    assert_eq!(hints[&1], SizeHint::new_expanding(20.ppx(), 60.ppx()));
+
+    let preferred = items
+        .iter()
+        .map(|(range, hints)| (range.clone(), hints.preferred))
+        .collect_vec();
+    assert_eq!(
+        size_hint(&preferred, &options, &config, 1.0, SizeHintMode::Preferred),
+        130.ppx()
+    );
+
+    // The spanned item gets the full size of both columns, including the spacing.
+    let layout_items = hints
+        .values()
+        .map(|hints| LayoutItem {
+            size_hints: *hints,
+            stretch: 0,
+        })
+        .collect_vec();
+    let output = solve_layout(&layout_items, 130.ppx(), &options);
+    let sizes: BTreeMap<_, _> = hints.keys().copied().zip(output.sizes).collect();
+    assert_eq!(span_size(&sizes, &(0..=1), output.spacing), Some(130.ppx()));
+}
+
+#[test]
+fn stretch_fixed() {
+    let options = GridAxisOptions {
+        min_padding: 0.ppx(),
+        min_spacing: 0.ppx(),
+        preferred_padding: 0.ppx(),
+        preferred_spacing: 0.ppx(),
+        border_collapse: 0.ppx(),
+        alignment: Alignment::Start,
+    };
+    let items = [
+        LayoutItem {
+            size_hints: SizeHint::new_fixed(50.ppx(), 50.ppx()),
+            stretch: 2,
+        },
+        LayoutItem {
+            size_hints: SizeHint::new_expanding(50.ppx(), 50.ppx()),
+            stretch: 1,
+        },
+    ];
+    assert_eq!(
+        solve_layout(&items, 200.ppx(), &options).sizes,
+        [50.ppx(), 150.ppx()]
+    );
+}
+
+#[test]
+fn baseline_alignment() {
+    // A small and a large label in the same row.
+    let small = (16.ppx(), 12.ppx());
+    let large = (30.ppx(), 24.ppx());
+    let mut extents = BTreeMap::new();
+    for (size_y, baseline) in [small, large] {
+        add_baseline_extent(&mut extents, 0, size_y, baseline);
+    }
+    // The large label requires more space both above and below the baseline.
+    assert_eq!(extents[&0], (24.ppx(), 6.ppx()));
+    let row_size = 30.ppx();
+    let small_offset = baseline_offset(24.ppx(), small.1, row_size, small.0);
+    let large_offset = baseline_offset(24.ppx(), large.1, row_size, large.0);
+    assert_eq!((small_offset, large_offset), (12.ppx(), 0.ppx()));
+    assert_eq!(small_offset + small.1, large_offset + large.1);
+
+    // A label with a deep descent extends the row below the baseline.
+    add_baseline_extent(&mut extents, 0, 20.ppx(), 10.ppx());
+    assert_eq!(extents[&0], (24.ppx(), 10.ppx()));
+
+    // The item never goes outside of a row that is too small.
+    assert_eq!(
+        baseline_offset(24.ppx(), small.1, 20.ppx(), small.0),
+        4.ppx()
+    );
+}
+
+#[test]
+fn dump_layout_node() {
+    let window = WidgetGeometry::root(Size::new(200.ppx(), 100.ppx()));
+    let geometry = WidgetGeometry::new(
+        &window,
+        Rect::from_xywh(10.ppx(), 20.ppx(), 50.ppx(), 30.ppx()),
+    );
+    let child = WidgetGeometry::new(
+        &geometry,
+        Rect::from_xywh(5.ppx(), 5.ppx(), 40.ppx(), 20.ppx()),
+    );
+    let output = layout_node_json(
+        "widgem::widgets::Label",
+        vec!["0".into(), "\"label\"".into()],
+        true,
+        SizeHint::new_expanding(30.ppx(), 50.ppx()),
+        SizeHint::new_fixed(20.ppx(), 30.ppx()),
+        Some(&child),
+    );
+    assert_eq!(
+        output,
+        json!({
+            "type_name": "widgem::widgets::Label",
+            "key": "\"label\"",
+            "address": ["0", "\"label\""],
+            "is_visible": true,
+            "size_hint_x": { "min": 30, "preferred": 50, "is_fixed": false },
+            "size_hint_y": { "min": 20, "preferred": 30, "is_fixed": true },
+            "geometry": {
+                "rect_in_parent": { "x": 5, "y": 5, "size_x": 40, "size_y": 20 },
+                "rect_in_window": { "x": 15, "y": 25, "size_x": 40, "size_y": 20 },
+            },
+        })
+    );
+
+    // Widgets without geometry are still listed.
+    let output = layout_node_json(
+        "widgem::widgets::Label",
+        Vec::new(),
+        false,
+        SizeHint::new_fixed(0.ppx(), 0.ppx()),
+        SizeHint::new_fixed(0.ppx(), 0.ppx()),
+        None,
+    );
+    assert_eq!(output["key"], serde_json::Value::Null);
+    assert_eq!(output["geometry"], serde_json::Value::Null);
+    assert_eq!(output["is_visible"], false);
+}
@@ -12,6 +12,7 @@ mod draw;
 pub mod event;
 mod event_loop;
 mod id;
+mod inspector;
 pub mod items;
 pub mod layout;
 mod monitor;
@@ -23,6 +24,7 @@ pub mod system;
 mod text;
 pub mod timer;
 pub mod types;
+pub mod validator;
 mod widget_base;
 mod widget_ext;
 pub mod widget_initializer;
@@ -46,6 +48,7 @@ pub use {
         id::{RawWidgetId, WidgetId},
         monitor::MonitorExt,
         pixmap::Pixmap,
+        text::{EchoMode, EditHistoryState, ElideMode, FindQuery, RichText, TextFormat, TextSpan},
         widget_base::{EventFilterFn, WidgetBase, WidgetBaseOf, WidgetGeometry},
         widget_ext::WidgetExt,
         widget_trait::Widget,
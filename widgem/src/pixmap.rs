@@ -1,6 +1,7 @@
 use std::{path::Path, rc::Rc};
 
 use anyhow::Context as _;
+use tiny_skia::{Color, Mask, MaskType, Paint, Shader, Transform};
 
 use crate::types::{PhysicalPixels, PpxSuffix, Size};
 
@@ -41,6 +42,26 @@ impl Pixmap {
     pub fn size_y(&self) -> PhysicalPixels {
         (self.0.height() as i32).ppx()
     }
+
+    /// Returns a copy of the image filled with `color`, preserving only the image's alpha channel.
+    ///
+    /// This is used for monochrome (symbolic) icons that should match the text color.
+    pub fn tinted(&self, color: Color) -> Self {
+        let mut output = tiny_skia::Pixmap::new(self.0.width(), self.0.height())
+            .expect("pixmap size is already valid");
+        let mask = Mask::from_pixmap(self.as_tiny_skia_ref(), MaskType::Alpha);
+        output.fill_rect(
+            tiny_skia::Rect::from_xywh(0.0, 0.0, self.0.width() as f32, self.0.height() as f32)
+                .expect("pixmap size is already valid"),
+            &Paint {
+                shader: Shader::SolidColor(color),
+                ..Paint::default()
+            },
+            Transform::identity(),
+            Some(&mask),
+        );
+        output.into()
+    }
 }
 
 impl From<tiny_skia::Pixmap> for Pixmap {
@@ -54,3 +75,28 @@ impl From<Rc<tiny_skia::Pixmap>> for Pixmap {
         Self(value)
     }
 }
+
+#[test]
+fn tinted() {
+    let mut image = tiny_skia::Pixmap::new(3, 1).unwrap();
+    let pixels = image.pixels_mut();
+    pixels[0] = tiny_skia::ColorU8::from_rgba(255, 0, 0, 255).premultiply();
+    pixels[1] = tiny_skia::ColorU8::from_rgba(0, 255, 0, 128).premultiply();
+    let image = Pixmap::from(image);
+    let blue = Color::from_rgba8(0, 0, 255, 255);
+    let output = image.tinted(blue);
+    assert_eq!(output.size(), image.size());
+    let pixels = output
+        .as_tiny_skia_ref()
+        .pixels()
+        .iter()
+        .map(|pixel| {
+            let pixel = pixel.demultiply();
+            (pixel.red(), pixel.green(), pixel.blue(), pixel.alpha())
+        })
+        .collect::<Vec<_>>();
+    // The color is replaced, and the alpha channel is preserved.
+    assert_eq!(pixels, [(0, 0, 255, 255), (0, 0, 255, 128), (0, 0, 0, 0)]);
+    // The original image is unchanged.
+    assert_eq!(image.as_tiny_skia_ref().pixel(0, 0).unwrap().red(), 255);
+}
@@ -413,6 +413,12 @@ impl SharedWindow {
             .base()
             .app()
             .add_winit_window(winit_id, self.id());
+        // If the window has a preferred theme, it doesn't reflect the system theme.
+        if attributes.preferred_theme.is_none() {
+            if let Some(theme) = winit_window.theme() {
+                root_widget.base().app().system_theme_changed(theme);
+            }
+        }
         if root_widget.base().app().config().fixed_scale.is_none() {
             if root_widget.base().self_scale().is_some_and(|widget_scale| {
                 (widget_scale - winit_window.scale_factor() as f32).abs() >= 0.1
@@ -466,6 +472,10 @@ impl SharedWindow {
             .add_node(parent, child, key_in_parent);
     }
 
+    pub(crate) fn accessibility_node_parent(&self, id: NodeId) -> Option<NodeId> {
+        self.0.borrow().accessibility_nodes.parent(id)
+    }
+
     pub(crate) fn accessibility_node_updated(&self, id: NodeId, node: Option<accesskit::Node>) {
         self.0
             .borrow_mut()
@@ -970,7 +980,15 @@ impl SharedWindow {
 
     pub(crate) fn request_style_change_event(&self, addr: WidgetAddress) {
         let this = &mut *self.0.borrow_mut();
-        this.pending_style_change_events.push(addr);
+        // The event is propagated to all descendants, so a pending request for the widget
+        // or any of its ancestors already covers it. This batches the requests made when
+        // many children are added to the same parent.
+        let pending = &mut this.pending_style_change_events;
+        if pending.iter().any(|pending| addr.starts_with(pending)) {
+            return;
+        }
+        pending.retain(|pending| !pending.starts_with(&addr));
+        pending.push(addr);
     }
 
     pub(crate) fn take_pending_style_change_events(&self) -> Vec<WidgetAddress> {
@@ -199,6 +199,9 @@ pub struct StandardShortcuts {
     pub bold: KeyCombinations,
     pub italic: KeyCombinations,
     pub underline: KeyCombinations,
+    pub find: KeyCombinations,
+    pub find_next: KeyCombinations,
+    pub find_previous: KeyCombinations,
     pub move_to_next_word: KeyCombinations,
     pub move_to_previous_word: KeyCombinations,
     pub move_to_start_of_line: KeyCombinations,
@@ -361,6 +364,12 @@ impl StandardShortcuts {
             italic: s("CtrlOrMacCmd+I"),
 
             underline: s("CtrlOrMacCmd+U"),
+
+            find: s("CtrlOrMacCmd+F"),
+
+            find_next: s("F3; CtrlOrMacCmd+G"),
+
+            find_previous: s("Shift+F3; Shift+CtrlOrMacCmd+G"),
             move_to_next_word,
             move_to_previous_word,
             move_to_start_of_line,
@@ -3,28 +3,41 @@ use {
         style::{
             common::ComputedElementStyle,
             css::{
-                convert_background_color, convert_font, convert_main_color, is_root, replace_vars,
+                affects_property, collect_custom_properties, convert_background_color,
+                convert_font, convert_main_color, css_wide_keyword, has_scoped_custom_properties,
+                has_sibling_combinator, has_structural_pseudo_class, is_custom_property, is_root,
+                substitute_property_vars, uses_vars, CustomProperties, PseudoClass, StyleKey,
                 StyleSelector,
             },
+            icons::{default_icon_provider, render_svg_tree, scale_pixmap, IconProvider},
+            inline::InlineStyle,
         },
+        system::OrWarn,
         types::{LogicalPixels, Point},
         Pixmap,
     },
     anyhow::{anyhow, bail, Context, Result},
+    cosmic_text::{Attrs, AttrsOwned, FamilyOwned, FontSystem, Weight},
+    itertools::Itertools,
     lightningcss::{
-        properties::Property, rules::CssRule, selector::Selector, stylesheet::StyleSheet,
+        properties::{CSSWideKeyword, Property},
+        rules::CssRule,
+        selector::Selector,
+        stylesheet::StyleSheet,
     },
     ordered_float::OrderedFloat,
     serde::{Deserialize, Serialize},
     std::{
         any::{Any, TypeId},
         borrow::Cow,
-        cell::RefCell,
+        cell::{OnceCell, RefCell},
         collections::HashMap,
         fmt::Debug,
         hash::Hash,
         path::{Path, PathBuf},
         rc::Rc,
+        sync::atomic::{AtomicU64, Ordering},
+        time::SystemTime,
     },
     tiny_skia::Color,
     tracing::warn,
@@ -33,6 +46,8 @@ use {
 pub mod common;
 pub mod css;
 pub mod defaults;
+pub mod icons;
+pub mod inline;
 pub mod text_input;
 
 pub trait ElementState: Eq + Hash + Sized {
@@ -52,7 +67,7 @@ impl RelativeOffset {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub struct Padding {
     pub x: LogicalPixels,
     pub y: LogicalPixels,
@@ -67,11 +82,36 @@ impl Padding {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum FontSlant {
+    #[default]
+    Normal,
+    Italic,
+    Oblique,
+}
+
+/// A font family specified by `font-family`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FontFamily {
+    /// A generic family, stored as its CSS name (e.g. `sans-serif`).
+    Generic(String),
+    /// A family name. Quoted names are always family names, even if they match
+    /// the name of a generic family (e.g. `"serif"`).
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FontStyle {
     pub font_size: LogicalPixels,
     pub line_height: LogicalPixels,
-    // TODO: font family, attributes, etc.
+    /// Font families in order of preference, as specified by `font-family`.
+    ///
+    /// Fonts added with [AppBuilder::with_font](crate::AppBuilder::with_font)
+    /// can be referenced by their family names.
+    pub families: Vec<FontFamily>,
+    pub weight: u16,
+    pub slant: FontSlant,
+    pub letter_spacing: LogicalPixels,
 }
 
 impl FontStyle {
@@ -81,59 +121,236 @@ impl FontStyle {
             line_height: self.line_height.to_physical(scale).to_i32() as f32,
         }
     }
+
+    /// Returns the first family from [families](Self::families) that is a generic family
+    /// or is available in `font_system`.
+    ///
+    /// Returns the default sans-serif family if none of the families are available.
+    pub fn resolve_family(&self, font_system: &FontSystem) -> FamilyOwned {
+        for family in &self.families {
+            match family {
+                FontFamily::Generic(name) => {
+                    let generic = match name.to_ascii_lowercase().as_str() {
+                        "serif" | "ui-serif" => FamilyOwned::Serif,
+                        "sans-serif" | "ui-sans-serif" | "system-ui" => FamilyOwned::SansSerif,
+                        "monospace" | "ui-monospace" => FamilyOwned::Monospace,
+                        "cursive" => FamilyOwned::Cursive,
+                        "fantasy" => FamilyOwned::Fantasy,
+                        _ => continue,
+                    };
+                    return generic;
+                }
+                FontFamily::Name(name) => {
+                    let is_available = font_system.db().faces().any(|face| {
+                        face.families
+                            .iter()
+                            .any(|(face_name, _)| face_name.eq_ignore_ascii_case(name))
+                    });
+                    if is_available {
+                        return FamilyOwned::Name(name.into());
+                    }
+                }
+            }
+        }
+        FamilyOwned::SansSerif
+    }
+
+    /// Returns text attributes corresponding to this font style.
+    pub fn to_attrs(&self, font_system: &FontSystem) -> AttrsOwned {
+        self.to_attrs_with_family(self.resolve_family(font_system))
+    }
+
+    // Same as `to_attrs` but with an already resolved family.
+    pub(crate) fn to_attrs_with_family(&self, family: FamilyOwned) -> AttrsOwned {
+        let mut attrs = Attrs::new()
+            .family(family.as_family())
+            .weight(Weight(self.weight))
+            .style(match self.slant {
+                FontSlant::Normal => cosmic_text::Style::Normal,
+                FontSlant::Italic => cosmic_text::Style::Italic,
+                FontSlant::Oblique => cosmic_text::Style::Oblique,
+            });
+        let font_size = f32::from(self.font_size);
+        let letter_spacing = f32::from(self.letter_spacing);
+        if letter_spacing != 0.0 && font_size > 0.0 {
+            // cosmic-text expects letter spacing in ems.
+            attrs = attrs.letter_spacing(letter_spacing / font_size);
+        }
+        AttrsOwned::new(&attrs)
+    }
 }
 
 // TODO: not pub
 #[derive(Debug)]
 pub enum StyleSource {
     File {
+        path: PathBuf,
         parent_dir: PathBuf,
+        // Modification time of the file at the moment it was loaded.
+        modified: Option<SystemTime>,
     },
     Bundle {
         files: HashMap<&'static str, &'static [u8]>,
     },
 }
 
-type CacheKey = (StyleSelector, OrderedFloat<f32>, TypeId);
+type CacheKey = (StyleKey, OrderedFloat<f32>, TypeId);
+// Scope of the custom properties and the index of the declaration in the style sheet.
+type SubstitutionCacheKey = (Option<StyleKey>, usize);
+type PixmapCacheKey = (String, OrderedFloat<f32>);
+type NamedIconCacheKey = (String, OrderedFloat<f32>, OrderedFloat<f32>);
+// Indices of the matching declarations in the style sheet.
+type SelectorRulesCacheKey = Vec<usize>;
+
+// Computed styles are discarded when the number of style keys exceeds this limit.
+// Otherwise widgets with frequently changing inline styles would fill the caches indefinitely.
+const MAX_STYLE_KEYS: usize = 10_000;
+
+// Everything that affects the computed style of an element. See `Style::style_key`.
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct StyleKeyData {
+    parent: Option<StyleKey>,
+    // Indices of the selectors matching the element or any of its variants.
+    selectors: Vec<usize>,
+    inline_selectors: Vec<usize>,
+    inline: Option<Rc<InlineStyle>>,
+    custom_pseudo_classes: Vec<PseudoClass>,
+    pseudo_element: Option<String>,
+}
+
+#[derive(Debug)]
+struct StyleKeys {
+    // Unique for each set of keys. Keys memoized in selectors are ignored
+    // if they belong to another generation.
+    generation: u64,
+    keys: HashMap<StyleKeyData, StyleKey>,
+}
+
+impl Default for StyleKeys {
+    fn default() -> Self {
+        static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+        Self {
+            generation: NEXT_GENERATION.fetch_add(1, Ordering::Relaxed),
+            keys: HashMap::new(),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Style {
     pub css: Rc<StyleSheet<'static, 'static>>,
     pub source: Rc<StyleSource>,
+    has_sibling_combinators: bool,
+    has_structural_pseudo_classes: bool,
+    has_scoped_custom_properties: bool,
+    root_custom_properties: Rc<CustomProperties>,
+    // Computed on first use.
+    root: Rc<OnceCell<RootStyle>>,
 
+    keys: Rc<RefCell<StyleKeys>>,
     cache: Rc<RefCell<HashMap<CacheKey, Box<dyn Any>>>>,
+    pixmap_cache: Rc<RefCell<HashMap<PixmapCacheKey, Pixmap>>>,
+    rules_cache: Rc<RefCell<HashMap<StyleKey, Rc<[Property<'static>]>>>>,
+    selector_rules_cache: Rc<RefCell<HashMap<SelectorRulesCacheKey, Rc<[Property<'static>]>>>>,
+    substitution_cache: Rc<RefCell<HashMap<SubstitutionCacheKey, Option<Property<'static>>>>>,
+    inherited_cache: Rc<RefCell<HashMap<StyleKey, Rc<InheritedStyle>>>>,
+    custom_properties_cache: Rc<RefCell<HashMap<StyleKey, VarScope>>>,
+    named_icon_cache: Rc<RefCell<HashMap<NamedIconCacheKey, (Pixmap, bool)>>>,
+    icon_provider: Rc<dyn IconProvider>,
 }
 
-fn find_rules<'a>(
+// A declaration as it's written in a style sheet. Declarations of the app's style
+// have an index that identifies them in the substitution cache.
+type Declaration<'a> = (Option<usize>, &'a Property<'static>);
+
+// Returns declarations of the rules matching `check_selector` along with their indices.
+// Declarations of all style rules are numbered in order.
+fn find_declarations<'a>(
     style: &'a StyleSheet<'static, 'static>,
     check_selector: impl Fn(&Selector<'static>) -> bool,
-) -> Vec<&'a Property<'static>> {
+) -> Vec<(usize, &'a Property<'static>)> {
     let mut results = Vec::new();
+    let mut first_index = 0;
     for rule in &style.rules.0 {
         if let CssRule::Style(rule) = rule {
-            for selector in &rule.selectors.0 {
-                if check_selector(selector) {
-                    let specificity = selector.specificity();
-                    results.extend(
-                        rule.declarations
-                            .iter()
-                            .map(|(dec, important)| (important, specificity, dec)),
-                    );
-                }
+            // If multiple selectors of the rule match, the most specific one determines
+            // the priority of the declarations.
+            let specificity = rule
+                .selectors
+                .0
+                .iter()
+                .filter(|selector| check_selector(selector))
+                .map(|selector| selector.specificity())
+                .max();
+            if let Some(specificity) = specificity {
+                results.extend(rule.declarations.iter().enumerate().map(
+                    |(index, (dec, important))| (important, specificity, first_index + index, dec),
+                ));
             }
+            first_index += rule.declarations.len();
         }
     }
     // Use stable sort because later statements should take priority.
-    results.sort_by_key(|(important, specificity, _dec)| (*important, *specificity));
-    results.into_iter().map(|(_, _, dec)| dec).collect()
+    results.sort_by_key(|(important, specificity, _index, _dec)| (*important, *specificity));
+    results
+        .into_iter()
+        .map(|(_, _, index, dec)| (index, dec))
+        .collect()
+}
+
+fn find_rules<'a>(
+    style: &'a StyleSheet<'static, 'static>,
+    check_selector: impl Fn(&Selector<'static>) -> bool,
+) -> Vec<&'a Property<'static>> {
+    find_declarations(style, check_selector)
+        .into_iter()
+        .map(|(_, dec)| dec)
+        .collect()
+}
+
+// Returns indices of the selectors matching `check_selector`. Selectors of all style rules
+// are numbered in order.
+fn matching_selectors(
+    style: &StyleSheet<'static, 'static>,
+    check_selector: impl Fn(&Selector<'static>) -> bool,
+) -> Vec<usize> {
+    style
+        .rules
+        .0
+        .iter()
+        .filter_map(|rule| match rule {
+            CssRule::Style(rule) => Some(&rule.selectors.0),
+            _ => None,
+        })
+        .flatten()
+        .positions(check_selector)
+        .collect()
+}
+
+fn any_selector(
+    style: &StyleSheet<'static, 'static>,
+    check_selector: impl Fn(&Selector<'static>) -> bool,
+) -> bool {
+    style.rules.0.iter().any(|rule| {
+        if let CssRule::Style(rule) = rule {
+            rule.selectors.0.iter().any(&check_selector)
+        } else {
+            false
+        }
+    })
+}
+
+// Custom properties declared for `:root`.
+fn root_custom_properties(style: &StyleSheet<'static, 'static>) -> CustomProperties {
+    let mut output = CustomProperties::new();
+    collect_custom_properties(&find_rules(style, is_root), &mut output);
+    output
 }
 
 pub(crate) fn load_css(css: &str) -> Result<StyleSheet<'static, 'static>> {
-    let mut style = StyleSheet::parse(css, Default::default())
-        .map_err(|e| anyhow!("failed to parse css: {e}"))?;
-    replace_vars(&mut style);
-    let code = style.to_css(Default::default())?.code;
-    let style = StyleSheet::parse(&code, Default::default())
+    // `var()` references are kept as is and substituted when styles are computed
+    // because custom properties can be redefined for specific elements.
+    let style = StyleSheet::parse(css, Default::default())
         .map_err(|e| anyhow!("failed to parse css: {e}"))?;
     // There is no StyleSheet::into_owned, so we have to use serialization :(
     let serialized = serde_value::to_value(&style)?;
@@ -143,68 +360,256 @@ pub(crate) fn load_css(css: &str) -> Result<StyleSheet<'static, 'static>> {
 }
 
 impl Style {
+    fn new(css: StyleSheet<'static, 'static>, source: StyleSource) -> Self {
+        Self {
+            has_sibling_combinators: any_selector(&css, has_sibling_combinator),
+            has_structural_pseudo_classes: any_selector(&css, has_structural_pseudo_class),
+            has_scoped_custom_properties: has_scoped_custom_properties(&css),
+            root_custom_properties: Rc::new(root_custom_properties(&css)),
+            root: Default::default(),
+            css: Rc::new(css),
+            source: Rc::new(source),
+            keys: Default::default(),
+            cache: Default::default(),
+            pixmap_cache: Default::default(),
+            rules_cache: Default::default(),
+            selector_rules_cache: Default::default(),
+            substitution_cache: Default::default(),
+            inherited_cache: Default::default(),
+            custom_properties_cache: Default::default(),
+            named_icon_cache: Default::default(),
+            icon_provider: default_icon_provider(),
+        }
+    }
+
     pub fn load_bundled(
         css: &str,
         files: impl IntoIterator<Item = (&'static str, &'static [u8])>,
     ) -> Result<Self> {
-        Ok(Self {
-            css: Rc::new(load_css(css)?),
-            source: Rc::new(StyleSource::Bundle {
+        let css = load_css(css)?;
+        Ok(Self::new(
+            css,
+            StyleSource::Bundle {
                 files: files.into_iter().collect(),
-            }),
-            cache: Default::default(),
-        })
+            },
+        ))
     }
 
     pub fn load_from_file(css_path: &Path) -> Result<Style> {
-        let css = fs_err::read_to_string(css_path)?;
+        let modified = fs_err::metadata(css_path)?.modified().ok();
+        let css = load_css(&fs_err::read_to_string(css_path)?)?;
 
-        Ok(Self {
-            css: Rc::new(load_css(&css)?),
-            source: Rc::new(StyleSource::File {
+        Ok(Self::new(
+            css,
+            StyleSource::File {
+                path: css_path.into(),
                 parent_dir: css_path
                     .parent()
                     .context("invalid css path (couldn't get parent)")?
                     .into(),
-            }),
-            cache: Default::default(),
-        })
-    }
-
-    pub fn find_rules_for_element(&self, element: &StyleSelector) -> Vec<&Property<'static>> {
-        self.find_rules(|selector| element.matches(selector))
+                modified,
+            },
+        ))
     }
 
+    /// Returns declarations of the rules matching `check_selector`.
+    ///
+    /// `var()` references are substituted using custom properties declared for `:root`.
+    /// The result is cached for each set of matching declarations.
     pub fn find_rules(
         &self,
         check_selector: impl Fn(&Selector<'static>) -> bool,
-    ) -> Vec<&Property<'static>> {
-        find_rules(&self.css, check_selector)
+    ) -> Rc<[Property<'static>]> {
+        let declarations = find_declarations(&self.css, check_selector);
+        let key = declarations.iter().map(|(index, _)| *index).collect_vec();
+        if let Some(rules) = self.selector_rules_cache.borrow().get(&key) {
+            return rules.clone();
+        }
+        let declarations = declarations
+            .into_iter()
+            .map(|(index, dec)| (Some(index), dec))
+            .collect();
+        let rules: Rc<[Property<'static>]> = self
+            .substitute_vars(declarations, &self.root_var_scope())
+            .into();
+        self.selector_rules_cache
+            .borrow_mut()
+            .insert(key, rules.clone());
+        rules
+    }
+
+    fn root_var_scope(&self) -> VarScope {
+        VarScope {
+            owner: None,
+            vars: self.root_custom_properties.clone(),
+        }
+    }
+
+    // Replaces declarations containing `var()` references with the substituted values.
+    // Declarations that become invalid after substitution are dropped.
+    //
+    // Substituted declarations of the app's style are cached for each scope of custom properties.
+    fn substitute_vars(
+        &self,
+        declarations: Vec<Declaration<'_>>,
+        scope: &VarScope,
+    ) -> Vec<Property<'static>> {
+        declarations
+            .into_iter()
+            .filter_map(|(index, property)| {
+                if !uses_vars(property) {
+                    return Some(property.clone());
+                }
+                let Some(index) = index else {
+                    return substitute_property_vars(property, &scope.vars).or_warn();
+                };
+                let key = (scope.owner, index);
+                if let Some(property) = self.substitution_cache.borrow().get(&key) {
+                    return property.clone();
+                }
+                let substituted = substitute_property_vars(property, &scope.vars).or_warn();
+                self.substitution_cache
+                    .borrow_mut()
+                    .insert(key, substituted.clone());
+                substituted
+            })
+            .collect()
+    }
+
+    fn root(&self) -> &RootStyle {
+        self.root.get_or_init(|| {
+            let rules = self.find_rules(is_root);
+            RootStyle {
+                font: convert_font(&rules, None),
+                color: convert_main_color(&rules).unwrap_or_else(|| {
+                    warn!("missing 'color' property for :root in style");
+                    defaults::text_color()
+                }),
+                background_color: convert_background_color(&rules)
+                    .unwrap_or_else(defaults::background_color),
+            }
+        })
+    }
+
+    /// Returns the path to the CSS file if the style was loaded with [load_from_file](Self::load_from_file).
+    pub fn file_path(&self) -> Option<&Path> {
+        match &*self.source {
+            StyleSource::File { path, .. } => Some(path),
+            StyleSource::Bundle { .. } => None,
+        }
+    }
+
+    /// Returns the modification time of the CSS file at the moment it was loaded.
+    pub fn file_modified_time(&self) -> Option<SystemTime> {
+        match &*self.source {
+            StyleSource::File { modified, .. } => *modified,
+            StyleSource::Bundle { .. } => None,
+        }
+    }
+
+    pub(crate) fn clear_cache(&self) {
+        self.clear_computed_styles();
+        self.pixmap_cache.borrow_mut().clear();
+        self.named_icon_cache.borrow_mut().clear();
+    }
+
+    fn clear_computed_styles(&self) {
+        *self.keys.borrow_mut() = StyleKeys::default();
+        self.cache.borrow_mut().clear();
+        self.rules_cache.borrow_mut().clear();
+        self.selector_rules_cache.borrow_mut().clear();
+        self.substitution_cache.borrow_mut().clear();
+        self.inherited_cache.borrow_mut().clear();
+        self.custom_properties_cache.borrow_mut().clear();
+    }
+
+    /// Returns the key identifying the computed style of `element`.
+    ///
+    /// Elements have equal keys if they match the same selectors, have equal inline styles
+    /// and their parents have equal keys. Selectors that would match variants of the element
+    /// with additional custom pseudo-classes or a pseudo-element (e.g. `:min` or `::gutter`)
+    /// are also taken into account, so styles of the variants are equal as well.
+    pub(crate) fn style_key(&self, element: &StyleSelector) -> StyleKey {
+        let generation = self.keys.borrow().generation;
+        if let Some(key) = element.memoized_key(generation) {
+            return key;
+        }
+        let parent = element.parent().map(|parent| self.style_key(parent));
+        let check_selector = |selector: &Selector<'static>| element.matches_variants(selector);
+        let data = StyleKeyData {
+            parent,
+            selectors: matching_selectors(&self.css, check_selector),
+            inline_selectors: element
+                .inline_style()
+                .map(|inline| inline.matching_selectors(check_selector))
+                .unwrap_or_default(),
+            inline: element.shared_inline_style().cloned(),
+            custom_pseudo_classes: element
+                .pseudo_classes()
+                .iter()
+                .filter(|class| matches!(class, PseudoClass::Custom(_)))
+                .cloned()
+                .collect(),
+            pseudo_element: element.pseudo_element().map(Into::into),
+        };
+        let mut keys = self.keys.borrow_mut();
+        let next_key = StyleKey(keys.keys.len() as u32);
+        let key = *keys.keys.entry(data).or_insert(next_key);
+        element.memoize_key(generation, key);
+        key
+    }
+
+    /// Returns the provider used to resolve named icons (`icon: theme("name")`).
+    pub fn icon_provider(&self) -> &Rc<dyn IconProvider> {
+        &self.icon_provider
+    }
+
+    /// Sets the provider used to resolve named icons (`icon: theme("name")`).
+    ///
+    /// The app replaces the provider of its style with the one set by
+    /// [AppBuilder::with_icon_provider](crate::AppBuilder::with_icon_provider).
+    pub fn set_icon_provider(&mut self, provider: Rc<dyn IconProvider>) -> &mut Self {
+        self.icon_provider = provider;
+        self.named_icon_cache = Default::default();
+        self
+    }
+
+    /// True if any selector in the style sheet uses `+` or `~` combinators.
+    ///
+    /// Selectors of sibling widgets are only tracked if this is true.
+    pub fn has_sibling_combinators(&self) -> bool {
+        self.has_sibling_combinators
+    }
+
+    /// True if any selector in the style sheet uses `:first-child`, `:last-child`,
+    /// `:only-child` or `:nth-child()`.
+    ///
+    /// Positions of widgets among their siblings are only tracked if this is true.
+    pub fn has_structural_pseudo_classes(&self) -> bool {
+        self.has_structural_pseudo_classes
+    }
+
+    /// True if styles of widgets may depend on their siblings.
+    pub(crate) fn depends_on_siblings(&self) -> bool {
+        self.has_sibling_combinators || self.has_structural_pseudo_classes
     }
 
-    // TODO: cache?
     pub fn root_font_style(&self) -> FontStyle {
-        let rules = self.find_rules(is_root);
-        convert_font(&rules, None)
+        self.root().font.clone()
     }
 
     pub fn root_background_color(&self) -> Color {
-        let rules = self.find_rules(is_root);
-        convert_background_color(&rules).unwrap_or_else(defaults::background_color)
+        self.root().background_color
     }
 
     pub fn root_color(&self) -> Color {
-        let rules = self.find_rules(is_root);
-        convert_main_color(&rules).unwrap_or_else(|| {
-            warn!("missing 'color' property for :root in style");
-            defaults::text_color()
-        })
+        self.root().color
     }
 
     pub fn load_resource(&self, path: &str) -> Result<Cow<'static, [u8]>> {
         match &*self.source {
             // TODO: forbid "../", allow only simple paths
-            StyleSource::File { parent_dir } => {
+            StyleSource::File { parent_dir, .. } => {
                 let path = parent_dir.join(path);
                 Ok(Cow::Owned(fs_err::read(path)?))
             }
@@ -216,43 +621,89 @@ impl Style {
         }
     }
 
+    /// Loads an SVG or PNG image from the style's resources and renders it at the specified scale.
+    ///
+    /// Loaded images are cached per path and scale.
     pub fn load_pixmap(&self, path: &str, scale: f32) -> Result<Pixmap> {
-        // TODO: cache pixmaps
-        // TODO: support png
-        if !path.ends_with(".svg") {
-            bail!("only svg is currently supported");
+        let key = (path.to_string(), OrderedFloat(scale));
+        if let Some(pixmap) = self.pixmap_cache.borrow().get(&key) {
+            return Ok(pixmap.clone());
         }
-        let data = self.load_resource(path)?;
+        let pixmap: Pixmap = if path.ends_with(".svg") {
+            self.load_svg(path, scale)?
+        } else if path.ends_with(".png") {
+            self.load_png(path, scale)?
+        } else {
+            bail!("unsupported image format (use svg or png): {path:?}");
+        }
+        .into();
+        self.pixmap_cache.borrow_mut().insert(key, pixmap.clone());
+        Ok(pixmap)
+    }
 
+    /// Loads a named icon from the [icon provider](Self::icon_provider) and renders it
+    /// to fit into a square with the side of `size` at the specified scale.
+    ///
+    /// Returns the pixmap and a flag indicating whether the icon is symbolic.
+    /// Loaded icons are cached per name, size and scale.
+    pub fn load_named_icon(
+        &self,
+        name: &str,
+        size: LogicalPixels,
+        scale: f32,
+    ) -> Result<(Pixmap, bool)> {
+        let key = (
+            name.to_string(),
+            OrderedFloat(size.to_f32()),
+            OrderedFloat(scale),
+        );
+        if let Some(icon) = self.named_icon_cache.borrow().get(&key) {
+            return Ok(icon.clone());
+        }
+        let icon_size = size.to_f32().round().max(1.0) as u32;
+        let icon_scale = scale.ceil().max(1.0) as u32;
+        let data = self
+            .icon_provider
+            .find_icon(name, icon_size, icon_scale)
+            .with_context(|| format!("icon not found: {name:?}"))?;
+        let pixmap = data.render(size.to_physical(scale).to_i32().max(1) as u32)?;
+        let icon = (pixmap, data.symbolic);
+        self.named_icon_cache.borrow_mut().insert(key, icon.clone());
+        Ok(icon)
+    }
+
+    fn load_svg(&self, path: &str, scale: f32) -> Result<tiny_skia::Pixmap> {
+        let data = self.load_resource(path)?;
         let tree = usvg::Tree::from_data(&data, &Default::default())?;
+        render_svg_tree(&tree, scale)
+    }
 
-        let pixmap_size_x = (tree.size().width() * scale).ceil() as u32;
-        let pixmap_size_y = (tree.size().height() * scale).ceil() as u32;
-        let mut pixmap = tiny_skia::Pixmap::new(pixmap_size_x, pixmap_size_y).unwrap();
-        resvg::render(
-            &tree,
-            tiny_skia::Transform::from_scale(scale, scale),
-            &mut pixmap.as_mut(),
-        );
-        Ok(pixmap.into())
+    // If the scale is greater than 1 and there is a `name@2x.png` file next to `name.png`,
+    // the high resolution version is used.
+    fn load_png(&self, path: &str, scale: f32) -> Result<tiny_skia::Pixmap> {
+        let high_res_data = if scale > 1.0 {
+            let high_res_path = format!("{}@2x.png", path.trim_end_matches(".png"));
+            self.load_resource(&high_res_path).ok()
+        } else {
+            None
+        };
+        let (data, image_scale) = match high_res_data {
+            Some(data) => (data, 2.0),
+            None => (self.load_resource(path)?, 1.0),
+        };
+        let image = tiny_skia::Pixmap::decode_png(&data)?;
+        scale_pixmap(image, scale / image_scale)
     }
 
-    pub fn get<T: ComputedElementStyle>(
-        &mut self,
-        element: &StyleSelector,
-        scale: f32,
-        custom_style: Option<&StyleSheet<'static, 'static>>,
-    ) -> Rc<T> {
-        if let Some(custom_style) = custom_style {
-            let styles = Styles {
-                main: self,
-                custom: Some(custom_style),
-            };
-            // TODO: avoid unneeded Rc
-            return Rc::new(T::new(&styles, element, scale));
+    /// Computes the style of `element`.
+    ///
+    /// Computed styles are cached per [style key](Self::style_key), scale and type.
+    pub fn get<T: ComputedElementStyle>(&self, element: &StyleSelector, scale: f32) -> Rc<T> {
+        if self.keys.borrow().keys.len() > MAX_STYLE_KEYS {
+            self.clear_computed_styles();
         }
         let type_id = TypeId::of::<T>();
-        let key = (element.clone(), OrderedFloat(scale), type_id);
+        let key = (self.style_key(element), OrderedFloat(scale), type_id);
         {
             if let Some(data) = self.cache.borrow().get(&key) {
                 return data
@@ -261,43 +712,228 @@ impl Style {
                     .clone();
             }
         }
-        let style = Rc::new(T::new(
-            &Styles {
-                main: self,
-                custom: None,
-            },
-            element,
-            scale,
-        ));
+        let styles = Styles::new(self, element.inline_style());
+        let style = Rc::new(T::new(&styles, element, scale));
         let style_clone = style.clone();
         self.cache.borrow_mut().insert(key, Box::new(style));
         style_clone
     }
 }
 
+// Values of inherited properties computed for an element.
+#[derive(Debug)]
+struct InheritedStyle {
+    color: Color,
+    font: FontStyle,
+}
+
+// Values computed from the declarations of `:root`.
+#[derive(Debug)]
+struct RootStyle {
+    font: FontStyle,
+    color: Color,
+    background_color: Color,
+}
+
+// Custom properties visible to an element.
+#[derive(Debug, Clone)]
+struct VarScope {
+    // Key of the closest element (the element itself or its ancestor) that declares custom
+    // properties, or `None` if only the properties declared for `:root` are visible.
+    owner: Option<StyleKey>,
+    vars: Rc<CustomProperties>,
+}
+
 #[derive(Debug)]
 pub struct Styles<'a> {
     main: &'a Style,
-    custom: Option<&'a StyleSheet<'static, 'static>>,
+    custom: Option<&'a InlineStyle>,
 }
 
 impl<'a> Styles<'a> {
-    pub fn find_rules_for_element(&self, element: &StyleSelector) -> Vec<&Property<'static>> {
-        self.find_rules(|selector| element.matches(selector))
+    fn new(main: &'a Style, custom: Option<&'a InlineStyle>) -> Self {
+        Self { main, custom }
+    }
+
+    /// Returns declarations that apply to `element`: matching rules of the app's style,
+    /// followed by the element's inline style.
+    ///
+    /// `var()` references are substituted using the [custom properties](Self::custom_properties)
+    /// of the element.
+    ///
+    /// `inherit`, `initial`, `unset` and `revert` keywords are resolved: earlier declarations of
+    /// the same property are dropped, and for `inherit`, declarations of the parent
+    /// element are used instead.
+    ///
+    /// The result is cached per [style key](Style::style_key).
+    pub fn find_rules_for_element(&self, element: &StyleSelector) -> Rc<[Property<'static>]> {
+        let key = self.main.style_key(element);
+        if let Some(rules) = self.main.rules_cache.borrow().get(&key) {
+            return rules.clone();
+        }
+        let declarations = self.declared_rules(element);
+        let scope = if declarations.iter().any(|(_, property)| uses_vars(property)) {
+            self.var_scope(element)
+        } else {
+            self.main.root_var_scope()
+        };
+        let rules = self.main.substitute_vars(declarations, &scope);
+        let rules: Rc<[Property<'static>]> = self.resolve_keywords(element, rules).into();
+        self.main
+            .rules_cache
+            .borrow_mut()
+            .insert(key, rules.clone());
+        rules
+    }
+
+    // Returns declarations that apply to `element` as they are written in the style sheets.
+    fn declared_rules<'b>(&'b self, element: &'b StyleSelector) -> Vec<Declaration<'b>> {
+        let check_selector = |selector: &Selector<'static>| element.matches(selector);
+        let mut rules = find_declarations(&self.main.css, check_selector)
+            .into_iter()
+            .map(|(index, dec)| (Some(index), dec))
+            .collect_vec();
+        if let Some(inline) = element.inline_style() {
+            rules.extend(
+                inline
+                    .find_rules(check_selector)
+                    .into_iter()
+                    .chain(inline.declarations())
+                    .map(|dec| (None, dec)),
+            );
+        }
+        rules
+    }
+
+    /// Returns custom properties (`--name: value;`) visible to `element`.
+    ///
+    /// They include properties declared for `:root`, the element's ancestors and
+    /// the element itself. Properties declared closer to the element take priority.
+    pub fn custom_properties(&self, element: &StyleSelector) -> Rc<CustomProperties> {
+        self.var_scope(element).vars
     }
 
+    fn var_scope(&self, element: &StyleSelector) -> VarScope {
+        if !self.main.has_scoped_custom_properties && !element.has_inline_custom_properties() {
+            return self.main.root_var_scope();
+        }
+        let key = self.main.style_key(element);
+        if let Some(scope) = self.main.custom_properties_cache.borrow().get(&key) {
+            return scope.clone();
+        }
+        let mut scope = match element.parent() {
+            Some(parent) => self.var_scope(parent),
+            None => self.main.root_var_scope(),
+        };
+        let declarations = self
+            .declared_rules(element)
+            .into_iter()
+            .map(|(_, dec)| dec)
+            .collect_vec();
+        if declarations.iter().any(|dec| is_custom_property(dec)) {
+            collect_custom_properties(&declarations, Rc::make_mut(&mut scope.vars));
+            scope.owner = Some(key);
+        }
+        self.main
+            .custom_properties_cache
+            .borrow_mut()
+            .insert(key, scope.clone());
+        scope
+    }
+
+    fn resolve_keywords(
+        &self,
+        element: &StyleSelector,
+        rules: Vec<Property<'static>>,
+    ) -> Vec<Property<'static>> {
+        if !rules
+            .iter()
+            .any(|property| css_wide_keyword(property).is_some())
+        {
+            return rules;
+        }
+        let mut output: Vec<Property<'static>> = Vec::new();
+        for property in rules {
+            let Some((id, keyword)) = css_wide_keyword(&property) else {
+                output.push(property);
+                continue;
+            };
+            let affects = |other: &Property<'static>| {
+                id.as_ref().is_none_or(|id| affects_property(id, other))
+            };
+            output.retain(|other| !affects(other));
+            if keyword == CSSWideKeyword::Inherit {
+                if let Some(parent) = element.parent() {
+                    output.extend(
+                        self.find_rules_for_element(parent)
+                            .iter()
+                            .filter(|other| affects(other))
+                            .cloned(),
+                    );
+                }
+            }
+        }
+        output
+    }
+
+    /// Returns declarations from the app's style and the inline style that match `check_selector`.
+    ///
+    /// `var()` references are substituted using custom properties declared for `:root`.
     pub fn find_rules(
         &self,
         check_selector: impl Fn(&Selector<'static>) -> bool,
-    ) -> Vec<&Property<'static>> {
-        let mut rules = self.main.find_rules(&check_selector);
-        if let Some(custom) = self.custom {
-            rules.extend(find_rules(custom, check_selector));
+    ) -> Rc<[Property<'static>]> {
+        let rules = self.main.find_rules(&check_selector);
+        let Some(custom) = self.custom else {
+            return rules;
+        };
+        let custom_declarations = custom
+            .find_rules(check_selector)
+            .into_iter()
+            .map(|dec| (None, dec))
+            .collect_vec();
+        if custom_declarations.is_empty() {
+            return rules;
         }
-        rules
+        let custom_rules = self
+            .main
+            .substitute_vars(custom_declarations, &self.main.root_var_scope());
+        rules.iter().cloned().chain(custom_rules).collect()
+    }
+
+    /// Returns the text color inherited by `element` from its parent.
+    pub fn inherited_color(&self, element: &StyleSelector) -> Color {
+        match element.parent() {
+            Some(parent) => self.inherited_style(parent).color,
+            None => self.root_color(),
+        }
+    }
+
+    /// Returns the font inherited by `element` from its parent.
+    pub fn inherited_font(&self, element: &StyleSelector) -> FontStyle {
+        match element.parent() {
+            Some(parent) => self.inherited_style(parent).font.clone(),
+            None => self.root_font_style(),
+        }
+    }
+
+    // Computes values of inherited properties for `element`.
+    fn inherited_style(&self, element: &StyleSelector) -> Rc<InheritedStyle> {
+        let key = self.main.style_key(element);
+        if let Some(style) = self.main.inherited_cache.borrow().get(&key) {
+            return style.clone();
+        }
+        let rules = self.find_rules_for_element(element);
+        let font = convert_font(&rules, Some(&self.inherited_font(element)));
+        let color = convert_main_color(&rules).unwrap_or_else(|| self.inherited_color(element));
+        let style = Rc::new(InheritedStyle { color, font });
+        self.main
+            .inherited_cache
+            .borrow_mut()
+            .insert(key, style.clone());
+        style
     }
 
-    // TODO: cache?
     pub fn root_font_style(&self) -> FontStyle {
         self.main.root_font_style()
     }
@@ -309,4 +945,378 @@ impl<'a> Styles<'a> {
     pub fn load_pixmap(&self, path: &str, scale: f32) -> Result<Pixmap> {
         self.main.load_pixmap(path, scale)
     }
+
+    pub fn load_named_icon(
+        &self,
+        name: &str,
+        size: LogicalPixels,
+        scale: f32,
+    ) -> Result<(Pixmap, bool)> {
+        self.main.load_named_icon(name, size, scale)
+    }
+}
+
+#[test]
+fn style_cache() {
+    use crate::style::css::convert_main_color;
+
+    struct TestStyle {
+        color: Color,
+    }
+
+    impl ComputedElementStyle for TestStyle {
+        fn new(style: &Styles, element: &StyleSelector, _scale: f32) -> Self {
+            let rules = style.find_rules_for_element(element);
+            Self {
+                color: convert_main_color(&rules).unwrap_or_else(|| style.inherited_color(element)),
+            }
+        }
+    }
+
+    fn element(tag: &str, parent: Option<&StyleSelector>) -> StyleSelector {
+        StyleSelector::new(tag.into()).with_context(parent.cloned().map(Rc::new), None, None)
+    }
+
+    fn inline(code: &str) -> Rc<InlineStyle> {
+        let mut inline = InlineStyle::default();
+        inline.set_code(Some(code)).unwrap();
+        Rc::new(inline)
+    }
+
+    let style = Style::load_bundled(
+        "
+        :root { color: black; }
+        Button { color: red; }
+        .dark Button { color: blue; }
+        Button:min { color: green; }
+        ",
+        [],
+    )
+    .unwrap();
+    let get = |element: &StyleSelector| style.get::<TestStyle>(element, 1.0);
+    let color = |element: &StyleSelector| get(element).color;
+    let red = Color::from_rgba8(255, 0, 0, 255);
+    let lime = Color::from_rgba8(0, 255, 0, 255);
+    let blue = Color::from_rgba8(0, 0, 255, 255);
+
+    let column = element("Column", None);
+    let row = element("Row", None).with_class("wide".into());
+    let dark_row = element("Row", None).with_class("dark".into());
+
+    let button = element("Button", Some(&column));
+    let computed = get(&button);
+    assert_eq!(computed.color, red);
+    // Ancestors that don't affect matching rules don't prevent cache hits.
+    let other_button = element("Button", Some(&row));
+    assert_eq!(style.style_key(&button), style.style_key(&other_button));
+    assert!(Rc::ptr_eq(&get(&other_button), &computed));
+    let dark = get(&element("Button", Some(&dark_row)));
+    assert_eq!(dark.color, blue);
+    assert!(!Rc::ptr_eq(&dark, &computed));
+
+    // Variants with custom pseudo-classes are matched by their own rules.
+    let min = button
+        .clone()
+        .with_pseudo_class(PseudoClass::Custom("min".into()));
+    assert_ne!(style.style_key(&min), style.style_key(&button));
+    assert_eq!(color(&min), Color::from_rgba8(0, 128, 0, 255));
+
+    style.clear_cache();
+    let recomputed = get(&other_button);
+    assert_eq!(recomputed.color, red);
+    assert!(!Rc::ptr_eq(&recomputed, &computed));
+
+    // Inline styles override the rules of the app's style.
+    let mut inline_button = element("Button", Some(&column));
+    inline_button.set_inline_style(Some(inline("color: #00ff00;")));
+    let inline_computed = get(&inline_button);
+    assert_eq!(inline_computed.color, lime);
+    // Elements with equal inline styles share the computed style.
+    let mut other_inline_button = element("Button", Some(&row));
+    other_inline_button.set_inline_style(Some(inline("color: #00ff00;")));
+    assert!(Rc::ptr_eq(&get(&other_inline_button), &inline_computed));
+    // Changing the inline style invalidates the key.
+    inline_button.set_inline_style(None);
+    assert_eq!(color(&inline_button), red);
+
+    // Rules of an inline style sheet only apply if they match the element.
+    let mut hover_button = element("Button", Some(&column));
+    hover_button.set_inline_style(Some(inline("Button:hover { color: #0000ff; }")));
+    assert_eq!(color(&hover_button), red);
+    hover_button.add_pseudo_class(PseudoClass::Hover);
+    assert_eq!(color(&hover_button), blue);
+
+    // Inline styles of ancestors are inherited.
+    let mut styled_column = element("Column", None);
+    styled_column.set_inline_style(Some(inline("color: white;")));
+    assert_eq!(color(&element("Label", Some(&styled_column))), Color::WHITE);
+    assert_eq!(color(&element("Label", Some(&column))), Color::BLACK);
+    // Descendants of elements with inline styles are cached as well.
+    let styled_button = |padding: &str| {
+        let mut window = element("Window", None);
+        window.set_inline_style(Some(inline(padding)));
+        element("Button", Some(&element("Column", Some(&window))))
+    };
+    let styled_computed = get(&styled_button("padding: 3px;"));
+    assert_eq!(styled_computed.color, red);
+    assert!(Rc::ptr_eq(
+        &get(&styled_button("padding: 3px;")),
+        &styled_computed
+    ));
+    assert!(!Rc::ptr_eq(
+        &get(&styled_button("padding: 4px;")),
+        &styled_computed
+    ));
+}
+
+#[test]
+fn scoped_custom_properties() {
+    use crate::style::css::{convert_background_color, convert_main_color, is_selection};
+
+    let style = Style::load_bundled(
+        "
+        :root { --accent: red; --text: black; color: var(--text); }
+        ::selection { background-color: var(--accent); }
+        Button { color: var(--accent); }
+        .dark { --accent: blue; }
+        .wide Button { padding: 2px; }
+        Label { color: inherit; }
+        ",
+        [],
+    )
+    .unwrap();
+    let styles = Styles::new(&style, None);
+    let color = |element: &StyleSelector| {
+        convert_main_color(&styles.find_rules_for_element(element)).unwrap()
+    };
+    let red = Color::from_rgba8(255, 0, 0, 255);
+    let blue = Color::from_rgba8(0, 0, 255, 255);
+
+    assert_eq!(style.root_color(), Color::BLACK);
+    assert_eq!(
+        convert_background_color(&style.find_rules(is_selection)),
+        Some(red)
+    );
+    // Declarations are substituted once and shared by subsequent lookups.
+    assert!(Rc::ptr_eq(
+        &style.find_rules(is_selection),
+        &style.find_rules(is_selection)
+    ));
+
+    let column = Rc::new(StyleSelector::new("Column".into()));
+    let row = Rc::new(StyleSelector::new("Row".into()).with_class("wide".into()));
+    let dark = Rc::new(StyleSelector::new("Row".into()).with_class("dark".into()));
+    let button = |parent: &Rc<StyleSelector>| {
+        StyleSelector::new("Button".into()).with_context(Some(parent.clone()), None, None)
+    };
+    assert_eq!(color(&button(&column)), red);
+    assert_eq!(color(&button(&dark)), blue);
+    // The substituted declaration is shared by elements in the same scope.
+    let substitutions = style.substitution_cache.borrow().len();
+    assert_ne!(
+        style.style_key(&button(&row)),
+        style.style_key(&button(&column))
+    );
+    assert_eq!(color(&button(&row)), red);
+    assert_eq!(style.substitution_cache.borrow().len(), substitutions);
+
+    // `inherit` uses the substituted declarations of the parent.
+    let dark_button = Rc::new(button(&dark));
+    let label = StyleSelector::new("Label".into()).with_context(Some(dark_button), None, None);
+    assert_eq!(color(&label), blue);
+
+    // Custom properties can be declared in inline styles.
+    let mut inline = InlineStyle::default();
+    inline.set_code(Some("--accent: blue;")).unwrap();
+    let mut styled_column = StyleSelector::new("Column".into());
+    styled_column.set_inline_style(Some(Rc::new(inline)));
+    assert_eq!(color(&button(&Rc::new(styled_column))), blue);
+}
+
+#[test]
+fn relative_font_weight() {
+    let style = Style::load_bundled(
+        "
+        :root { font-size: 10px; line-height: 12px; font-weight: 300; }
+        Column { font-weight: bold; }
+        Label { font-weight: bolder; }
+        .light { font-weight: lighter; }
+        ",
+        [],
+    )
+    .unwrap();
+    let styles = Styles::new(&style, None);
+    let element = |tag: &str, parent: Option<&StyleSelector>| {
+        StyleSelector::new(tag.into()).with_context(parent.cloned().map(Rc::new), None, None)
+    };
+    let weight = |element: &StyleSelector| {
+        let rules = styles.find_rules_for_element(element);
+        convert_font(&rules, Some(&styles.inherited_font(element))).weight
+    };
+
+    // Relative weights are computed from the weight of the parent, not the root.
+    let column = element("Column", None);
+    let row = element("Row", None);
+    assert_eq!(weight(&column), 700);
+    assert_eq!(weight(&row), 300);
+    assert_eq!(weight(&element("Label", Some(&column))), 900);
+    assert_eq!(weight(&element("Label", Some(&row))), 400);
+    let light = element("Text", Some(&column)).with_class("light".into());
+    assert_eq!(weight(&light), 400);
+    assert_eq!(weight(&element("Label", Some(&light))), 700);
+}
+
+#[cfg(test)]
+fn test_png(size: u32, color: Color) -> &'static [u8] {
+    let mut pixmap = tiny_skia::Pixmap::new(size, size).unwrap();
+    pixmap.fill(color);
+    Box::leak(pixmap.encode_png().unwrap().into_boxed_slice())
+}
+
+#[test]
+fn load_pixmap() {
+    let red = Color::from_rgba8(255, 0, 0, 255);
+    let blue = Color::from_rgba8(0, 0, 255, 255);
+    let style = Style::load_bundled(
+        "",
+        [
+            ("icon.png", test_png(4, red)),
+            ("icon@2x.png", test_png(8, blue)),
+            ("plain.png", test_png(4, red)),
+        ],
+    )
+    .unwrap();
+    let load = |path: &str, scale: f32| {
+        let pixmap = style.load_pixmap(path, scale).unwrap();
+        let color = pixmap.as_tiny_skia_ref().pixel(1, 1).unwrap().demultiply();
+        let color = (color.red(), color.green(), color.blue());
+        (pixmap.size_x().to_i32(), color)
+    };
+
+    // The high resolution version is used for scales above 1.
+    assert_eq!(load("icon.png", 1.0), (4, (255, 0, 0)));
+    assert_eq!(load("icon.png", 2.0), (8, (0, 0, 255)));
+    assert_eq!(load("icon.png", 1.5), (6, (0, 0, 255)));
+    // Without a high resolution version, the image is scaled up.
+    assert_eq!(load("plain.png", 2.0), (8, (255, 0, 0)));
+    assert!(style.load_pixmap("missing.png", 1.0).is_err());
+
+    // Loaded images are cached per path and scale (`Pixmap` equality is identity).
+    let pixmap = style.load_pixmap("icon.png", 1.0).unwrap();
+    assert_eq!(style.load_pixmap("icon.png", 1.0).unwrap(), pixmap);
+    assert_ne!(style.load_pixmap("icon.png", 2.0).unwrap(), pixmap);
+    assert_ne!(style.load_pixmap("plain.png", 1.0).unwrap(), pixmap);
+    style.clear_cache();
+    assert_ne!(style.load_pixmap("icon.png", 1.0).unwrap(), pixmap);
+}
+
+#[test]
+fn symbolic_icons() {
+    use crate::style::{common::ComputedIconStyle, icons::is_symbolic_icon};
+
+    let black = Color::from_rgba8(0, 0, 0, 255);
+    let style = Style::load_bundled(
+        "
+        :root { color: black; }
+        Button { color: #ff0000; icon: url(go-up-symbolic.png); }
+        Label { color: #ff0000; icon: url(logo.png); }
+        ",
+        [
+            ("go-up-symbolic.png", test_png(4, black)),
+            ("go-up-symbolic@2x.png", test_png(8, black)),
+            ("logo.png", test_png(4, black)),
+        ],
+    )
+    .unwrap();
+    let icon_color = |tag: &str, scale: f32| {
+        let icon = style
+            .get::<ComputedIconStyle>(&StyleSelector::new(tag.into()), scale)
+            .icon
+            .clone()
+            .unwrap();
+        let color = icon.as_tiny_skia_ref().pixel(1, 1).unwrap().demultiply();
+        (color.red(), color.green(), color.blue(), color.alpha())
+    };
+    // Symbolic icons are tinted with the text color, other icons are drawn as is.
+    assert_eq!(icon_color("Button", 1.0), (255, 0, 0, 255));
+    assert_eq!(icon_color("Button", 2.0), (255, 0, 0, 255));
+    assert_eq!(icon_color("Label", 1.0), (0, 0, 0, 255));
+
+    assert!(is_symbolic_icon("go-up-symbolic.svg"));
+    assert!(is_symbolic_icon("icons/go-up-symbolic@2x.png"));
+    assert!(!is_symbolic_icon("symbolic-logo.png"));
+}
+
+#[test]
+fn inheritance() {
+    use crate::{
+        style::css::{convert_background_color, convert_main_color},
+        types::LpxSuffix,
+    };
+
+    let style = Style::load_bundled(
+        "
+        :root { color: black; font-size: 10px; line-height: 12px; font-family: sans-serif; }
+        Column {
+            color: #ff0000;
+            font-size: 20px;
+            line-height: 24px;
+            font-family: monospace;
+            font-style: italic;
+            background-color: #00ff00;
+        }
+        Button { color: #0000ff; background-color: inherit; }
+        .reset { color: inherit; }
+        ",
+        [],
+    )
+    .unwrap();
+    let styles = Styles::new(&style, None);
+    let element = |tag: &str, parent: Option<&StyleSelector>| {
+        StyleSelector::new(tag.into()).with_context(parent.cloned().map(Rc::new), None, None)
+    };
+    let color = |element: &StyleSelector| {
+        convert_main_color(&styles.find_rules_for_element(element))
+            .unwrap_or_else(|| styles.inherited_color(element))
+    };
+    let background =
+        |element: &StyleSelector| convert_background_color(&styles.find_rules_for_element(element));
+    let red = Color::from_rgba8(255, 0, 0, 255);
+    let lime = Color::from_rgba8(0, 255, 0, 255);
+    let blue = Color::from_rgba8(0, 0, 255, 255);
+
+    let column = element("Column", None);
+    let row = element("Row", None);
+
+    // Color and font are inherited from the parent, through any number of levels.
+    let label = element("Label", Some(&column));
+    let nested_label = element("Label", Some(&label));
+    assert_eq!(color(&label), red);
+    assert_eq!(color(&nested_label), red);
+    assert_eq!(color(&element("Label", Some(&row))), Color::BLACK);
+    for element in [&label, &nested_label] {
+        let font = styles.inherited_font(element);
+        assert_eq!(font.font_size, 20.0.lpx());
+        assert_eq!(font.line_height, 24.0.lpx());
+        assert_eq!(font.families, [FontFamily::Generic("monospace".into())]);
+        assert_eq!(font.slant, FontSlant::Italic);
+    }
+    let font = styles.inherited_font(&element("Label", Some(&row)));
+    assert_eq!(font.font_size, 10.0.lpx());
+    assert_eq!(font.families, [FontFamily::Generic("sans-serif".into())]);
+    assert_eq!(font.slant, FontSlant::Normal);
+    // Other properties are not inherited.
+    assert_eq!(background(&label), None);
+
+    // `inherit` takes the value declared for the parent.
+    let button = element("Button", Some(&column));
+    assert_eq!(color(&button), blue);
+    assert_eq!(background(&button), Some(lime));
+    assert_eq!(color(&element("Label", Some(&button))), blue);
+    let reset_button = element("Button", Some(&column)).with_class("reset".into());
+    assert_eq!(color(&reset_button), red);
+    // If the parent doesn't declare the property, the inherited value is used.
+    let reset_button = element("Button", Some(&row)).with_class("reset".into());
+    assert_eq!(color(&reset_button), Color::BLACK);
+    assert_eq!(background(&reset_button), None);
 }
@@ -1,8 +1,9 @@
 use {
     super::{
         css::{
-            convert_background, convert_border, convert_font, convert_main_color, convert_padding,
-            convert_zoom, is_root, PseudoClass, StyleSelector,
+            convert_background, convert_border, convert_box_shadow, convert_font, convert_icon,
+            convert_icon_size, convert_main_color, convert_outline, convert_padding, convert_zoom,
+            is_root, IconSource, PseudoClass, StyleSelector,
         },
         RelativeOffset,
     },
@@ -13,68 +14,166 @@ use {
                 convert_layout_ignores_border, convert_spacing, get_border_collapse,
                 get_text_alignment, get_vertical_alignment, is_root_min,
             },
-            defaults, Styles,
+            defaults,
+            icons::is_symbolic_icon,
+            Styles,
         },
         types::{LpxSuffix, PhysicalPixels, Point, PpxSuffix},
+        Pixmap,
     },
-    std::any::Any,
+    std::{any::Any, cmp::max},
     tiny_skia::{Color, GradientStop, SpreadMode},
     tracing::warn,
 };
 
-#[derive(Debug, Clone)]
-pub struct ComputedBorderStyle {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ComputedBorderSide {
     pub width: PhysicalPixels,
     pub color: Color,
-    pub radius: PhysicalPixels,
 }
 
-impl Default for ComputedBorderStyle {
+impl Default for ComputedBorderSide {
     fn default() -> Self {
         Self {
             width: Default::default(),
             color: Color::TRANSPARENT,
-            radius: Default::default(),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ComputedBorderRadius {
+    pub top_left: PhysicalPixels,
+    pub top_right: PhysicalPixels,
+    pub bottom_right: PhysicalPixels,
+    pub bottom_left: PhysicalPixels,
+}
+
+impl ComputedBorderRadius {
+    /// Returns radii of a box that is larger than this box by `amount` on each side.
+    ///
+    /// Corners without rounding stay sharp.
+    pub fn expand(&self, amount: PhysicalPixels) -> Self {
+        let expand = |radius: PhysicalPixels| {
+            if radius > 0.ppx() {
+                max(radius + amount, 0.ppx())
+            } else {
+                radius
+            }
+        };
+        Self {
+            top_left: expand(self.top_left),
+            top_right: expand(self.top_right),
+            bottom_right: expand(self.bottom_right),
+            bottom_left: expand(self.bottom_left),
+        }
+    }
+
+    /// Returns radii of the horizontally mirrored box.
+    pub fn mirrored(&self) -> Self {
+        Self {
+            top_left: self.top_right,
+            top_right: self.top_left,
+            bottom_right: self.bottom_left,
+            bottom_left: self.bottom_right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ComputedBorderStyle {
+    pub top: ComputedBorderSide,
+    pub right: ComputedBorderSide,
+    pub bottom: ComputedBorderSide,
+    pub left: ComputedBorderSide,
+    pub radius: ComputedBorderRadius,
+}
+
+impl ComputedBorderStyle {
+    /// Returns the border side if all sides have the same width and color.
+    pub fn uniform_side(&self) -> Option<ComputedBorderSide> {
+        (self.top == self.right && self.top == self.bottom && self.top == self.left)
+            .then_some(self.top)
+    }
+
+    /// Maximum of left and right border widths.
+    pub fn width_x(&self) -> PhysicalPixels {
+        max(self.left.width, self.right.width)
+    }
+
+    /// Maximum of top and bottom border widths.
+    pub fn width_y(&self) -> PhysicalPixels {
+        max(self.top.width, self.bottom.width)
+    }
+
+    /// Returns the border of the horizontally mirrored box: left and right sides
+    /// and the corresponding corners are swapped.
+    pub fn mirrored(&self) -> Self {
+        Self {
+            top: self.top,
+            right: self.left,
+            bottom: self.bottom,
+            left: self.right,
+            radius: self.radius.mirrored(),
+        }
+    }
+}
+
+/// A shadow specified by the `box-shadow` property. Inset shadows are not supported.
+#[derive(Debug, Clone)]
+pub struct ComputedBoxShadow {
+    pub offset: Point,
+    pub blur: PhysicalPixels,
+    pub spread: PhysicalPixels,
+    pub color: Color,
+}
+
+/// An outline specified by `outline` and `outline-offset` properties.
+///
+/// The outline is drawn outside of the widget's border and doesn't affect layout.
+#[derive(Debug, Clone)]
+pub struct ComputedOutline {
+    pub width: PhysicalPixels,
+    pub color: Color,
+    pub offset: PhysicalPixels,
+}
+
 #[derive(Debug)]
 pub(crate) struct BaseComputedStyle {
     pub(crate) border: ComputedBorderStyle,
     pub(crate) background: Option<ComputedBackground>,
+    pub(crate) box_shadows: Vec<ComputedBoxShadow>,
+    pub(crate) outline: Option<ComputedOutline>,
     pub(crate) font_metrics: cosmic_text::Metrics,
     pub(crate) grid: GridOptions,
 }
 
 impl ComputedElementStyle for BaseComputedStyle {
     fn new(style: &Styles, element: &StyleSelector, scale: f32) -> Self {
-        let rules = style.find_rules(|s| element.matches(s));
+        let rules = style.find_rules_for_element(element);
 
-        let mut rules_with_root = style.find_rules(is_root);
-        rules_with_root.extend(rules.clone());
+        let mut rules_with_root = style.find_rules(is_root).to_vec();
+        rules_with_root.extend(rules.iter().cloned());
         let element_min = element
             .clone()
             .with_pseudo_class(PseudoClass::Custom("min".into()));
-        let mut min_rules_with_root = style.find_rules(is_root_min);
-        min_rules_with_root.extend(style.find_rules(|s| element_min.matches(s)));
-        let properties_with_root =
-            style.find_rules(|selector| is_root(selector) || element.matches(selector));
+        let mut min_rules_with_root = style.find_rules(is_root_min).to_vec();
+        min_rules_with_root.extend(style.find_rules_for_element(&element_min).iter().cloned());
 
         let scale = scale * convert_zoom(&rules);
-        let font = convert_font(&rules, Some(&style.root_font_style()));
+        let font = convert_font(&rules, Some(&style.inherited_font(element)));
         let min_padding = convert_padding(&min_rules_with_root, scale, font.font_size);
         let preferred_padding = convert_padding(&rules_with_root, scale, font.font_size);
 
         let min_spacing = convert_spacing(&min_rules_with_root, scale, font.font_size);
         let preferred_spacing = convert_spacing(&rules_with_root, scale, font.font_size);
 
-        let text_color = convert_main_color(&properties_with_root).unwrap_or_else(|| {
-            warn!("text color is not specified");
-            defaults::text_color()
-        });
+        let text_color =
+            convert_main_color(&rules).unwrap_or_else(|| style.inherited_color(element));
         let border = convert_border(&rules_with_root, scale, text_color);
         let background = convert_background(&rules);
+        let box_shadows = convert_box_shadow(&rules, scale, text_color);
+        let outline = convert_outline(&rules, scale, text_color);
         let border_collapse = if get_border_collapse(&rules_with_root) {
             // TODO: somehow fetch border width of children and use it
             1.0.lpx().to_physical(scale)
@@ -86,12 +185,12 @@ impl ComputedElementStyle for BaseComputedStyle {
         let min_padding_with_border = if layout_ignores_border {
             min_padding
         } else {
-            min_padding + Point::new(border.width, border.width)
+            min_padding + Point::new(border.width_x(), border.width_y())
         };
         let preferred_padding_with_border = if layout_ignores_border {
             preferred_padding
         } else {
-            preferred_padding + Point::new(border.width, border.width)
+            preferred_padding + Point::new(border.width_x(), border.width_y())
         };
 
         let grid = GridOptions {
@@ -117,11 +216,67 @@ impl ComputedElementStyle for BaseComputedStyle {
             font_metrics: font.to_metrics(scale),
             border,
             background,
+            box_shadows,
+            outline,
             grid,
         }
     }
 }
 
+impl BaseComputedStyle {
+    /// Returns how far the box shadows and the outline extend beyond the widget's rect.
+    pub(crate) fn visual_overflow(&self) -> PhysicalPixels {
+        let shadows = self.box_shadows.iter().map(|shadow| {
+            max(shadow.offset.x().abs(), shadow.offset.y().abs()) + shadow.blur + shadow.spread
+        });
+        let outline = self
+            .outline
+            .iter()
+            .map(|outline| outline.offset + outline.width);
+        shadows.chain(outline).fold(0.ppx(), max)
+    }
+}
+
+/// Icon specified by the `icon` (or `content`) property.
+///
+/// Icons with names ending with `-symbolic` (e.g. `go-up-symbolic.svg`) are considered monochrome
+/// and are tinted with the element's `color`.
+#[derive(Debug, Clone, Default)]
+pub struct ComputedIconStyle {
+    pub icon: Option<Pixmap>,
+}
+
+impl ComputedElementStyle for ComputedIconStyle {
+    fn new(style: &Styles, element: &StyleSelector, scale: f32) -> Self {
+        let properties = style.find_rules_for_element(element);
+
+        let scale = scale * convert_zoom(&properties);
+        let loaded = match convert_icon(&properties) {
+            Some(IconSource::Url(url)) => style
+                .load_pixmap(&url, scale)
+                .map(|pixmap| (pixmap, is_symbolic_icon(&url))),
+            Some(IconSource::Theme(name)) => {
+                let size = convert_icon_size(&properties).unwrap_or_else(defaults::icon_size);
+                style.load_named_icon(&name, size, scale)
+            }
+            None => return Self::default(),
+        };
+        let icon = match loaded {
+            Ok((pixmap, true)) => {
+                let color = convert_main_color(&properties)
+                    .unwrap_or_else(|| style.inherited_color(element));
+                Some(pixmap.tinted(color))
+            }
+            Ok((pixmap, false)) => Some(pixmap),
+            Err(err) => {
+                warn!("failed to load icon: {err:?}");
+                None
+            }
+        };
+        Self { icon }
+    }
+}
+
 pub trait ComputedElementStyle: Any + Sized {
     fn new(styles: &Styles, element: &StyleSelector, scale: f32) -> Self;
 }
@@ -2,42 +2,68 @@
 
 use {
     super::{
-        common::{ComputedBackground, ComputedBorderStyle, ComputedLinearGradient},
+        common::{
+            ComputedBackground, ComputedBorderRadius, ComputedBorderSide, ComputedBorderStyle,
+            ComputedBoxShadow, ComputedLinearGradient, ComputedOutline,
+        },
         defaults::DEFAULT_LINE_HEIGHT,
-        FontStyle, RelativeOffset,
+        inline::InlineStyle,
+        FontFamily, FontSlant, FontStyle, RelativeOffset,
     },
     crate::{
         layout::Alignment,
         style::defaults,
         system::OrWarn,
-        types::{LogicalPixels, LpxSuffix, PhysicalPixels, Point},
+        text::TextFormat,
+        types::{LogicalPixels, LpxSuffix, PhysicalPixels, Point, PpxSuffix},
     },
-    anyhow::{bail, Context, Result},
+    anyhow::{anyhow, bail, Context, Result},
     itertools::Itertools,
     lightningcss::{
+        printer::PrinterOptions,
         properties::{
             align::GapValue,
-            border::{BorderSideWidth, LineStyle},
-            custom::{CustomProperty, CustomPropertyName, Token, TokenOrValue},
-            font::{FontSize, LineHeight, VerticalAlign, VerticalAlignKeyword},
+            border::{BorderSideWidth, GenericBorder, LineStyle},
+            box_shadow::BoxShadow,
+            custom::{
+                CustomProperty, CustomPropertyName, Token, TokenList, TokenOrValue,
+                UnparsedProperty,
+            },
+            font::{
+                AbsoluteFontWeight, FontFamily as CssFontFamily, FontSize,
+                FontStyle as CssFontStyle, FontWeight, LineHeight, VerticalAlign,
+                VerticalAlignKeyword,
+            },
+            outline::OutlineStyle,
             size::Size,
-            text::TextAlign,
-            Property,
+            text::{Spacing, TextAlign, TextDecorationLine},
+            CSSWideKeyword, Property, PropertyId,
         },
         rules::CssRule,
-        selector::{self, Component, PseudoElement, Selector},
+        selector::{self, Combinator, Component, PseudoElement, Selector},
         stylesheet::StyleSheet,
+        traits::{IntoOwned, Parse, ToCss},
         values::{
+            calc::{Calc, MathFunction},
             color::CssColor,
             gradient::{Gradient, GradientItem, LineDirection, LinearGradient},
             image::Image,
             length::{Length, LengthPercentage, LengthPercentageOrAuto, LengthValue},
             percentage::DimensionPercentage,
             position::{HorizontalPositionKeyword, VerticalPositionKeyword},
+            size::Size2D,
             string::CowArcStr,
         },
     },
-    std::{borrow::Cow, collections::HashMap},
+    std::{
+        borrow::Cow,
+        cell::Cell,
+        collections::HashMap,
+        fmt::Debug,
+        hash::{Hash, Hasher},
+        iter,
+        rc::Rc,
+    },
     tiny_skia::{Color, GradientStop, SpreadMode},
     tracing::warn,
 };
@@ -66,7 +92,12 @@ fn convert_length(value: &LengthValue, font_size: Option<LogicalPixels>) -> Resu
             }
         }
         _ => {
-            bail!("unsupported value, use px: {value:?}");
+            // Absolute units (in, cm, mm, q, pt, pc).
+            if let Some(size) = value.to_px() {
+                Ok(size.lpx())
+            } else {
+                bail!("unsupported value, use px: {value:?}");
+            }
         }
     }
 }
@@ -74,8 +105,8 @@ fn convert_length(value: &LengthValue, font_size: Option<LogicalPixels>) -> Resu
 #[allow(clippy::collapsible_match)]
 fn convert_font_size(size: &FontSize) -> Result<LogicalPixels> {
     if let FontSize::Length(size) = size {
-        if let LengthPercentage::Dimension(size) = size {
-            return convert_length(size, None);
+        if let LengthPercentage::Dimension(_) | LengthPercentage::Calc(_) = size {
+            return convert_dimension_percentage(size, None, None);
         }
     }
     bail!("unsupported font size, use px: {size:?}");
@@ -95,10 +126,46 @@ fn convert_dimension_percentage(
                 bail!("percentage is unsupported in this context");
             }
         }
-        DimensionPercentage::Calc(_) => bail!("calc is unsupported"),
+        DimensionPercentage::Calc(value) => convert_calc(value, &|value| {
+            convert_dimension_percentage(value, total, font_size)
+        }),
     }
 }
 
+// Evaluates a `calc()` expression. Units may be mixed (e.g. `calc(1em + 2px)`).
+// `min()`, `max()` and `clamp()` are also supported.
+fn convert_calc<V: Debug>(
+    value: &Calc<V>,
+    convert_value: &impl Fn(&V) -> Result<LogicalPixels>,
+) -> Result<LogicalPixels> {
+    let value = match value {
+        Calc::Value(value) => convert_value(value)?,
+        Calc::Number(value) => bail!("unitless number is unsupported in calc: {value}"),
+        Calc::Sum(a, b) => (convert_calc(a, convert_value)?.to_f32()
+            + convert_calc(b, convert_value)?.to_f32())
+        .lpx(),
+        Calc::Product(factor, value) => convert_calc(value, convert_value)? * *factor,
+        Calc::Function(function) => match &**function {
+            MathFunction::Calc(value) => convert_calc(value, convert_value)?,
+            MathFunction::Min(values) => values
+                .iter()
+                .map(|value| convert_calc(value, convert_value))
+                .process_results(|values| values.min())?
+                .context("min() without arguments")?,
+            MathFunction::Max(values) => values
+                .iter()
+                .map(|value| convert_calc(value, convert_value))
+                .process_results(|values| values.max())?
+                .context("max() without arguments")?,
+            MathFunction::Clamp(min, value, max) => convert_calc(value, convert_value)?
+                .min(convert_calc(max, convert_value)?)
+                .max(convert_calc(min, convert_value)?),
+            _ => bail!("unsupported math function: {function:?}"),
+        },
+    };
+    Ok(value)
+}
+
 fn convert_line_height(value: &LineHeight, font_size: LogicalPixels) -> Result<LogicalPixels> {
     match value {
         LineHeight::Normal => Ok(font_size * DEFAULT_LINE_HEIGHT),
@@ -109,38 +176,161 @@ fn convert_line_height(value: &LineHeight, font_size: LogicalPixels) -> Result<L
     }
 }
 
-// This function requires passing root font separately because it has special logic
-// for default line height depending on whether font size was specified for the element or not.
-pub fn convert_font(properties: &[&Property<'static>], root: Option<&FontStyle>) -> FontStyle {
+fn convert_font_families(families: &[CssFontFamily]) -> Vec<FontFamily> {
+    families
+        .iter()
+        .filter_map(|family| match family {
+            CssFontFamily::Generic(generic) => {
+                Some(FontFamily::Generic(generic.as_str().to_string()))
+            }
+            CssFontFamily::FamilyName(name) => {
+                match name.to_css_string(PrinterOptions::default()) {
+                    Ok(name) => Some(FontFamily::Name(unescape_family_name(&name))),
+                    Err(err) => {
+                        warn!("failed to convert font family name: {err:?}");
+                        None
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+// `FamilyName` doesn't expose the name, so it's read from the serialized CSS:
+// either a quoted string or a sequence of identifiers, both with backslash escapes.
+fn unescape_family_name(css: &str) -> String {
+    let css = css
+        .strip_prefix('"')
+        .and_then(|css| css.strip_suffix('"'))
+        .unwrap_or(css);
+    let mut name = String::new();
+    let mut chars = css.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            name.push(c);
+            continue;
+        }
+        let mut code = String::new();
+        while code.len() < 6 && chars.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+            code.extend(chars.next());
+        }
+        if code.is_empty() {
+            name.extend(chars.next());
+        } else {
+            // A single whitespace character terminates a hex escape.
+            chars.next_if(|c| c.is_ascii_whitespace());
+            let code = u32::from_str_radix(&code, 16).unwrap_or_default();
+            name.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+    }
+    name
+}
+
+fn convert_font_weight(value: &FontWeight, inherited: u16) -> Result<u16> {
+    // See https://www.w3.org/TR/css-fonts-4/#relative-weights
+    Ok(match value {
+        FontWeight::Absolute(AbsoluteFontWeight::Normal) => 400,
+        FontWeight::Absolute(AbsoluteFontWeight::Bold) => 700,
+        FontWeight::Absolute(AbsoluteFontWeight::Weight(value)) => {
+            if !(1.0..=1000.0).contains(value) {
+                bail!("font weight must be between 1 and 1000");
+            }
+            value.round() as u16
+        }
+        FontWeight::Bolder => match inherited {
+            0..350 => 400,
+            350..550 => 700,
+            _ => 900,
+        },
+        FontWeight::Lighter => match inherited {
+            0..550 => 100,
+            550..750 => 400,
+            _ => 700,
+        },
+    })
+}
+
+fn convert_font_slant(value: &CssFontStyle) -> FontSlant {
+    match value {
+        CssFontStyle::Normal => FontSlant::Normal,
+        CssFontStyle::Italic => FontSlant::Italic,
+        CssFontStyle::Oblique(_) => FontSlant::Oblique,
+    }
+}
+
+fn convert_letter_spacing(value: &Spacing, font_size: LogicalPixels) -> Result<LogicalPixels> {
+    match value {
+        Spacing::Normal => Ok(LogicalPixels::default()),
+        Spacing::Length(Length::Value(value)) => convert_length(value, Some(font_size)),
+        Spacing::Length(Length::Calc(value)) => convert_calc(value, &|value| match value {
+            Length::Value(value) => convert_length(value, Some(font_size)),
+            Length::Calc(_) => convert_plain_length(value),
+        }),
+    }
+}
+
+// `parent` is the font computed for the parent element (or `None` for `:root`).
+// It's passed separately because relative font weights (`bolder`, `lighter`) depend on it and
+// because the default line height depends on whether font size was specified for the element or not.
+pub fn convert_font(properties: &[Property<'static>], parent: Option<&FontStyle>) -> FontStyle {
     let mut self_font_size = None;
     let mut line_height = None;
+    let mut families = None;
+    let mut weight = None;
+    let mut slant = None;
+    let inherited_weight = parent.map_or(defaults::DEFAULT_FONT_WEIGHT, |parent| parent.weight);
     for property in properties {
         match property {
             Property::FontSize(size) => match convert_font_size(size) {
                 Ok(value) => self_font_size = Some(value),
                 Err(err) => warn!("invalid font size: {err:?}"),
             },
-            Property::Font(font) => match convert_font_size(&font.size) {
-                Ok(value) => self_font_size = Some(value),
-                Err(err) => warn!("invalid font size: {err:?}"),
+            Property::Font(font) => {
+                match convert_font_size(&font.size) {
+                    Ok(value) => self_font_size = Some(value),
+                    Err(err) => warn!("invalid font size: {err:?}"),
+                }
+                families = Some(convert_font_families(&font.family));
+                match convert_font_weight(&font.weight, inherited_weight) {
+                    Ok(value) => weight = Some(value),
+                    Err(err) => warn!("invalid font weight: {err:?}"),
+                }
+                slant = Some(convert_font_slant(&font.style));
+            }
+            Property::FontFamily(value) => families = Some(convert_font_families(value)),
+            Property::FontWeight(value) => match convert_font_weight(value, inherited_weight) {
+                Ok(value) => weight = Some(value),
+                Err(err) => warn!("invalid font weight: {err:?}"),
             },
+            Property::FontStyle(value) => slant = Some(convert_font_slant(value)),
             _ => {}
         }
     }
 
     let final_font_size = self_font_size
-        .or_else(|| root.map(|root| root.font_size))
+        .or_else(|| parent.map(|parent| parent.font_size))
         .unwrap_or_else(|| {
             warn!("font size is not specified in style");
             defaults::font_size()
         });
 
+    let mut letter_spacing = None;
     for property in properties {
         match property {
             Property::LineHeight(value) => match convert_line_height(value, final_font_size) {
                 Ok(value) => line_height = Some(value),
                 Err(err) => warn!("invalid line height: {err:?}"),
             },
+            Property::Font(font) => match convert_line_height(&font.line_height, final_font_size) {
+                Ok(value) => line_height = Some(value),
+                Err(err) => warn!("invalid line height: {err:?}"),
+            },
+            Property::LetterSpacing(value) => {
+                match convert_letter_spacing(value, final_font_size) {
+                    Ok(value) => letter_spacing = Some(value),
+                    Err(err) => warn!("invalid letter spacing: {err:?}"),
+                }
+            }
             _ => {}
         }
     }
@@ -150,7 +340,7 @@ pub fn convert_font(properties: &[&Property<'static>], root: Option<&FontStyle>)
             warn!("line height is not specified in style");
             self_font_size * DEFAULT_LINE_HEIGHT
         } else {
-            root.map(|root| root.line_height).unwrap_or_else(|| {
+            parent.map(|parent| parent.line_height).unwrap_or_else(|| {
                 warn!("line height is not specified in style");
                 final_font_size * DEFAULT_LINE_HEIGHT
             })
@@ -160,11 +350,21 @@ pub fn convert_font(properties: &[&Property<'static>], root: Option<&FontStyle>)
     FontStyle {
         font_size: final_font_size,
         line_height,
+        families: families
+            .or_else(|| parent.map(|parent| parent.families.clone()))
+            .unwrap_or_default(),
+        weight: weight.unwrap_or(inherited_weight),
+        slant: slant
+            .or_else(|| parent.map(|parent| parent.slant))
+            .unwrap_or_default(),
+        letter_spacing: letter_spacing
+            .or_else(|| parent.map(|parent| parent.letter_spacing))
+            .unwrap_or_default(),
     }
 }
 
 // TODO: support zoom for all widgets?
-pub fn convert_zoom(properties: &[&Property<'static>]) -> f32 {
+pub fn convert_zoom(properties: &[Property<'static>]) -> f32 {
     let mut zoom = 1.0;
     for property in properties {
         match property {
@@ -198,7 +398,7 @@ pub fn convert_zoom(properties: &[&Property<'static>]) -> f32 {
     zoom
 }
 
-pub fn convert_main_color(properties: &[&Property<'static>]) -> Option<Color> {
+pub fn convert_main_color(properties: &[Property<'static>]) -> Option<Color> {
     let mut color = None;
     for property in properties {
         match property {
@@ -212,19 +412,53 @@ pub fn convert_main_color(properties: &[&Property<'static>]) -> Option<Color> {
     color
 }
 
+/// Applies text formatting properties of a rich text span on top of the `parent` format.
+pub fn convert_text_format(properties: &[Property<'static>], parent: &TextFormat) -> TextFormat {
+    let mut format = parent.clone();
+    if let Some(color) = convert_main_color(properties) {
+        format.color = Some(color);
+    }
+    if let Some(color) = convert_background_color(properties) {
+        format.background = Some(color);
+    }
+    let inherited_weight = parent.weight.unwrap_or(defaults::DEFAULT_FONT_WEIGHT);
+    for property in properties {
+        match property {
+            Property::FontSize(size) => match convert_font_size(size) {
+                Ok(value) => format.font_size = Some(value),
+                Err(err) => warn!("invalid font size: {err:?}"),
+            },
+            Property::FontWeight(value) => match convert_font_weight(value, inherited_weight) {
+                Ok(value) => format.weight = Some(value),
+                Err(err) => warn!("invalid font weight: {err:?}"),
+            },
+            Property::FontStyle(value) => format.slant = Some(convert_font_slant(value)),
+            Property::TextDecorationLine(line, _) => {
+                format.underline = line.contains(TextDecorationLine::Underline);
+                format.strikethrough = line.contains(TextDecorationLine::LineThrough);
+            }
+            Property::TextDecoration(decoration, _) => {
+                format.underline = decoration.line.contains(TextDecorationLine::Underline);
+                format.strikethrough = decoration.line.contains(TextDecorationLine::LineThrough);
+            }
+            _ => {}
+        }
+    }
+    format
+}
+
 fn convert_single_padding(
     value: &LengthPercentageOrAuto,
     font_size: LogicalPixels,
 ) -> Result<LogicalPixels> {
     match value {
         LengthPercentageOrAuto::Auto => Ok(0.0.into()),
-        LengthPercentageOrAuto::LengthPercentage(value) => {
-            if let LengthPercentage::Dimension(value) = value {
-                convert_length(value, Some(font_size))
-            } else {
-                bail!("unsupported value ({value:?})")
+        LengthPercentageOrAuto::LengthPercentage(value) => match value {
+            LengthPercentage::Dimension(_) | LengthPercentage::Calc(_) => {
+                convert_dimension_percentage(value, None, Some(font_size))
             }
-        }
+            LengthPercentage::Percentage(_) => bail!("unsupported value ({value:?})"),
+        },
     }
 }
 
@@ -232,16 +466,16 @@ fn convert_single_spacing(value: &GapValue, font_size: LogicalPixels) -> Result<
     match value {
         GapValue::Normal => Ok(0.0.into()),
         GapValue::LengthPercentage(value) => match value {
-            DimensionPercentage::Dimension(value) => convert_length(value, Some(font_size)),
-            DimensionPercentage::Percentage(_) | DimensionPercentage::Calc(_) => {
-                bail!("unsupported value ({value:?})")
+            DimensionPercentage::Dimension(_) | DimensionPercentage::Calc(_) => {
+                convert_dimension_percentage(value, None, Some(font_size))
             }
+            DimensionPercentage::Percentage(_) => bail!("unsupported value ({value:?})"),
         },
     }
 }
 
 pub fn convert_padding(
-    properties: &[&Property<'static>],
+    properties: &[Property<'static>],
     scale: f32,
     font_size: LogicalPixels,
 ) -> Point {
@@ -276,7 +510,7 @@ pub fn convert_padding(
     )
 }
 
-pub fn convert_layout_ignores_border(properties: &[&Property<'static>]) -> bool {
+pub fn convert_layout_ignores_border(properties: &[Property<'static>]) -> bool {
     let mut output = false;
     for property in properties {
         match property {
@@ -313,7 +547,7 @@ pub fn convert_layout_ignores_border(properties: &[&Property<'static>]) -> bool
 }
 
 pub fn convert_spacing(
-    properties: &[&Property<'static>],
+    properties: &[Property<'static>],
     scale: f32,
     font_size: LogicalPixels,
 ) -> Point {
@@ -349,7 +583,7 @@ pub fn convert_spacing(
 }
 
 pub fn convert_width(
-    properties: &[&Property<'static>],
+    properties: &[Property<'static>],
     scale: f32,
     font_size: LogicalPixels,
 ) -> Result<Option<PhysicalPixels>> {
@@ -370,7 +604,7 @@ pub fn convert_width(
 }
 
 pub fn convert_height(
-    properties: &[&Property<'static>],
+    properties: &[Property<'static>],
     scale: f32,
     font_size: LogicalPixels,
 ) -> Result<Option<PhysicalPixels>> {
@@ -394,71 +628,258 @@ fn convert_border_width(width: &BorderSideWidth) -> Result<LogicalPixels> {
     if let BorderSideWidth::Length(width) = width {
         match width {
             Length::Value(width) => convert_length(width, None),
-            Length::Calc(_) => bail!("calc is unsupported"),
+            Length::Calc(_) => convert_plain_length(width),
         }
     } else {
         bail!("unsupported border width (use explicit width): {width:?}");
     }
 }
 
+fn convert_color_or_current(color: &CssColor, text_color: Color) -> Result<Color> {
+    if let CssColor::CurrentColor = color {
+        Ok(text_color)
+    } else {
+        convert_color(color)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct BorderSideProperties {
+    width: Option<LogicalPixels>,
+    color: Option<Color>,
+    style: Option<LineStyle>,
+}
+
+impl BorderSideProperties {
+    fn set_width(&mut self, width: &BorderSideWidth) {
+        match convert_border_width(width) {
+            Ok(value) => self.width = Some(value),
+            Err(err) => warn!("invalid border: {err:?}"),
+        }
+    }
+
+    fn set_color(&mut self, color: &CssColor, text_color: Color) {
+        match convert_color_or_current(color, text_color) {
+            Ok(value) => self.color = Some(value),
+            Err(err) => warn!("invalid border: {err:?}"),
+        }
+    }
+
+    fn set<const P: u8>(&mut self, value: &GenericBorder<LineStyle, P>, text_color: Color) {
+        self.set_width(&value.width);
+        self.set_color(&value.color, text_color);
+        self.style = Some(value.style);
+    }
+
+    fn compute(&self, scale: f32, text_color: Color) -> ComputedBorderSide {
+        match self.style.unwrap_or(LineStyle::None) {
+            LineStyle::None | LineStyle::Hidden => ComputedBorderSide::default(),
+            LineStyle::Solid => ComputedBorderSide {
+                width: self.width.unwrap_or_default().to_physical(scale),
+                color: self.color.unwrap_or(text_color),
+            },
+            style => {
+                warn!("unsupported border line style: {style:?}");
+                ComputedBorderSide::default()
+            }
+        }
+    }
+}
+
+fn convert_border_radius(value: &Size2D<LengthPercentage>, output: &mut Option<LogicalPixels>) {
+    match convert_dimension_percentage(&value.0, None, None) {
+        Ok(value) => *output = Some(value),
+        Err(err) => warn!("invalid border radius: {err:?}"),
+    }
+}
+
 pub fn convert_border(
-    properties: &[&Property<'static>],
+    properties: &[Property<'static>],
     scale: f32,
     text_color: Color,
 ) -> ComputedBorderStyle {
-    let mut width = None;
-    let mut color = None;
-    let mut radius = None;
-    let mut style = LineStyle::None;
+    let mut top = BorderSideProperties::default();
+    let mut right = BorderSideProperties::default();
+    let mut bottom = BorderSideProperties::default();
+    let mut left = BorderSideProperties::default();
+    let mut top_left_radius = None;
+    let mut top_right_radius = None;
+    let mut bottom_right_radius = None;
+    let mut bottom_left_radius = None;
     for property in properties {
         match property {
             Property::Border(value) => {
-                match convert_border_width(&value.width) {
-                    Ok(value) => width = Some(value),
-                    Err(err) => warn!("invalid border: {err:?}"),
-                }
-                match convert_color(&value.color) {
-                    Ok(value) => color = Some(value),
-                    Err(err) => warn!("invalid border: {err:?}"),
+                for side in [&mut top, &mut right, &mut bottom, &mut left] {
+                    side.set(value, text_color);
                 }
-                style = value.style;
             }
+            Property::BorderTop(value) => top.set(value, text_color),
+            Property::BorderRight(value) => right.set(value, text_color),
+            Property::BorderBottom(value) => bottom.set(value, text_color),
+            Property::BorderLeft(value) => left.set(value, text_color),
             Property::BorderWidth(value) => {
-                // TODO: support different sides
-                match convert_border_width(&value.top) {
-                    Ok(value) => width = Some(value),
-                    Err(err) => warn!("invalid border: {err:?}"),
-                }
+                top.set_width(&value.top);
+                right.set_width(&value.right);
+                bottom.set_width(&value.bottom);
+                left.set_width(&value.left);
             }
-            Property::BorderColor(value) => match convert_color(&value.top) {
-                Ok(value) => color = Some(value),
-                Err(err) => warn!("invalid border: {err:?}"),
-            },
+            Property::BorderTopWidth(value) => top.set_width(value),
+            Property::BorderRightWidth(value) => right.set_width(value),
+            Property::BorderBottomWidth(value) => bottom.set_width(value),
+            Property::BorderLeftWidth(value) => left.set_width(value),
+            Property::BorderColor(value) => {
+                top.set_color(&value.top, text_color);
+                right.set_color(&value.right, text_color);
+                bottom.set_color(&value.bottom, text_color);
+                left.set_color(&value.left, text_color);
+            }
+            Property::BorderTopColor(value) => top.set_color(value, text_color),
+            Property::BorderRightColor(value) => right.set_color(value, text_color),
+            Property::BorderBottomColor(value) => bottom.set_color(value, text_color),
+            Property::BorderLeftColor(value) => left.set_color(value, text_color),
             Property::BorderStyle(value) => {
-                style = value.top;
+                top.style = Some(value.top);
+                right.style = Some(value.right);
+                bottom.style = Some(value.bottom);
+                left.style = Some(value.left);
             }
+            Property::BorderTopStyle(value) => top.style = Some(*value),
+            Property::BorderRightStyle(value) => right.style = Some(*value),
+            Property::BorderBottomStyle(value) => bottom.style = Some(*value),
+            Property::BorderLeftStyle(value) => left.style = Some(*value),
             Property::BorderRadius(value, _prefix) => {
-                match convert_dimension_percentage(&value.top_left.0, None, None) {
-                    Ok(value) => radius = Some(value),
-                    Err(err) => warn!("invalid border radius: {err:?}"),
-                }
+                convert_border_radius(&value.top_left, &mut top_left_radius);
+                convert_border_radius(&value.top_right, &mut top_right_radius);
+                convert_border_radius(&value.bottom_right, &mut bottom_right_radius);
+                convert_border_radius(&value.bottom_left, &mut bottom_left_radius);
+            }
+            Property::BorderTopLeftRadius(value, _prefix) => {
+                convert_border_radius(value, &mut top_left_radius);
+            }
+            Property::BorderTopRightRadius(value, _prefix) => {
+                convert_border_radius(value, &mut top_right_radius);
+            }
+            Property::BorderBottomRightRadius(value, _prefix) => {
+                convert_border_radius(value, &mut bottom_right_radius);
+            }
+            Property::BorderBottomLeftRadius(value, _prefix) => {
+                convert_border_radius(value, &mut bottom_left_radius);
             }
             _ => {}
         }
     }
 
-    match style {
-        LineStyle::None => ComputedBorderStyle::default(),
-        LineStyle::Solid => ComputedBorderStyle {
-            width: width.unwrap_or_default().to_physical(scale),
-            color: color.unwrap_or(text_color),
-            radius: radius.unwrap_or_default().to_physical(scale),
+    ComputedBorderStyle {
+        top: top.compute(scale, text_color),
+        right: right.compute(scale, text_color),
+        bottom: bottom.compute(scale, text_color),
+        left: left.compute(scale, text_color),
+        radius: ComputedBorderRadius {
+            top_left: top_left_radius.unwrap_or_default().to_physical(scale),
+            top_right: top_right_radius.unwrap_or_default().to_physical(scale),
+            bottom_right: bottom_right_radius.unwrap_or_default().to_physical(scale),
+            bottom_left: bottom_left_radius.unwrap_or_default().to_physical(scale),
         },
-        _ => {
-            warn!("unsupported border line style: {style:?}");
-            ComputedBorderStyle::default()
+    }
+}
+
+// Parses the value of a property unknown to lightningcss as a length.
+fn convert_custom_length(property: &CustomProperty<'static>) -> Result<LogicalPixels> {
+    let code = Property::Custom(property.clone()).value_to_css_string(Default::default())?;
+    let length = Length::parse_string(&code)
+        .map_err(|err| anyhow!("expected length, got {code:?}: {err}"))?;
+    convert_plain_length(&length)
+}
+
+fn convert_plain_length(value: &Length) -> Result<LogicalPixels> {
+    match value {
+        Length::Value(value) => convert_length(value, None),
+        Length::Calc(value) => convert_calc(value, &convert_plain_length),
+    }
+}
+
+fn convert_single_box_shadow(
+    value: &BoxShadow,
+    scale: f32,
+    text_color: Color,
+) -> Result<ComputedBoxShadow> {
+    if value.inset {
+        bail!("inset box shadows are not supported");
+    }
+    Ok(ComputedBoxShadow {
+        offset: Point::new(
+            convert_plain_length(&value.x_offset)?.to_physical(scale),
+            convert_plain_length(&value.y_offset)?.to_physical(scale),
+        ),
+        blur: convert_plain_length(&value.blur)?.to_physical(scale),
+        spread: convert_plain_length(&value.spread)?.to_physical(scale),
+        color: convert_color_or_current(&value.color, text_color)?,
+    })
+}
+
+pub fn convert_box_shadow(
+    properties: &[Property<'static>],
+    scale: f32,
+    text_color: Color,
+) -> Vec<ComputedBoxShadow> {
+    let mut output = Vec::new();
+    for property in properties {
+        if let Property::BoxShadow(shadows, _prefix) = property {
+            output = shadows
+                .iter()
+                .filter_map(|shadow| {
+                    convert_single_box_shadow(shadow, scale, text_color)
+                        .map_err(|err| warn!("invalid box-shadow: {err:?}"))
+                        .ok()
+                })
+                .collect();
+        }
+    }
+    output
+}
+
+pub fn convert_outline(
+    properties: &[Property<'static>],
+    scale: f32,
+    text_color: Color,
+) -> Option<ComputedOutline> {
+    let mut side = BorderSideProperties::default();
+    let mut offset = None;
+    for property in properties {
+        match property {
+            Property::Outline(value) => {
+                side.set_width(&value.width);
+                side.set_color(&value.color, text_color);
+                side.style = Some(convert_outline_style(&value.style));
+            }
+            Property::OutlineWidth(value) => side.set_width(value),
+            Property::OutlineColor(value) => side.set_color(value, text_color),
+            Property::OutlineStyle(value) => side.style = Some(convert_outline_style(value)),
+            Property::Custom(property) if property.name.as_ref() == "outline-offset" => {
+                match convert_custom_length(property) {
+                    Ok(value) => offset = Some(value),
+                    Err(err) => warn!("invalid outline-offset: {err:?}"),
+                }
+            }
+            _ => {}
         }
     }
+    let side = side.compute(scale, text_color);
+    if side.width == 0.ppx() {
+        return None;
+    }
+    Some(ComputedOutline {
+        width: side.width,
+        color: side.color,
+        offset: offset.unwrap_or_default().to_physical(scale),
+    })
+}
+
+fn convert_outline_style(value: &OutlineStyle) -> LineStyle {
+    match value {
+        OutlineStyle::Auto => LineStyle::Solid,
+        OutlineStyle::LineStyle(value) => *value,
+    }
 }
 
 fn convert_linear_gradient(value: &LinearGradient) -> Result<ComputedLinearGradient> {
@@ -526,7 +947,7 @@ fn convert_linear_gradient(value: &LinearGradient) -> Result<ComputedLinearGradi
     })
 }
 
-pub fn convert_background_color(properties: &[&Property<'static>]) -> Option<Color> {
+pub fn convert_background_color(properties: &[Property<'static>]) -> Option<Color> {
     let bg = convert_background(properties);
     if let Some(bg) = bg {
         match bg {
@@ -541,7 +962,7 @@ pub fn convert_background_color(properties: &[&Property<'static>]) -> Option<Col
     }
 }
 
-pub fn convert_background(properties: &[&Property<'static>]) -> Option<ComputedBackground> {
+pub fn convert_background(properties: &[Property<'static>]) -> Option<ComputedBackground> {
     let mut final_background = None;
     for property in properties {
         match property {
@@ -585,7 +1006,7 @@ pub fn convert_background(properties: &[&Property<'static>]) -> Option<ComputedB
     final_background
 }
 
-pub fn get_border_collapse(properties: &[&Property<'static>]) -> bool {
+pub fn get_border_collapse(properties: &[Property<'static>]) -> bool {
     let mut value = false;
     for property in properties {
         match property {
@@ -621,7 +1042,7 @@ pub fn get_border_collapse(properties: &[&Property<'static>]) -> bool {
     value
 }
 
-pub fn get_text_alignment(properties: &[&Property<'static>]) -> Alignment {
+pub fn get_text_alignment(properties: &[Property<'static>]) -> Alignment {
     let mut value = Alignment::Start;
     for property in properties {
         match property {
@@ -629,8 +1050,9 @@ pub fn get_text_alignment(properties: &[&Property<'static>]) -> Alignment {
                 TextAlign::Start => value = Alignment::Start,
                 TextAlign::End => value = Alignment::End,
                 TextAlign::Center => value = Alignment::Middle,
+                TextAlign::Justify => value = Alignment::Justify,
                 _ => {
-                    warn!("unsupported text-align value: {align:?}, use one of start, end, center");
+                    warn!("unsupported text-align value: {align:?}, use one of start, end, center, justify");
                 }
             },
             _ => {}
@@ -639,7 +1061,7 @@ pub fn get_text_alignment(properties: &[&Property<'static>]) -> Alignment {
     value
 }
 
-pub fn get_vertical_alignment(properties: &[&Property<'static>]) -> Alignment {
+pub fn get_vertical_alignment(properties: &[Property<'static>]) -> Alignment {
     let mut value = Alignment::Start;
     for property in properties {
         match property {
@@ -648,12 +1070,13 @@ pub fn get_vertical_alignment(properties: &[&Property<'static>]) -> Alignment {
                     VerticalAlignKeyword::Top => value = Alignment::Start,
                     VerticalAlignKeyword::Middle => value = Alignment::Middle,
                     VerticalAlignKeyword::Bottom => value = Alignment::End,
+                    VerticalAlignKeyword::Baseline => value = Alignment::Baseline,
                     _ => {
-                        warn!("unsupported vertical-align value: {align:?}, use one of top, bottom, middle");
+                        warn!("unsupported vertical-align value: {align:?}, use one of top, bottom, middle, baseline");
                     }
                 },
                 VerticalAlign::Length(_) => {
-                    warn!("unsupported vertical-align value: {align:?}, use one of top, bottom, middle");
+                    warn!("unsupported vertical-align value: {align:?}, use one of top, bottom, middle, baseline");
                 }
             },
             _ => {}
@@ -662,22 +1085,58 @@ pub fn get_vertical_alignment(properties: &[&Property<'static>]) -> Alignment {
     value
 }
 
-pub fn convert_content_url(properties: &[&Property<'static>]) -> Option<String> {
-    let mut final_url = None;
+/// Value of the `icon` property.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IconSource {
+    /// `url("path")`: an image from the style's resources.
+    Url(String),
+    /// `theme("name")`: a named icon from the icon provider.
+    Theme(String),
+}
+
+fn convert_icon_theme_name(arguments: &TokenList) -> Option<String> {
+    let tokens = arguments
+        .0
+        .iter()
+        .filter(|token| !matches!(token, TokenOrValue::Token(Token::WhiteSpace(_))))
+        .collect_vec();
+    match &tokens[..] {
+        [TokenOrValue::Token(Token::String(name) | Token::Ident(name))] => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// Returns the icon specified by `icon` or `content` property: `url(...)` or `theme(...)`.
+pub fn convert_icon(properties: &[Property<'static>]) -> Option<IconSource> {
+    let mut final_icon = None;
     for property in properties {
         match property {
             Property::Custom(property) => {
                 if let CustomPropertyName::Unknown(name) = &property.name {
-                    if name.as_ref() == "content" {
-                        if property.value.0.len() != 1 {
-                            warn!("expected 1 token in content proprety");
-                            continue;
-                        }
-                        if let TokenOrValue::Url(url) = &property.value.0[0] {
-                            final_url = Some(url.url.to_string());
-                        } else {
-                            warn!("expected url() in content proprety");
-                            continue;
+                    if name.as_ref() == "icon" || name.as_ref() == "content" {
+                        match &property.value.0[..] {
+                            [TokenOrValue::Url(url)] => {
+                                final_icon = Some(IconSource::Url(url.url.to_string()));
+                            }
+                            [TokenOrValue::Token(Token::Ident(value))]
+                                if value.as_ref() == "none" =>
+                            {
+                                final_icon = None;
+                            }
+                            [TokenOrValue::Function(function)]
+                                if function.name.as_ref() == "theme" =>
+                            {
+                                if let Some(icon_name) =
+                                    convert_icon_theme_name(&function.arguments)
+                                {
+                                    final_icon = Some(IconSource::Theme(icon_name));
+                                } else {
+                                    warn!("expected icon name in theme() in {name} property");
+                                }
+                            }
+                            _ => {
+                                warn!("expected url(), theme() or none in {name} property");
+                            }
                         }
                     }
                 }
@@ -685,62 +1144,235 @@ pub fn convert_content_url(properties: &[&Property<'static>]) -> Option<String>
             _ => {}
         }
     }
-    final_url
-}
-
-pub fn replace_vars(style_sheet: &mut StyleSheet) {
-    //let mut style_sheet: StyleSheet<'static, 'static> = style_sheet.into_owned();
-    let mut vars = HashMap::new();
-    for rule in &style_sheet.rules.0 {
-        if let CssRule::Style(rule) = rule {
-            // println!("selectors: {:?}", rule.selectors);
-            for selector in &rule.selectors.0 {
-                // print_selector(selector);
-                if is_root(selector) {
-                    // println!("found root!");
-                    for (property, _) in rule.declarations.iter() {
-                        //println!("root declaration: {declaration:?}");
-                        if let Property::Custom(property) = property {
-                            if let CustomPropertyName::Custom(name) = &property.name {
-                                vars.insert(name.as_ref().to_string(), property.value.clone());
-                            }
-                        }
-                    }
+    final_icon
+}
+
+pub fn convert_icon_size(properties: &[Property<'static>]) -> Option<LogicalPixels> {
+    let mut size = None;
+    for property in properties {
+        if let Property::Custom(CustomProperty {
+            name: CustomPropertyName::Unknown(name),
+            value,
+        }) = property
+        {
+            if name.as_ref() == "icon-size" {
+                if let [TokenOrValue::Length(LengthValue::Px(value))] = &value.0[..] {
+                    size = Some(value.lpx());
+                } else {
+                    warn!("invalid icon-size: expected length in px, got {value:?}");
                 }
-                // if let Some(data) = as_tag_with_class(selector) {
-                // println!(
-                //     "found tag with class {:?}, {}",
-                //     data.tag,
-                //     serde_json::to_string(&data.class).unwrap()
-                // );
-                // }
-                // print_selector(selector);
             }
         }
     }
-    for rule in &mut style_sheet.rules.0 {
-        if let CssRule::Style(rule) = rule {
-            for property in rule.declarations.iter_mut() {
-                if let Property::Unparsed(property) = property {
-                    let mut new_tokens = Vec::new();
-                    for token in &property.value.0 {
-                        if let TokenOrValue::Var(variable) = token {
-                            if let Some(value) = vars.get(variable.name.ident.as_ref()) {
-                                // println!("substitute!");
-                                // TODO: use substitute_variables
-                                new_tokens.extend(value.0.clone());
-                                continue;
-                            }
-                        }
-                        new_tokens.push(token.clone());
-                    }
-                    property.value.0 = new_tokens;
+    size
+}
+
+/// If the value of the declaration is a CSS-wide keyword (e.g. `color: inherit;`), returns
+/// the affected property and the keyword. The property is `None` for `all: <keyword>`.
+pub fn css_wide_keyword(
+    property: &Property<'static>,
+) -> Option<(Option<PropertyId<'static>>, CSSWideKeyword)> {
+    let (id, value) = match property {
+        Property::All(keyword) => return Some((None, *keyword)),
+        Property::Unparsed(property) => (property.property_id.clone(), &property.value),
+        Property::Custom(property) if matches!(property.name, CustomPropertyName::Unknown(_)) => {
+            (PropertyId::Custom(property.name.clone()), &property.value)
+        }
+        _ => return None,
+    };
+    let [TokenOrValue::Token(Token::Ident(value))] = &value.0[..] else {
+        return None;
+    };
+    let keyword = match value.as_ref() {
+        "inherit" => CSSWideKeyword::Inherit,
+        "initial" => CSSWideKeyword::Initial,
+        "unset" => CSSWideKeyword::Unset,
+        "revert" => CSSWideKeyword::Revert,
+        "revert-layer" => CSSWideKeyword::RevertLayer,
+        _ => return None,
+    };
+    Some((Some(id), keyword))
+}
+
+/// True if a CSS-wide keyword set for the property `id` overrides the `other` declaration,
+/// i.e. `other` is a declaration of the same property or of one of its longhands.
+pub fn affects_property<'i>(id: &PropertyId<'i>, other: &Property<'i>) -> bool {
+    let other_id = other.property_id();
+    other_id == *id
+        || id
+            .longhands()
+            .is_some_and(|longhands| longhands.contains(&other_id))
+}
+
+/// Values of custom properties (`--name: value;`) visible to an element.
+///
+/// Custom properties are inherited: an element sees the properties declared for `:root`,
+/// its ancestors and the element itself, in that order.
+pub type CustomProperties = HashMap<String, TokenList<'static>>;
+
+/// True if the declaration defines a custom property (`--name: value;`).
+pub fn is_custom_property(property: &Property) -> bool {
+    matches!(
+        property,
+        Property::Custom(CustomProperty {
+            name: CustomPropertyName::Custom(_),
+            ..
+        })
+    )
+}
+
+/// Adds custom properties declared in `properties` to `output`.
+///
+/// References to other variables in the values are substituted
+/// using the values already present in `output`. A property referencing itself
+/// (e.g. `--a: var(--a);`) is invalid.
+pub fn collect_custom_properties(properties: &[&Property<'static>], output: &mut CustomProperties) {
+    for property in properties {
+        if let Property::Custom(CustomProperty {
+            name: CustomPropertyName::Custom(name),
+            value,
+        }) = property
+        {
+            let mut value = value.clone();
+            let result = if references_var(&value, name.as_ref()) {
+                Err(anyhow!("cyclic reference"))
+            } else {
+                substitute_vars(&mut value, output)
+            };
+            match result {
+                Ok(()) => {
+                    output.insert(name.as_ref().to_string(), value);
+                }
+                Err(err) => {
+                    warn!(
+                        "invalid value of custom property {:?}: {err:?}",
+                        name.as_ref()
+                    );
+                    output.remove(name.as_ref());
                 }
             }
         }
     }
+}
 
-    // println!("vars: {vars:#?}");
+/// True if custom properties are declared in any rule of `style_sheet` other than `:root`.
+pub fn has_scoped_custom_properties(style_sheet: &StyleSheet) -> bool {
+    style_sheet.rules.0.iter().any(|rule| {
+        let CssRule::Style(rule) = rule else {
+            return false;
+        };
+        !rule.selectors.0.iter().all(is_root)
+            && rule
+                .declarations
+                .iter()
+                .any(|(property, _)| is_custom_property(property))
+    })
+}
+
+/// True if the value of the property contains `var()` references.
+pub fn uses_vars(property: &Property) -> bool {
+    match property {
+        Property::Unparsed(property) => has_vars(&property.value),
+        Property::Custom(property) => {
+            matches!(property.name, CustomPropertyName::Unknown(_)) && has_vars(&property.value)
+        }
+        _ => false,
+    }
+}
+
+fn has_vars(tokens: &TokenList) -> bool {
+    tokens.0.iter().any(|token| match token {
+        TokenOrValue::Var(_) => true,
+        TokenOrValue::Function(function) => has_vars(&function.arguments),
+        _ => false,
+    })
+}
+
+fn references_var(tokens: &TokenList, name: &str) -> bool {
+    tokens.0.iter().any(|token| match token {
+        TokenOrValue::Var(variable) => {
+            variable.name.ident.as_ref() == name
+                || variable
+                    .fallback
+                    .as_ref()
+                    .is_some_and(|fallback| references_var(fallback, name))
+        }
+        TokenOrValue::Function(function) => references_var(&function.arguments, name),
+        _ => false,
+    })
+}
+
+/// Substitutes `var()` references in the value of the property and parses the result.
+pub fn substitute_property_vars(
+    property: &Property<'static>,
+    vars: &CustomProperties,
+) -> Result<Property<'static>> {
+    match property {
+        Property::Unparsed(property) => {
+            let mut value = property.value.clone();
+            substitute_vars(&mut value, vars)?;
+            let code = Property::Unparsed(UnparsedProperty {
+                property_id: property.property_id.clone(),
+                value,
+            })
+            .value_to_css_string(Default::default())?;
+            let parsed =
+                Property::parse_string(property.property_id.clone(), &code, Default::default())
+                    .map_err(|err| {
+                        anyhow!(
+                            "invalid value for {:?} after substituting variables: {code:?}: {err}",
+                            property.property_id.name()
+                        )
+                    })?;
+            Ok(parsed.into_owned())
+        }
+        Property::Custom(property) => {
+            let mut value = property.value.clone();
+            substitute_vars(&mut value, vars)?;
+            Ok(Property::Custom(CustomProperty {
+                name: property.name.clone(),
+                value,
+            }))
+        }
+        _ => Ok(property.clone()),
+    }
+}
+
+fn substitute_vars(tokens: &mut TokenList<'static>, vars: &CustomProperties) -> Result<()> {
+    let mut new_tokens = Vec::new();
+    for (index, token) in tokens.0.iter().enumerate() {
+        match token {
+            TokenOrValue::Var(variable) => {
+                let name = variable.name.ident.as_ref();
+                if let Some(value) = vars.get(name) {
+                    new_tokens.extend(value.0.iter().cloned());
+                } else if let Some(fallback) = &variable.fallback {
+                    let mut fallback = fallback.clone();
+                    substitute_vars(&mut fallback, vars)?;
+                    new_tokens.extend(fallback.0);
+                } else {
+                    bail!("undefined variable: {name:?}");
+                }
+                // Whitespace is not preserved in the token list, so we have to separate
+                // the value from the next token, e.g. in `var(--a) 0%`.
+                if index + 1 < tokens.0.len() {
+                    new_tokens.push(TokenOrValue::Token(Token::WhiteSpace(" ".into())));
+                }
+                continue;
+            }
+            // Variables may be used inside functions, e.g. `linear-gradient(var(--a), var(--b))`.
+            TokenOrValue::Function(function) => {
+                let mut function = function.clone();
+                substitute_vars(&mut function.arguments, vars)?;
+                new_tokens.push(TokenOrValue::Function(function));
+                continue;
+            }
+            _ => {}
+        }
+        new_tokens.push(token.clone());
+    }
+    tokens.0 = new_tokens;
+    Ok(())
 }
 
 #[allow(dead_code)]
@@ -821,6 +1453,22 @@ pub fn selector_items<'i, 'a>(selector: &'a Selector<'i>) -> Option<Vec<&'a Comp
     Some(components)
 }
 
+pub fn has_sibling_combinator(selector: &Selector) -> bool {
+    selector.iter_raw_match_order().any(|item| {
+        matches!(
+            item,
+            Component::Combinator(Combinator::NextSibling | Combinator::LaterSibling)
+        )
+    })
+}
+
+/// True if the selector uses `:first-child`, `:last-child`, `:only-child` or `:nth-child()`.
+pub fn has_structural_pseudo_class(selector: &Selector) -> bool {
+    selector
+        .iter_raw_match_order()
+        .any(|item| matches!(item, Component::Nth(_) | Component::NthOf(_)))
+}
+
 pub fn is_root(selector: &Selector) -> bool {
     selector_items(selector)
         .is_some_and(|items| items.len() == 1 && matches!(items[0], Component::Root))
@@ -850,6 +1498,36 @@ pub fn is_selection(selector: &Selector) -> bool {
     })
 }
 
+pub fn is_link(selector: &Selector) -> bool {
+    selector_items(selector).is_some_and(|items| {
+        items.len() == 1
+            && matches!(
+                items[0],
+                Component::NonTSPseudoClass(selector::PseudoClass::Link)
+            )
+    })
+}
+
+pub fn is_target_text(selector: &Selector) -> bool {
+    selector_items(selector).is_some_and(|items| {
+        items.len() == 1
+            && matches!(
+                items[0],
+                Component::PseudoElement(PseudoElement::TargetText)
+            )
+    })
+}
+
+pub fn is_placeholder(selector: &Selector) -> bool {
+    selector_items(selector).is_some_and(|items| {
+        items.len() == 1
+            && matches!(
+                items[0],
+                Component::PseudoElement(PseudoElement::Placeholder(_))
+            )
+    })
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PseudoClass {
     Hover,
@@ -860,7 +1538,16 @@ pub enum PseudoClass {
     Disabled,
     // Like current menu item.
     Current,
-    // TODO: add more relevant classes
+    // Checked state of a checkable widget (e.g. a checkable button).
+    Checked,
+    // Widget has focus that was received from keyboard.
+    FocusVisible,
+    // Text can't be edited by the user.
+    ReadOnly,
+    // Input is empty and displays its placeholder text.
+    PlaceholderShown,
+    // Input content is not accepted by its validator.
+    Invalid,
     Custom(CowArcStr<'static>),
 }
 
@@ -874,18 +1561,103 @@ impl PseudoClass {
             selector::PseudoClass::Enabled => Some(Self::Enabled),
             selector::PseudoClass::Disabled => Some(Self::Disabled),
             selector::PseudoClass::Current => Some(Self::Current),
+            selector::PseudoClass::Checked => Some(Self::Checked),
+            selector::PseudoClass::FocusVisible => Some(Self::FocusVisible),
+            selector::PseudoClass::ReadOnly(_) => Some(Self::ReadOnly),
+            selector::PseudoClass::PlaceholderShown(_) => Some(Self::PlaceholderShown),
+            selector::PseudoClass::Invalid => Some(Self::Invalid),
             selector::PseudoClass::Custom { name } => Some(Self::Custom(name.clone())),
             _ => None,
         }
     }
 }
 
+/// Position of a widget among its siblings, ordered by their [ChildKey](crate::ChildKey)s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChildPosition {
+    /// Zero-based index of the widget.
+    pub index: usize,
+    /// Total number of the parent's children.
+    pub count: usize,
+}
+
+// Arguments of an `:nth-*` pseudo-class.
+struct NthPattern {
+    of_type: bool,
+    only: bool,
+    from_end: bool,
+    a: i32,
+    b: i32,
+}
+
+impl ChildPosition {
+    // Only `*-child` selectors are supported. `*-of-type` selectors never match.
+    fn matches(&self, data: &NthPattern) -> bool {
+        if data.of_type {
+            return false;
+        }
+        if data.only {
+            return self.count == 1;
+        }
+        let n = if data.from_end {
+            self.count - self.index
+        } else {
+            self.index + 1
+        } as i32;
+        // Matches if `n = a * k + b` for some non-negative integer `k`.
+        if data.a == 0 {
+            n == data.b
+        } else {
+            let diff = n - data.b;
+            diff % data.a == 0 && diff / data.a >= 0
+        }
+    }
+}
+
+/// Identifies a computed style within a [Style](super::Style).
+///
+/// See [Style::style_key](super::Style::style_key).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct StyleKey(pub(crate) u32);
+
+// Style key memoized along with the generation of the style's keys. It's not a part of
+// the selector's identity, so it's ignored by comparisons.
+#[derive(Debug, Clone, Default)]
+struct StyleKeyMemo(Cell<Option<(u64, StyleKey)>>);
+
+impl PartialEq for StyleKeyMemo {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for StyleKeyMemo {}
+
+impl Hash for StyleKeyMemo {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct StyleSelector {
     tag: String,
     // TODO: small vec?
     classes: Vec<Cow<'static, str>>,
     pseudo_classes: Vec<PseudoClass>,
+    // Name of a custom pseudo-element (e.g. `gutter` in `TextArea::gutter`) that represents
+    // a part of the widget. Only selectors that specify this pseudo-element match it.
+    pseudo_element: Option<Cow<'static, str>>,
+    // Selector of the parent widget. Used to match descendant and child combinators.
+    parent: Option<Rc<StyleSelector>>,
+    // Selector of the preceding sibling widget. Used to match sibling combinators.
+    // Only tracked if the style sheet uses sibling combinators.
+    previous_sibling: Option<Rc<StyleSelector>>,
+    // Used to match structural pseudo-classes (e.g. `:first-child`).
+    // Only tracked if the style sheet uses them.
+    child_position: Option<ChildPosition>,
+    // Style set on the widget itself.
+    inline: Option<Rc<InlineStyle>>,
+    // Reset whenever the selector changes.
+    key: StyleKeyMemo,
 }
 
 impl StyleSelector {
@@ -894,17 +1666,106 @@ impl StyleSelector {
             tag,
             classes: Vec::new(),
             pseudo_classes: Vec::new(),
+            pseudo_element: None,
+            parent: None,
+            previous_sibling: None,
+            child_position: None,
+            inline: None,
+            key: Default::default(),
         }
     }
 
+    /// Returns the inline style of the widget, if any.
+    pub fn inline_style(&self) -> Option<&InlineStyle> {
+        self.inline.as_deref()
+    }
+
+    pub(crate) fn set_inline_style(&mut self, style: Option<Rc<InlineStyle>>) {
+        self.inline = style;
+        self.key = Default::default();
+    }
+
+    pub(crate) fn shared_inline_style(&self) -> Option<&Rc<InlineStyle>> {
+        self.inline.as_ref()
+    }
+
+    pub(crate) fn memoized_key(&self, generation: u64) -> Option<StyleKey> {
+        self.key
+            .0
+            .get()
+            .filter(|(key_generation, _)| *key_generation == generation)
+            .map(|(_, key)| key)
+    }
+
+    pub(crate) fn memoize_key(&self, generation: u64, key: StyleKey) {
+        self.key.0.set(Some((generation, key)));
+    }
+
+    /// True if the inline style of the widget or any of its ancestors declares custom properties.
+    pub(crate) fn has_inline_custom_properties(&self) -> bool {
+        iter::once(self)
+            .chain(self.ancestors())
+            .filter_map(|element| element.inline.as_ref())
+            .any(|inline| inline.has_custom_properties())
+    }
+
+    /// Returns the selector of the parent widget, if known.
+    pub fn parent(&self) -> Option<&StyleSelector> {
+        self.parent.as_deref()
+    }
+
+    /// Returns the selector of the preceding sibling widget, if known.
+    pub fn previous_sibling(&self) -> Option<&StyleSelector> {
+        self.previous_sibling.as_deref()
+    }
+
+    /// Returns the position of the widget among its siblings, if known.
+    pub fn child_position(&self) -> Option<ChildPosition> {
+        self.child_position
+    }
+
+    pub(crate) fn set_context(
+        &mut self,
+        parent: Option<Rc<StyleSelector>>,
+        previous_sibling: Option<Rc<StyleSelector>>,
+        child_position: Option<ChildPosition>,
+    ) {
+        self.parent = parent;
+        self.previous_sibling = previous_sibling;
+        self.child_position = child_position;
+        self.key = Default::default();
+    }
+
+    pub(crate) fn with_context(
+        mut self,
+        parent: Option<Rc<StyleSelector>>,
+        previous_sibling: Option<Rc<StyleSelector>>,
+        child_position: Option<ChildPosition>,
+    ) -> Self {
+        self.set_context(parent, previous_sibling, child_position);
+        self
+    }
+
+    fn ancestors(&self) -> impl Iterator<Item = &StyleSelector> {
+        iter::successors(self.parent(), |selector| selector.parent())
+    }
+
+    fn previous_siblings(&self) -> impl Iterator<Item = &StyleSelector> {
+        iter::successors(self.previous_sibling(), |selector| {
+            selector.previous_sibling()
+        })
+    }
+
     pub fn add_class(&mut self, class: Cow<'static, str>) {
         self.classes.push(class);
         self.classes.sort_unstable();
         self.classes.dedup();
+        self.key = Default::default();
     }
 
     pub fn remove_class(&mut self, class: Cow<'static, str>) {
         self.classes.retain(|c| *c != class);
+        self.key = Default::default();
     }
 
     pub fn has_class(&self, class: &str) -> bool {
@@ -922,10 +1783,12 @@ impl StyleSelector {
     pub fn add_pseudo_class(&mut self, class: PseudoClass) {
         self.pseudo_classes.push(class);
         self.pseudo_classes.sort_unstable();
+        self.key = Default::default();
     }
 
     pub fn remove_pseudo_class(&mut self, class: PseudoClass) {
         self.pseudo_classes.retain(|c| *c != class);
+        self.key = Default::default();
     }
 
     pub fn has_pseudo_class(&self, class: PseudoClass) -> bool {
@@ -950,12 +1813,85 @@ impl StyleSelector {
         self
     }
 
+    /// Returns the selector of a part of the widget that is styled with
+    /// a custom pseudo-element (e.g. `TextArea::gutter`).
+    pub fn with_pseudo_element(mut self, name: Cow<'static, str>) -> Self {
+        self.pseudo_element = Some(name);
+        self.key = Default::default();
+        self
+    }
+
+    pub fn pseudo_element(&self) -> Option<&str> {
+        self.pseudo_element.as_deref()
+    }
+
+    /// Checks whether the selector matches the widget.
+    ///
+    /// Descendant (`A B`), child (`A > B`), next sibling (`A + B`) and subsequent sibling (`A ~ B`)
+    /// combinators are matched against the ancestors and the preceding siblings of the widget.
     pub fn matches(&self, selector: &Selector<'static>) -> bool {
-        let Some(items) = selector_items(selector) else {
+        let components = selector.iter_raw_match_order().collect_vec();
+        let has_pseudo_element = components
+            .iter()
+            .take_while(|item| !matches!(item, Component::Combinator(_)))
+            .any(|item| matches!(item, Component::PseudoElement(_)));
+        if has_pseudo_element != self.pseudo_element.is_some() {
+            return false;
+        }
+        self.matches_components(&components, false)
+    }
+
+    /// Checks whether the selector matches the widget or any of its variants that have
+    /// additional custom pseudo-classes (e.g. `:min`) or a custom pseudo-element
+    /// (e.g. `::gutter`).
+    ///
+    /// Custom pseudo-classes and pseudo-elements of the widget itself are ignored.
+    pub(crate) fn matches_variants(&self, selector: &Selector<'static>) -> bool {
+        let components = selector.iter_raw_match_order().collect_vec();
+        self.matches_components(&components, true)
+    }
+
+    // `components` are in match order (right to left), with compound selectors separated by
+    // `Component::Combinator`. If `any_variant` is true, custom pseudo-classes and
+    // pseudo-elements are not checked in the compound selectors that apply to the widget itself.
+    fn matches_components(&self, components: &[&Component<'static>], any_variant: bool) -> bool {
+        let compound_len = components
+            .iter()
+            .position(|item| matches!(item, Component::Combinator(_)))
+            .unwrap_or(components.len());
+        let (compound, rest) = components.split_at(compound_len);
+        if !self.matches_compound(compound, any_variant) {
             return false;
+        }
+        let Some((Component::Combinator(combinator), rest)) = rest.split_first() else {
+            return true;
         };
-        for item in items {
+        match combinator {
+            Combinator::Child => self
+                .parent()
+                .is_some_and(|parent| parent.matches_components(rest, false)),
+            Combinator::Descendant => self
+                .ancestors()
+                .any(|ancestor| ancestor.matches_components(rest, false)),
+            Combinator::NextSibling => self
+                .previous_sibling()
+                .is_some_and(|sibling| sibling.matches_components(rest, false)),
+            Combinator::LaterSibling => self
+                .previous_siblings()
+                .any(|sibling| sibling.matches_components(rest, false)),
+            // `TextArea::gutter` is split into `::gutter` and `TextArea`, both of which
+            // apply to the same element.
+            Combinator::PseudoElement => self.matches_components(rest, any_variant),
+            _ => false,
+        }
+    }
+
+    fn matches_compound(&self, compound: &[&Component<'static>], any_variant: bool) -> bool {
+        for item in compound {
             match item {
+                Component::NonTSPseudoClass(selector::PseudoClass::Custom { .. })
+                | Component::PseudoElement(PseudoElement::Custom { .. })
+                    if any_variant => {}
                 Component::NonTSPseudoClass(item_class) => {
                     if let Some(item_class) = PseudoClass::from_css(item_class) {
                         if !self.pseudo_classes.contains(&item_class) {
@@ -975,6 +1911,27 @@ impl StyleSelector {
                         return false;
                     }
                 }
+                Component::Nth(data) => {
+                    let pattern = NthPattern {
+                        of_type: data.ty.is_of_type(),
+                        only: data.ty.is_only(),
+                        from_end: data.ty.is_from_end(),
+                        a: data.a,
+                        b: data.b,
+                    };
+                    if !self
+                        .child_position
+                        .is_some_and(|position| position.matches(&pattern))
+                    {
+                        return false;
+                    }
+                }
+                Component::PseudoElement(PseudoElement::Custom { name }) => {
+                    if self.pseudo_element.as_deref() != Some(name.as_ref()) {
+                        return false;
+                    }
+                }
+                Component::ExplicitUniversalType => {}
                 _ => return false,
             }
         }
@@ -984,6 +1941,14 @@ impl StyleSelector {
     pub fn tag(&self) -> &str {
         &self.tag
     }
+
+    pub fn classes(&self) -> &[Cow<'static, str>] {
+        &self.classes
+    }
+
+    pub fn pseudo_classes(&self) -> &[PseudoClass] {
+        &self.pseudo_classes
+    }
 }
 
 // pub struct TagSelector<'a, 'b> {
@@ -1038,3 +2003,262 @@ impl StyleSelector {
 //         None
 //     }
 // }
+
+#[test]
+fn font_families() {
+    let property = Property::parse_string(
+        PropertyId::FontFamily,
+        r#""Open Sans", Noto\ Serif, "Quote \"Sans\"", monospace, "serif""#,
+        Default::default(),
+    )
+    .unwrap();
+    let Property::FontFamily(families) = property else {
+        panic!("unexpected property: {property:?}");
+    };
+    let name = |name: &str| FontFamily::Name(name.into());
+    assert_eq!(
+        convert_font_families(&families),
+        [
+            name("Open Sans"),
+            name("Noto Serif"),
+            name("Quote \"Sans\""),
+            FontFamily::Generic("monospace".into()),
+            // A quoted generic name is a family name.
+            name("serif"),
+        ]
+    );
+    assert_eq!(unescape_family_name(r"\31 23 Font"), "123 Font");
+}
+
+#[cfg(test)]
+fn test_declarations(code: &str) -> Vec<Property<'static>> {
+    let style_sheet = super::load_css(&format!("* {{ {code} }}")).unwrap();
+    let CssRule::Style(rule) = &style_sheet.rules.0[0] else {
+        unreachable!();
+    };
+    rule.declarations.declarations.clone()
+}
+
+#[test]
+fn calc() {
+    let width = |code: &str| {
+        convert_width(&test_declarations(code), 1.0, 10.0.lpx())
+            .unwrap()
+            .map(|width| width.to_i32())
+    };
+    assert_eq!(width("width: calc(1em + 2px)"), Some(12));
+    assert_eq!(width("width: calc((1em + 2px) * 2)"), Some(24));
+    assert_eq!(width("width: calc(1em - 3px)"), Some(7));
+    assert_eq!(width("width: min(1em, 15px)"), Some(10));
+    assert_eq!(width("width: max(1em, 15px)"), Some(15));
+    assert_eq!(width("width: clamp(1em, 30px, 2em)"), Some(20));
+    assert_eq!(width("width: clamp(1em, 5px, 2em)"), Some(10));
+    assert_eq!(width("width: calc(1em + min(2px, 1em))"), Some(12));
+    assert!(convert_width(
+        &test_declarations("width: calc(1em + 10%)"),
+        1.0,
+        10.0.lpx()
+    )
+    .is_err());
+}
+
+#[test]
+fn outline_offset() {
+    let offset = |value: &str| {
+        let code = format!("outline: 1px solid red; outline-offset: {value}");
+        convert_outline(&test_declarations(&code), 1.0, Color::BLACK)
+            .unwrap()
+            .offset
+            .to_i32()
+    };
+    assert_eq!(offset("2px"), 2);
+    assert_eq!(offset("-1px"), -1);
+    assert_eq!(offset("0"), 0);
+    assert_eq!(offset("12pt"), 16);
+    assert_eq!(offset("0.25in"), 24);
+    assert_eq!(offset("calc(6pt + 2px)"), 10);
+    // Invalid values are ignored.
+    assert_eq!(offset("red"), 0);
+}
+
+#[test]
+fn custom_properties() {
+    let collect = |code: &str| {
+        let declarations = test_declarations(code);
+        let mut vars = CustomProperties::new();
+        collect_custom_properties(&declarations.iter().collect_vec(), &mut vars);
+        vars
+    };
+    let color = |code: &str, vars: &CustomProperties| {
+        let declarations = test_declarations(code)
+            .iter()
+            .map(|property| substitute_property_vars(property, vars))
+            .collect::<Result<Vec<_>>>()?;
+        convert_main_color(&declarations).context("no color")
+    };
+    let red = Color::from_rgba8(255, 0, 0, 255);
+    let blue = Color::from_rgba8(0, 0, 255, 255);
+
+    // References are substituted with the values declared earlier, and later declarations
+    // take priority.
+    let vars = collect("--a: red; --b: var(--a); --a: blue; --c: var(--d); --d: red;");
+    assert_eq!(color("color: var(--a)", &vars).unwrap(), blue);
+    assert_eq!(color("color: var(--b)", &vars).unwrap(), red);
+    assert!(!vars.contains_key("--c"));
+    assert!(color("color: var(--c)", &vars).is_err());
+
+    // Fallbacks are used for undefined properties and may contain references.
+    assert_eq!(color("color: var(--x, red)", &vars).unwrap(), red);
+    assert_eq!(
+        color("color: var(--x, var(--y, var(--a)))", &vars).unwrap(),
+        blue
+    );
+    assert_eq!(color("color: var(--a, red)", &vars).unwrap(), blue);
+    assert!(color("color: var(--x)", &vars).is_err());
+
+    // Cyclic references make the property invalid.
+    let vars = collect("--a: red; --a: var(--a); --b: var(--x, var(--b)); --c: var(--a, blue);");
+    assert!(!vars.contains_key("--a"));
+    assert!(!vars.contains_key("--b"));
+    assert_eq!(color("color: var(--c)", &vars).unwrap(), blue);
+
+    // References are substituted in function arguments and multi-token values.
+    let vars = collect("--width: 2px; --color: red;");
+    let border = substitute_property_vars(
+        &test_declarations("border: var(--width) solid var(--color)")[0],
+        &vars,
+    )
+    .unwrap();
+    assert_eq!(
+        border.value_to_css_string(Default::default()).unwrap(),
+        "2px solid red"
+    );
+    let background = substitute_property_vars(
+        &test_declarations("background: linear-gradient(var(--color), blue)")[0],
+        &vars,
+    )
+    .unwrap();
+    assert_eq!(
+        background.value_to_css_string(Default::default()).unwrap(),
+        "linear-gradient(red, #00f)"
+    );
+}
+
+#[test]
+fn pseudo_elements() {
+    let style_sheet = super::load_css(
+        "TextArea::gutter {} TextArea.code::gutter {} TextArea {} Row > TextArea::gutter {}",
+    )
+    .unwrap();
+    let selectors = style_sheet
+        .rules
+        .0
+        .iter()
+        .map(|rule| {
+            let CssRule::Style(rule) = rule else {
+                unreachable!();
+            };
+            rule.selectors.0[0].clone()
+        })
+        .collect_vec();
+    let matching = |element: &StyleSelector| {
+        selectors
+            .iter()
+            .positions(|selector| element.matches(selector))
+            .collect_vec()
+    };
+
+    let text_area = StyleSelector::new("TextArea".into());
+    let gutter = text_area.clone().with_pseudo_element("gutter".into());
+    assert_eq!(gutter.pseudo_element(), Some("gutter"));
+    // Selectors with a pseudo-element only match the pseudo-element, and vice versa.
+    assert_eq!(matching(&text_area), [2]);
+    assert_eq!(matching(&gutter), [0]);
+    assert_eq!(
+        matching(&text_area.clone().with_pseudo_element("whitespace".into())),
+        Vec::<usize>::new()
+    );
+    assert_eq!(
+        matching(
+            &text_area
+                .clone()
+                .with_class("code".into())
+                .with_pseudo_element("gutter".into())
+        ),
+        [0, 1]
+    );
+    let row = Rc::new(StyleSelector::new("Row".into()));
+    let gutter_in_row = text_area
+        .with_context(Some(row), None, None)
+        .with_pseudo_element("gutter".into());
+    assert_eq!(matching(&gutter_in_row), [0, 3]);
+}
+
+#[test]
+fn css_wide_keywords() {
+    let keywords = |code: &str| {
+        test_declarations(code)
+            .iter()
+            .map(|property| {
+                css_wide_keyword(property)
+                    .map(|(id, keyword)| (id.map(|id| id.name().to_string()), keyword))
+            })
+            .collect_vec()
+    };
+    assert_eq!(
+        keywords("color: inherit; padding: initial; foo: unset; all: revert; color: red"),
+        [
+            Some((Some("color".into()), CSSWideKeyword::Inherit)),
+            Some((Some("padding".into()), CSSWideKeyword::Initial)),
+            Some((Some("foo".into()), CSSWideKeyword::Unset)),
+            Some((None, CSSWideKeyword::Revert)),
+            None,
+        ]
+    );
+    // Custom properties are handled by `var()` substitution.
+    assert_eq!(keywords("--x: unset"), [None]);
+
+    let padding = PropertyId::from("padding");
+    let declarations = test_declarations("padding-left: 1px; padding: 2px; margin-left: 3px");
+    assert!(affects_property(&padding, &declarations[0]));
+    assert!(affects_property(&padding, &declarations[1]));
+    assert!(!affects_property(&padding, &declarations[2]));
+}
+
+#[test]
+fn child_position() {
+    let matching = |code: &str, count: usize| {
+        let style_sheet = super::load_css(&format!("{code} {{}}")).unwrap();
+        let CssRule::Style(rule) = &style_sheet.rules.0[0] else {
+            unreachable!();
+        };
+        let selector = &rule.selectors.0[0];
+        // One-based indexes of the matching children.
+        (0..count)
+            .filter(|&index| {
+                StyleSelector::new("Label".into())
+                    .with_context(None, None, Some(ChildPosition { index, count }))
+                    .matches(selector)
+            })
+            .map(|index| index + 1)
+            .collect_vec()
+    };
+    const NONE: [usize; 0] = [];
+    assert_eq!(matching(":first-child", 3), [1]);
+    assert_eq!(matching(":last-child", 3), [3]);
+    assert_eq!(matching(":first-child:last-child", 3), NONE);
+    assert_eq!(matching(":first-child:last-child", 1), [1]);
+    assert_eq!(matching(":only-child", 1), [1]);
+    assert_eq!(matching(":only-child", 2), NONE);
+    assert_eq!(matching(":nth-child(3)", 6), [3]);
+    assert_eq!(matching(":nth-child(2n+1)", 6), [1, 3, 5]);
+    assert_eq!(matching(":nth-child(odd)", 6), [1, 3, 5]);
+    assert_eq!(matching(":nth-child(even)", 6), [2, 4, 6]);
+    assert_eq!(matching(":nth-child(3n)", 7), [3, 6]);
+    assert_eq!(matching(":nth-child(n+4)", 6), [4, 5, 6]);
+    assert_eq!(matching(":nth-child(-n+3)", 6), [1, 2, 3]);
+    assert_eq!(matching(":nth-last-child(2)", 6), [5]);
+    assert_eq!(matching(":nth-last-child(2n)", 5), [2, 4]);
+    // `*-of-type` selectors are not supported.
+    assert_eq!(matching(":first-of-type", 3), NONE);
+}
@@ -1,42 +1,85 @@
 use {
     super::Style,
     crate::{
-        style::FontStyle,
+        style::{FontSlant, FontStyle},
         types::{LogicalPixels, LpxSuffix},
     },
     tiny_skia::Color,
+    winit::window::Theme,
 };
 
-macro_rules! file {
-    ($path: literal) => {
+macro_rules! theme_files {
+    ($theme: literal) => {
+        [
+            theme_files!($theme, "scroll_left.svg"),
+            theme_files!($theme, "scroll_right.svg"),
+            theme_files!($theme, "scroll_up.svg"),
+            theme_files!($theme, "scroll_down.svg"),
+            theme_files!($theme, "scroll_grip_x.svg"),
+            theme_files!($theme, "scroll_grip_y.svg"),
+            theme_files!($theme, "scroll_left_disabled.svg"),
+            theme_files!($theme, "scroll_right_disabled.svg"),
+            theme_files!($theme, "scroll_up_disabled.svg"),
+            theme_files!($theme, "scroll_down_disabled.svg"),
+            theme_files!($theme, "scroll_grip_x_disabled.svg"),
+            theme_files!($theme, "scroll_grip_y_disabled.svg"),
+        ]
+    };
+    ($theme: literal, $path: literal) => {
         (
             $path,
-            &include_bytes!(concat!("../../themes/default/", $path))[..],
+            &include_bytes!(concat!("../../themes/", $theme, "/", $path))[..],
         )
     };
 }
 
+/// Light theme used by default.
 pub fn default_style() -> Style {
     Style::load_bundled(
         include_str!("../../themes/default/theme.css"),
-        [
-            file!("scroll_left.svg"),
-            file!("scroll_right.svg"),
-            file!("scroll_up.svg"),
-            file!("scroll_down.svg"),
-            file!("scroll_grip_x.svg"),
-            file!("scroll_grip_y.svg"),
-            file!("scroll_left_disabled.svg"),
-            file!("scroll_right_disabled.svg"),
-            file!("scroll_up_disabled.svg"),
-            file!("scroll_down_disabled.svg"),
-            file!("scroll_grip_x_disabled.svg"),
-            file!("scroll_grip_y_disabled.svg"),
-        ],
+        theme_files!("default"),
+    )
+    .unwrap()
+}
+
+/// Dark theme. It reuses the rules of the default theme and only overrides its colors.
+pub fn dark_style() -> Style {
+    Style::load_bundled(
+        concat!(
+            include_str!("../../themes/default/theme.css"),
+            include_str!("../../themes/dark/theme.css"),
+        ),
+        theme_files!("dark"),
     )
     .unwrap()
 }
 
+/// High contrast theme (light text on black background).
+/// It reuses the rules of the default theme and only overrides its colors.
+pub fn high_contrast_style() -> Style {
+    Style::load_bundled(
+        concat!(
+            include_str!("../../themes/default/theme.css"),
+            include_str!("../../themes/high_contrast/theme.css"),
+        ),
+        theme_files!("high_contrast"),
+    )
+    .unwrap()
+}
+
+/// Returns the bundled theme for the specified color scheme.
+pub fn style_for_theme(theme: Theme) -> Style {
+    match theme {
+        Theme::Light => default_style(),
+        Theme::Dark => dark_style(),
+    }
+}
+
+/// Size of named icons if `icon-size` is not specified.
+pub fn icon_size() -> LogicalPixels {
+    16.0.lpx()
+}
+
 pub fn font_size() -> LogicalPixels {
     13.0.lpx()
 }
@@ -57,14 +100,44 @@ pub fn selected_text_background() -> Color {
     Color::from_rgba8(100, 100, 150, 255)
 }
 
+pub fn placeholder_text_color() -> Color {
+    Color::from_rgba8(128, 128, 128, 255)
+}
+
+pub fn link_text_color() -> Color {
+    Color::from_rgba8(0, 0, 238, 255)
+}
+
+pub fn find_match_background() -> Color {
+    Color::from_rgba8(248, 228, 92, 255)
+}
+
+pub fn current_line_background() -> Color {
+    Color::from_rgba8(240, 244, 250, 255)
+}
+
+pub fn whitespace_color() -> Color {
+    Color::from_rgba8(200, 200, 200, 255)
+}
+
+pub fn gutter_text_color() -> Color {
+    Color::from_rgba8(140, 140, 140, 255)
+}
+
 pub const DEFAULT_PREFERRED_WIDTH_EM: f32 = 10.0;
 pub const DEFAULT_MIN_WIDTH_EM: f32 = 2.0;
 
 pub const DEFAULT_LINE_HEIGHT: f32 = 1.2;
 
+pub const DEFAULT_FONT_WEIGHT: u16 = 400;
+
 pub fn font_style() -> FontStyle {
     FontStyle {
         font_size: font_size(),
         line_height: font_size() * DEFAULT_LINE_HEIGHT,
+        families: Vec::new(),
+        weight: DEFAULT_FONT_WEIGHT,
+        slant: FontSlant::Normal,
+        letter_spacing: LogicalPixels::default(),
     }
 }
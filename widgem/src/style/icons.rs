@@ -0,0 +1,720 @@
+//! Named icons (e.g. `edit-copy`).
+//!
+//! Icons are looked up by their names as defined by the
+//! [freedesktop Icon Naming Specification](https://specifications.freedesktop.org/icon-naming-spec/latest/).
+//! An [IconProvider] maps a name to the icon's image data. The app uses
+//! [default_icon_provider] unless another provider is set with
+//! [AppBuilder::with_icon_provider](crate::AppBuilder::with_icon_provider).
+//!
+//! Named icons can be used in styles with `icon: theme("edit-copy");` and from Rust code
+//! with [App::load_icon](crate::App::load_icon).
+
+use {
+    crate::Pixmap,
+    anyhow::{bail, Context, Result},
+    std::{
+        borrow::Cow,
+        cell::RefCell,
+        cmp::Reverse,
+        collections::{HashMap, HashSet},
+        env,
+        fmt::Debug,
+        path::{Path, PathBuf},
+        rc::Rc,
+    },
+    tracing::warn,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconFormat {
+    Svg,
+    Png,
+}
+
+impl IconFormat {
+    /// Determines the format by the file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "svg" => Some(Self::Svg),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Image data of an icon returned by an [IconProvider].
+#[derive(Debug, Clone)]
+pub struct IconData {
+    pub data: Cow<'static, [u8]>,
+    pub format: IconFormat,
+    /// Symbolic icons are single-color icons that are tinted with the text color
+    /// (the value of the `color` property) when displayed.
+    pub symbolic: bool,
+}
+
+impl IconData {
+    /// Reads an icon file. The format is determined by the file extension.
+    ///
+    /// The icon is considered symbolic if its name ends with `-symbolic`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let format = IconFormat::from_path(path)
+            .with_context(|| format!("unsupported icon format: {:?}", path.display()))?;
+        Ok(Self {
+            data: Cow::Owned(fs_err::read(path)?),
+            format,
+            symbolic: is_symbolic_icon(path),
+        })
+    }
+
+    /// Renders the icon so that it fits into a square with the side of `size` physical pixels.
+    pub fn render(&self, size: u32) -> Result<Pixmap> {
+        let pixmap = match self.format {
+            IconFormat::Svg => {
+                let tree = usvg::Tree::from_data(&self.data, &Default::default())?;
+                let source_size = tree.size().width().max(tree.size().height());
+                render_svg_tree(&tree, size as f32 / source_size)?
+            }
+            IconFormat::Png => {
+                let image = tiny_skia::Pixmap::decode_png(&self.data)?;
+                let source_size = image.width().max(image.height());
+                if source_size == 0 {
+                    bail!("empty png image");
+                }
+                scale_pixmap(image, size as f32 / source_size as f32)?
+            }
+        };
+        Ok(pixmap.into())
+    }
+}
+
+/// Source of named icons.
+pub trait IconProvider: Debug {
+    /// Finds the icon with the specified name.
+    ///
+    /// `size` is the nominal size of the icon in logical pixels,
+    /// and `scale` is the integer scale of the display (e.g. 2 for HiDPI displays).
+    /// The returned icon is not required to have the exact requested size.
+    ///
+    /// Returns `None` if the provider doesn't have the icon.
+    fn find_icon(&self, name: &str, size: u32, scale: u32) -> Option<IconData>;
+}
+
+/// Icon provider that queries other providers in order and returns the first icon found.
+#[derive(Debug, Default)]
+pub struct IconProviderChain {
+    providers: Vec<Rc<dyn IconProvider>>,
+}
+
+impl IconProviderChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a provider to the end of the chain.
+    pub fn push(&mut self, provider: Rc<dyn IconProvider>) -> &mut Self {
+        self.providers.push(provider);
+        self
+    }
+}
+
+impl IconProvider for IconProviderChain {
+    fn find_icon(&self, name: &str, size: u32, scale: u32) -> Option<IconData> {
+        self.providers
+            .iter()
+            .find_map(|provider| provider.find_icon(name, size, scale))
+    }
+}
+
+macro_rules! embedded_icons {
+    ($($name: literal,)*) => {
+        &[$(
+            (
+                $name,
+                include_bytes!(concat!("../../icons/", $name, "-symbolic.svg")),
+            ),
+        )*]
+    };
+}
+
+const EMBEDDED_ICONS: &[(&str, &[u8])] = embedded_icons![
+    "dialog-error",
+    "dialog-information",
+    "dialog-warning",
+    "document-new",
+    "document-open",
+    "document-save",
+    "edit-clear",
+    "edit-copy",
+    "edit-cut",
+    "edit-delete",
+    "edit-find",
+    "edit-paste",
+    "edit-redo",
+    "edit-select-all",
+    "edit-undo",
+    "go-down",
+    "go-next",
+    "go-previous",
+    "go-up",
+    "list-add",
+    "list-remove",
+    "window-close",
+];
+
+/// A small set of symbolic icons bundled with the library.
+///
+/// It's used as a fallback when the system doesn't provide an icon.
+/// Icons can be requested with or without the `-symbolic` suffix.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EmbeddedIcons;
+
+impl EmbeddedIcons {
+    /// Returns names of all embedded icons.
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        EMBEDDED_ICONS.iter().map(|(name, _)| *name)
+    }
+}
+
+impl IconProvider for EmbeddedIcons {
+    fn find_icon(&self, name: &str, _size: u32, _scale: u32) -> Option<IconData> {
+        let name = name.strip_suffix("-symbolic").unwrap_or(name);
+        let (_, data) = EMBEDDED_ICONS.iter().find(|(n, _)| *n == name)?;
+        Some(IconData {
+            data: Cow::Borrowed(data),
+            format: IconFormat::Svg,
+            symbolic: true,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DirectoryType {
+    Fixed,
+    Scalable,
+    Threshold,
+}
+
+#[derive(Debug, Clone)]
+struct ThemeDirectory {
+    path: String,
+    size: u32,
+    scale: u32,
+    min_size: u32,
+    max_size: u32,
+    threshold: u32,
+    type_: DirectoryType,
+}
+
+impl ThemeDirectory {
+    fn parse(path: &str, section: &HashMap<String, String>) -> Option<Self> {
+        let get = |key: &str| section.get(key).and_then(|value| value.parse::<u32>().ok());
+        let size = get("Size")?;
+        let type_ = match section.get("Type").map(|s| s.as_str()) {
+            Some("Fixed") => DirectoryType::Fixed,
+            Some("Scalable") => DirectoryType::Scalable,
+            _ => DirectoryType::Threshold,
+        };
+        Some(Self {
+            path: path.into(),
+            size,
+            scale: get("Scale").unwrap_or(1),
+            min_size: get("MinSize").unwrap_or(size),
+            max_size: get("MaxSize").unwrap_or(size),
+            threshold: get("Threshold").unwrap_or(2),
+            type_,
+        })
+    }
+
+    fn matches_size(&self, size: u32, scale: u32) -> bool {
+        if self.scale != scale {
+            return false;
+        }
+        match self.type_ {
+            DirectoryType::Fixed => self.size == size,
+            DirectoryType::Scalable => (self.min_size..=self.max_size).contains(&size),
+            DirectoryType::Threshold => (self.size.saturating_sub(self.threshold)
+                ..=self.size + self.threshold)
+                .contains(&size),
+        }
+    }
+
+    fn size_distance(&self, size: u32, scale: u32) -> u32 {
+        let target = size * scale;
+        let (min, max) = match self.type_ {
+            DirectoryType::Fixed => (self.size, self.size),
+            DirectoryType::Scalable => (self.min_size, self.max_size),
+            DirectoryType::Threshold => (
+                self.size.saturating_sub(self.threshold),
+                self.size + self.threshold,
+            ),
+        };
+        let (min, max) = (min * self.scale, max * self.scale);
+        if target < min {
+            min - target
+        } else {
+            target.saturating_sub(max)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ThemeIndex {
+    inherits: Vec<String>,
+    directories: Vec<ThemeDirectory>,
+}
+
+impl ThemeIndex {
+    fn parse(text: &str) -> Result<Self> {
+        let sections = parse_ini(text);
+        let main = sections
+            .get("Icon Theme")
+            .context("missing [Icon Theme] section")?;
+        let list = |key: &str| -> Vec<String> {
+            main.get(key)
+                .map(|value| {
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let directories = list("Directories")
+            .into_iter()
+            .chain(list("ScaledDirectories"))
+            .filter_map(|path| ThemeDirectory::parse(&path, sections.get(&path)?))
+            .collect();
+        Ok(Self {
+            inherits: list("Inherits"),
+            directories,
+        })
+    }
+}
+
+const ICON_EXTENSIONS: &[&str] = &["png", "svg"];
+
+/// Icon provider that implements the
+/// [freedesktop Icon Theme Specification](https://specifications.freedesktop.org/icon-theme-spec/latest/).
+///
+/// Icons are searched in the specified theme, then in the themes it inherits from,
+/// then in the `hicolor` theme and finally in the unthemed directories: the base directories
+/// and the [pixmap directories](Self::with_pixmap_dirs).
+/// If no theme directory matches the requested size, the closest size is used.
+/// If two sizes are equally close, the larger one is preferred because downscaling
+/// looks better than upscaling.
+/// If an icon is not found, the last dash-separated part of the name is removed and the search
+/// is repeated (e.g. `edit-copy-special` falls back to `edit-copy`).
+#[derive(Debug)]
+pub struct FreedesktopIconTheme {
+    theme_name: String,
+    base_dirs: Vec<PathBuf>,
+    pixmap_dirs: Vec<PathBuf>,
+    themes: RefCell<HashMap<String, Option<Rc<ThemeIndex>>>>,
+    lookup_cache: RefCell<HashMap<(String, u32, u32), Option<PathBuf>>>,
+}
+
+impl FreedesktopIconTheme {
+    /// Creates a provider for the theme with the specified name (e.g. `Adwaita` or `breeze`),
+    /// using the standard icon directories.
+    pub fn new(theme_name: impl Into<String>) -> Self {
+        Self::with_base_dirs(theme_name, default_base_dirs())
+    }
+
+    /// Creates a provider for the theme with the specified name, looking for
+    /// themes in `base_dirs` (in order of priority).
+    pub fn with_base_dirs(theme_name: impl Into<String>, base_dirs: Vec<PathBuf>) -> Self {
+        Self {
+            theme_name: theme_name.into(),
+            base_dirs,
+            pixmap_dirs: vec!["/usr/share/pixmaps".into()],
+            themes: Default::default(),
+            lookup_cache: Default::default(),
+        }
+    }
+
+    /// Creates a provider for the icon theme configured in the desktop environment.
+    ///
+    /// GTK and KDE settings files are checked. If the theme can't be determined,
+    /// `hicolor` is used.
+    pub fn system() -> Self {
+        Self::new(system_theme_name().unwrap_or_else(|| "hicolor".into()))
+    }
+
+    /// Replaces the directories containing unthemed icons (`/usr/share/pixmaps` by default).
+    ///
+    /// These directories are searched after the base directories if the icon
+    /// is not found in any theme.
+    pub fn with_pixmap_dirs(mut self, pixmap_dirs: Vec<PathBuf>) -> Self {
+        self.pixmap_dirs = pixmap_dirs;
+        self
+    }
+
+    pub fn theme_name(&self) -> &str {
+        &self.theme_name
+    }
+
+    pub fn base_dirs(&self) -> &[PathBuf] {
+        &self.base_dirs
+    }
+
+    pub fn pixmap_dirs(&self) -> &[PathBuf] {
+        &self.pixmap_dirs
+    }
+
+    /// Returns the path of the icon file, or `None` if the icon is not found.
+    pub fn find_icon_path(&self, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let key = (name.to_string(), size, scale);
+        if let Some(path) = self.lookup_cache.borrow().get(&key) {
+            return path.clone();
+        }
+        let mut candidate = name;
+        let path = loop {
+            if let Some(path) = self.find_icon_path_exact(candidate, size, scale) {
+                break Some(path);
+            }
+            match candidate.rsplit_once('-') {
+                Some((prefix, _)) => candidate = prefix,
+                None => break None,
+            }
+        };
+        self.lookup_cache.borrow_mut().insert(key, path.clone());
+        path
+    }
+
+    fn find_icon_path_exact(&self, name: &str, size: u32, scale: u32) -> Option<PathBuf> {
+        let mut visited = HashSet::new();
+        self.find_in_theme(&self.theme_name, name, size, scale, &mut visited)
+            .or_else(|| self.find_in_theme("hicolor", name, size, scale, &mut visited))
+            .or_else(|| self.find_unthemed(name))
+    }
+
+    fn find_in_theme(
+        &self,
+        theme_name: &str,
+        name: &str,
+        size: u32,
+        scale: u32,
+        visited: &mut HashSet<String>,
+    ) -> Option<PathBuf> {
+        if !visited.insert(theme_name.to_string()) {
+            return None;
+        }
+        let theme = self.theme(theme_name)?;
+        if let Some(path) = self.lookup_icon(theme_name, &theme, name, size, scale) {
+            return Some(path);
+        }
+        theme
+            .inherits
+            .iter()
+            .find_map(|parent| self.find_in_theme(parent, name, size, scale, visited))
+    }
+
+    fn lookup_icon(
+        &self,
+        theme_name: &str,
+        theme: &ThemeIndex,
+        name: &str,
+        size: u32,
+        scale: u32,
+    ) -> Option<PathBuf> {
+        let candidates = || {
+            theme.directories.iter().flat_map(move |dir| {
+                self.base_dirs.iter().flat_map(move |base_dir| {
+                    ICON_EXTENSIONS.iter().map(move |extension| {
+                        let path = base_dir
+                            .join(theme_name)
+                            .join(&dir.path)
+                            .join(format!("{name}.{extension}"));
+                        (dir, path)
+                    })
+                })
+            })
+        };
+        if let Some((_, path)) = candidates()
+            .filter(|(dir, _)| dir.matches_size(size, scale))
+            .find(|(_, path)| path.is_file())
+        {
+            return Some(path);
+        }
+        candidates()
+            .filter(|(_, path)| path.is_file())
+            .min_by_key(|(dir, _)| {
+                // On ties, prefer the larger size.
+                (
+                    dir.size_distance(size, scale),
+                    Reverse(dir.size * dir.scale),
+                )
+            })
+            .map(|(_, path)| path)
+    }
+
+    fn find_unthemed(&self, name: &str) -> Option<PathBuf> {
+        self.base_dirs
+            .iter()
+            .chain(&self.pixmap_dirs)
+            .flat_map(|dir| {
+                ICON_EXTENSIONS
+                    .iter()
+                    .map(move |extension| dir.join(format!("{name}.{extension}")))
+            })
+            .find(|path| path.is_file())
+    }
+
+    fn theme(&self, theme_name: &str) -> Option<Rc<ThemeIndex>> {
+        if let Some(theme) = self.themes.borrow().get(theme_name) {
+            return theme.clone();
+        }
+        let theme = self
+            .base_dirs
+            .iter()
+            .map(|dir| dir.join(theme_name).join("index.theme"))
+            .find(|path| path.is_file())
+            .and_then(|path| {
+                let text = fs_err::read_to_string(&path)
+                    .map_err(|err| warn!("failed to read icon theme index: {err}"))
+                    .ok()?;
+                ThemeIndex::parse(&text)
+                    .map_err(|err| warn!("invalid icon theme index {path:?}: {err}"))
+                    .ok()
+            })
+            .map(Rc::new);
+        self.themes
+            .borrow_mut()
+            .insert(theme_name.to_string(), theme.clone());
+        theme
+    }
+}
+
+impl IconProvider for FreedesktopIconTheme {
+    fn find_icon(&self, name: &str, size: u32, scale: u32) -> Option<IconData> {
+        let path = self.find_icon_path(name, size, scale)?;
+        IconData::load(&path)
+            .map_err(|err| warn!("failed to load icon {path:?}: {err:?}"))
+            .ok()
+    }
+}
+
+thread_local! {
+    static DEFAULT_ICON_PROVIDER: Rc<dyn IconProvider> = {
+        let mut chain = IconProviderChain::new();
+        #[cfg(all(unix, not(target_os = "macos")))]
+        chain.push(Rc::new(FreedesktopIconTheme::system()));
+        chain.push(Rc::new(EmbeddedIcons));
+        Rc::new(chain)
+    };
+}
+
+/// Returns the icon provider used by default.
+///
+/// On Linux and BSD, it uses the system icon theme ([FreedesktopIconTheme::system]).
+/// Icons not found in the system theme (and all icons on other platforms) are taken
+/// from [EmbeddedIcons].
+pub fn default_icon_provider() -> Rc<dyn IconProvider> {
+    DEFAULT_ICON_PROVIDER.with(Rc::clone)
+}
+
+/// Returns true if the icon is symbolic, i.e. its name ends with `-symbolic`.
+pub(crate) fn is_symbolic_icon(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| stem.trim_end_matches("@2x").ends_with("-symbolic"))
+}
+
+pub(crate) fn render_svg_tree(tree: &usvg::Tree, scale: f32) -> Result<tiny_skia::Pixmap> {
+    let pixmap_size_x = (tree.size().width() * scale).ceil() as u32;
+    let pixmap_size_y = (tree.size().height() * scale).ceil() as u32;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(pixmap_size_x, pixmap_size_y).context("invalid svg size")?;
+    resvg::render(
+        tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    Ok(pixmap)
+}
+
+pub(crate) fn scale_pixmap(image: tiny_skia::Pixmap, scale: f32) -> Result<tiny_skia::Pixmap> {
+    if scale == 1.0 {
+        return Ok(image);
+    }
+    let pixmap_size_x = (image.width() as f32 * scale).ceil() as u32;
+    let pixmap_size_y = (image.height() as f32 * scale).ceil() as u32;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(pixmap_size_x, pixmap_size_y).context("invalid image size")?;
+    pixmap.draw_pixmap(
+        0,
+        0,
+        image.as_ref(),
+        &tiny_skia::PixmapPaint {
+            quality: tiny_skia::FilterQuality::Bicubic,
+            ..Default::default()
+        },
+        tiny_skia::Transform::from_scale(scale, scale),
+        None,
+    );
+    Ok(pixmap)
+}
+
+fn default_base_dirs() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let mut dirs = Vec::new();
+    if let Some(home) = &home {
+        dirs.push(home.join(".icons"));
+    }
+    match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => dirs.push(PathBuf::from(dir).join("icons")),
+        _ => {
+            if let Some(home) = &home {
+                dirs.push(home.join(".local/share/icons"));
+            }
+        }
+    }
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".into());
+    dirs.extend(
+        data_dirs
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| Path::new(dir).join("icons")),
+    );
+    dirs
+}
+
+fn system_theme_name() -> Option<String> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    let read_setting = |file: &str, section: &str, key: &str| {
+        let text = std::fs::read_to_string(config_dir.join(file)).ok()?;
+        parse_ini(&text).get(section)?.get(key).cloned()
+    };
+    let is_kde = env::var("XDG_CURRENT_DESKTOP").is_ok_and(|desktop| desktop.contains("KDE"));
+    if is_kde {
+        if let Some(name) = read_setting("kdeglobals", "Icons", "Theme") {
+            return Some(name);
+        }
+        return Some("breeze".into());
+    }
+    read_setting("gtk-4.0/settings.ini", "Settings", "gtk-icon-theme-name")
+        .or_else(|| read_setting("gtk-3.0/settings.ini", "Settings", "gtk-icon-theme-name"))
+}
+
+fn parse_ini(text: &str) -> HashMap<String, HashMap<String, String>> {
+    let mut sections = HashMap::<String, HashMap<String, String>>::new();
+    let mut current = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            current = Some(name.to_string());
+            sections.entry(name.to_string()).or_default();
+        } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+    sections
+}
+
+#[test]
+fn theme_index() {
+    let text = "# comment\n[Icon Theme]\nName = Test\nInherits=parent, , hicolor\n\
+         Directories=16x16/actions,scalable/actions,missing\nScaledDirectories=16x16@2/actions\n\n\
+         [16x16/actions]\nSize=16\nType=Fixed\n\
+         [scalable/actions]\nSize=16\nMinSize=8\nMaxSize=512\nType=Scalable\n\
+         [16x16@2/actions]\nSize=16\nScale=2\n";
+    let sections = parse_ini(text);
+    assert_eq!(sections["Icon Theme"]["Name"], "Test");
+    assert_eq!(sections["scalable/actions"]["MaxSize"], "512");
+
+    let index = ThemeIndex::parse(text).unwrap();
+    assert_eq!(index.inherits, ["parent", "hicolor"]);
+    let [fixed, scalable, scaled] = &index.directories[..] else {
+        panic!("unexpected directories: {:?}", index.directories);
+    };
+    assert!(fixed.matches_size(16, 1));
+    assert!(!fixed.matches_size(17, 1));
+    assert!(!fixed.matches_size(16, 2));
+    assert_eq!(fixed.size_distance(24, 1), 8);
+    assert!(scalable.matches_size(100, 1));
+    assert_eq!(scalable.size_distance(4, 1), 4);
+    // Threshold directories match sizes within 2 pixels by default.
+    assert!(scaled.matches_size(18, 2));
+    assert!(!scaled.matches_size(19, 2));
+    assert!(!scaled.matches_size(16, 1));
+    assert_eq!(scaled.size_distance(16, 2), 0);
+
+    assert!(ThemeIndex::parse("[Other]\nName=x").is_err());
+}
+
+#[test]
+fn freedesktop_icon_theme() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |path: &str, text: &str| {
+        let path = dir.path().join(path);
+        fs_err::create_dir_all(path.parent().unwrap()).unwrap();
+        fs_err::write(path, text).unwrap();
+    };
+    write(
+        "icons/child/index.theme",
+        "[Icon Theme]\nInherits=parent\nDirectories=16x16/actions\n\
+         [16x16/actions]\nSize=16\nType=Fixed\n",
+    );
+    write(
+        "icons/parent/index.theme",
+        "[Icon Theme]\nDirectories=16x16/actions,32x32/actions\n\
+         [16x16/actions]\nSize=16\nType=Fixed\n[32x32/actions]\nSize=32\nType=Fixed\n",
+    );
+    write("icons/child/16x16/actions/edit-copy.svg", "");
+    write("icons/parent/16x16/actions/edit-paste.png", "");
+    write("icons/parent/32x32/actions/edit-paste.png", "");
+    write("icons/unthemed.png", "");
+    write("pixmaps/legacy.png", "");
+
+    let theme = FreedesktopIconTheme::with_base_dirs("child", vec![dir.path().join("icons")])
+        .with_pixmap_dirs(vec![dir.path().join("pixmaps")]);
+    let path = |name: &str, size: u32| {
+        theme.find_icon_path(name, size, 1).map(|path| {
+            path.strip_prefix(dir.path())
+                .unwrap()
+                .to_string_lossy()
+                .replace('\\', "/")
+        })
+    };
+    assert_eq!(
+        path("edit-copy", 16).as_deref(),
+        Some("icons/child/16x16/actions/edit-copy.svg")
+    );
+    // Icons are found in the inherited theme, using the closest size.
+    assert_eq!(
+        path("edit-paste", 32).as_deref(),
+        Some("icons/parent/32x32/actions/edit-paste.png")
+    );
+    assert_eq!(
+        path("edit-paste", 20).as_deref(),
+        Some("icons/parent/16x16/actions/edit-paste.png")
+    );
+    // The larger size wins if both sizes are equally close.
+    assert_eq!(
+        path("edit-paste", 24).as_deref(),
+        Some("icons/parent/32x32/actions/edit-paste.png")
+    );
+    // The last part of the name is removed if the icon is not found.
+    assert_eq!(
+        path("edit-copy-special", 16).as_deref(),
+        Some("icons/child/16x16/actions/edit-copy.svg")
+    );
+    assert_eq!(path("unthemed", 16).as_deref(), Some("icons/unthemed.png"));
+    assert_eq!(path("legacy", 16).as_deref(), Some("pixmaps/legacy.png"));
+    assert_eq!(path("missing", 16), None);
+}
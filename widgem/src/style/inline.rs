@@ -0,0 +1,302 @@
+use {
+    super::{css::is_custom_property, load_css, Padding},
+    crate::types::LogicalPixels,
+    anyhow::Result,
+    lightningcss::{
+        properties::{
+            border::BorderSideWidth,
+            font::{AbsoluteFontWeight, FontSize, FontWeight},
+            size::Size,
+            Property,
+        },
+        selector::Selector,
+        stylesheet::StyleSheet,
+        values::{
+            color::{CssColor, RGBA},
+            length::{Length, LengthPercentage, LengthPercentageOrAuto, LengthValue},
+            size::Size2D,
+        },
+        vendor_prefix::VendorPrefix,
+    },
+    std::{
+        hash::{Hash, Hasher},
+        mem,
+        rc::Rc,
+    },
+    tiny_skia::Color,
+};
+
+/// A style property that can be set on a widget directly from Rust code
+/// with [WidgetBase::set_style_property](crate::WidgetBase::set_style_property).
+///
+/// Unlike [WidgetBase::set_style](crate::WidgetBase::set_style), it doesn't require
+/// formatting and parsing CSS code, so it's suitable for frequently changing values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StyleProperty {
+    Color(Color),
+    BackgroundColor(Color),
+    BorderColor(Color),
+    BorderWidth(LogicalPixels),
+    BorderRadius(LogicalPixels),
+    Padding(Padding),
+    Width(LogicalPixels),
+    Height(LogicalPixels),
+    FontSize(LogicalPixels),
+    FontWeight(u16),
+}
+
+fn css_color(color: Color) -> CssColor {
+    let color = color.to_color_u8();
+    CssColor::RGBA(RGBA {
+        red: color.red(),
+        green: color.green(),
+        blue: color.blue(),
+        alpha: color.alpha(),
+    })
+}
+
+fn css_length(value: LogicalPixels) -> LengthPercentage {
+    LengthPercentage::Dimension(LengthValue::Px(value.to_f32()))
+}
+
+impl StyleProperty {
+    fn to_css(&self) -> Vec<Property<'static>> {
+        match self {
+            Self::Color(color) => vec![Property::Color(css_color(*color))],
+            Self::BackgroundColor(color) => vec![Property::BackgroundColor(css_color(*color))],
+            Self::BorderColor(color) => vec![
+                Property::BorderTopColor(css_color(*color)),
+                Property::BorderRightColor(css_color(*color)),
+                Property::BorderBottomColor(css_color(*color)),
+                Property::BorderLeftColor(css_color(*color)),
+            ],
+            Self::BorderWidth(width) => {
+                let width =
+                    || BorderSideWidth::Length(Length::Value(LengthValue::Px(width.to_f32())));
+                vec![
+                    Property::BorderTopWidth(width()),
+                    Property::BorderRightWidth(width()),
+                    Property::BorderBottomWidth(width()),
+                    Property::BorderLeftWidth(width()),
+                ]
+            }
+            Self::BorderRadius(radius) => {
+                let radius = || Size2D(css_length(*radius), css_length(*radius));
+                vec![
+                    Property::BorderTopLeftRadius(radius(), VendorPrefix::None),
+                    Property::BorderTopRightRadius(radius(), VendorPrefix::None),
+                    Property::BorderBottomRightRadius(radius(), VendorPrefix::None),
+                    Property::BorderBottomLeftRadius(radius(), VendorPrefix::None),
+                ]
+            }
+            Self::Padding(padding) => {
+                let x = || LengthPercentageOrAuto::LengthPercentage(css_length(padding.x));
+                let y = || LengthPercentageOrAuto::LengthPercentage(css_length(padding.y));
+                vec![
+                    Property::PaddingTop(y()),
+                    Property::PaddingRight(x()),
+                    Property::PaddingBottom(y()),
+                    Property::PaddingLeft(x()),
+                ]
+            }
+            Self::Width(width) => vec![Property::Width(Size::LengthPercentage(css_length(*width)))],
+            Self::Height(height) => vec![Property::Height(Size::LengthPercentage(css_length(
+                *height,
+            )))],
+            Self::FontSize(size) => vec![Property::FontSize(FontSize::Length(css_length(*size)))],
+            Self::FontWeight(weight) => vec![Property::FontWeight(FontWeight::Absolute(
+                AbsoluteFontWeight::Weight(f32::from(*weight)),
+            ))],
+        }
+    }
+}
+
+/// Style set on a specific widget. It takes priority over the rules of the app's style.
+///
+/// It consists of the CSS code set with [WidgetBase::set_style](crate::WidgetBase::set_style)
+/// and properties set with
+/// [WidgetBase::set_style_property](crate::WidgetBase::set_style_property).
+/// Properties set from Rust take priority over the CSS code.
+#[derive(Debug, Clone, Default)]
+pub struct InlineStyle {
+    code: Option<String>,
+    style_sheet: Option<Rc<StyleSheet<'static, 'static>>>,
+    properties: Vec<StyleProperty>,
+    declarations: Vec<Property<'static>>,
+    has_custom_properties: bool,
+}
+
+impl PartialEq for InlineStyle {
+    fn eq(&self, other: &Self) -> bool {
+        self.code == other.code && self.properties == other.properties
+    }
+}
+
+impl Eq for InlineStyle {}
+
+impl Hash for InlineStyle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.code.hash(state);
+        for property in &self.properties {
+            mem::discriminant(property).hash(state);
+        }
+    }
+}
+
+impl InlineStyle {
+    /// Returns the CSS code of the style.
+    pub fn code(&self) -> Option<&str> {
+        self.code.as_deref()
+    }
+
+    /// Replaces the CSS code of the style.
+    ///
+    /// The code can either be a complete style sheet (e.g. `Button { color: red; }`) or
+    /// a list of declarations applied to the widget itself (e.g. `color: red; padding: 2px;`).
+    pub(crate) fn set_code(&mut self, code: Option<&str>) -> Result<()> {
+        let Some(code) = code else {
+            self.code = None;
+            self.style_sheet = None;
+            self.has_custom_properties = false;
+            return Ok(());
+        };
+        let style_sheet = if code.contains('{') {
+            load_css(code)?
+        } else {
+            load_css(&format!("* {{ {code} }}"))?
+        };
+        self.code = Some(code.into());
+        self.has_custom_properties = super::find_rules(&style_sheet, |_| true)
+            .into_iter()
+            .any(is_custom_property);
+        self.style_sheet = Some(Rc::new(style_sheet));
+        Ok(())
+    }
+
+    /// Returns properties set from Rust code.
+    pub fn properties(&self) -> &[StyleProperty] {
+        &self.properties
+    }
+
+    /// Sets the property, replacing the previous value of the same property.
+    pub(crate) fn set_property(&mut self, property: StyleProperty) {
+        if let Some(old) = self
+            .properties
+            .iter_mut()
+            .find(|old| mem::discriminant(*old) == mem::discriminant(&property))
+        {
+            *old = property;
+        } else {
+            self.properties.push(property);
+        }
+        self.update_declarations();
+    }
+
+    pub(crate) fn clear_properties(&mut self) {
+        self.properties.clear();
+        self.update_declarations();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.code.is_none() && self.properties.is_empty()
+    }
+
+    fn update_declarations(&mut self) {
+        self.declarations = self
+            .properties
+            .iter()
+            .flat_map(StyleProperty::to_css)
+            .collect();
+    }
+
+    /// Returns declarations from the CSS code matching `check_selector`.
+    pub(crate) fn find_rules(
+        &self,
+        check_selector: impl Fn(&Selector<'static>) -> bool,
+    ) -> Vec<&Property<'static>> {
+        self.style_sheet
+            .as_ref()
+            .map(|style_sheet| super::find_rules(style_sheet, check_selector))
+            .unwrap_or_default()
+    }
+
+    /// Returns indices of the selectors in the CSS code matching `check_selector`.
+    pub(crate) fn matching_selectors(
+        &self,
+        check_selector: impl Fn(&Selector<'static>) -> bool,
+    ) -> Vec<usize> {
+        self.style_sheet
+            .as_ref()
+            .map(|style_sheet| super::matching_selectors(style_sheet, check_selector))
+            .unwrap_or_default()
+    }
+
+    /// True if the CSS code declares custom properties (`--name: value;`).
+    pub(crate) fn has_custom_properties(&self) -> bool {
+        self.has_custom_properties
+    }
+
+    /// Returns declarations corresponding to the properties set from Rust code.
+    pub(crate) fn declarations(&self) -> &[Property<'static>] {
+        &self.declarations
+    }
+}
+
+#[test]
+fn inline_style() {
+    use {
+        super::css::{convert_background_color, convert_main_color, StyleSelector},
+        std::hash::{BuildHasher, RandomState},
+    };
+
+    let red = Color::from_rgba8(255, 0, 0, 255);
+    let green = Color::from_rgba8(0, 128, 0, 255);
+    let blue = Color::from_rgba8(0, 0, 255, 255);
+    let color = |properties: Vec<&Property<'static>>| {
+        convert_main_color(&properties.into_iter().cloned().collect::<Vec<_>>())
+    };
+
+    let mut style = InlineStyle::default();
+    assert!(style.is_empty());
+
+    // A list of declarations applies to the widget itself.
+    style.set_code(Some("color: red")).unwrap();
+    assert!(!style.is_empty());
+    assert_eq!(style.code(), Some("color: red"));
+    assert_eq!(color(style.find_rules(|_| true)), Some(red));
+
+    // Rules of a complete style sheet apply according to their selectors.
+    style
+        .set_code(Some("Button { color: red; } Label { color: blue; }"))
+        .unwrap();
+    let label = StyleSelector::new("Label".into());
+    assert_eq!(style.matching_selectors(|s| label.matches(s)), [1]);
+    assert_eq!(color(style.find_rules(|s| label.matches(s))), Some(blue));
+
+    // Setting a property replaces the previous value of the same property.
+    style.set_property(StyleProperty::Color(blue));
+    style.set_property(StyleProperty::BackgroundColor(red));
+    style.set_property(StyleProperty::Color(green));
+    assert_eq!(
+        style.properties(),
+        [
+            StyleProperty::Color(green),
+            StyleProperty::BackgroundColor(red)
+        ]
+    );
+    assert_eq!(convert_main_color(style.declarations()), Some(green));
+    assert_eq!(convert_background_color(style.declarations()), Some(red));
+
+    let hasher = RandomState::new();
+    let copy = style.clone();
+    assert_eq!(copy, style);
+    assert_eq!(hasher.hash_one(&copy), hasher.hash_one(&style));
+    let mut other = style.clone();
+    other.set_property(StyleProperty::Color(blue));
+    assert_ne!(other, style);
+
+    style.clear_properties();
+    assert!(style.declarations().is_empty());
+    style.set_code(None).unwrap();
+    assert!(style.is_empty());
+}
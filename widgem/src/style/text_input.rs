@@ -20,8 +20,8 @@ impl ComputedElementStyle for TextInputStyle {
             .clone()
             .with_pseudo_class(PseudoClass::Custom("min".into()));
 
-        let properties = style.find_rules(|s| element.matches(s));
-        let font = convert_font(&properties, Some(&style.root_font_style()));
+        let properties = style.find_rules_for_element(element);
+        let font = convert_font(&properties, Some(&style.inherited_font(element)));
         let preferred_width = convert_width(&properties, scale, font.font_size)
             .or_warn()
             .flatten()
@@ -30,7 +30,7 @@ impl ComputedElementStyle for TextInputStyle {
                 (font.font_size * DEFAULT_PREFERRED_WIDTH_EM).to_physical(scale)
             });
 
-        let min_properties = style.find_rules(|s| element_min.matches(s));
+        let min_properties = style.find_rules_for_element(&element_min);
         let min_width = convert_width(&min_properties, scale, font.font_size)
             .or_warn()
             .flatten()
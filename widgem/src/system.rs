@@ -2,18 +2,19 @@ use {
     crate::{
         callback::{CallbackId, WidgetCallbackData},
         event_loop::UserEvent,
+        layout::LayoutDirection,
         shared_window::{WindowId, WindowInfo},
         shortcut::Shortcut,
-        style::Style,
+        style::{icons::IconProvider, FontFamily, Style},
         timer::Timers,
         RawWidgetId, WidgetAddress,
     },
     anyhow::Result,
     arboard::Clipboard,
-    cosmic_text::{FontSystem, SwashCache},
+    cosmic_text::{FamilyOwned, FontSystem, SwashCache},
     std::{collections::HashMap, fmt::Debug, rc::Rc, time::Duration},
     tracing::warn,
-    winit::event_loop::EventLoopProxy,
+    winit::{event_loop::EventLoopProxy, window::Theme},
 };
 
 #[derive(Debug)]
@@ -22,15 +23,26 @@ pub struct SystemConfig {
     pub auto_repeat_interval: Duration,
     pub exit_after_last_window_closes: bool,
     pub fixed_scale: Option<f32>,
+    pub layout_direction: LayoutDirection,
+    pub debug_layout: bool,
+    pub inspector: bool,
+    pub style_hot_reload: bool,
+    pub follow_system_theme: bool,
 }
 
 pub struct SharedSystemDataInner {
     pub config: Rc<SystemConfig>,
     pub address_book: HashMap<RawWidgetId, WidgetAddress>,
     pub font_system: FontSystem,
+    // Results of `FontStyle::resolve_family` for each list of families.
+    pub resolved_font_families: HashMap<Vec<FontFamily>, FamilyOwned>,
     pub swash_cache: SwashCache,
 
     pub style: Style,
+    // False if the style was set by the app with `with_style` or `set_style`.
+    pub is_builtin_style: bool,
+    pub icon_provider: Rc<dyn IconProvider>,
+    pub system_theme: Option<Theme>,
     pub(crate) event_loop_proxy: EventLoopProxy<UserEvent>,
     pub timers: Timers,
     pub clipboard: Clipboard,
@@ -1,6 +1,9 @@
 pub mod action;
 pub mod edit;
 pub mod editor;
+pub mod find;
+pub mod history;
+pub mod rich_text;
 pub mod text_handler;
 
 use {
@@ -10,7 +13,12 @@ use {
     std::{borrow::Cow, ops::Range},
 };
 
-pub use self::text_handler::{TextHandler, TextStyle};
+pub use self::{
+    find::FindQuery,
+    history::EditHistoryState,
+    rich_text::{RichText, TextFormat, TextSpan},
+    text_handler::{EchoMode, ElideMode, LinePosition, TextHandler, TextStyle},
+};
 
 pub fn text_without_preedit(buffer: &Buffer) -> String {
     buffer
@@ -86,4 +94,14 @@ impl Metadata {
         self.0 = (self.0 & !1) | (preedit as usize);
         self
     }
+
+    /// Index of the rich text format in the format table of the text handler.
+    pub fn format_index(self) -> usize {
+        self.0 >> 1
+    }
+
+    pub fn with_format_index(mut self, index: usize) -> Self {
+        self.0 = (self.0 & 1) | (index << 1);
+        self
+    }
 }
@@ -9,12 +9,69 @@ use {
     core::{iter::once, ops::Range},
     cosmic_text::{
         Attrs, AttrsList, BufferLine, BufferRef, Change, ChangeItem, Color, Cursor, FontSystem,
-        LayoutRun, Selection, Shaping, SwashCache,
+        LayoutGlyph, LayoutRun, Selection, Shaping, SwashCache,
     },
     std::cmp,
     unicode_segmentation::UnicodeSegmentation,
 };
 
+// Returns attributes of the text of the lines joined with single-byte line breaks.
+fn joined_attrs(lines: &[BufferLine]) -> AttrsList {
+    let mut output = AttrsList::new(
+        &lines
+            .first()
+            .map_or(Attrs::new(), |line| line.attrs_list().defaults()),
+    );
+    let mut offset = 0;
+    for line in lines {
+        let attrs_list = line.attrs_list();
+        let len = line.text().len();
+        output.add_span(offset..offset + len, &attrs_list.defaults());
+        for (range, attrs) in attrs_list.spans_iter() {
+            output.add_span(
+                offset + range.start..offset + range.end.min(len),
+                &attrs.as_attrs(),
+            );
+        }
+        offset += len + 1;
+    }
+    output
+}
+
+/// Draw the glyphs of a layout run
+///
+/// `offset` is applied to the glyphs before rounding them to physical pixels, `line_y` is added
+/// to the vertical position of the pixels. `glyph_color` returns the color of each glyph.
+pub fn draw_run_glyphs<F>(
+    font_system: &mut FontSystem,
+    cache: &mut SwashCache,
+    run: &LayoutRun,
+    offset: (f32, f32),
+    line_y: i32,
+    mut glyph_color: impl FnMut(&LayoutGlyph) -> Color,
+    f: &mut F,
+) where
+    F: FnMut(i32, i32, u32, u32, Color),
+{
+    for glyph in run.glyphs.iter() {
+        let physical_glyph = glyph.physical(offset, 1.0);
+        cache.with_pixels(
+            font_system,
+            physical_glyph.cache_key,
+            glyph_color(glyph),
+            |x, y, color| {
+                f(
+                    physical_glyph.x + x,
+                    line_y + physical_glyph.y + y,
+                    1,
+                    1,
+                    color,
+                );
+            },
+        );
+    }
+}
+
 /// A wrapper of [`Buffer`] for easy editing
 #[derive(Debug)]
 pub struct Editor<'buffer> {
@@ -26,6 +83,10 @@ pub struct Editor<'buffer> {
     auto_indent: bool,
     tab_width: u16,
     change: Option<Change>,
+    // Attributes of the text of each item of `change`.
+    change_attrs: Vec<AttrsList>,
+    // First line modified since the last call of `take_first_changed_line`.
+    first_changed_line: Option<usize>,
     // A preedit was specified with non-empty text but with empty cursor,
     // indicating that the cursor should be hidden
     has_preedit_without_cursor: bool,
@@ -118,11 +179,59 @@ impl<'buffer> Editor<'buffer> {
             auto_indent: false,
             tab_width: 4,
             change: None,
+            change_attrs: Vec::new(),
+            first_changed_line: None,
             has_preedit_without_cursor: false,
             cursor_hidden_by_setting: false,
         }
     }
 
+    /// Finish a change and return it along with the attributes of the inserted or deleted text
+    /// of each change item
+    pub fn finish_change_with_attrs(&mut self) -> Option<(Change, Vec<AttrsList>)> {
+        let change = self.change.take()?;
+        Some((change, std::mem::take(&mut self.change_attrs)))
+    }
+
+    /// Get the index of the first line that was modified since the last call of this function
+    pub fn take_first_changed_line(&mut self) -> Option<usize> {
+        self.first_changed_line.take()
+    }
+
+    fn line_changed(&mut self, line_i: usize) {
+        self.first_changed_line = Some(
+            self.first_changed_line
+                .map_or(line_i, |first| cmp::min(first, line_i)),
+        );
+    }
+
+    /// Apply a change, inserting text with the specified attributes
+    ///
+    /// `attrs` contains the attributes of the text of each change item. The attributes of
+    /// the surrounding text are used for items without attributes.
+    pub fn apply_change_with_attrs(&mut self, change: &Change, attrs: &[AttrsList]) -> bool {
+        // Cannot apply changes if there is a pending change
+        if let Some(pending) = self.change.take() {
+            if !pending.items.is_empty() {
+                //TODO: is this a good idea?
+                tracing::warn!("pending change caused apply_change to be ignored!");
+                self.change = Some(pending);
+                return false;
+            }
+        }
+
+        for (index, item) in change.items.iter().enumerate() {
+            //TODO: edit cursor if needed?
+            if item.insert {
+                self.cursor = self.insert_at(item.start, &item.text, attrs.get(index).cloned());
+            } else {
+                self.cursor = item.start;
+                self.delete_range(item.start, item.end);
+            }
+        }
+        true
+    }
+
     /// Draw the editor
     pub fn draw<F>(
         &self,
@@ -213,40 +322,32 @@ impl<'buffer> Editor<'buffer> {
                     }
                 }
 
-                for glyph in run.glyphs.iter() {
-                    let physical_glyph = glyph.physical((0., 0.), 1.0);
-
-                    let mut glyph_color = match glyph.color_opt {
-                        Some(some) => some,
-                        None => style.text_color,
-                    };
-                    if style.text_color != style.selected_text_color {
-                        if let Some((start, end)) = selection_bounds {
-                            if line_i >= start.line
-                                && line_i <= end.line
-                                && (start.line != line_i || glyph.end > start.index)
-                                && (end.line != line_i || glyph.start < end.index)
-                            {
-                                glyph_color = style.selected_text_color;
+                draw_run_glyphs(
+                    font_system,
+                    cache,
+                    &run,
+                    (0., 0.),
+                    line_y as i32,
+                    |glyph| {
+                        let mut glyph_color = match glyph.color_opt {
+                            Some(some) => some,
+                            None => style.text_color,
+                        };
+                        if style.text_color != style.selected_text_color {
+                            if let Some((start, end)) = selection_bounds {
+                                if line_i >= start.line
+                                    && line_i <= end.line
+                                    && (start.line != line_i || glyph.end > start.index)
+                                    && (end.line != line_i || glyph.start < end.index)
+                                {
+                                    glyph_color = style.selected_text_color;
+                                }
                             }
                         }
-                    }
-
-                    cache.with_pixels(
-                        font_system,
-                        physical_glyph.cache_key,
-                        glyph_color,
-                        |x, y, color| {
-                            f(
-                                physical_glyph.x + x,
-                                line_y as i32 + physical_glyph.y + y,
-                                1,
-                                1,
-                                color,
-                            );
-                        },
-                    );
-                }
+                        glyph_color
+                    },
+                    &mut f,
+                );
             }
         });
     }
@@ -323,9 +424,11 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
     }
 
     fn delete_range(&mut self, start: Cursor, end: Cursor) {
-        let change_item = self.with_buffer_mut(|buffer| {
+        self.line_changed(start.line);
+        let (change_item, removed_lines) = self.with_buffer_mut(|buffer| {
             // Collect removed data for change tracking
             let mut change_lines = Vec::new();
+            let mut removed_lines = Vec::new();
 
             // Delete the selection from the last line
             let end_line_opt = if end.line > start.line {
@@ -335,6 +438,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                 // Remove end line
                 let removed = buffer.lines.remove(end.line);
                 change_lines.insert(0, removed.text().to_string());
+                removed_lines.insert(0, removed);
 
                 Some(after)
             } else {
@@ -345,6 +449,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             for line_i in (start.line + 1..end.line).rev() {
                 let removed = buffer.lines.remove(line_i);
                 change_lines.insert(0, removed.text().to_string());
+                removed_lines.insert(0, removed);
             }
 
             // Delete the selection from the first line
@@ -359,6 +464,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                 // Delete selected part of line
                 let removed = buffer.lines[start.line].split_off(start.index);
                 change_lines.insert(0, removed.text().to_string());
+                removed_lines.insert(0, removed);
 
                 // Re-add part of line after selection
                 if let Some(after) = after_opt {
@@ -371,16 +477,20 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
                 }
             }
 
-            ChangeItem {
-                start,
-                end,
-                text: change_lines.join("\n"),
-                insert: false,
-            }
+            (
+                ChangeItem {
+                    start,
+                    end,
+                    text: change_lines.join("\n"),
+                    insert: false,
+                },
+                removed_lines,
+            )
         });
 
         if let Some(ref mut change) = self.change {
             change.items.push(change_item);
+            self.change_attrs.push(joined_attrs(&removed_lines));
         }
     }
 
@@ -394,8 +504,9 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
         if remaining_split_len == 0 {
             return cursor;
         }
+        self.line_changed(cursor.line);
 
-        let change_item = self.with_buffer_mut(|buffer| {
+        let (change_item, inserted_attrs) = self.with_buffer_mut(|buffer| {
             // Save cursor for change tracking
             let start = cursor;
 
@@ -435,6 +546,7 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             let mut final_attrs = attrs_list.unwrap_or_else(|| {
                 AttrsList::new(&line.attrs_list().get_span(cursor.index.saturating_sub(1)))
             });
+            let inserted_attrs = final_attrs.clone();
 
             // Append the inserted text, line by line
             // we want to see a blank entry if the string ends with a newline
@@ -491,16 +603,20 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
             // Append the text after insertion
             cursor.index = buffer.lines[cursor.line].text().len() - after_len;
 
-            ChangeItem {
-                start,
-                end: cursor,
-                text: data.to_string(),
-                insert: true,
-            }
+            (
+                ChangeItem {
+                    start,
+                    end: cursor,
+                    text: data.to_string(),
+                    insert: true,
+                },
+                inserted_attrs,
+            )
         });
 
         if let Some(ref mut change) = self.change {
             change.items.push(change_item);
+            self.change_attrs.push(inserted_attrs);
         }
 
         cursor
@@ -556,36 +672,18 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
     }
 
     fn apply_change(&mut self, change: &Change) -> bool {
-        // Cannot apply changes if there is a pending change
-        if let Some(pending) = self.change.take() {
-            if !pending.items.is_empty() {
-                //TODO: is this a good idea?
-                tracing::warn!("pending change caused apply_change to be ignored!");
-                self.change = Some(pending);
-                return false;
-            }
-        }
-
-        for item in change.items.iter() {
-            //TODO: edit cursor if needed?
-            if item.insert {
-                self.cursor = self.insert_at(item.start, &item.text, None);
-            } else {
-                self.cursor = item.start;
-                self.delete_range(item.start, item.end);
-            }
-        }
-        true
+        self.apply_change_with_attrs(change, &[])
     }
 
     fn start_change(&mut self) {
         if self.change.is_none() {
             self.change = Some(Change::default());
+            self.change_attrs.clear();
         }
     }
 
     fn finish_change(&mut self) -> Option<Change> {
-        self.change.take()
+        self.finish_change_with_attrs().map(|(change, _)| change)
     }
 
     fn preedit_range(&self) -> Option<Range<usize>> {
@@ -1087,3 +1185,42 @@ impl<'buffer> Edit<'buffer> for Editor<'buffer> {
         })
     }
 }
+
+#[test]
+fn undo_formatted_delete() {
+    use cosmic_text::{Buffer, LineEnding, Metrics, Weight};
+
+    let mut buffer = Buffer::new_empty(Metrics::new(14.0, 20.0));
+    for (text, bold) in [("ab", 1..2), ("cd", 0..1)] {
+        let mut attrs = AttrsList::new(&Attrs::new());
+        attrs.add_span(bold, &Attrs::new().weight(Weight::BOLD));
+        buffer.lines.push(BufferLine::new(
+            text,
+            LineEnding::default(),
+            attrs,
+            Shaping::Advanced,
+        ));
+    }
+    let mut editor = Editor::new(&mut buffer);
+    editor.start_change();
+    editor.delete_range(Cursor::new(0, 1), Cursor::new(1, 1));
+    let (mut change, mut attrs) = editor.finish_change_with_attrs().unwrap();
+    assert_eq!(change.items[0].text, "b\nc");
+    editor.with_buffer(|buffer| {
+        assert_eq!(buffer.lines.len(), 1);
+        assert_eq!(buffer.lines[0].text(), "ad");
+    });
+
+    change.reverse();
+    attrs.reverse();
+    assert!(editor.apply_change_with_attrs(&change, &attrs));
+    editor.with_buffer(|buffer| {
+        let texts: Vec<_> = buffer.lines.iter().map(|line| line.text()).collect();
+        assert_eq!(texts, ["ab", "cd"]);
+        for (line, bold_index, normal_index) in [(0, 1, 0), (1, 0, 1)] {
+            let attrs_list = buffer.lines[line].attrs_list();
+            assert_eq!(attrs_list.get_span(bold_index).weight, Weight::BOLD);
+            assert_eq!(attrs_list.get_span(normal_index).weight, Weight::NORMAL);
+        }
+    });
+}
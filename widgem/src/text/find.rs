@@ -0,0 +1,182 @@
+use {
+    anyhow::{Context as _, Result},
+    regex::{Regex, RegexBuilder},
+    std::ops::Range,
+};
+
+/// Describes what to search for in a text.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FindQuery {
+    pub pattern: String,
+    /// Letters match regardless of their case.
+    pub case_insensitive: bool,
+    /// Only matches that are not a part of a longer word are found, i.e. matches that are
+    /// not preceded or followed by a letter, a digit or `_`.
+    pub whole_word: bool,
+    /// The pattern is a regular expression. Replacement text can refer to its capture groups
+    /// (e.g. `$1` or `${name}`).
+    pub regex: bool,
+}
+
+impl FindQuery {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn with_whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    pub fn with_regex(mut self, regex: bool) -> Self {
+        self.regex = regex;
+        self
+    }
+}
+
+/// Compiled [FindQuery].
+#[derive(Debug, Clone)]
+pub(crate) struct Finder {
+    query: FindQuery,
+    regex: Regex,
+}
+
+impl Finder {
+    /// Returns an error if the query contains an invalid regular expression.
+    pub fn new(query: FindQuery) -> Result<Self> {
+        let mut pattern = if query.regex {
+            query.pattern.clone()
+        } else {
+            regex::escape(&query.pattern)
+        };
+        if query.whole_word {
+            // Unlike `\b`, half boundaries only require that the match is not adjacent to
+            // a word character, so patterns starting or ending with non-word characters
+            // (e.g. `(cat)`) can match.
+            pattern = format!(r"\b{{start-half}}(?:{pattern})\b{{end-half}}");
+        }
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(query.case_insensitive)
+            .build()
+            .with_context(|| format!("invalid regex: {:?}", query.pattern))?;
+        Ok(Self { query, regex })
+    }
+
+    pub fn query(&self) -> &FindQuery {
+        &self.query
+    }
+
+    /// Returns byte ranges of all non-overlapping, non-empty matches in the text.
+    pub fn find_all(&self, text: &str) -> Vec<Range<usize>> {
+        if self.query.pattern.is_empty() {
+            return Vec::new();
+        }
+        self.regex
+            .find_iter(text)
+            .map(|m| m.range())
+            .filter(|range| !range.is_empty())
+            .collect()
+    }
+
+    /// Returns the text that should replace the match with the specified range.
+    pub fn replacement(&self, text: &str, range: Range<usize>, replacement: &str) -> String {
+        if !self.query.regex {
+            return replacement.into();
+        }
+        let Some(captures) = self
+            .regex
+            .captures_at(text, range.start)
+            .filter(|captures| captures.get(0).is_some_and(|m| m.range() == range))
+        else {
+            return replacement.into();
+        };
+        let mut output = String::new();
+        captures.expand(replacement, &mut output);
+        output
+    }
+}
+
+#[test]
+fn find_query() {
+    fn find(query: FindQuery, text: &str) -> Vec<(usize, &str)> {
+        Finder::new(query)
+            .unwrap()
+            .find_all(text)
+            .into_iter()
+            .map(|range| (range.start, &text[range]))
+            .collect()
+    }
+
+    let text = "Cat cat concat cat_1 (cat)";
+    assert_eq!(
+        find(FindQuery::new("cat"), text),
+        [(4, "cat"), (11, "cat"), (15, "cat"), (22, "cat")]
+    );
+    assert_eq!(
+        find(FindQuery::new("CAT").with_case_insensitive(true), text),
+        [
+            (0, "Cat"),
+            (4, "cat"),
+            (11, "cat"),
+            (15, "cat"),
+            (22, "cat")
+        ]
+    );
+    assert_eq!(
+        find(FindQuery::new("cat").with_whole_word(true), text),
+        [(4, "cat"), (22, "cat")]
+    );
+    assert_eq!(
+        find(FindQuery::new("cat_1").with_whole_word(true), text),
+        [(15, "cat_1")]
+    );
+    assert_eq!(find(FindQuery::new("c.t"), "cat c.t"), [(4, "c.t")]);
+    assert_eq!(
+        find(FindQuery::new("c.t").with_regex(true), "cat c.t"),
+        [(0, "cat"), (4, "c.t")]
+    );
+    assert_eq!(
+        find(
+            FindQuery::new("a|b").with_regex(true).with_whole_word(true),
+            "ab a b"
+        ),
+        [(3, "a"), (5, "b")]
+    );
+    assert_eq!(
+        find(
+            FindQuery::new("(cat)").with_whole_word(true),
+            "(cat) x(cat) (cat)y [(cat)]"
+        ),
+        [(0, "(cat)"), (21, "(cat)")]
+    );
+    assert_eq!(
+        find(
+            FindQuery::new("-1").with_whole_word(true),
+            "-1 x-1 x -1 -12"
+        ),
+        [(0, "-1"), (9, "-1")]
+    );
+    assert!(find(FindQuery::new(""), text).is_empty());
+    assert!(find(FindQuery::new("x*").with_regex(true), text).is_empty());
+
+    assert!(Finder::new(FindQuery::new("(").with_regex(true)).is_err());
+    assert!(Finder::new(FindQuery::new("(")).is_ok());
+
+    let finder = Finder::new(FindQuery::new(r"(\w+)@(?<host>\w+)").with_regex(true)).unwrap();
+    let text = "mail: user@example";
+    assert_eq!(finder.find_all(text).first(), Some(&(6..18)));
+    assert_eq!(
+        finder.replacement(text, 6..18, "${host}: $1"),
+        "example: user"
+    );
+    let finder = Finder::new(FindQuery::new("user")).unwrap();
+    assert_eq!(finder.replacement(text, 6..10, "$1"), "$1");
+}
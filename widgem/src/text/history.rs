@@ -0,0 +1,229 @@
+use {
+    cosmic_text::{AttrsList, Change, Cursor, Selection},
+    std::collections::VecDeque,
+};
+
+// Oldest steps are discarded when the history grows beyond this limit.
+const MAX_UNDO_STEPS: usize = 1000;
+
+/// Availability of undo and redo in a text widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EditHistoryState {
+    pub can_undo: bool,
+    pub can_redo: bool,
+}
+
+/// Kind of an edit. Consecutive edits of the same kind may be merged into one undo step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditKind {
+    /// Text typed by the user (including committed input method text).
+    Typing,
+    /// Text deleted with Backspace.
+    Backspace,
+    /// Text deleted with Delete.
+    Delete,
+    /// Any other edit (paste, cut, new line, etc.). Never merged.
+    Other,
+}
+
+/// Cursor and selection of an editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorState {
+    pub cursor: Cursor,
+    pub selection: Selection,
+}
+
+impl CursorState {
+    fn has_selection(&self) -> bool {
+        match self.selection {
+            Selection::None => false,
+            Selection::Normal(select) => select != self.cursor,
+            Selection::Line(_) | Selection::Word(_) => true,
+        }
+    }
+}
+
+/// Formatting of a range of lines before and after an edit.
+#[derive(Debug, Clone)]
+pub struct FormattingChange {
+    pub first_line: usize,
+    pub before: Vec<AttrsList>,
+    pub after: Vec<AttrsList>,
+}
+
+/// A single undo step.
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub change: Change,
+    /// Attributes of the inserted or deleted text of each item of `change`.
+    /// They are used to restore the formatting of the text inserted on undo or redo.
+    pub attrs: Vec<AttrsList>,
+    /// Set if the edit changed the formatting of rich text.
+    pub formatting: Option<FormattingChange>,
+    pub kind: EditKind,
+    /// Cursor and selection before the edit. They are restored on undo.
+    pub before: CursorState,
+    /// Cursor and selection after the edit. They are restored on redo.
+    pub after: CursorState,
+}
+
+/// Undo and redo stacks of a text editor.
+#[derive(Debug, Default)]
+pub struct History {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    // Prevents the next edit from being merged into the last undo step.
+    is_merge_blocked: bool,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> EditHistoryState {
+        EditHistoryState {
+            can_undo: !self.undo_stack.is_empty(),
+            can_redo: !self.redo_stack.is_empty(),
+        }
+    }
+
+    /// Records a new edit and clears the redo stack.
+    ///
+    /// Typing and deletions are merged into the previous step if it has the same kind and
+    /// the new edit continues at the position where the previous one ended.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.redo_stack.clear();
+        if !self.is_merge_blocked {
+            if let Some(last) = self.undo_stack.back_mut() {
+                if last.kind == entry.kind
+                    && entry.kind != EditKind::Other
+                    && last.after == entry.before
+                    && !entry.before.has_selection()
+                {
+                    last.change.items.extend(entry.change.items);
+                    last.attrs.extend(entry.attrs);
+                    last.after = entry.after;
+                    return;
+                }
+            }
+        }
+        self.is_merge_blocked = false;
+        self.undo_stack.push_back(entry);
+        if self.undo_stack.len() > MAX_UNDO_STEPS {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Removes the last undo step and moves it to the redo stack.
+    pub fn undo(&mut self) -> Option<HistoryEntry> {
+        let entry = self.undo_stack.pop_back()?;
+        self.redo_stack.push(entry.clone());
+        self.is_merge_blocked = true;
+        Some(entry)
+    }
+
+    /// Removes the last redo step and moves it to the undo stack.
+    pub fn redo(&mut self) -> Option<HistoryEntry> {
+        let entry = self.redo_stack.pop()?;
+        self.undo_stack.push_back(entry.clone());
+        self.is_merge_blocked = true;
+        Some(entry)
+    }
+
+    /// Makes sure that the next edit starts a new undo step.
+    pub fn block_merge(&mut self) {
+        self.is_merge_blocked = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.is_merge_blocked = false;
+    }
+}
+
+#[test]
+fn history() {
+    use cosmic_text::ChangeItem;
+
+    fn typed(index: usize, text: &str) -> HistoryEntry {
+        let start = Cursor::new(0, index);
+        let end = Cursor::new(0, index + text.len());
+        HistoryEntry {
+            change: Change {
+                items: vec![ChangeItem {
+                    start,
+                    end,
+                    text: text.into(),
+                    insert: true,
+                }],
+            },
+            attrs: Vec::new(),
+            formatting: None,
+            kind: EditKind::Typing,
+            before: CursorState {
+                cursor: start,
+                selection: Selection::None,
+            },
+            after: CursorState {
+                cursor: end,
+                selection: Selection::None,
+            },
+        }
+    }
+
+    let mut history = History::new();
+    assert_eq!(history.state(), EditHistoryState::default());
+
+    // Continuous typing is merged into one step.
+    history.push(typed(0, "a"));
+    history.push(typed(1, "b"));
+    assert_eq!(history.undo_stack.len(), 1);
+    assert_eq!(history.undo_stack[0].change.items.len(), 2);
+    // Typing at another position starts a new step.
+    history.push(typed(5, "c"));
+    assert_eq!(history.undo_stack.len(), 2);
+    // Steps of other kinds are never merged.
+    let mut other = typed(6, "d");
+    other.kind = EditKind::Other;
+    history.push(other.clone());
+    let mut other = typed(7, "e");
+    other.kind = EditKind::Other;
+    history.push(other);
+    assert_eq!(history.undo_stack.len(), 4);
+
+    let entry = history.undo().unwrap();
+    assert_eq!(entry.change.items[0].text, "e");
+    assert_eq!(
+        history.state(),
+        EditHistoryState {
+            can_undo: true,
+            can_redo: true,
+        }
+    );
+    // Typing after undo is not merged into the previous step.
+    history.redo().unwrap();
+    history.push(typed(8, "f"));
+    history.push(typed(9, "g"));
+    assert_eq!(history.undo_stack.len(), 5);
+    assert!(history.redo_stack.is_empty());
+
+    // A new edit clears the redo stack.
+    history.undo().unwrap();
+    history.push(typed(0, "h"));
+    assert!(!history.state().can_redo);
+
+    history.block_merge();
+    history.push(typed(1, "i"));
+    assert_eq!(history.undo_stack.len(), 6);
+
+    // The oldest steps are discarded.
+    history.clear();
+    for i in 0..MAX_UNDO_STEPS + 10 {
+        history.block_merge();
+        history.push(typed(i, "x"));
+    }
+    assert_eq!(history.undo_stack.len(), MAX_UNDO_STEPS);
+    assert_eq!(history.undo_stack[0].before.cursor, Cursor::new(0, 10));
+}
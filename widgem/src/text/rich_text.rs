@@ -0,0 +1,494 @@
+use {
+    crate::{
+        style::{css::convert_text_format, load_css, FontSlant},
+        types::LogicalPixels,
+    },
+    anyhow::{bail, Context as _, Result},
+    lightningcss::rules::CssRule,
+    std::{
+        fmt::{Display, Write as _},
+        hash::{Hash, Hasher},
+    },
+    tiny_skia::Color,
+};
+
+const BOLD_WEIGHT: u16 = 700;
+
+/// Formatting of a span of rich text.
+///
+/// `None` values are taken from the style of the widget.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextFormat {
+    pub weight: Option<u16>,
+    pub slant: Option<FontSlant>,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+    pub font_size: Option<LogicalPixels>,
+    /// Target of the link, e.g. a URL. Links are underlined.
+    pub link: Option<String>,
+}
+
+impl Eq for TextFormat {}
+
+impl Hash for TextFormat {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Colors and font size are not hashed because they contain floats.
+        self.weight.hash(state);
+        self.slant.hash(state);
+        self.underline.hash(state);
+        self.strikethrough.hash(state);
+        self.link.hash(state);
+    }
+}
+
+impl TextFormat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_bold(&self) -> bool {
+        self.weight.is_some_and(|weight| weight >= 600)
+    }
+
+    pub fn is_italic(&self) -> bool {
+        self.slant.is_some_and(|slant| slant != FontSlant::Normal)
+    }
+
+    pub fn with_bold(mut self, bold: bool) -> Self {
+        self.weight = bold.then_some(BOLD_WEIGHT);
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.slant = italic.then_some(FontSlant::Italic);
+        self
+    }
+
+    pub fn with_underline(mut self, underline: bool) -> Self {
+        self.underline = underline;
+        self
+    }
+
+    pub fn with_strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = strikethrough;
+        self
+    }
+
+    pub fn with_color(mut self, color: Option<Color>) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_background(mut self, background: Option<Color>) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn with_font_size(mut self, font_size: Option<LogicalPixels>) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    pub fn with_link(mut self, link: Option<String>) -> Self {
+        self.link = link;
+        self
+    }
+}
+
+/// A part of rich text with the same formatting.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextSpan {
+    pub text: String,
+    pub format: TextFormat,
+}
+
+/// Text consisting of differently formatted spans.
+///
+/// Rich text can be converted from and to a simple HTML subset:
+///
+/// - `<b>`, `<strong>`: bold text;
+/// - `<i>`, `<em>`: italic text;
+/// - `<u>`, `<ins>`: underlined text;
+/// - `<s>`, `<del>`, `<strike>`: strikethrough text;
+/// - `<a href="...">`: a link;
+/// - `<span style="...">`: `color`, `background-color`, `font-size`, `font-weight`,
+///   `font-style` and `text-decoration` CSS properties;
+/// - `<br>`: a line break.
+///
+/// Whitespace is preserved as is. `&amp;`, `&lt;`, `&gt;`, `&quot;`, `&apos;`, `&nbsp;`
+/// and numeric character references are supported.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RichText {
+    spans: Vec<TextSpan>,
+}
+
+impl RichText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends text with the specified format. It's merged into the last span
+    /// if the format is the same.
+    pub fn push(&mut self, text: impl Display, format: TextFormat) -> &mut Self {
+        let text = text.to_string();
+        if text.is_empty() {
+            return self;
+        }
+        if let Some(last) = self.spans.last_mut() {
+            if last.format == format {
+                last.text.push_str(&text);
+                return self;
+            }
+        }
+        self.spans.push(TextSpan { text, format });
+        self
+    }
+
+    pub fn with(mut self, text: impl Display, format: TextFormat) -> Self {
+        self.push(text, format);
+        self
+    }
+
+    pub fn spans(&self) -> &[TextSpan] {
+        &self.spans
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Returns the text without formatting.
+    pub fn plain_text(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// Parses the supported [HTML subset](Self).
+    pub fn from_html(html: &str) -> Result<Self> {
+        let mut output = Self::new();
+        // Open tags and the formats of their content.
+        let mut stack: Vec<(String, TextFormat)> = Vec::new();
+        let mut rest = html;
+        while !rest.is_empty() {
+            let format = stack
+                .last()
+                .map(|(_, format)| format.clone())
+                .unwrap_or_default();
+            let Some(tag_start) = rest.find('<') else {
+                output.push(decode_entities(rest)?, format);
+                break;
+            };
+            output.push(decode_entities(&rest[..tag_start])?, format.clone());
+            let tag_end = find_tag_end(&rest[tag_start..])
+                .with_context(|| format!("unterminated tag at {:?}", &rest[tag_start..]))?;
+            let tag = &rest[tag_start + 1..tag_start + tag_end];
+            rest = &rest[tag_start + tag_end + 1..];
+
+            if let Some(name) = tag.strip_prefix('/') {
+                let name = name.trim().to_ascii_lowercase();
+                match stack.pop() {
+                    Some((open_name, _)) if open_name == name => {}
+                    Some((open_name, _)) => bail!("expected </{open_name}>, got </{name}>"),
+                    None => bail!("unexpected </{name}>"),
+                }
+                continue;
+            }
+            let (name, attributes) = parse_tag(tag.trim_end_matches('/'))?;
+            let new_format = match name.as_str() {
+                "br" => {
+                    output.push("\n", format);
+                    continue;
+                }
+                "b" | "strong" => format.with_bold(true),
+                "i" | "em" => format.with_italic(true),
+                "u" | "ins" => format.with_underline(true),
+                "s" | "del" | "strike" => format.with_strikethrough(true),
+                "a" => {
+                    let href = attributes
+                        .into_iter()
+                        .find(|(name, _)| name == "href")
+                        .map(|(_, value)| value)
+                        .context("missing href in <a>")?;
+                    format.with_link(Some(href))
+                }
+                "span" => {
+                    if let Some((_, style)) = attributes.iter().find(|(name, _)| name == "style") {
+                        parse_style_attribute(style, &format)?
+                    } else {
+                        format
+                    }
+                }
+                _ => bail!("unsupported tag: <{name}>"),
+            };
+            if tag.ends_with('/') {
+                continue;
+            }
+            stack.push((name, new_format));
+        }
+        if let Some((name, _)) = stack.last() {
+            bail!("unclosed tag: <{name}>");
+        }
+        Ok(output)
+    }
+
+    /// Converts the text to the supported [HTML subset](Self).
+    pub fn to_html(&self) -> String {
+        let mut output = String::new();
+        for span in &self.spans {
+            let format = &span.format;
+            let mut closing_tags = Vec::new();
+            if let Some(link) = &format.link {
+                write!(output, "<a href=\"{}\">", encode_entities(link)).unwrap();
+                closing_tags.push("a");
+            }
+            let style = style_attribute(format);
+            if !style.is_empty() {
+                write!(output, "<span style=\"{style}\">").unwrap();
+                closing_tags.push("span");
+            }
+            for (is_enabled, tag) in [
+                (format.weight == Some(BOLD_WEIGHT), "b"),
+                (format.slant == Some(FontSlant::Italic), "i"),
+                (format.underline, "u"),
+                (format.strikethrough, "s"),
+            ] {
+                if is_enabled {
+                    write!(output, "<{tag}>").unwrap();
+                    closing_tags.push(tag);
+                }
+            }
+            output.push_str(&encode_entities(&span.text).replace('\n', "<br>"));
+            for tag in closing_tags.into_iter().rev() {
+                write!(output, "</{tag}>").unwrap();
+            }
+        }
+        output
+    }
+}
+
+impl From<&str> for RichText {
+    fn from(text: &str) -> Self {
+        Self::new().with(text, TextFormat::default())
+    }
+}
+
+impl From<String> for RichText {
+    fn from(text: String) -> Self {
+        Self::new().with(text, TextFormat::default())
+    }
+}
+
+// Returns the index of `>` that ends the tag at the start of `text`, skipping quoted values.
+fn find_tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+// Parses the contents of an opening tag (without `<` and `>`) into the name and attributes.
+fn parse_tag(tag: &str) -> Result<(String, Vec<(String, String)>)> {
+    let tag = tag.trim();
+    let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+    let name = tag[..name_end].to_ascii_lowercase();
+    if name.is_empty() {
+        bail!("empty tag name");
+    }
+    let mut attributes = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let (attribute_name, after_name) = rest
+            .split_once('=')
+            .with_context(|| format!("expected value of attribute in <{tag}>"))?;
+        let after_name = after_name.trim_start();
+        let quote = after_name
+            .chars()
+            .next()
+            .filter(|c| *c == '"' || *c == '\'')
+            .with_context(|| format!("attribute value must be quoted in <{tag}>"))?;
+        let value_end = after_name[1..]
+            .find(quote)
+            .with_context(|| format!("unterminated attribute value in <{tag}>"))?;
+        attributes.push((
+            attribute_name.trim().to_ascii_lowercase(),
+            decode_entities(&after_name[1..value_end + 1])?,
+        ));
+        rest = after_name[value_end + 2..].trim_start();
+    }
+    Ok((name, attributes))
+}
+
+fn parse_style_attribute(code: &str, parent: &TextFormat) -> Result<TextFormat> {
+    let style_sheet = load_css(&format!("* {{ {code} }}"))?;
+    let properties = style_sheet
+        .rules
+        .0
+        .iter()
+        .filter_map(|rule| match rule {
+            CssRule::Style(rule) => Some(rule),
+            _ => None,
+        })
+        .flat_map(|rule| {
+            rule.declarations
+                .declarations
+                .iter()
+                .chain(&rule.declarations.important_declarations)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    Ok(convert_text_format(&properties, parent))
+}
+
+fn style_attribute(format: &TextFormat) -> String {
+    let mut declarations = Vec::new();
+    if let Some(color) = format.color {
+        declarations.push(format!("color: {}", css_color(color)));
+    }
+    if let Some(color) = format.background {
+        declarations.push(format!("background-color: {}", css_color(color)));
+    }
+    if let Some(font_size) = format.font_size {
+        declarations.push(format!("font-size: {}px", font_size.to_f32()));
+    }
+    if let Some(weight) = format.weight.filter(|weight| *weight != BOLD_WEIGHT) {
+        declarations.push(format!("font-weight: {weight}"));
+    }
+    match format.slant {
+        Some(FontSlant::Normal) => declarations.push("font-style: normal".into()),
+        Some(FontSlant::Oblique) => declarations.push("font-style: oblique".into()),
+        Some(FontSlant::Italic) | None => {}
+    }
+    declarations.join("; ")
+}
+
+fn css_color(color: Color) -> String {
+    let color = color.to_color_u8();
+    if color.alpha() == 255 {
+        format!(
+            "#{:02x}{:02x}{:02x}",
+            color.red(),
+            color.green(),
+            color.blue()
+        )
+    } else {
+        format!(
+            "#{:02x}{:02x}{:02x}{:02x}",
+            color.red(),
+            color.green(),
+            color.blue(),
+            color.alpha()
+        )
+    }
+}
+
+fn encode_entities(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            _ => output.push(c),
+        }
+    }
+    output
+}
+
+fn decode_entities(text: &str) -> Result<String> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let name_len = after
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '#')
+            .unwrap_or(after.len());
+        if name_len == 0 || !after[name_len..].starts_with(';') {
+            // Not a character reference, e.g. `a & b`.
+            output.push('&');
+            rest = after;
+            continue;
+        }
+        let name = &after[..name_len];
+        let c = match name {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            "nbsp" => '\u{a0}',
+            _ => {
+                let code = if let Some(hex) =
+                    name.strip_prefix("#x").or_else(|| name.strip_prefix("#X"))
+                {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = name.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(char::from_u32)
+                    .with_context(|| format!("unsupported character reference: &{name};"))?
+            }
+        };
+        output.push(c);
+        rest = &after[name_len + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[test]
+fn html() {
+    let text = RichText::from_html(
+        "a <b>bold <i>italic</i></b><br><a href=\"https://example.com/?a=1&amp;b=2\">link</a>\
+         <span style=\"color: #ff0000; font-size: 20px\">red</span>",
+    )
+    .unwrap();
+    let bold = TextFormat::default().with_bold(true);
+    let expected = RichText::new()
+        .with("a ", TextFormat::default())
+        .with("bold ", bold.clone())
+        .with("italic", bold.with_italic(true))
+        .with("\n", TextFormat::default())
+        .with(
+            "link",
+            TextFormat::default().with_link(Some("https://example.com/?a=1&b=2".into())),
+        )
+        .with(
+            "red",
+            TextFormat::default()
+                .with_color(Some(Color::from_rgba8(255, 0, 0, 255)))
+                .with_font_size(Some(20.0.into())),
+        );
+    assert_eq!(text, expected);
+    assert_eq!(RichText::from_html(&text.to_html()).unwrap(), text);
+
+    assert!(RichText::from_html("<b>unclosed").is_err());
+    assert!(RichText::from_html("<b>mismatched</i>").is_err());
+    assert!(RichText::from_html("<script>").is_err());
+}
+
+#[test]
+fn entities() {
+    assert_eq!(
+        decode_entities("&lt;&amp;&gt; &#65;&#x42; &quot;&apos;").unwrap(),
+        "<&> AB \"'"
+    );
+    // Ampersands that don't start a character reference are kept as is.
+    assert_eq!(decode_entities("a & b && c &").unwrap(), "a & b && c &");
+    assert_eq!(decode_entities("&amp").unwrap(), "&amp");
+    assert!(decode_entities("&unknown;").is_err());
+
+    let text = RichText::from_html("Tom & Jerry <b>&lt;3</b>").unwrap();
+    assert_eq!(text.plain_text(), "Tom & Jerry <3");
+    assert_eq!(RichText::from_html(&text.to_html()).unwrap(), text);
+}
@@ -1,6 +1,7 @@
 use {
     crate::{
         accessibility::new_accessibility_node_id,
+        callback::{Callback, Callbacks},
         draw::DrawEvent,
         event::{
             FocusReason, InputMethodEvent, KeyboardInputEvent, LayoutEvent, MouseInputEvent,
@@ -12,34 +13,44 @@ use {
         style::{
             common::ComputedElementStyle,
             css::{
-                convert_background_color, convert_font, convert_main_color, is_selection,
-                StyleSelector,
+                convert_background_color, convert_font, convert_main_color, is_link,
+                is_placeholder, is_selection, is_target_text, StyleSelector,
             },
-            defaults, Styles,
+            defaults, FontSlant, FontStyle, Styles,
         },
         system::OrWarn,
         text::{
             action::Action,
             edit::Edit,
             editor::{Editor, EditorDrawStyle},
-            text_without_preedit, Metadata,
+            find::{FindQuery, Finder},
+            history::{
+                CursorState, EditHistoryState, EditKind, FormattingChange, History, HistoryEntry,
+            },
+            line_text_without_preedit, preedit_range,
+            rich_text::{RichText, TextFormat},
+            text_without_preedit, AttrsExt, Metadata,
         },
         timer::TimerId,
         types::{PhysicalPixels, Point, PpxSuffix, Rect, Size},
+        validator::{InputMask, ValidationState, Validator},
         widget_initializer::{self, WidgetInitializer},
         RawWidgetId, Widget, WidgetBaseOf, WidgetExt,
     },
     accesskit::{NodeId, Role, TextDirection, TextPosition, TextSelection},
     anyhow::{bail, Context as _, Result},
     cosmic_text::{
-        Affinity, Attrs, AttrsList, AttrsOwned, BorrowedWithFontSystem, Buffer, Cursor, Motion,
-        Shaping, Wrap,
+        Affinity, Attrs, AttrsList, AttrsOwned, BorrowedWithFontSystem, Buffer, BufferLine, Change,
+        Cursor, FontSystem, LineEnding, Motion, Selection, Shaping, Weight, Wrap,
     },
+    itertools::Itertools,
     line_straddler::{GlyphStyle, LineGenerator, LineType},
     range_ext::intersect::Intersect,
     std::{
         cmp::{max, min},
+        collections::HashMap,
         fmt::Display,
+        iter, mem,
         ops::Range,
         rc::Rc,
         time::Duration,
@@ -58,12 +69,58 @@ use {
 
 const CURSOR_SIZE_X: i32 = 1; // TODO: configurable, scalable
 
+// Character displayed instead of each character of a password.
+const PASSWORD_CHAR: char = '•';
+
+/// Determines how the text of an input is displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EchoMode {
+    /// The text is displayed as is.
+    #[default]
+    Normal,
+    /// Each character is displayed as a bullet. Copying the text is disabled.
+    Password,
+}
+
+// Replaces the omitted part of elided text.
+const ELLIPSIS: &str = "…";
+
+/// Determines how text that doesn't fit into the available width is shortened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ElideMode {
+    /// The text is not shortened.
+    #[default]
+    None,
+    /// The beginning of the text is replaced with an ellipsis ("…xyz").
+    Start,
+    /// The middle of the text is replaced with an ellipsis ("ab…yz").
+    Middle,
+    /// The end of the text is replaced with an ellipsis ("abc…").
+    End,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct TextStyle {
-    font_metrics: cosmic_text::Metrics,
+    pub(crate) font_metrics: cosmic_text::Metrics,
+    pub(crate) font: FontStyle,
     pub(crate) text_color: Color,
     selected_text_color: Color,
     selected_text_background: Color,
+    placeholder_text_color: Color,
+    link_text_color: Color,
+    find_match_background: Color,
+    pub(crate) current_line_background: Color,
+    whitespace_color: Color,
+}
+
+/// Vertical position of a line of text (including all its rows if it's wrapped),
+/// relative to the text widget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinePosition {
+    pub top: f32,
+    /// Baseline of the first row of the line.
+    pub baseline: f32,
+    pub bottom: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -100,11 +157,28 @@ impl ComputedElementStyle for TextStyle {
                 warn!("selected text background is unspecified");
                 defaults::selected_text_background()
             });
+        let font = convert_font(&rules, Some(&style.inherited_font(element)));
+        let current_line_element = element.clone().with_pseudo_element("current-line".into());
+        let current_line_properties = style.find_rules_for_element(&current_line_element);
+        let whitespace_element = element.clone().with_pseudo_element("whitespace".into());
+        let whitespace_properties = style.find_rules_for_element(&whitespace_element);
         Self {
-            font_metrics: convert_font(&rules, Some(&style.root_font_style())).to_metrics(scale),
-            text_color: convert_main_color(&rules).unwrap_or_else(|| style.root_color()),
+            font_metrics: font.to_metrics(scale),
+            font,
+            text_color: convert_main_color(&rules)
+                .unwrap_or_else(|| style.inherited_color(element)),
             selected_text_color,
             selected_text_background,
+            placeholder_text_color: convert_main_color(&style.find_rules(is_placeholder))
+                .unwrap_or_else(defaults::placeholder_text_color),
+            link_text_color: convert_main_color(&style.find_rules(is_link))
+                .unwrap_or_else(defaults::link_text_color),
+            find_match_background: convert_background_color(&style.find_rules(is_target_text))
+                .unwrap_or_else(defaults::find_match_background),
+            current_line_background: convert_background_color(&current_line_properties)
+                .unwrap_or_else(defaults::current_line_background),
+            whitespace_color: convert_main_color(&whitespace_properties)
+                .unwrap_or_else(defaults::whitespace_color),
         }
     }
 }
@@ -112,10 +186,15 @@ impl ComputedElementStyle for TextStyle {
 pub struct TextHandler {
     base: WidgetBaseOf<Self>,
     style: Rc<TextStyle>,
+    // Default text attributes derived from the font style.
+    attrs: AttrsOwned,
     editor: Editor<'static>,
     pixmap: Option<Pixmap>,
     is_multiline: bool,
     is_editable: bool,
+    // Text can be selected and copied, but not modified by the user.
+    is_read_only: bool,
+    is_cursor_visible_when_read_only: bool,
     is_cursor_hidden: bool,
     is_host_focused: bool,
     host_id: Option<RawWidgetId>,
@@ -124,6 +203,61 @@ pub struct TextHandler {
     selected_text: String,
     accessibility_text_run_ids: Vec<(NodeId, TextRunInfo)>,
     old_size: Size,
+    history: History,
+    on_history_changed: Callbacks<EditHistoryState>,
+    // Values reported by the last emitted signals.
+    cursor_offset: usize,
+    selection_range: Option<Range<usize>>,
+    on_text_changed: Callbacks<String>,
+    on_cursor_position_changed: Callbacks<usize>,
+    on_selection_changed: Callbacks<Option<Range<usize>>>,
+    validator: Option<Rc<dyn Validator>>,
+    input_mask: Option<InputMask>,
+    max_length: Option<usize>,
+    validation_state: ValidationState,
+    on_validation_state_changed: Callbacks<ValidationState>,
+    echo_mode: EchoMode,
+    is_password_revealed: bool,
+    // Actual text while the buffer contains `PASSWORD_CHAR`s instead of it.
+    hidden_text: String,
+    placeholder: String,
+    // Shaped placeholder text. Created on demand.
+    placeholder_buffer: Option<Buffer>,
+    is_placeholder_shown: bool,
+    on_placeholder_shown_changed: Callbacks<bool>,
+    // Formats of rich text spans. Spans refer to them by index stored in the metadata
+    // of their attributes. The first format is the default one.
+    formats: Vec<TextFormat>,
+    // Index of each item of `formats`.
+    format_indices: HashMap<TextFormat, usize>,
+    is_rich_text_editable: bool,
+    // Byte ranges of the links in the text and their targets. Updated when the text
+    // or its formatting changes.
+    links: Vec<(Range<usize>, String)>,
+    // Index of the link that is focused by the host widget (in the list returned by `links()`).
+    focused_link: Option<usize>,
+    // Index of the link on which the left mouse button was pressed.
+    pressed_link: Option<usize>,
+    on_link_activated: Callbacks<String>,
+    is_word_wrap: bool,
+    elide_mode: ElideMode,
+    // Displayed instead of the editor's buffer when some of the lines are elided.
+    elided_buffer: Option<Buffer>,
+    // For each line of `elided_buffer`, the byte range of the original line
+    // that was replaced with an ellipsis.
+    elided_ranges: Vec<Option<Range<usize>>>,
+    finder: Option<Finder>,
+    // Byte ranges of the text that match the find query.
+    find_matches: Vec<Range<usize>>,
+    is_current_line_highlighted: bool,
+    is_whitespace_visible: bool,
+    // Vertical positions of the lines. The positions starting with
+    // `first_outdated_line_position` are recomputed by `line_positions`.
+    line_positions: Vec<LinePosition>,
+    first_outdated_line_position: Option<usize>,
+    // Set when the user modifies the text. Reset by `take_edited` and when the whole text
+    // is replaced by a setter.
+    is_edited: bool,
 }
 
 // TODO: get system setting
@@ -132,15 +266,19 @@ const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 #[impl_with]
 impl TextHandler {
     fn new(base: WidgetBaseOf<Self>, text: String, style: Rc<TextStyle>) -> Self {
+        let attrs = base.app().font_attrs(&style.font);
         let editor = base.app().with_font_system(|font_system| {
             Editor::new(Buffer::new(font_system, style.font_metrics))
         });
         let mut t = TextHandler {
             editor,
+            attrs,
             pixmap: None,
             style,
             is_multiline: true,
             is_editable: false,
+            is_read_only: false,
+            is_cursor_visible_when_read_only: true,
             is_cursor_hidden: true,
             is_host_focused: false,
             host_id: None,
@@ -150,6 +288,43 @@ impl TextHandler {
             accessibility_text_run_ids: Vec::new(),
             base,
             old_size: Size::default(),
+            history: History::new(),
+            on_history_changed: Callbacks::default(),
+            cursor_offset: 0,
+            selection_range: None,
+            on_text_changed: Callbacks::default(),
+            on_cursor_position_changed: Callbacks::default(),
+            on_selection_changed: Callbacks::default(),
+            validator: None,
+            input_mask: None,
+            max_length: None,
+            validation_state: ValidationState::Acceptable,
+            on_validation_state_changed: Callbacks::default(),
+            echo_mode: EchoMode::Normal,
+            is_password_revealed: false,
+            hidden_text: String::new(),
+            placeholder: String::new(),
+            placeholder_buffer: None,
+            is_placeholder_shown: false,
+            on_placeholder_shown_changed: Callbacks::default(),
+            formats: vec![TextFormat::default()],
+            format_indices: [(TextFormat::default(), 0)].into(),
+            is_rich_text_editable: false,
+            links: Vec::new(),
+            focused_link: None,
+            pressed_link: None,
+            on_link_activated: Callbacks::default(),
+            is_word_wrap: false,
+            elide_mode: ElideMode::None,
+            elided_buffer: None,
+            elided_ranges: Vec::new(),
+            finder: None,
+            find_matches: Vec::new(),
+            is_current_line_highlighted: false,
+            is_whitespace_visible: false,
+            line_positions: Vec::new(),
+            first_outdated_line_position: Some(0),
+            is_edited: false,
         };
         t.editor.set_cursor_hidden(true);
         t.set_text(text);
@@ -172,7 +347,9 @@ impl TextHandler {
 
     pub fn set_editable(&mut self, editable: bool) -> &mut Self {
         self.is_editable = editable;
-        self.base.set_input_method_enabled(editable);
+        let is_masked = self.is_masked();
+        self.base
+            .set_input_method_enabled(editable && !self.is_read_only && !is_masked);
         self.base.set_cursor_icon(if editable {
             CursorIcon::Text
         } else {
@@ -188,6 +365,47 @@ impl TextHandler {
         self
     }
 
+    /// Prevents the user from modifying the text.
+    ///
+    /// The text can still be focused, selected, copied and navigated with the keyboard.
+    /// It can also be changed programmatically.
+    pub fn set_read_only(&mut self, read_only: bool) -> &mut Self {
+        if self.is_read_only == read_only {
+            return self;
+        }
+        self.is_read_only = read_only;
+        if read_only {
+            self.interrupt_preedit();
+        }
+        let is_masked = self.is_masked();
+        self.base
+            .set_input_method_enabled(self.is_editable && !read_only && !is_masked);
+        self.reset_blink_timer();
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.is_read_only
+    }
+
+    // Returns `true` if the user has modified the text since the last call of `take_edited`
+    // or the last time the whole text was replaced by a setter.
+    pub(crate) fn take_edited(&mut self) -> bool {
+        mem::take(&mut self.is_edited)
+    }
+
+    /// Shows or hides the text cursor in [read-only](Self::set_read_only) mode.
+    /// The cursor is visible by default.
+    pub fn set_cursor_visible_when_read_only(&mut self, visible: bool) -> &mut Self {
+        self.is_cursor_visible_when_read_only = visible;
+        self.reset_blink_timer();
+        self
+    }
+
+    pub fn is_cursor_visible_when_read_only(&self) -> bool {
+        self.is_cursor_visible_when_read_only
+    }
+
     pub fn set_multiline(&mut self, multiline: bool) -> &mut Self {
         self.is_multiline = multiline;
         if !multiline {
@@ -231,6 +449,7 @@ impl TextHandler {
                 changed
             });
         });
+        self.line_positions_changed(0);
         self.adjust_size();
         self.base.size_hint_changed();
         self.request_scroll();
@@ -245,14 +464,242 @@ impl TextHandler {
             self.editor
                 .with_buffer_mut(|buffer| buffer.set_wrap(font_system, wrap));
         });
+        self.line_positions_changed(0);
+        self.adjust_size();
+        self.base.size_hint_changed();
+        self.request_scroll();
+        self
+    }
+
+    /// Sets the distance between tab stops, in the number of space characters. The default is 8.
+    ///
+    /// Zero is ignored.
+    pub fn set_tab_width(&mut self, tab_width: u16) -> &mut Self {
+        if tab_width == 0 || self.tab_width() == tab_width {
+            return self;
+        }
+        self.base.app().with_font_system(|font_system| {
+            self.editor
+                .with_buffer_mut(|buffer| buffer.set_tab_width(font_system, tab_width));
+        });
+        self.line_positions_changed(0);
         self.adjust_size();
         self.base.size_hint_changed();
+        self.base.update();
         self.request_scroll();
         self
     }
 
+    pub fn tab_width(&self) -> u16 {
+        self.editor.with_buffer(|buffer| buffer.tab_width())
+    }
+
+    /// Fills the background of the line that contains the cursor.
+    ///
+    /// The color is specified by the `::current-line` pseudo-element of the host widget
+    /// (e.g. `TextArea::current-line`).
+    pub fn set_current_line_highlighted(&mut self, highlighted: bool) -> &mut Self {
+        if self.is_current_line_highlighted != highlighted {
+            self.is_current_line_highlighted = highlighted;
+            self.pixmap = None;
+            self.base.update();
+        }
+        self
+    }
+
+    pub fn is_current_line_highlighted(&self) -> bool {
+        self.is_current_line_highlighted
+    }
+
+    /// Draws a dot in place of each space and an arrow in place of each tab character.
+    ///
+    /// The color is specified by the `::whitespace` pseudo-element of the host widget
+    /// (e.g. `TextArea::whitespace`).
+    pub fn set_whitespace_visible(&mut self, visible: bool) -> &mut Self {
+        if self.is_whitespace_visible != visible {
+            self.is_whitespace_visible = visible;
+            self.pixmap = None;
+            self.base.update();
+        }
+        self
+    }
+
+    pub fn is_whitespace_visible(&self) -> bool {
+        self.is_whitespace_visible
+    }
+
+    /// Returns the vertical positions of all lines of the text.
+    ///
+    /// Only the positions of the lines that were modified (and the lines after them)
+    /// are recomputed.
+    pub fn line_positions(&mut self) -> &[LinePosition] {
+        if let Some(line_index) = self.editor.take_first_changed_line() {
+            self.line_positions_changed(line_index);
+        }
+        let Some(first_line) = self.first_outdated_line_position.take() else {
+            return &self.line_positions;
+        };
+        let positions = &mut self.line_positions;
+        positions.truncate(first_line);
+        self.base.app().with_font_system(|font_system| {
+            self.editor.with_buffer_mut(|buffer| {
+                let default_line_height = buffer.metrics().line_height;
+                let mut top = positions.last().map_or(0.0, |position| position.bottom);
+                for line_index in positions.len()..buffer.lines.len() {
+                    let mut position = LinePosition {
+                        top,
+                        baseline: top,
+                        bottom: top,
+                    };
+                    let layout_lines = buffer.line_layout(font_system, line_index);
+                    for (i, layout_line) in layout_lines.into_iter().flatten().enumerate() {
+                        let line_height =
+                            layout_line.line_height_opt.unwrap_or(default_line_height);
+                        if i == 0 {
+                            // Same as the baseline calculation in `cosmic_text::LayoutRunIter`.
+                            let glyph_height = layout_line.max_ascent + layout_line.max_descent;
+                            position.baseline =
+                                top + (line_height - glyph_height) / 2.0 + layout_line.max_ascent;
+                        }
+                        top += line_height;
+                    }
+                    position.bottom = top;
+                    positions.push(position);
+                }
+            })
+        });
+        &self.line_positions
+    }
+
+    // Marks the positions of the lines starting with `first_line` as outdated.
+    fn line_positions_changed(&mut self, first_line: usize) {
+        self.first_outdated_line_position = Some(
+            self.first_outdated_line_position
+                .map_or(first_line, |line| min(line, first_line)),
+        );
+    }
+
+    /// Wraps the text at word boundaries when it doesn't fit into the available width.
+    ///
+    /// When enabled, the minimum width of the widget is the width of the longest word.
+    /// When disabled, lines are never wrapped.
+    pub fn set_word_wrap(&mut self, word_wrap: bool) -> &mut Self {
+        if self.is_word_wrap == word_wrap {
+            return self;
+        }
+        self.is_word_wrap = word_wrap;
+        self.set_wrap(if word_wrap { Wrap::Word } else { Wrap::None });
+        self.base.size_hint_changed();
+        self.update_elision();
+        self
+    }
+
+    pub fn is_word_wrap(&self) -> bool {
+        self.is_word_wrap
+    }
+
+    /// Sets how lines are shortened when the widget is narrower than the text.
+    ///
+    /// Elision only applies if [word wrap](Self::set_word_wrap) is disabled.
+    /// The text is only shortened on screen: [text](Self::text) returns the full text.
+    /// While the text is elided, it can't be selected with the mouse.
+    pub fn set_elide_mode(&mut self, mode: ElideMode) -> &mut Self {
+        if self.elide_mode == mode {
+            return self;
+        }
+        self.elide_mode = mode;
+        self.base.size_hint_changed();
+        self.update_elision();
+        self
+    }
+
+    pub fn elide_mode(&self) -> ElideMode {
+        self.elide_mode
+    }
+
+    /// Returns true if some of the lines are currently shortened according to the [elide mode](Self::set_elide_mode).
+    pub fn is_elided(&self) -> bool {
+        self.elided_buffer.is_some()
+    }
+
+    // Recreates the shortened text that is displayed if the text doesn't fit into the widget.
+    fn update_elision(&mut self) {
+        let was_elided = self.elided_buffer.take().is_some();
+        self.elided_ranges.clear();
+        let size = self.base.size();
+        if let Some(size) = size.filter(|_| {
+            self.elide_mode != ElideMode::None && !self.is_word_wrap && !self.is_masked()
+        }) {
+            let width = size.x().to_i32() as f32;
+            let (metrics, lines) = self
+                .editor
+                .with_buffer(|buffer| (buffer.metrics(), buffer.lines.clone()));
+            let mode = self.elide_mode;
+            self.base.app().with_font_system(|font_system| {
+                let mut measure_buffer = Buffer::new(font_system, metrics);
+                measure_buffer.set_wrap(font_system, Wrap::None);
+                measure_buffer.set_size(
+                    font_system,
+                    Some(MEASURE_MAX_SIZE),
+                    Some(MEASURE_MAX_SIZE),
+                );
+                let mut elided_lines = Vec::new();
+                for line in lines {
+                    if let Some((elided_line, range)) =
+                        elide_line(&mut measure_buffer, font_system, &line, mode, width)
+                    {
+                        elided_lines.push(elided_line);
+                        self.elided_ranges.push(Some(range));
+                    } else {
+                        elided_lines.push(line);
+                        self.elided_ranges.push(None);
+                    }
+                }
+                if self.elided_ranges.iter().any(Option::is_some) {
+                    let mut buffer = Buffer::new(font_system, metrics);
+                    buffer.set_wrap(font_system, Wrap::None);
+                    buffer.lines = elided_lines;
+                    buffer.set_size(font_system, Some(width), Some(size.y().to_i32() as f32));
+                    buffer.shape_until_scroll(font_system, false);
+                    self.elided_buffer = Some(buffer);
+                } else {
+                    self.elided_ranges.clear();
+                }
+            });
+        }
+        if was_elided || self.elided_buffer.is_some() {
+            self.pixmap = None;
+            self.base.update();
+        }
+    }
+
+    // Calls `f` with the buffer that is displayed on screen.
+    fn with_displayed_buffer<T>(&self, f: impl FnOnce(&Buffer) -> T) -> T {
+        if let Some(buffer) = &self.elided_buffer {
+            f(buffer)
+        } else {
+            self.editor.with_buffer(f)
+        }
+    }
+
+    // Converts a byte index in a line of the displayed buffer to a byte index in the line
+    // of the editor's buffer. Returns `None` for the ellipsis.
+    fn displayed_to_text_index(&self, line_index: usize, index: usize) -> Option<usize> {
+        let Some(range) = self.elided_ranges.get(line_index).cloned().flatten() else {
+            return Some(index);
+        };
+        if index < range.start {
+            Some(index)
+        } else if index >= range.start + ELLIPSIS.len() {
+            Some(index - range.start - ELLIPSIS.len() + range.end)
+        } else {
+            None
+        }
+    }
+
     pub fn handle_host_focus_in(&mut self, reason: FocusReason) -> Result<()> {
         self.is_host_focused = true;
+        self.update_placeholder_shown();
         if reason == FocusReason::Tab {
             self.action(Action::SelectAll);
         }
@@ -263,7 +710,9 @@ impl TextHandler {
 
     pub fn handle_host_focus_out(&mut self) -> Result<()> {
         self.is_host_focused = false;
+        self.update_placeholder_shown();
         self.interrupt_preedit();
+        self.history.block_merge();
         self.action(Action::ClearSelection);
         self.reset_blink_timer();
         Ok(())
@@ -351,19 +800,40 @@ impl TextHandler {
         } else if shortcuts.backspace.matches(&event) {
             self.action(Action::Backspace);
         } else if shortcuts.cut.matches(&event) {
-            self.copy_to_clipboard();
-            self.action(Action::Delete);
+            if self.echo_mode != EchoMode::Password {
+                self.copy_to_clipboard();
+                self.action(Action::Delete);
+            }
         } else if shortcuts.copy.matches(&event) {
             self.copy_to_clipboard();
         } else if shortcuts.paste.matches(&event) {
+            if self.is_read_only {
+                return Ok(false);
+            }
             if let Some(text) = self.base.app().clipboard_text().or_warn() {
                 let text = self.sanitize(&text);
                 self.insert_string(&text, None);
             }
         } else if shortcuts.undo.matches(&event) {
-            // TODO
+            self.apply_undo(false);
         } else if shortcuts.redo.matches(&event) {
-            // TODO
+            self.apply_redo(false);
+        } else if self.is_rich_text_editable && shortcuts.bold.matches(&event) {
+            if !self.is_read_only {
+                self.toggle_selection_format(TextFormat::is_bold, TextFormat::with_bold, false);
+            }
+        } else if self.is_rich_text_editable && shortcuts.italic.matches(&event) {
+            if !self.is_read_only {
+                self.toggle_selection_format(TextFormat::is_italic, TextFormat::with_italic, false);
+            }
+        } else if self.is_rich_text_editable && shortcuts.underline.matches(&event) {
+            if !self.is_read_only {
+                self.toggle_selection_format(
+                    |format| format.underline,
+                    TextFormat::with_underline,
+                    false,
+                );
+            }
         } else if shortcuts.select_all.matches(&event) {
             self.action(Action::SelectAll);
         } else if shortcuts.deselect.matches(&event) {
@@ -491,12 +961,14 @@ impl TextHandler {
                     return Ok(false);
                 }
             }
+            if self.is_read_only {
+                return Ok(false);
+            }
             let text = self.sanitize(text);
-            self.insert_string(&text, None);
+            self.insert_string_as(&text, None, EditKind::Typing);
         } else {
             return Ok(false);
         }
-        // TODO: notify parent?
         //self.adjust_scroll();
         self.base.update();
         self.reset_blink_timer();
@@ -519,11 +991,14 @@ impl TextHandler {
                 });
             }
             Ime::Commit(string) => {
-                self.editor.insert_string(&self.sanitize(&string), None);
+                if self.is_read_only {
+                    return Ok(false);
+                }
+                let text = self.sanitize(&string);
+                self.insert_string_as(&text, None, EditKind::Typing);
             }
             Ime::Disabled => {}
         }
-        // TODO: notify parent?
         //self.adjust_scroll();
         self.base.update();
         self.reset_blink_timer();
@@ -537,137 +1012,885 @@ impl TextHandler {
             return self;
         }
 
+        let displayed_text = if self.is_masked() {
+            self.hidden_text = text;
+            password_text(&self.hidden_text)
+        } else {
+            text
+        };
+        self.clear_formats();
         self.base.app().with_font_system(|font_system| {
             self.editor.with_buffer_mut(|buffer| {
-                buffer.set_text(font_system, &text, &attrs, Shaping::Advanced)
+                buffer.set_text(font_system, &displayed_text, &attrs, Shaping::Advanced)
             });
         });
+        self.text_replaced();
+        self
+    }
+
+    pub fn set_text(&mut self, text: impl Display) -> &mut Self {
+        let attrs = self.attrs.clone();
+        self.set_text_and_attrs(text, attrs.as_attrs())
+    }
+
+    // Updates the state after the whole text was replaced by a setter.
+    fn text_replaced(&mut self) {
+        self.line_positions_changed(0);
+        self.focused_link = None;
+        self.pressed_link = None;
+        let old_history_state = self.history.state();
+        self.history.clear();
+        self.history_changed(old_history_state, true);
         self.adjust_size();
         self.base.size_hint_changed();
         self.after_change();
+        self.update_elision();
+        self.is_edited = false;
+        self.text_changed(true);
+        self.check_cursor_changes(true);
         self.reset_blink_timer();
         self.base.update();
         self.request_scroll();
+    }
+
+    /// Replaces the text with formatted text. The undo history is cleared.
+    ///
+    /// In [password](EchoMode::Password) mode, the formatting is discarded.
+    pub fn set_rich_text(&mut self, text: &RichText) -> &mut Self {
+        if self.is_masked() {
+            return self.set_text(text.plain_text());
+        }
+        self.clear_formats();
+        let spans = text
+            .spans()
+            .iter()
+            .map(|span| {
+                let index = self.format_index(&span.format);
+                (self.sanitize(&span.text), self.format_attrs(index))
+            })
+            .collect_vec();
+        self.base.app().with_font_system(|font_system| {
+            self.editor.with_buffer_mut(|buffer| {
+                buffer.set_rich_text(
+                    font_system,
+                    spans
+                        .iter()
+                        .map(|(text, attrs)| (text.as_str(), attrs.as_attrs())),
+                    &self.attrs.as_attrs(),
+                    Shaping::Advanced,
+                    None,
+                )
+            });
+        });
+        self.text_replaced();
         self
     }
 
-    pub fn set_text(&mut self, text: impl Display) -> &mut Self {
-        self.set_text_and_attrs(text, Attrs::new())
+    /// Returns the text with its formatting.
+    pub fn rich_text(&self) -> RichText {
+        if self.is_masked() {
+            return self.hidden_text.as_str().into();
+        }
+        self.editor.with_buffer(|buffer| {
+            let mut output = RichText::new();
+            let mut last_format = &self.formats[0];
+            for (line_index, line) in buffer.lines.iter().enumerate() {
+                if line_index > 0 {
+                    output.push("\n", last_format.clone());
+                }
+                for (range, format_index) in format_runs(line) {
+                    last_format = self.format(format_index);
+                    output.push(&line.text()[range], last_format.clone());
+                }
+            }
+            output
+        })
     }
 
-    pub fn text(&self) -> String {
-        self.editor.with_buffer(text_without_preedit)
+    /// Returns the format of the character at the specified byte offset in the [text](Self::text).
+    pub fn format_at(&self, offset: usize) -> TextFormat {
+        let cursor = self.cursor_at_offset(offset);
+        let index = self.editor.with_buffer(|buffer| {
+            buffer.lines.get(cursor.line).map_or(0, |line| {
+                Metadata(line.attrs_list().get_span(cursor.index).metadata).format_index()
+            })
+        });
+        self.format(index).clone()
     }
 
-    pub fn set_text_style(&mut self, style: Rc<TextStyle>) -> &mut Self {
-        if self.style == style {
-            return self;
-        }
-        let old_style = self.style.clone();
-        self.style = style;
-        self.set_font_metrics(self.style.font_metrics);
-        if old_style.text_color != self.style.text_color
-            || old_style.selected_text_color != self.style.selected_text_color
-            || old_style.selected_text_background != self.style.selected_text_background
-        {
-            self.editor.with_buffer_mut(|b| b.set_redraw(true));
-            self.base.update();
-        }
+    /// Sets the format of the specified byte range of the [text](Self::text).
+    ///
+    /// The change can be reverted with [undo](Self::undo).
+    pub fn set_format(&mut self, range: Range<usize>, format: &TextFormat) -> &mut Self {
+        self.update_format(range, |_| format.clone(), true);
         self
     }
 
-    fn after_change(&mut self) {
-        let new_selected_text = self.selected_text().unwrap_or_default();
-        if new_selected_text != self.selected_text {
-            self.selected_text = new_selected_text;
-            #[cfg(all(
-                unix,
-                not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
-            ))]
-            self.base
-                .app()
-                .set_linux_primary_selection(&self.selected_text)
-                .or_warn();
-        }
+    /// Makes the selected text bold or, if all of it is already bold, removes bold.
+    pub fn toggle_bold(&mut self) -> &mut Self {
+        self.toggle_selection_format(TextFormat::is_bold, TextFormat::with_bold, true);
+        self
     }
 
-    #[cfg(all(
-        unix,
-        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
-    ))]
-    fn paste_selection(&mut self) {
-        if self.is_mouse_interaction_forbidden() {
-            return;
-        }
-        let text = self.base.app().linux_primary_selection().or_warn();
-        if let Some(text) = text {
-            let text = self.sanitize(&text);
-            self.insert_string(&text, None);
-        }
+    /// Makes the selected text italic or, if all of it is already italic, removes italic.
+    pub fn toggle_italic(&mut self) -> &mut Self {
+        self.toggle_selection_format(TextFormat::is_italic, TextFormat::with_italic, true);
+        self
     }
 
-    fn copy_to_clipboard(&mut self) {
-        if let Some(text) = self.selected_text() {
-            self.base.app().set_clipboard_text(&text).or_warn();
-        }
+    /// Underlines the selected text or, if all of it is already underlined, removes underline.
+    pub fn toggle_underline(&mut self) -> &mut Self {
+        self.toggle_selection_format(|format| format.underline, TextFormat::with_underline, true);
+        self
     }
 
-    fn reset_blink_timer(&mut self) {
-        if let Some(id) = self.blink_timer.take() {
-            self.base.app().cancel_timer(id);
-        }
-        self.editor
-            .set_cursor_hidden(!self.is_host_focused || !self.is_editable);
-        if self.is_host_focused && self.is_editable {
-            let id = self.base.app().add_interval(
-                CURSOR_BLINK_INTERVAL,
-                self.callback(|this, _| this.toggle_cursor_hidden()),
-            );
-            self.blink_timer = Some(id);
-        }
-        self.base.update();
+    /// Enables the standard shortcuts that change formatting of the selected text
+    /// (e.g. `Ctrl+B` for bold).
+    pub fn set_rich_text_editable(&mut self, editable: bool) -> &mut Self {
+        self.is_rich_text_editable = editable;
+        self
     }
 
-    fn toggle_cursor_hidden(&mut self) -> Result<()> {
-        self.set_cursor_hidden(!self.is_cursor_hidden);
-        if !self.editor.has_selection() {
-            self.base.update();
-        }
-        Ok(())
+    pub fn is_rich_text_editable(&self) -> bool {
+        self.is_rich_text_editable
     }
 
-    fn accessibility_text_runs(&mut self) -> Vec<(accesskit::Node, TextRunInfo)> {
-        #[derive(Debug)]
-        struct CharStats {
-            bytes: Range<usize>,
-            pixels: Option<Range<FiniteF32>>,
+    /// Returns the byte ranges of the links in the [text](Self::text) and their targets.
+    pub fn links(&self) -> &[(Range<usize>, String)] {
+        &self.links
+    }
+
+    fn update_links(&mut self) {
+        let runs = self.editor.with_buffer(|buffer| {
+            buffer
+                .lines
+                .iter()
+                .enumerate()
+                .flat_map(|(line_index, line)| {
+                    format_runs(line)
+                        .into_iter()
+                        .map(move |(range, format_index)| (line_index, range, format_index))
+                })
+                .collect_vec()
+        });
+        let mut output: Vec<(Range<usize>, String)> = Vec::new();
+        for (line_index, range, format_index) in runs {
+            let Some(link) = &self.format(format_index).link else {
+                continue;
+            };
+            let start = self.text_offset(Cursor::new(line_index, range.start));
+            let end = self.text_offset(Cursor::new(line_index, range.end));
+            match output.last_mut() {
+                Some((last_range, last_link)) if last_link == link && last_range.end == start => {
+                    last_range.end = end;
+                }
+                _ => output.push((start..end, link.clone())),
+            }
         }
+        self.links = output;
+    }
 
-        self.editor.with_buffer_mut(|buffer| {
-            self.base.app().with_font_system(|font_system| {
-                buffer.set_scroll(cosmic_text::Scroll::default());
-                buffer.shape_until_scroll(font_system, false);
-            });
-            // TODO: separate text runs based on attributes and text direction? how to handle preedit?
-            let mut output = Vec::new();
+    /// Returns the bounding rectangle of the link with the specified index
+    /// (in the list returned by [links](Self::links)), relative to this widget.
+    pub fn link_rect(&self, index: usize) -> Option<Rect> {
+        let (range, _) = self.links.get(index)?;
+        self.range_rect(range.clone())
+    }
 
-            let mut layout_runs = buffer.layout_runs().peekable();
-            while let Some(layout_run) = layout_runs.next() {
-                let text = &layout_run.text;
-                let mut text_start = text.len();
-                let mut text_end = 0;
-                for glyph in layout_run.glyphs {
-                    text_start = min(text_start, glyph.start);
-                    text_end = max(text_end, glyph.end);
+    // Returns the bounding rectangle of the displayed glyphs of the specified byte range of the text.
+    fn range_rect(&self, range: Range<usize>) -> Option<Rect> {
+        let start = self.cursor_at_offset(range.start);
+        let end = self.cursor_at_offset(range.end);
+        let mut bounds: Option<(f32, f32, f32, f32)> = None;
+        self.with_displayed_buffer(|buffer| {
+            for run in buffer.layout_runs() {
+                for glyph in run.glyphs {
+                    let Some(glyph_start) = self.displayed_to_text_index(run.line_i, glyph.start)
+                    else {
+                        continue;
+                    };
+                    let position = (run.line_i, glyph_start);
+                    if position < (start.line, start.index) || position >= (end.line, end.index) {
+                        continue;
+                    }
+                    let (x1, y1, x2, y2) = bounds.get_or_insert((
+                        glyph.x,
+                        run.line_top,
+                        glyph.x + glyph.w,
+                        run.line_top + run.line_height,
+                    ));
+                    *x1 = x1.min(glyph.x);
+                    *y1 = y1.min(run.line_top);
+                    *x2 = x2.max(glyph.x + glyph.w);
+                    *y2 = y2.max(run.line_top + run.line_height);
                 }
-                //println!("text={text:?}, text_start={text_start:?}, text_end={text_end:?}");
-                let mut text = if let Some(part) = text.get(text_start..max(text_start, text_end)) {
-                    part.to_owned()
-                } else {
-                    warn!("invalid index while fetching text corresponding to glyphs");
-                    "".to_owned()
-                };
-
+            }
+        });
+        bounds.map(|(x1, y1, x2, y2)| {
+            Rect::from_x1y1x2y2(
+                PhysicalPixels::from_i32(x1.floor() as i32),
+                PhysicalPixels::from_i32(y1.floor() as i32),
+                PhysicalPixels::from_i32(x2.ceil() as i32),
+                PhysicalPixels::from_i32(y2.ceil() as i32),
+            )
+        })
+    }
+
+    /// Highlights all matches of the query in the text. Returns the number of matches.
+    ///
+    /// The matches are updated automatically when the text changes.
+    /// Returns an error if the query contains an invalid regular expression.
+    pub fn find(&mut self, query: FindQuery) -> Result<usize> {
+        self.finder = Some(Finder::new(query)?);
+        self.update_find_matches();
+        Ok(self.find_matches.len())
+    }
+
+    /// Removes the find query and the highlighting of its matches.
+    pub fn clear_find(&mut self) -> &mut Self {
+        self.finder = None;
+        self.update_find_matches();
+        self
+    }
+
+    pub fn find_query(&self) -> Option<&FindQuery> {
+        self.finder.as_ref().map(|finder| finder.query())
+    }
+
+    /// Returns the byte ranges of the [text](Self::text) that match the find query.
+    pub fn find_matches(&self) -> &[Range<usize>] {
+        &self.find_matches
+    }
+
+    /// Selects the first match after the cursor or the selection, wrapping around
+    /// at the end of the text. Returns false if there are no matches.
+    pub fn find_next(&mut self) -> bool {
+        let position = self
+            .selection_range()
+            .map_or_else(|| self.cursor_offset(), |range| range.end);
+        let index = self
+            .find_matches
+            .iter()
+            .position(|range| range.start >= position)
+            .unwrap_or(0);
+        self.select_find_match(index)
+    }
+
+    /// Selects the last match before the cursor or the selection, wrapping around
+    /// at the start of the text. Returns false if there are no matches.
+    pub fn find_previous(&mut self) -> bool {
+        let position = self
+            .selection_range()
+            .map_or_else(|| self.cursor_offset(), |range| range.start);
+        let index = self
+            .find_matches
+            .iter()
+            .rposition(|range| range.end <= position)
+            .unwrap_or(self.find_matches.len().saturating_sub(1));
+        self.select_find_match(index)
+    }
+
+    /// Replaces the selected match with `replacement` and selects the next match.
+    /// If the selection is not a match, only selects the next match.
+    /// Returns false if there are no matches.
+    ///
+    /// The replacement can be reverted with a single [undo](Self::undo).
+    pub fn replace(&mut self, replacement: &str) -> bool {
+        if self.is_read_only {
+            return false;
+        }
+        let Some(finder) = self.finder.clone() else {
+            return false;
+        };
+        if let Some(range) = self
+            .selection_range()
+            .filter(|range| self.find_matches.contains(range))
+        {
+            let new_text = finder.replacement(&self.text(), range.clone(), replacement);
+            self.replace_ranges(&[(range, new_text)]);
+        }
+        self.find_next()
+    }
+
+    /// Replaces all matches with `replacement`. Returns the number of replaced matches.
+    ///
+    /// The replacement can be reverted with a single [undo](Self::undo).
+    pub fn replace_all(&mut self, replacement: &str) -> usize {
+        if self.is_read_only {
+            return 0;
+        }
+        let Some(finder) = &self.finder else {
+            return 0;
+        };
+        let text = self.text();
+        let replacements = self
+            .find_matches
+            .iter()
+            .map(|range| {
+                let new_text = finder.replacement(&text, range.clone(), replacement);
+                (range.clone(), new_text)
+            })
+            .collect_vec();
+        self.replace_ranges(&replacements);
+        replacements.len()
+    }
+
+    fn update_find_matches(&mut self) {
+        let matches = match &self.finder {
+            Some(finder) if !self.is_masked() => finder.find_all(&self.text()),
+            _ => Vec::new(),
+        };
+        if matches != self.find_matches {
+            self.find_matches = matches;
+            self.pixmap = None;
+            self.base.update();
+        }
+    }
+
+    fn select_find_match(&mut self, index: usize) -> bool {
+        let Some(range) = self.find_matches.get(index).cloned() else {
+            return false;
+        };
+        self.interrupt_preedit();
+        let start = self.cursor_at_offset(range.start);
+        let end = self.cursor_at_offset(range.end);
+        self.editor.set_selection(Selection::Normal(start));
+        self.editor.set_cursor(end);
+        self.after_change();
+        self.check_cursor_changes(true);
+        self.reset_blink_timer();
+        self.base.update();
+        if let Some(rect) = self.range_rect(range) {
+            self.base.ensure_rect_visible(rect);
+        }
+        true
+    }
+
+    // Replaces the specified byte ranges of the text as a single edit.
+    // The ranges must be sorted and must not overlap.
+    fn replace_ranges(&mut self, replacements: &[(Range<usize>, String)]) {
+        if replacements.is_empty() {
+            return;
+        }
+        self.interrupt_preedit();
+        let before = self.start_edit();
+        for (range, text) in replacements.iter().rev() {
+            let start = self.cursor_at_offset(range.start);
+            let end = self.cursor_at_offset(range.end);
+            self.editor.set_selection(Selection::Normal(start));
+            self.editor.set_cursor(end);
+            self.editor.insert_string(text, None);
+        }
+        self.finish_edit(EditKind::Other, before, None);
+        self.check_cursor_changes(false);
+        self.after_change();
+        self.adjust_size();
+        self.base.size_hint_changed();
+        self.base.update();
+        self.request_scroll();
+    }
+
+    /// Sets the link that is displayed as focused (by index in the list returned by [links](Self::links)).
+    pub fn set_focused_link(&mut self, index: Option<usize>) -> &mut Self {
+        if self.focused_link != index {
+            self.focused_link = index;
+            self.base.update();
+        }
+        self
+    }
+
+    pub fn focused_link(&self) -> Option<usize> {
+        self.focused_link
+    }
+
+    /// Invokes the link activation callbacks for the link with the specified index
+    /// (in the list returned by [links](Self::links)).
+    pub fn activate_link(&mut self, index: usize) {
+        if let Some((_, link)) = self.links.get(index) {
+            self.on_link_activated.invoke(link.clone(), false);
+        }
+    }
+
+    /// Adds a callback that is called when the user activates a link
+    /// (by clicking it or with the keyboard). The callback receives the link target.
+    pub fn on_link_activated(&mut self, callback: Callback<String>) -> &mut Self {
+        self.on_link_activated.add(callback);
+        self
+    }
+
+    // Links can be clicked when the text cannot be edited.
+    fn are_links_interactive(&self) -> bool {
+        !self.is_editable || self.is_read_only
+    }
+
+    // Returns the index of the link at the specified position (in the list returned by `links()`).
+    fn link_at(&self, pos: Point) -> Option<usize> {
+        let x = pos.x().to_i32() as f32;
+        let y = pos.y().to_i32() as f32;
+        let (line_index, index) = self.with_displayed_buffer(|buffer| {
+            buffer.layout_runs().find_map(|run| {
+                if y < run.line_top || y >= run.line_top + run.line_height {
+                    return None;
+                }
+                let glyph = run.glyphs.iter().find(|glyph| {
+                    x >= glyph.x
+                        && x < glyph.x + glyph.w
+                        && self
+                            .format(Metadata(glyph.metadata).format_index())
+                            .link
+                            .is_some()
+                })?;
+                let index = self.displayed_to_text_index(run.line_i, glyph.start)?;
+                Some((run.line_i, index))
+            })
+        })?;
+        let offset = self.text_offset(Cursor::new(line_index, index));
+        self.links
+            .iter()
+            .position(|(range, _)| range.contains(&offset))
+    }
+
+    fn format(&self, index: usize) -> &TextFormat {
+        self.formats.get(index).unwrap_or(&self.formats[0])
+    }
+
+    // Returns the index of the format in the format table, adding it if necessary.
+    fn format_index(&mut self, format: &TextFormat) -> usize {
+        if let Some(index) = self.format_indices.get(format) {
+            return *index;
+        }
+        self.formats.push(format.clone());
+        self.format_indices
+            .insert(format.clone(), self.formats.len() - 1);
+        self.formats.len() - 1
+    }
+
+    // Removes all formats except the default one. Must only be called when the whole text
+    // is replaced and the undo history is cleared, because formats are referenced by index.
+    fn clear_formats(&mut self) {
+        self.formats.truncate(1);
+        self.format_indices.retain(|_, index| *index == 0);
+    }
+
+    fn format_attrs(&self, format_index: usize) -> AttrsOwned {
+        format_attrs(
+            &self.attrs,
+            &self.style,
+            self.base.scale(),
+            self.format(format_index),
+            format_index,
+        )
+    }
+
+    // Returns the runs of the same format within the specified byte range of the text.
+    // Each item contains the line index, the byte range in the line and the format index.
+    fn format_runs_in_range(&self, range: Range<usize>) -> Vec<(usize, Range<usize>, usize)> {
+        let start = self.cursor_at_offset(range.start);
+        let end = self.cursor_at_offset(range.end);
+        self.editor.with_buffer(|buffer| {
+            let mut output = Vec::new();
+            for line_index in start.line..=end.line {
+                let Some(line) = buffer.lines.get(line_index) else {
+                    break;
+                };
+                let line_start = if line_index == start.line {
+                    start.index
+                } else {
+                    0
+                };
+                let line_end = if line_index == end.line {
+                    end.index
+                } else {
+                    line.text().len()
+                };
+                for (run, format_index) in format_runs(line) {
+                    let run = max(run.start, line_start)..min(run.end, line_end);
+                    if !run.is_empty() {
+                        output.push((line_index, run, format_index));
+                    }
+                }
+            }
+            output
+        })
+    }
+
+    fn toggle_selection_format(
+        &mut self,
+        is_set: impl Fn(&TextFormat) -> bool,
+        set: impl Fn(TextFormat, bool) -> TextFormat,
+        from_setter: bool,
+    ) {
+        let Some(range) = self.selection_range() else {
+            return;
+        };
+        let is_set_everywhere = self
+            .format_runs_in_range(range.clone())
+            .into_iter()
+            .all(|(_, _, format_index)| is_set(self.format(format_index)));
+        self.update_format(
+            range,
+            |format| set(format.clone(), !is_set_everywhere),
+            from_setter,
+        );
+    }
+
+    fn update_format(
+        &mut self,
+        range: Range<usize>,
+        mut update: impl FnMut(&TextFormat) -> TextFormat,
+        from_setter: bool,
+    ) {
+        if self.is_masked() || range.is_empty() {
+            return;
+        }
+        self.interrupt_preedit();
+        let runs = self.format_runs_in_range(range);
+        let Some(first_line) = runs.first().map(|(line_index, _, _)| *line_index) else {
+            return;
+        };
+        let before = self.editor.with_buffer(|buffer| {
+            let last_line = runs
+                .last()
+                .map_or(first_line, |(line_index, _, _)| *line_index);
+            buffer.lines[first_line..=last_line]
+                .iter()
+                .map(|line| line.attrs_list().clone())
+                .collect_vec()
+        });
+        let mut after = before.clone();
+        for (line_index, run, format_index) in runs {
+            let format = update(self.format(format_index));
+            let new_index = self.format_index(&format);
+            after[line_index - first_line].add_span(run, &self.format_attrs(new_index).as_attrs());
+        }
+        if before == after {
+            return;
+        }
+        self.set_lines_attrs(first_line, &after);
+        let old_state = self.history.state();
+        self.history.push(HistoryEntry {
+            change: Change::default(),
+            attrs: Vec::new(),
+            formatting: Some(FormattingChange {
+                first_line,
+                before,
+                after,
+            }),
+            kind: EditKind::Other,
+            before: self.cursor_state(),
+            after: self.cursor_state(),
+        });
+        self.history_changed(old_state, from_setter);
+    }
+
+    fn set_lines_attrs(&mut self, first_line: usize, attrs_lists: &[AttrsList]) {
+        self.editor.with_buffer_mut(|buffer| {
+            for (line, attrs_list) in buffer.lines.iter_mut().skip(first_line).zip(attrs_lists) {
+                line.set_attrs_list(attrs_list.clone());
+            }
+            buffer.set_redraw(true);
+        });
+        self.update_links();
+        self.line_positions_changed(first_line);
+        self.adjust_size();
+        self.base.size_hint_changed();
+        self.base.update();
+        self.request_scroll();
+    }
+
+    pub fn text(&self) -> String {
+        if self.is_masked() {
+            self.hidden_text.clone()
+        } else {
+            self.editor.with_buffer(text_without_preedit)
+        }
+    }
+
+    // True if the buffer contains `PASSWORD_CHAR`s instead of the actual text.
+    fn is_masked(&self) -> bool {
+        self.echo_mode == EchoMode::Password && !self.is_password_revealed
+    }
+
+    /// Sets how the text is displayed.
+    ///
+    /// In [password](EchoMode::Password) mode, the text can't be copied
+    /// and the undo history is disabled.
+    pub fn set_echo_mode(&mut self, echo_mode: EchoMode) -> &mut Self {
+        self.set_masking(echo_mode, self.is_password_revealed);
+        self
+    }
+
+    pub fn echo_mode(&self) -> EchoMode {
+        self.echo_mode
+    }
+
+    /// Displays the actual text in [password](EchoMode::Password) mode.
+    pub fn set_password_revealed(&mut self, revealed: bool) -> &mut Self {
+        self.set_masking(self.echo_mode, revealed);
+        self
+    }
+
+    pub fn is_password_revealed(&self) -> bool {
+        self.is_password_revealed
+    }
+
+    fn set_masking(&mut self, echo_mode: EchoMode, is_password_revealed: bool) {
+        self.interrupt_preedit();
+        let text = self.text();
+        let cursor = self.cursor_offset();
+        let selection = self.selection_range();
+        let was_masked = self.is_masked();
+        if echo_mode == EchoMode::Password && self.echo_mode != EchoMode::Password {
+            let old_history_state = self.history.state();
+            self.history.clear();
+            self.history_changed(old_history_state, true);
+        }
+        self.echo_mode = echo_mode;
+        self.is_password_revealed = is_password_revealed;
+        let is_masked = self.is_masked();
+        self.base
+            .set_input_method_enabled(self.is_editable && !self.is_read_only && !is_masked);
+        if self.is_masked() == was_masked {
+            return;
+        }
+        let displayed_text = if self.is_masked() {
+            self.hidden_text = text;
+            password_text(&self.hidden_text)
+        } else {
+            mem::take(&mut self.hidden_text)
+        };
+        self.base.app().with_font_system(|font_system| {
+            self.editor.with_buffer_mut(|buffer| {
+                buffer.set_text(
+                    font_system,
+                    &displayed_text,
+                    &self.attrs.as_attrs(),
+                    Shaping::Advanced,
+                )
+            });
+        });
+        if let Some(selection) = selection {
+            let (anchor, focus) = if cursor == selection.start {
+                (selection.end, selection.start)
+            } else {
+                (selection.start, selection.end)
+            };
+            self.editor
+                .set_selection(Selection::Normal(self.cursor_at_offset(anchor)));
+            self.editor.set_cursor(self.cursor_at_offset(focus));
+        } else {
+            self.editor.set_selection(Selection::None);
+            self.editor.set_cursor(self.cursor_at_offset(cursor));
+        }
+        self.update_links();
+        self.line_positions_changed(0);
+        self.adjust_size();
+        self.base.size_hint_changed();
+        self.base.update();
+        self.request_scroll();
+    }
+
+    /// Sets the text displayed while the text is empty and the input is not focused.
+    pub fn set_placeholder(&mut self, placeholder: impl Display) -> &mut Self {
+        let placeholder = placeholder.to_string();
+        if placeholder == self.placeholder {
+            return self;
+        }
+        self.placeholder = placeholder;
+        self.placeholder_buffer = None;
+        self.editor.set_redraw(true);
+        self.base.size_hint_changed();
+        self.base.update();
+        self.update_placeholder_shown();
+        self
+    }
+
+    pub fn placeholder(&self) -> &str {
+        &self.placeholder
+    }
+
+    pub fn is_placeholder_shown(&self) -> bool {
+        self.is_placeholder_shown
+    }
+
+    /// Adds a callback that is called when the placeholder is shown or hidden.
+    pub fn on_placeholder_shown_changed(&mut self, callback: Callback<bool>) -> &mut Self {
+        self.on_placeholder_shown_changed.add(callback);
+        self
+    }
+
+    fn update_placeholder_shown(&mut self) {
+        let is_shown = !self.placeholder.is_empty()
+            && !self.is_host_focused
+            && self
+                .editor
+                .with_buffer(|buffer| buffer.lines.iter().all(|line| line.text().is_empty()));
+        if is_shown == self.is_placeholder_shown {
+            return;
+        }
+        self.is_placeholder_shown = is_shown;
+        self.editor.set_redraw(true);
+        self.base.size_hint_changed();
+        self.base.update();
+        self.on_placeholder_shown_changed.invoke(is_shown, true);
+    }
+
+    pub fn set_text_style(&mut self, style: Rc<TextStyle>) -> &mut Self {
+        if self.style == style {
+            return self;
+        }
+        let old_style = self.style.clone();
+        self.style = style;
+        self.set_font_metrics(self.style.font_metrics);
+        if old_style.font != self.style.font
+            || old_style.font_metrics != self.style.font_metrics
+            || old_style.link_text_color != self.style.link_text_color
+        {
+            self.update_attrs();
+        }
+        self.placeholder_buffer = None;
+        if old_style.text_color != self.style.text_color
+            || old_style.selected_text_color != self.style.selected_text_color
+            || old_style.selected_text_background != self.style.selected_text_background
+            || old_style.placeholder_text_color != self.style.placeholder_text_color
+            || old_style.find_match_background != self.style.find_match_background
+            || old_style.current_line_background != self.style.current_line_background
+            || old_style.whitespace_color != self.style.whitespace_color
+        {
+            self.editor.with_buffer_mut(|b| b.set_redraw(true));
+            self.base.update();
+        }
+        self.update_elision();
+        self
+    }
+
+    /// Applies the font attributes of the current style to the existing text.
+    fn update_attrs(&mut self) {
+        self.attrs = self.base.app().font_attrs(&self.style.font);
+        let scale = self.base.scale();
+        self.editor.with_buffer_mut(|buffer| {
+            for line in &mut buffer.lines {
+                let old_list = line.attrs_list();
+                let mut new_list = AttrsList::new(&self.attrs.as_attrs());
+                for (range, span_attrs) in old_list.spans_iter() {
+                    let metadata = Metadata(span_attrs.metadata);
+                    let index = metadata.format_index();
+                    let format = self.formats.get(index).unwrap_or(&self.formats[0]);
+                    let attrs = format_attrs(&self.attrs, &self.style, scale, format, index)
+                        .preedit(metadata.is_preedit());
+                    new_list.add_span(range.clone(), &attrs.as_attrs());
+                }
+                line.set_attrs_list(new_list);
+            }
+            buffer.set_redraw(true);
+        });
+        self.line_positions_changed(0);
+        self.adjust_size();
+        self.base.size_hint_changed();
+        self.base.update();
+        self.request_scroll();
+    }
+
+    fn after_change(&mut self) {
+        if self.echo_mode == EchoMode::Password {
+            return;
+        }
+        let new_selected_text = self.selected_text().unwrap_or_default();
+        if new_selected_text != self.selected_text {
+            self.selected_text = new_selected_text;
+            #[cfg(all(
+                unix,
+                not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+            ))]
+            self.base
+                .app()
+                .set_linux_primary_selection(&self.selected_text)
+                .or_warn();
+        }
+    }
+
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    fn paste_selection(&mut self) {
+        if self.is_mouse_interaction_forbidden() || self.is_read_only {
+            return;
+        }
+        let text = self.base.app().linux_primary_selection().or_warn();
+        if let Some(text) = text {
+            let text = self.sanitize(&text);
+            self.insert_string(&text, None);
+        }
+    }
+
+    fn copy_to_clipboard(&mut self) {
+        if self.echo_mode == EchoMode::Password {
+            return;
+        }
+        if let Some(text) = self.selected_text() {
+            self.base.app().set_clipboard_text(&text).or_warn();
+        }
+    }
+
+    fn reset_blink_timer(&mut self) {
+        if let Some(id) = self.blink_timer.take() {
+            self.base.app().cancel_timer(id);
+        }
+        let has_cursor = self.is_host_focused
+            && self.is_editable
+            && (!self.is_read_only || self.is_cursor_visible_when_read_only);
+        self.editor.set_cursor_hidden(!has_cursor);
+        if has_cursor {
+            let id = self.base.app().add_interval(
+                CURSOR_BLINK_INTERVAL,
+                self.callback(|this, _| this.toggle_cursor_hidden()),
+            );
+            self.blink_timer = Some(id);
+        }
+        self.base.update();
+    }
+
+    fn toggle_cursor_hidden(&mut self) -> Result<()> {
+        self.set_cursor_hidden(!self.is_cursor_hidden);
+        if !self.editor.has_selection() {
+            self.base.update();
+        }
+        Ok(())
+    }
+
+    fn accessibility_text_runs(&mut self) -> Vec<(accesskit::Node, TextRunInfo)> {
+        #[derive(Debug)]
+        struct CharStats {
+            bytes: Range<usize>,
+            pixels: Option<Range<FiniteF32>>,
+        }
+
+        self.editor.with_buffer_mut(|buffer| {
+            self.base.app().with_font_system(|font_system| {
+                buffer.set_scroll(cosmic_text::Scroll::default());
+                buffer.shape_until_scroll(font_system, false);
+            });
+            // TODO: separate text runs based on attributes and text direction? how to handle preedit?
+            let mut output = Vec::new();
+
+            let mut layout_runs = buffer.layout_runs().peekable();
+            while let Some(layout_run) = layout_runs.next() {
+                let text = &layout_run.text;
+                let mut text_start = text.len();
+                let mut text_end = 0;
+                for glyph in layout_run.glyphs {
+                    text_start = min(text_start, glyph.start);
+                    text_end = max(text_end, glyph.end);
+                }
+                //println!("text={text:?}, text_start={text_start:?}, text_end={text_end:?}");
+                let mut text = if let Some(part) = text.get(text_start..max(text_start, text_end)) {
+                    part.to_owned()
+                } else {
+                    warn!("invalid index while fetching text corresponding to glyphs");
+                    "".to_owned()
+                };
+
                 let mut character_lengths = Vec::new();
                 let mut character_stats = Vec::new();
                 for (i, c) in text.grapheme_indices(true) {
@@ -677,366 +1900,1024 @@ impl TextHandler {
                         pixels: None,
                     });
                 }
-                let mut word_lengths = Vec::new();
-                // TODO: expose from cosmic-text
-                let mut prev_index_in_chars = None;
-                let mut total_chars_in_words = 0;
-                for (i, word) in text.unicode_word_indices() {
-                    let end_i = i + word.len();
-                    let index_in_chars = character_stats
-                        .iter()
-                        .take_while(|s| s.bytes.start < end_i)
-                        .count();
-                    // TODO: checked_sub?
-                    let len_in_chars = index_in_chars - prev_index_in_chars.unwrap_or(0);
-                    word_lengths.push(len_in_chars as u8);
-                    prev_index_in_chars = Some(index_in_chars);
-                    total_chars_in_words += len_in_chars;
+                let mut word_lengths = Vec::new();
+                // TODO: expose from cosmic-text
+                let mut prev_index_in_chars = None;
+                let mut total_chars_in_words = 0;
+                for (i, word) in text.unicode_word_indices() {
+                    let end_i = i + word.len();
+                    let index_in_chars = character_stats
+                        .iter()
+                        .take_while(|s| s.bytes.start < end_i)
+                        .count();
+                    // TODO: checked_sub?
+                    let len_in_chars = index_in_chars - prev_index_in_chars.unwrap_or(0);
+                    word_lengths.push(len_in_chars as u8);
+                    prev_index_in_chars = Some(index_in_chars);
+                    total_chars_in_words += len_in_chars;
+                }
+                if total_chars_in_words < character_stats.len() {
+                    word_lengths.push((character_stats.len() - total_chars_in_words) as u8);
+                }
+
+                for glyph in layout_run.glyphs {
+                    let mut found = false;
+                    // TODO: optimize
+                    for stats in &mut character_stats {
+                        if stats
+                            .bytes
+                            .does_intersect(&(glyph.start - text_start..glyph.end - text_start))
+                        {
+                            found = true;
+                            let new_start = FiniteF32::new(glyph.x).unwrap();
+                            let new_end = FiniteF32::new(glyph.x + glyph.w).unwrap();
+                            if let Some(pixels) = &mut stats.pixels {
+                                pixels.start = min(pixels.start, new_start);
+                                pixels.end = max(pixels.end, new_end);
+                            } else {
+                                stats.pixels = Some(new_start..new_end);
+                            }
+                        }
+                    }
+                    if !found {
+                        warn!("no char found for glyph: {glyph:?}");
+                    }
+                }
+                //println!("character_stats={character_stats:?}");
+                //println!("text={text:?}");
+                let mut character_positions = character_stats
+                    .iter()
+                    .map(|s| {
+                        s.pixels.as_ref().map_or_else(
+                            || {
+                                warn!(
+                                    "glyph for char not found: range={:?}, c={:?}",
+                                    s.bytes,
+                                    &text[s.bytes.clone()]
+                                );
+                                0.0
+                            },
+                            |range| range.start.get(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                let mut character_widths = character_stats
+                    .iter()
+                    .map(|s| {
+                        s.pixels.as_ref().map_or_else(
+                            || {
+                                warn!("glyph for char not found");
+                                0.0
+                            },
+                            |range| range.end.get() - range.start.get(),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if layout_runs.peek().is_some() {
+                    text.push(if text_end < layout_run.text.len() {
+                        ' '
+                    } else {
+                        '\n'
+                    });
+                    character_lengths.push(1);
+                    character_widths.push(0.);
+                    character_positions.push(character_positions.last().copied().unwrap_or(0.));
+                }
+
+                let mut line_node = accesskit::Node::new(Role::TextRun);
+                line_node.set_text_direction(if layout_run.rtl {
+                    TextDirection::RightToLeft
+                } else {
+                    TextDirection::LeftToRight
+                });
+                line_node.set_value(text.clone());
+                line_node.set_character_lengths(character_lengths);
+                line_node.set_character_positions(character_positions);
+                line_node.set_character_widths(character_widths);
+                line_node.set_word_lengths(word_lengths);
+                if let Some(rect_in_window) = self.base.rect_in_window() {
+                    let top = PhysicalPixels::from_i32(layout_run.line_top.round() as i32);
+                    let height = PhysicalPixels::from_i32(layout_run.line_height.round() as i32);
+                    let bounds = Rect::from_pos_size(
+                        Point::new(rect_in_window.left(), rect_in_window.top() + top),
+                        Size::new(rect_in_window.size_x(), height),
+                    );
+                    line_node.set_bounds(bounds.into());
+                }
+                let info = TextRunInfo {
+                    line_i: layout_run.line_i,
+                    text_start,
+                    text_end,
+                    text,
+                };
+                output.push((line_node, info));
+            }
+            //println!("text runs: {:?}", output);
+            output
+        })
+    }
+
+    pub fn handle_accessibility_set_selection_action(&mut self, data: TextSelection) {
+        let text = self
+            .editor
+            .with_buffer(|buffer| buffer.lines[0].text().to_string());
+        let char_to_byte_index =
+            |char_index| text.grapheme_indices(true).nth(char_index).map(|(i, _)| i);
+        if data.anchor == data.focus {
+            self.set_select_opt(None);
+        } else {
+            let Some(index) = char_to_byte_index(data.anchor.character_index) else {
+                warn!("char index is too large");
+                return;
+            };
+            self.set_select_opt(Some(Cursor {
+                line: 0,
+                index,
+                affinity: Affinity::Before,
+            }));
+        }
+        let Some(index) = char_to_byte_index(data.focus.character_index) else {
+            warn!("char index is too large");
+            return;
+        };
+        self.set_cursor(Cursor {
+            line: 0,
+            index,
+            affinity: Affinity::Before,
+        });
+        self.check_cursor_changes(false);
+        self.base.update();
+        self.reset_blink_timer();
+    }
+
+    fn accessibility_text_position(&self, cursor: Cursor) -> anyhow::Result<TextPosition> {
+        let Some((id, info)) = self.accessibility_text_run_ids.iter().find(|(_, info)| {
+            info.line_i == cursor.line
+                && cursor.index >= info.text_start
+                && cursor.index <= info.text_end
+        }) else {
+            bail!("no text run info corresponds to cursor {:?}", cursor);
+        };
+
+        // Convert byte index to char index:
+        let character_index = info
+            .text
+            .grapheme_indices(true)
+            .take_while(|(i, _)| *i < cursor.index - info.text_start)
+            .count();
+
+        Ok(TextPosition {
+            node: *id,
+            character_index,
+        })
+    }
+
+    fn selection_accessibility_info(&mut self) -> anyhow::Result<TextSelection> {
+        let focus = if let Ok(v) = self.accessibility_text_position(self.cursor()) {
+            v
+        } else if let Some((id, _)) = self.accessibility_text_run_ids.first() {
+            TextPosition {
+                node: *id,
+                character_index: 0,
+            }
+        } else {
+            bail!("no text runs");
+        };
+
+        let anchor = if let Some(select) = self.select_opt() {
+            self.accessibility_text_position(select).unwrap_or(focus)
+        } else {
+            focus
+        };
+        // println!("anchor={anchor:?}, focus={focus:?}");
+        Ok(TextSelection { anchor, focus })
+    }
+
+    /// Inserts `text` at the cursor, replacing the selection.
+    ///
+    /// Unlike typing and pasting, it also works if the text is read-only.
+    pub fn insert_string(&mut self, text: &str, attrs_list: Option<AttrsList>) {
+        self.insert_string_as(text, attrs_list, EditKind::Other);
+    }
+
+    /// Inserts `text` at the end and moves the cursor there.
+    ///
+    /// Like [insert_string](Self::insert_string), it also works if the text is read-only.
+    pub fn append(&mut self, text: &str) {
+        self.interrupt_preedit();
+        let end = self.cursor_at_offset(self.text().len());
+        self.editor.set_selection(Selection::None);
+        self.editor.set_cursor(end);
+        self.insert_string(text, None);
+    }
+
+    fn insert_string_as(&mut self, text: &str, attrs_list: Option<AttrsList>, kind: EditKind) {
+        let text = self.restrict_insertion(text);
+        if text.is_empty() {
+            return;
+        }
+        let before = self.start_edit();
+        if self.is_masked() {
+            self.editor.insert_string(&password_text(&text), attrs_list);
+        } else {
+            self.editor.insert_string(&text, attrs_list);
+        }
+        self.finish_edit(kind, before, Some(&text));
+        self.check_cursor_changes(false);
+        self.adjust_size();
+        self.base.size_hint_changed();
+        self.base.update();
+        self.request_scroll();
+    }
+
+    fn draw_placeholder(&mut self, pixmap: &mut Pixmap) {
+        let size = (pixmap.width() as f32, pixmap.height() as f32);
+        let color = convert_color(self.style.placeholder_text_color);
+        let app = self.base.app();
+        app.with_font_system_and_swash_cache(|font_system, swash_cache| {
+            let buffer = self.placeholder_buffer.get_or_insert_with(|| {
+                placeholder_buffer(font_system, &self.style, &self.attrs, &self.placeholder)
+            });
+            let mut buffer = buffer.borrow_with(font_system);
+            buffer.set_size(Some(size.0), Some(size.1));
+            buffer.draw(swash_cache, color, |x, y, w, h, c| {
+                let paint = Paint {
+                    shader: Shader::SolidColor(Color::from_rgba8(c.r(), c.g(), c.b(), c.a())),
+                    anti_alias: false,
+                    ..Paint::default()
+                };
+                if let Some(rect) =
+                    tiny_skia::Rect::from_xywh(x as f32, y as f32, w as f32, h as f32)
+                {
+                    pixmap.fill_rect(rect, &paint, Transform::default(), None);
+                }
+            });
+        });
+    }
+
+    // Fills the backgrounds of the text that matches the find query.
+    // The selection is drawn on top of it.
+    fn draw_find_matches(&self, pixmap: &mut Pixmap) {
+        if self.find_matches.is_empty() {
+            return;
+        }
+        let matches = self
+            .find_matches
+            .iter()
+            .map(|range| {
+                let start = self.cursor_at_offset(range.start);
+                let end = self.cursor_at_offset(range.end);
+                ((start.line, start.index), (end.line, end.index))
+            })
+            .collect_vec();
+        let paint = Paint {
+            shader: Shader::SolidColor(self.style.find_match_background),
+            ..Paint::default()
+        };
+        self.with_displayed_buffer(|buffer| {
+            for run in buffer.layout_runs() {
+                for glyph in run.glyphs {
+                    let Some(index) = self.displayed_to_text_index(run.line_i, glyph.start) else {
+                        continue;
+                    };
+                    let position = (run.line_i, index);
+                    let match_index = matches.partition_point(|(_, end)| *end <= position);
+                    let is_match = matches
+                        .get(match_index)
+                        .is_some_and(|(start, _)| *start <= position);
+                    if !is_match {
+                        continue;
+                    }
+                    if let Some(rect) =
+                        tiny_skia::Rect::from_xywh(glyph.x, run.line_top, glyph.w, run.line_height)
+                    {
+                        pixmap.fill_rect(rect, &paint, Transform::default(), None);
+                    }
+                }
+            }
+        });
+    }
+
+    fn draw_elided_text(&mut self, pixmap: &mut Pixmap) {
+        let color = convert_color(self.style.text_color);
+        let app = self.base.app();
+        let Some(buffer) = &mut self.elided_buffer else {
+            return;
+        };
+        app.with_font_system_and_swash_cache(|font_system, swash_cache| {
+            buffer.draw(font_system, swash_cache, color, |x, y, w, h, c| {
+                let paint = Paint {
+                    shader: Shader::SolidColor(Color::from_rgba8(c.r(), c.g(), c.b(), c.a())),
+                    anti_alias: false,
+                    ..Paint::default()
+                };
+                if let Some(rect) =
+                    tiny_skia::Rect::from_xywh(x as f32, y as f32, w as f32, h as f32)
+                {
+                    pixmap.fill_rect(rect, &paint, Transform::default(), None);
+                }
+            });
+        });
+    }
+
+    pub fn shape_as_needed(&mut self) {
+        self.base
+            .app()
+            .with_font_system(|font_system| self.editor.shape_as_needed(font_system, false));
+    }
+
+    pub fn needs_redraw(&mut self) -> bool {
+        self.shape_as_needed();
+        self.editor.redraw()
+    }
+
+    pub fn is_mouse_interaction_forbidden(&self) -> bool {
+        self.forbid_mouse_interaction
+    }
+
+    // TODO: private
+    pub fn pixmap(&mut self) -> &Pixmap {
+        if self.pixmap.is_none() || self.needs_redraw() {
+            let (buffer_width, buffer_height) = self.editor.with_buffer(|buffer| buffer.size());
+            let size_x = max(1, buffer_width.unwrap_or(0.).ceil() as u32);
+            let size_y = max(1, buffer_height.unwrap_or(0.).ceil() as u32);
+
+            let mut pixmap = Pixmap::new(size_x, size_y).expect("failed to create pixmap");
+            self.draw_current_line(&mut pixmap);
+            self.draw_backgrounds(&mut pixmap);
+            self.draw_find_matches(&mut pixmap);
+            if self.elided_buffer.is_some() {
+                self.draw_elided_text(&mut pixmap);
+            } else {
+                self.base
+                    .app()
+                    .with_font_system_and_swash_cache(|font_system, swash_cache| {
+                        self.editor.draw(
+                            font_system,
+                            swash_cache,
+                            &EditorDrawStyle {
+                                text_color: convert_color(self.style.text_color),
+                                cursor_color: convert_color(self.style.text_color), // TODO: cursor color,
+                                selection_color: convert_color(self.style.selected_text_background),
+                                selected_text_color: convert_color(self.style.selected_text_color),
+                            },
+                            |x, y, w, h, c| {
+                                // let color = PremultipliedColorU8::from_rgba(
+                                //     min(c.a(), c.r()),
+                                //     min(c.a(), c.g()),
+                                //     min(c.a(), c.b()),
+                                //     c.a(),
+                                // )
+                                // .expect("RGB components must be <= alpha");
+
+                                // for iy in y..(y + h as i32) {
+                                //     for ix in x..(x + w as i32) {
+                                //         if ix >= 0 && ix < pixmap_width && iy >= 0 && iy < pixmap_height {
+                                //             pixels[(ix + iy * pixmap_width) as usize] = color;
+                                //         }
+                                //     }
+                                // }
+
+                                let color = Color::from_rgba8(c.r(), c.g(), c.b(), c.a());
+                                let paint = Paint {
+                                    shader: Shader::SolidColor(color),
+                                    anti_alias: false,
+                                    ..Paint::default()
+                                };
+                                pixmap.fill_rect(
+                                    tiny_skia::Rect::from_xywh(
+                                        x as f32, y as f32, w as f32, h as f32,
+                                    )
+                                    .unwrap(),
+                                    &paint,
+                                    Transform::default(),
+                                    None,
+                                );
+                            },
+                        );
+                    });
+            }
+            if self.is_placeholder_shown {
+                self.draw_placeholder(&mut pixmap);
+            }
+            if self.is_whitespace_visible {
+                self.draw_whitespace(&mut pixmap);
+            }
+            let mut underline_alg = LineGenerator::new(LineType::Underline);
+            let mut strikethrough_alg = LineGenerator::new(LineType::StrikeThrough);
+            let mut lines = Vec::new();
+            // TODO: determine from glyph width?
+            let stroke_width = 1.0;
+            let snap = |y: f32| (y + stroke_width / 2.0).round() - stroke_width / 2.0;
+            self.with_displayed_buffer(|buffer| {
+                for run in buffer.layout_runs() {
+                    let underline_space = run.line_top + run.line_height - run.line_y;
+                    let underline_y = snap(run.line_y + underline_space / 2.0);
+                    for glyph in run.glyphs {
+                        let metadata = Metadata(glyph.metadata);
+                        let format = self.format(metadata.format_index());
+                        let color = glyph
+                            .color_opt
+                            .unwrap_or(convert_color(self.style.text_color));
+                        let line_glyph = |line_y| line_straddler::Glyph {
+                            line_y,
+                            font_size: glyph.font_size,
+                            width: glyph.w,
+                            x: glyph.x,
+                            style: GlyphStyle {
+                                boldness: 1,
+                                color: line_straddler::Color::rgba(
+                                    color.r(),
+                                    color.g(),
+                                    color.b(),
+                                    color.a(),
+                                ),
+                            },
+                        };
+                        if metadata.is_preedit() || format.underline || format.link.is_some() {
+                            lines.extend(underline_alg.add_glyph(line_glyph(underline_y)));
+                        }
+                        if format.strikethrough {
+                            let strikethrough_y = snap(run.line_y - glyph.font_size * 0.3);
+                            lines.extend(strikethrough_alg.add_glyph(line_glyph(strikethrough_y)));
+                        }
+                    }
+                }
+            });
+            lines.extend(underline_alg.pop_line());
+            lines.extend(strikethrough_alg.pop_line());
+            for line in lines {
+                let mut path = PathBuilder::new();
+                path.move_to(line.start_x, line.y);
+                path.line_to(line.end_x, line.y);
+                pixmap.stroke_path(
+                    &path.finish().unwrap(),
+                    &Paint {
+                        shader: Shader::SolidColor(tiny_skia::Color::from_rgba8(
+                            line.style.color.red(),
+                            line.style.color.green(),
+                            line.style.color.blue(),
+                            line.style.color.alpha(),
+                        )),
+                        ..Paint::default()
+                    },
+                    &Stroke {
+                        width: stroke_width,
+                        ..Stroke::default()
+                    },
+                    Transform::default(),
+                    None,
+                );
+            }
+            self.pixmap = Some(pixmap);
+            self.editor.set_redraw(false);
+        }
+        self.pixmap.as_ref().expect("created above")
+    }
+
+    fn draw_current_line(&self, pixmap: &mut Pixmap) {
+        if !self.is_current_line_highlighted {
+            return;
+        }
+        let line = self.editor.cursor().line;
+        let paint = Paint {
+            shader: Shader::SolidColor(self.style.current_line_background),
+            ..Paint::default()
+        };
+        let width = pixmap.width() as f32;
+        self.with_displayed_buffer(|buffer| {
+            for run in buffer.layout_runs().filter(|run| run.line_i == line) {
+                if let Some(rect) =
+                    tiny_skia::Rect::from_xywh(0.0, run.line_top, width, run.line_height)
+                {
+                    pixmap.fill_rect(rect, &paint, Transform::default(), None);
+                }
+            }
+        });
+    }
+
+    // Marks spaces with dots and tabs with arrows.
+    fn draw_whitespace(&self, pixmap: &mut Pixmap) {
+        let paint = Paint {
+            shader: Shader::SolidColor(self.style.whitespace_color),
+            ..Paint::default()
+        };
+        let mut path = PathBuilder::new();
+        for mark in self.with_displayed_buffer(whitespace_marks) {
+            match mark {
+                WhitespaceMark::Dot(rect) => {
+                    pixmap.fill_rect(rect, &paint, Transform::default(), None);
                 }
-                if total_chars_in_words < character_stats.len() {
-                    word_lengths.push((character_stats.len() - total_chars_in_words) as u8);
+                WhitespaceMark::Arrow {
+                    start_x,
+                    end_x,
+                    y,
+                    head,
+                } => {
+                    path.move_to(start_x, y);
+                    path.line_to(end_x, y);
+                    path.move_to(end_x - head, y - head);
+                    path.line_to(end_x, y);
+                    path.line_to(end_x - head, y + head);
                 }
+            }
+        }
+        if let Some(path) = path.finish() {
+            pixmap.stroke_path(
+                &path,
+                &paint,
+                &Stroke::default(),
+                Transform::default(),
+                None,
+            );
+        }
+    }
 
-                for glyph in layout_run.glyphs {
-                    let mut found = false;
-                    // TODO: optimize
-                    for stats in &mut character_stats {
-                        if stats
-                            .bytes
-                            .does_intersect(&(glyph.start - text_start..glyph.end - text_start))
-                        {
-                            found = true;
-                            let new_start = FiniteF32::new(glyph.x).unwrap();
-                            let new_end = FiniteF32::new(glyph.x + glyph.w).unwrap();
-                            if let Some(pixels) = &mut stats.pixels {
-                                pixels.start = min(pixels.start, new_start);
-                                pixels.end = max(pixels.end, new_end);
-                            } else {
-                                stats.pixels = Some(new_start..new_end);
-                            }
-                        }
-                    }
-                    if !found {
-                        warn!("no char found for glyph: {glyph:?}");
-                    }
-                }
-                //println!("character_stats={character_stats:?}");
-                //println!("text={text:?}");
-                let mut character_positions = character_stats
-                    .iter()
-                    .map(|s| {
-                        s.pixels.as_ref().map_or_else(
-                            || {
-                                warn!(
-                                    "glyph for char not found: range={:?}, c={:?}",
-                                    s.bytes,
-                                    &text[s.bytes.clone()]
-                                );
-                                0.0
-                            },
-                            |range| range.start.get(),
-                        )
-                    })
-                    .collect::<Vec<_>>();
-                let mut character_widths = character_stats
-                    .iter()
-                    .map(|s| {
-                        s.pixels.as_ref().map_or_else(
-                            || {
-                                warn!("glyph for char not found");
-                                0.0
+    // Fills the backgrounds of rich text spans.
+    fn draw_backgrounds(&self, pixmap: &mut Pixmap) {
+        self.with_displayed_buffer(|buffer| {
+            for run in buffer.layout_runs() {
+                for glyph in run.glyphs {
+                    let format = self.format(Metadata(glyph.metadata).format_index());
+                    let Some(background) = format.background else {
+                        continue;
+                    };
+                    if let Some(rect) =
+                        tiny_skia::Rect::from_xywh(glyph.x, run.line_top, glyph.w, run.line_height)
+                    {
+                        pixmap.fill_rect(
+                            rect,
+                            &Paint {
+                                shader: Shader::SolidColor(background),
+                                ..Paint::default()
                             },
-                            |range| range.end.get() - range.start.get(),
-                        )
-                    })
-                    .collect::<Vec<_>>();
-                if layout_runs.peek().is_some() {
-                    text.push(if text_end < layout_run.text.len() {
-                        ' '
-                    } else {
-                        '\n'
-                    });
-                    character_lengths.push(1);
-                    character_widths.push(0.);
-                    character_positions.push(character_positions.last().copied().unwrap_or(0.));
+                            Transform::default(),
+                            None,
+                        );
+                    }
                 }
+            }
+        });
+    }
 
-                let mut line_node = accesskit::Node::new(Role::TextRun);
-                line_node.set_text_direction(if layout_run.rtl {
-                    TextDirection::RightToLeft
-                } else {
-                    TextDirection::LeftToRight
-                });
-                line_node.set_value(text.clone());
-                line_node.set_character_lengths(character_lengths);
-                line_node.set_character_positions(character_positions);
-                line_node.set_character_widths(character_widths);
-                line_node.set_word_lengths(word_lengths);
-                if let Some(rect_in_window) = self.base.rect_in_window() {
-                    let top = PhysicalPixels::from_i32(layout_run.line_top.round() as i32);
-                    let height = PhysicalPixels::from_i32(layout_run.line_height.round() as i32);
-                    let bounds = Rect::from_pos_size(
-                        Point::new(rect_in_window.left(), rect_in_window.top() + top),
-                        Size::new(rect_in_window.size_x(), height),
-                    );
-                    line_node.set_bounds(bounds.into());
+    pub fn cursor_position(&self) -> Option<Point> {
+        self.editor
+            .cursor_position()
+            .map(|(x, y)| Point::new(PhysicalPixels::from_i32(x), PhysicalPixels::from_i32(y)))
+    }
+
+    pub fn line_height(&self) -> f32 {
+        self.editor
+            .with_buffer(|buffer| buffer.metrics().line_height)
+    }
+
+    pub fn action(&mut self, mut action: Action) {
+        if let Action::Drag { .. } = &mut action {
+            if self.forbid_mouse_interaction {
+                return;
+            }
+        }
+        if self.is_masked() {
+            match action {
+                Action::Insert(c) => {
+                    if !self.is_read_only {
+                        self.insert_string_as(&c.to_string(), None, EditKind::Typing);
+                    }
+                    return;
                 }
-                let info = TextRunInfo {
-                    line_i: layout_run.line_i,
-                    text_start,
-                    text_end,
-                    text,
-                };
-                output.push((line_node, info));
+                Action::Enter | Action::Indent | Action::Unindent | Action::SetPreedit { .. } => {
+                    return
+                }
+                _ => {}
             }
-            //println!("text runs: {:?}", output);
-            output
-        })
+        }
+        let edit_kind = match &action {
+            Action::Insert(_) => Some(EditKind::Typing),
+            Action::Backspace => Some(EditKind::Backspace),
+            Action::Delete => Some(EditKind::Delete),
+            Action::Enter
+            | Action::DeleteStartOfWord
+            | Action::DeleteEndOfWord
+            | Action::Indent
+            | Action::Unindent => Some(EditKind::Other),
+            _ => None,
+        };
+        if self.is_read_only && (edit_kind.is_some() || matches!(action, Action::SetPreedit { .. }))
+        {
+            return;
+        }
+        let edit = edit_kind.map(|kind| (kind, self.start_edit()));
+        self.base
+            .app()
+            .with_font_system(|font_system| self.editor.action(font_system, action));
+        if let Some((kind, before)) = edit {
+            self.finish_edit(kind, before, None);
+        }
+        self.check_cursor_changes(false);
+        self.adjust_size();
+        self.base.size_hint_changed();
+        self.base.update();
+        self.request_scroll();
     }
 
-    pub fn handle_accessibility_set_selection_action(&mut self, data: TextSelection) {
-        let text = self
-            .editor
-            .with_buffer(|buffer| buffer.lines[0].text().to_string());
-        let char_to_byte_index =
-            |char_index| text.grapheme_indices(true).nth(char_index).map(|(i, _)| i);
-        if data.anchor == data.focus {
-            self.set_select_opt(None);
-        } else {
-            let Some(index) = char_to_byte_index(data.anchor.character_index) else {
-                warn!("char index is too large");
-                return;
-            };
-            self.set_select_opt(Some(Cursor {
-                line: 0,
-                index,
-                affinity: Affinity::Before,
-            }));
+    fn cursor_state(&self) -> CursorState {
+        CursorState {
+            cursor: self.editor.cursor(),
+            selection: self.editor.selection(),
         }
-        let Some(index) = char_to_byte_index(data.focus.character_index) else {
-            warn!("char index is too large");
+    }
+
+    // Starts recording changes of the text. Returns the cursor state before the edit
+    // that must be passed to `finish_edit`.
+    fn start_edit(&mut self) -> CursorState {
+        self.editor.start_change();
+        self.cursor_state()
+    }
+
+    // Adds the changes recorded since `start_edit` to the undo history.
+    // `inserted_text` is the actual text inserted into the buffer if it's masked.
+    fn finish_edit(&mut self, kind: EditKind, before: CursorState, inserted_text: Option<&str>) {
+        let Some((change, attrs)) = self.editor.finish_change_with_attrs() else {
             return;
         };
-        self.set_cursor(Cursor {
-            line: 0,
-            index,
-            affinity: Affinity::Before,
-        });
-        self.base.update();
-        self.reset_blink_timer();
+        if change.items.is_empty() {
+            return;
+        }
+        let old_hidden_text = self
+            .is_masked()
+            .then(|| self.apply_change_to_hidden_text(&change, inserted_text));
+        if self.is_rejected(&change) {
+            if let Some(old_hidden_text) = old_hidden_text {
+                self.hidden_text = old_hidden_text;
+            }
+            let mut change = change;
+            change.reverse();
+            let attrs = attrs.into_iter().rev().collect_vec();
+            self.editor.apply_change_with_attrs(&change, &attrs);
+            self.editor.set_cursor(before.cursor);
+            self.editor.set_selection(before.selection);
+            self.editor.set_redraw(true);
+            return;
+        }
+        if self.echo_mode != EchoMode::Password {
+            let old_state = self.history.state();
+            self.history.push(HistoryEntry {
+                change,
+                attrs,
+                formatting: None,
+                kind,
+                before,
+                after: self.cursor_state(),
+            });
+            self.history_changed(old_state, false);
+        }
+        self.text_changed(false);
     }
 
-    fn accessibility_text_position(&self, cursor: Cursor) -> anyhow::Result<TextPosition> {
-        let Some((id, info)) = self.accessibility_text_run_ids.iter().find(|(_, info)| {
-            info.line_i == cursor.line
-                && cursor.index >= info.text_start
-                && cursor.index <= info.text_end
-        }) else {
-            bail!("no text run info corresponds to cursor {:?}", cursor);
+    // Applies a change of the masked buffer to the actual text. Returns the old text.
+    fn apply_change_to_hidden_text(
+        &mut self,
+        change: &Change,
+        inserted_text: Option<&str>,
+    ) -> String {
+        let old_text = self.hidden_text.clone();
+        apply_change_to_hidden_text(&mut self.hidden_text, change, inserted_text);
+        old_text
+    }
+
+    fn text_changed(&mut self, from_setter: bool) {
+        self.is_edited |= !from_setter;
+        self.update_links();
+        self.update_find_matches();
+        self.on_text_changed.invoke(self.text(), from_setter);
+        self.update_validation_state(from_setter);
+        self.update_placeholder_shown();
+    }
+
+    // Applies the input mask and the max length to the text that is about to be inserted.
+    fn restrict_insertion(&self, text: &str) -> String {
+        if self.input_mask.is_none() && self.max_length.is_none() {
+            return text.into();
+        }
+        let current_text = self.text();
+        let (start, end) = self
+            .selection_range()
+            .map(|range| (range.start, range.end))
+            .unwrap_or_else(|| (self.cursor_offset(), self.cursor_offset()));
+        let mut text = if let Some(mask) = &self.input_mask {
+            mask.expand_insertion(&current_text[..start], text)
+        } else {
+            text.into()
         };
+        if let Some(max_length) = self.max_length {
+            let kept_chars =
+                current_text.chars().count() - current_text[start..end].chars().count();
+            let available = max_length.saturating_sub(kept_chars);
+            if let Some((index, _)) = text.char_indices().nth(available) {
+                text.truncate(index);
+            }
+        }
+        text
+    }
+
+    // Returns true if the edit must be reverted because it makes the text invalid.
+    // Edits of a text that is already invalid (e.g. set by `set_text`) are allowed.
+    fn is_rejected(&self, change: &Change) -> bool {
+        let text = self.text();
+        if let Some(max_length) = self.max_length {
+            let added_chars: isize = change
+                .items
+                .iter()
+                .map(|item| {
+                    let count = item.text.chars().count() as isize;
+                    if item.insert {
+                        count
+                    } else {
+                        -count
+                    }
+                })
+                .sum();
+            if added_chars > 0 && text.chars().count() > max_length {
+                return true;
+            }
+        }
+        self.validation_state != ValidationState::Invalid
+            && self.validate(&text) == ValidationState::Invalid
+    }
+
+    fn validate(&self, text: &str) -> ValidationState {
+        let mask_state = self
+            .input_mask
+            .as_ref()
+            .map_or(ValidationState::Acceptable, |mask| mask.validate(text));
+        let validator_state = self
+            .validator
+            .as_ref()
+            .map_or(ValidationState::Acceptable, |validator| {
+                validator.validate(text)
+            });
+        min(mask_state, validator_state)
+    }
+
+    fn update_validation_state(&mut self, from_setter: bool) {
+        let state = self.validate(&self.text());
+        if state != self.validation_state {
+            self.validation_state = state;
+            self.on_validation_state_changed.invoke(state, from_setter);
+        }
+    }
+
+    /// Sets the validator that checks the text entered by the user.
+    ///
+    /// Edits that would make the text [invalid](ValidationState::Invalid) are rejected.
+    /// The current text is not modified.
+    pub fn set_validator(&mut self, validator: Option<Rc<dyn Validator>>) -> &mut Self {
+        self.validator = validator;
+        self.update_validation_state(true);
+        self
+    }
+
+    pub fn validator(&self) -> Option<&Rc<dyn Validator>> {
+        self.validator.as_ref()
+    }
+
+    /// Sets the format of the text entered by the user.
+    ///
+    /// The mask is checked in addition to the [validator](Self::set_validator).
+    pub fn set_input_mask(&mut self, mask: Option<InputMask>) -> &mut Self {
+        self.input_mask = mask;
+        self.update_validation_state(true);
+        self
+    }
+
+    pub fn input_mask(&self) -> Option<&InputMask> {
+        self.input_mask.as_ref()
+    }
+
+    /// Limits the number of characters the user can enter.
+    ///
+    /// Inserted text is truncated to fit the limit. The current text is not modified.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) -> &mut Self {
+        self.max_length = max_length;
+        self
+    }
+
+    pub fn max_length(&self) -> Option<usize> {
+        self.max_length
+    }
+
+    /// Returns the result of validating the current text
+    /// with the [validator](Self::set_validator) and the [input mask](Self::set_input_mask).
+    pub fn validation_state(&self) -> ValidationState {
+        self.validation_state
+    }
+
+    /// Adds a callback that is called when the [validation state](Self::validation_state) changes.
+    pub fn on_validation_state_changed(
+        &mut self,
+        callback: Callback<ValidationState>,
+    ) -> &mut Self {
+        self.on_validation_state_changed.add(callback);
+        self
+    }
 
-        // Convert byte index to char index:
-        let character_index = info
-            .text
-            .grapheme_indices(true)
-            .take_while(|(i, _)| *i < cursor.index - info.text_start)
-            .count();
+    // Emits signals if the cursor position or the selection changed
+    // since the last call.
+    fn check_cursor_changes(&mut self, from_setter: bool) {
+        let cursor_offset = self.cursor_offset();
+        if cursor_offset != self.cursor_offset {
+            self.cursor_offset = cursor_offset;
+            self.on_cursor_position_changed
+                .invoke(cursor_offset, from_setter);
+        }
+        let selection_range = self.selection_range();
+        if selection_range != self.selection_range {
+            self.selection_range = selection_range.clone();
+            self.on_selection_changed
+                .invoke(selection_range, from_setter);
+        }
+    }
 
-        Ok(TextPosition {
-            node: *id,
-            character_index,
+    // Converts the cursor to a byte offset in the text returned by `text()`.
+    fn text_offset(&self, cursor: Cursor) -> usize {
+        if self.is_masked() {
+            return hidden_text_offset(&self.hidden_text, cursor);
+        }
+        self.editor.with_buffer(|buffer| {
+            let previous_lines: usize = buffer
+                .lines
+                .iter()
+                .take(cursor.line)
+                .map(|line| line_text_without_preedit(line).len() + 1)
+                .sum();
+            let Some(line) = buffer.lines.get(cursor.line) else {
+                return previous_lines.saturating_sub(1);
+            };
+            let mut index = min(cursor.index, line.text().len());
+            if let Some(preedit) = preedit_range(line) {
+                if index >= preedit.end {
+                    index -= preedit.len();
+                } else if index > preedit.start {
+                    index = preedit.start;
+                }
+            }
+            previous_lines + index
         })
     }
 
-    fn selection_accessibility_info(&mut self) -> anyhow::Result<TextSelection> {
-        let focus = if let Ok(v) = self.accessibility_text_position(self.cursor()) {
-            v
-        } else if let Some((id, _)) = self.accessibility_text_run_ids.first() {
-            TextPosition {
-                node: *id,
-                character_index: 0,
+    // Converts a byte offset in the text returned by `text()` to a cursor.
+    fn cursor_at_offset(&self, offset: usize) -> Cursor {
+        if self.is_masked() {
+            return masked_cursor(&self.hidden_text, offset);
+        }
+        self.editor.with_buffer(|buffer| {
+            let mut offset = offset;
+            for (line_index, line) in buffer.lines.iter().enumerate() {
+                let len = line.text().len();
+                if offset <= len || line_index == buffer.lines.len() - 1 {
+                    return Cursor::new(line_index, min(offset, len));
+                }
+                offset -= len + 1;
             }
-        } else {
-            bail!("no text runs");
-        };
+            Cursor::default()
+        })
+    }
 
-        let anchor = if let Some(select) = self.select_opt() {
-            self.accessibility_text_position(select).unwrap_or(focus)
-        } else {
-            focus
-        };
-        // println!("anchor={anchor:?}, focus={focus:?}");
-        Ok(TextSelection { anchor, focus })
+    /// Returns the byte offset of the cursor in the [text](Self::text).
+    pub fn cursor_offset(&self) -> usize {
+        self.text_offset(self.editor.cursor())
     }
 
-    pub fn insert_string(&mut self, text: &str, attrs_list: Option<AttrsList>) {
-        if text.is_empty() {
-            return;
+    /// Returns the byte range of the selected part of the [text](Self::text).
+    pub fn selection_range(&self) -> Option<Range<usize>> {
+        self.selection_bounds()
+            .map(|(start, end)| self.text_offset(start)..self.text_offset(end))
+            .filter(|range| !range.is_empty())
+    }
+
+    /// Adds a callback that is called when the text changes.
+    pub fn on_text_changed(&mut self, callback: Callback<String>) -> &mut Self {
+        self.on_text_changed.add(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the cursor moves.
+    ///
+    /// The callback receives the byte offset of the cursor in the text.
+    pub fn on_cursor_position_changed(&mut self, callback: Callback<usize>) -> &mut Self {
+        self.on_cursor_position_changed.add(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the selection changes.
+    ///
+    /// The callback receives the byte range of the selected text or `None` if
+    /// nothing is selected.
+    pub fn on_selection_changed(&mut self, callback: Callback<Option<Range<usize>>>) -> &mut Self {
+        self.on_selection_changed.add(callback);
+        self
+    }
+
+    fn history_changed(&mut self, old_state: EditHistoryState, from_setter: bool) {
+        let state = self.history.state();
+        if state != old_state {
+            self.on_history_changed.invoke(state, from_setter);
         }
-        self.editor.insert_string(text, attrs_list);
-        self.adjust_size();
-        self.base.size_hint_changed();
-        self.base.update();
-        self.request_scroll();
     }
 
-    pub fn shape_as_needed(&mut self) {
-        self.base
-            .app()
-            .with_font_system(|font_system| self.editor.shape_as_needed(font_system, false));
+    /// Reverts the last edit of the text, restoring the cursor and the selection
+    /// that were present before the edit.
+    pub fn undo(&mut self) -> &mut Self {
+        self.apply_undo(true);
+        self
     }
 
-    pub fn needs_redraw(&mut self) -> bool {
-        self.shape_as_needed();
-        self.editor.redraw()
+    /// Repeats the last edit reverted by [undo](Self::undo).
+    pub fn redo(&mut self) -> &mut Self {
+        self.apply_redo(true);
+        self
     }
 
-    pub fn is_mouse_interaction_forbidden(&self) -> bool {
-        self.forbid_mouse_interaction
+    pub fn can_undo(&self) -> bool {
+        self.history.state().can_undo
     }
 
-    // TODO: private
-    pub fn pixmap(&mut self) -> &Pixmap {
-        if self.pixmap.is_none() || self.needs_redraw() {
-            let (buffer_width, buffer_height) = self.editor.with_buffer(|buffer| buffer.size());
-            let size_x = max(1, buffer_width.unwrap_or(0.).ceil() as u32);
-            let size_y = max(1, buffer_height.unwrap_or(0.).ceil() as u32);
+    pub fn can_redo(&self) -> bool {
+        self.history.state().can_redo
+    }
 
-            let mut pixmap = Pixmap::new(size_x, size_y).expect("failed to create pixmap");
-            self.base
-                .app()
-                .with_font_system_and_swash_cache(|font_system, swash_cache| {
-                    self.editor.draw(
-                        font_system,
-                        swash_cache,
-                        &EditorDrawStyle {
-                            text_color: convert_color(self.style.text_color),
-                            cursor_color: convert_color(self.style.text_color), // TODO: cursor color,
-                            selection_color: convert_color(self.style.selected_text_background),
-                            selected_text_color: convert_color(self.style.selected_text_color),
-                        },
-                        |x, y, w, h, c| {
-                            // let color = PremultipliedColorU8::from_rgba(
-                            //     min(c.a(), c.r()),
-                            //     min(c.a(), c.g()),
-                            //     min(c.a(), c.b()),
-                            //     c.a(),
-                            // )
-                            // .expect("RGB components must be <= alpha");
-
-                            // for iy in y..(y + h as i32) {
-                            //     for ix in x..(x + w as i32) {
-                            //         if ix >= 0 && ix < pixmap_width && iy >= 0 && iy < pixmap_height {
-                            //             pixels[(ix + iy * pixmap_width) as usize] = color;
-                            //         }
-                            //     }
-                            // }
-
-                            let color = Color::from_rgba8(c.r(), c.g(), c.b(), c.a());
-                            let paint = Paint {
-                                shader: Shader::SolidColor(color),
-                                anti_alias: false,
-                                ..Paint::default()
-                            };
-                            pixmap.fill_rect(
-                                tiny_skia::Rect::from_xywh(x as f32, y as f32, w as f32, h as f32)
-                                    .unwrap(),
-                                &paint,
-                                Transform::default(),
-                                None,
-                            );
-                        },
-                    );
-                });
-            let mut alg = LineGenerator::new(LineType::Underline);
-            let mut lines = Vec::new();
-            let line_height = self
-                .editor
-                .with_buffer(|buffer| buffer.metrics().line_height);
-            // TODO: determine from glyph width?
-            let stroke_width = 1.0;
-            self.editor.with_buffer(|buffer| {
-                for run in buffer.layout_runs() {
-                    let underline_space = line_height - run.line_y;
-                    let line_y = run.line_top + underline_space / 2.0;
-                    let line_y = (line_y + stroke_width / 2.0).round() - stroke_width / 2.0;
-                    for glyph in run.glyphs {
-                        if Metadata(glyph.metadata).is_preedit() {
-                            let color = glyph
-                                .color_opt
-                                .unwrap_or(convert_color(self.style.text_color));
-                            let glyph = line_straddler::Glyph {
-                                line_y,
-                                font_size: glyph.font_size,
-                                width: glyph.w,
-                                x: glyph.x,
-                                style: GlyphStyle {
-                                    boldness: 1,
-                                    color: line_straddler::Color::rgba(
-                                        color.r(),
-                                        color.g(),
-                                        color.b(),
-                                        color.a(),
-                                    ),
-                                },
-                            };
-                            lines.extend(alg.add_glyph(glyph));
-                        }
-                    }
-                }
-            });
-            lines.extend(alg.pop_line());
-            for line in lines {
-                let mut path = PathBuilder::new();
-                path.move_to(line.start_x, line.y);
-                path.line_to(line.end_x, line.y);
-                pixmap.stroke_path(
-                    &path.finish().unwrap(),
-                    &Paint {
-                        shader: Shader::SolidColor(tiny_skia::Color::from_rgba8(
-                            line.style.color.red(),
-                            line.style.color.green(),
-                            line.style.color.blue(),
-                            line.style.color.alpha(),
-                        )),
-                        ..Paint::default()
-                    },
-                    &Stroke {
-                        width: stroke_width,
-                        ..Stroke::default()
-                    },
-                    Transform::default(),
-                    None,
-                );
-            }
-            self.pixmap = Some(pixmap);
-            self.editor.set_redraw(false);
-        }
-        self.pixmap.as_ref().expect("created above")
+    /// Adds a callback that is called when availability of undo or redo changes.
+    pub fn on_history_changed(&mut self, callback: Callback<EditHistoryState>) -> &mut Self {
+        self.on_history_changed.add(callback);
+        self
     }
 
-    pub fn cursor_position(&self) -> Option<Point> {
-        self.editor
-            .cursor_position()
-            .map(|(x, y)| Point::new(PhysicalPixels::from_i32(x), PhysicalPixels::from_i32(y)))
+    fn apply_undo(&mut self, from_setter: bool) {
+        if self.is_read_only && !from_setter {
+            return;
+        }
+        self.interrupt_preedit();
+        let old_state = self.history.state();
+        let Some(entry) = self.history.undo() else {
+            return;
+        };
+        let mut change = entry.change;
+        change.reverse();
+        let attrs = entry.attrs.into_iter().rev().collect_vec();
+        let formatting = entry
+            .formatting
+            .map(|formatting| (formatting.first_line, formatting.before));
+        self.apply_history_change(&change, &attrs, formatting, entry.before, from_setter);
+        self.history_changed(old_state, from_setter);
     }
 
-    pub fn line_height(&self) -> f32 {
-        self.editor
-            .with_buffer(|buffer| buffer.metrics().line_height)
+    fn apply_redo(&mut self, from_setter: bool) {
+        if self.is_read_only && !from_setter {
+            return;
+        }
+        self.interrupt_preedit();
+        let old_state = self.history.state();
+        let Some(entry) = self.history.redo() else {
+            return;
+        };
+        let formatting = entry
+            .formatting
+            .map(|formatting| (formatting.first_line, formatting.after));
+        self.apply_history_change(
+            &entry.change,
+            &entry.attrs,
+            formatting,
+            entry.after,
+            from_setter,
+        );
+        self.history_changed(old_state, from_setter);
     }
 
-    pub fn action(&mut self, mut action: Action) {
-        if let Action::Drag { .. } = &mut action {
-            if self.forbid_mouse_interaction {
-                return;
-            }
+    // `attrs` contains the attributes of the text of each change item.
+    // `formatting` contains the index of the first line and the formatting of lines
+    // that must be restored.
+    fn apply_history_change(
+        &mut self,
+        change: &Change,
+        attrs: &[AttrsList],
+        formatting: Option<(usize, Vec<AttrsList>)>,
+        cursor_state: CursorState,
+        from_setter: bool,
+    ) {
+        self.editor.apply_change_with_attrs(change, attrs);
+        if let Some((first_line, attrs_lists)) = formatting {
+            self.set_lines_attrs(first_line, &attrs_lists);
         }
-        self.base
-            .app()
-            .with_font_system(|font_system| self.editor.action(font_system, action));
+        self.editor.set_cursor(cursor_state.cursor);
+        self.editor.set_selection(cursor_state.selection);
+        self.editor.set_redraw(true);
         self.adjust_size();
         self.base.size_hint_changed();
+        self.after_change();
+        if !change.items.is_empty() {
+            self.text_changed(from_setter);
+        }
+        self.check_cursor_changes(from_setter);
         self.base.update();
         self.request_scroll();
     }
@@ -1080,7 +2961,7 @@ impl TextHandler {
                 cursor: None,
                 attrs: None,
             });
-            self.insert_string(&text, None);
+            self.insert_string_as(&text, None, EditKind::Typing);
             if let Some(window) = self.base.window() {
                 window.cancel_ime_preedit();
             } else {
@@ -1097,8 +2978,25 @@ impl TextHandler {
         })
     }
 
+    fn ellipsis_width(&self) -> PhysicalPixels {
+        let metrics = self.editor.with_buffer(|buffer| buffer.metrics());
+        let line = BufferLine::new(
+            ELLIPSIS,
+            LineEnding::None,
+            AttrsList::new(&self.attrs.as_attrs()),
+            Shaping::Advanced,
+        );
+        let width = self.base.app().with_font_system(|font_system| {
+            let mut buffer = Buffer::new(font_system, metrics);
+            buffer.set_wrap(font_system, Wrap::None);
+            buffer.set_size(font_system, Some(MEASURE_MAX_SIZE), Some(MEASURE_MAX_SIZE));
+            line_width(&mut buffer, font_system, line)
+        });
+        PhysicalPixels::from_i32(width.ceil() as i32)
+    }
+
     fn unrestricted_text_size(&mut self, width: Option<PhysicalPixels>) -> Size {
-        let new_size = self.base.app().with_font_system(|font_system| {
+        let mut new_size = self.base.app().with_font_system(|font_system| {
             self.editor.with_buffer_mut(|buffer| {
                 unrestricted_text_size(
                     &mut buffer.borrow_with(font_system),
@@ -1106,6 +3004,21 @@ impl TextHandler {
                 )
             })
         });
+        if self.is_placeholder_shown {
+            let placeholder_size = self.base.app().with_font_system(|font_system| {
+                let buffer = self.placeholder_buffer.get_or_insert_with(|| {
+                    placeholder_buffer(font_system, &self.style, &self.attrs, &self.placeholder)
+                });
+                unrestricted_text_size(
+                    &mut buffer.borrow_with(font_system),
+                    width.map(|size| size.to_i32() as f32),
+                )
+            });
+            new_size = Size::new(
+                max(new_size.x(), placeholder_size.x()),
+                max(new_size.y(), placeholder_size.y()),
+            );
+        }
         if self.is_editable {
             Size::new(max(new_size.x(), CURSOR_SIZE_X.ppx()), new_size.y())
         } else {
@@ -1159,7 +3072,7 @@ impl TextHandler {
     }
 
     fn handle_main_click(&mut self, event: MouseInputEvent) -> Result<()> {
-        if !self.is_editable {
+        if !self.is_editable || self.is_elided() {
             return Ok(());
         }
         let window = self.base.window_or_err()?;
@@ -1239,7 +3152,9 @@ impl TextHandler {
             *id_info = info;
         }
 
-        let role = if self.is_multiline {
+        let role = if self.echo_mode == EchoMode::Password {
+            Role::PasswordInput
+        } else if self.is_multiline {
             Role::TextInput
         } else {
             Role::MultilineTextInput
@@ -1247,12 +3162,20 @@ impl TextHandler {
         let mut node = accesskit::Node::new(role);
         // TODO: use label widget and `Node::set_labeled_by`
         //node.set_label("some input");
-        node.set_value(self.text());
-        if !self.is_editable {
+        if self.is_masked() {
+            node.set_value(password_text(&self.hidden_text));
+        } else {
+            node.set_value(self.text());
+        }
+        if !self.placeholder.is_empty() {
+            node.set_placeholder(self.placeholder.clone());
+        }
+        if !self.is_editable || self.is_read_only {
             node.set_read_only();
+        } else {
+            node.add_action(accesskit::Action::SetValue);
         }
         node.add_action(accesskit::Action::Click);
-        node.add_action(accesskit::Action::SetValue);
         node.set_text_selection(self.selection_accessibility_info()?);
         // println!("text handler id {:?}", self.base.id());
         // println!("text handler host id {:?}", self.host_id);
@@ -1273,8 +3196,38 @@ impl Widget for TextHandler {
     }
 
     fn handle_mouse_input(&mut self, event: MouseInputEvent) -> Result<bool> {
+        let mut is_link_clicked = false;
+        if self.are_links_interactive() && event.button == MouseButton::Left {
+            let link = self.link_at(event.pos);
+            match event.state {
+                ElementState::Pressed => {
+                    self.pressed_link = link;
+                    if let Some(index) = link {
+                        is_link_clicked = true;
+                        if !self.is_editable {
+                            self.set_focused_link(Some(index));
+                            if let (Some(host_id), Some(window)) =
+                                (self.host_id, self.base.window())
+                            {
+                                self.base
+                                    .app()
+                                    .set_focus(window.id(), host_id, FocusReason::Mouse);
+                            }
+                        }
+                    }
+                }
+                ElementState::Released => {
+                    if let Some(index) = self.pressed_link.take() {
+                        is_link_clicked = true;
+                        if link == Some(index) {
+                            self.activate_link(index);
+                        }
+                    }
+                }
+            }
+        }
         if !self.is_editable {
-            return Ok(false);
+            return Ok(is_link_clicked);
         }
         if event.state == ElementState::Pressed {
             match event.button {
@@ -1308,7 +3261,6 @@ impl Widget for TextHandler {
         if is_released {
             self.forbid_mouse_interaction = false;
         }
-        // TODO: notify parent?
         //self.adjust_scroll();
         self.reset_blink_timer();
         self.request_scroll();
@@ -1317,11 +3269,21 @@ impl Widget for TextHandler {
     }
 
     fn handle_mouse_move(&mut self, event: MouseMoveEvent) -> Result<bool> {
+        if self.are_links_interactive() {
+            let cursor_icon = if self.link_at(event.pos).is_some() {
+                CursorIcon::Pointer
+            } else if self.is_editable {
+                CursorIcon::Text
+            } else {
+                CursorIcon::Default
+            };
+            self.base.set_cursor_icon(cursor_icon);
+        }
         if !self.is_editable {
             return Ok(false);
         }
         let window = self.base.window_or_err()?;
-        if window.is_mouse_button_pressed(MouseButton::Left) {
+        if window.is_mouse_button_pressed(MouseButton::Left) && !self.is_elided() {
             let old_selection = (self.select_opt(), self.editor.cursor());
             self.action(Action::Drag {
                 x: event.pos.x().to_i32(),
@@ -1329,7 +3291,6 @@ impl Widget for TextHandler {
             });
             let new_selection = (self.select_opt(), self.editor.cursor());
             if old_selection != new_selection {
-                // TODO: notify parent?
                 //self.adjust_scroll();
                 self.base.update();
                 self.request_scroll();
@@ -1340,6 +3301,9 @@ impl Widget for TextHandler {
 
     fn handle_draw(&mut self, event: DrawEvent) -> Result<()> {
         event.draw_pixmap(Point::default(), self.pixmap().as_ref(), Default::default());
+        if let Some(rect) = self.focused_link.and_then(|index| self.link_rect(index)) {
+            event.stroke_rect(rect, self.style.link_text_color);
+        }
         if self.is_editable && self.is_host_focused {
             if let Some(editor_cursor) = self.cursor_position() {
                 // We specify an area below the input because on Windows
@@ -1362,8 +3326,15 @@ impl Widget for TextHandler {
 
     fn handle_size_hint_x_request(&mut self, _size_y: Option<PhysicalPixels>) -> Result<SizeHint> {
         let size = self.unrestricted_text_size(None);
-        // TODO: calculate min size?
-        Ok(SizeHint::new_fixed(size.x(), size.x()))
+        let min_size = if self.is_word_wrap {
+            // Every word is placed on a separate line.
+            self.unrestricted_text_size(Some(1.ppx())).x()
+        } else if self.elide_mode != ElideMode::None {
+            min(self.ellipsis_width(), size.x())
+        } else {
+            size.x()
+        };
+        Ok(SizeHint::new_fixed(min_size, size.x()))
     }
 
     fn handle_size_hint_y_request(&mut self, size_x: PhysicalPixels) -> Result<SizeHint> {
@@ -1372,6 +3343,18 @@ impl Widget for TextHandler {
         Ok(SizeHint::new_fixed(size.y(), size.y()))
     }
 
+    fn handle_baseline_request(&mut self, size: Size) -> Result<Option<PhysicalPixels>> {
+        let baseline = self.base.app().with_font_system(|font_system| {
+            self.editor.with_buffer_mut(|buffer| {
+                first_baseline(
+                    &mut buffer.borrow_with(font_system),
+                    size.x().to_i32() as f32,
+                )
+            })
+        });
+        Ok(baseline)
+    }
+
     fn handle_layout(&mut self, _event: LayoutEvent) -> Result<()> {
         let Some(size) = self.base.size() else {
             return Ok(());
@@ -1392,8 +3375,12 @@ impl Widget for TextHandler {
                     )
                 });
             });
+            if self.old_size.x() != size.x() {
+                self.line_positions_changed(0);
+            }
             self.request_scroll();
             self.old_size = size;
+            self.update_elision();
         }
 
         Ok(())
@@ -1412,6 +3399,242 @@ impl Drop for TextHandler {
 
 const MEASURE_MAX_SIZE: f32 = 10_000.;
 
+fn placeholder_buffer(
+    font_system: &mut FontSystem,
+    style: &TextStyle,
+    attrs: &AttrsOwned,
+    text: &str,
+) -> Buffer {
+    let mut buffer = Buffer::new(font_system, style.font_metrics);
+    buffer.set_wrap(font_system, Wrap::None);
+    buffer.set_text(font_system, text, &attrs.as_attrs(), Shaping::Advanced);
+    buffer
+}
+
+// Returns byte ranges of the line text that have the same format, excluding the preedit.
+// Each item contains the range and the format index.
+fn format_runs(line: &BufferLine) -> Vec<(Range<usize>, usize)> {
+    let preedit = preedit_range(line);
+    let attrs_list = line.attrs_list();
+    let mut runs: Vec<(Range<usize>, usize)> = Vec::new();
+    for (index, c) in line.text().char_indices() {
+        if preedit.as_ref().is_some_and(|range| range.contains(&index)) {
+            continue;
+        }
+        let format_index = Metadata(attrs_list.get_span(index).metadata).format_index();
+        let end = index + c.len_utf8();
+        match runs.last_mut() {
+            Some((range, last_index)) if *last_index == format_index && range.end == index => {
+                range.end = end;
+            }
+            _ => runs.push((index..end, format_index)),
+        }
+    }
+    runs
+}
+
+// Returns the attributes of text that has the specified format.
+fn format_attrs(
+    defaults: &AttrsOwned,
+    style: &TextStyle,
+    scale: f32,
+    format: &TextFormat,
+    format_index: usize,
+) -> AttrsOwned {
+    let mut attrs = defaults.clone();
+    if let Some(weight) = format.weight {
+        attrs.weight = Weight(weight);
+    }
+    if let Some(slant) = format.slant {
+        attrs.style = match slant {
+            FontSlant::Normal => cosmic_text::Style::Normal,
+            FontSlant::Italic => cosmic_text::Style::Italic,
+            FontSlant::Oblique => cosmic_text::Style::Oblique,
+        };
+    }
+    attrs.color_opt = format
+        .color
+        .or_else(|| format.link.is_some().then_some(style.link_text_color))
+        .map(convert_color);
+    if let Some(font_size) = format.font_size {
+        let line_height_ratio = style.font.line_height.to_f32() / style.font.font_size.to_f32();
+        let metrics = cosmic_text::Metrics {
+            font_size: font_size.to_physical(scale).to_i32() as f32,
+            line_height: (font_size * line_height_ratio).to_physical(scale).to_i32() as f32,
+        };
+        attrs.metrics_opt = Some(metrics.into());
+    }
+    attrs.metadata = Metadata(attrs.metadata)
+        .with_format_index(format_index)
+        .into();
+    attrs
+}
+
+// Returns the width of the line when it's laid out without wrapping.
+fn line_width(buffer: &mut Buffer, font_system: &mut FontSystem, line: BufferLine) -> f32 {
+    buffer.lines = vec![line];
+    buffer.shape_until_scroll(font_system, false);
+    buffer
+        .layout_runs()
+        .map(|run| run.line_w)
+        .fold(0.0, f32::max)
+}
+
+// Shortens the line to fit into `width` by replacing a part of it with an ellipsis.
+// Returns the shortened line and the byte range of the original line that was replaced,
+// or `None` if the line fits.
+fn elide_line(
+    measure_buffer: &mut Buffer,
+    font_system: &mut FontSystem,
+    line: &BufferLine,
+    mode: ElideMode,
+    width: f32,
+) -> Option<(BufferLine, Range<usize>)> {
+    if mode == ElideMode::None || line_width(measure_buffer, font_system, line.clone()) <= width {
+        return None;
+    }
+    let text = line.text();
+    let boundaries = text
+        .grapheme_indices(true)
+        .map(|(index, _)| index)
+        .chain([text.len()])
+        .collect_vec();
+    let num_graphemes = boundaries.len() - 1;
+    // Returns the replaced range if `kept` graphemes are kept.
+    let replaced_range = |kept: usize| match mode {
+        ElideMode::None | ElideMode::End => boundaries[kept]..text.len(),
+        ElideMode::Start => 0..boundaries[num_graphemes - kept],
+        ElideMode::Middle => boundaries[kept.div_ceil(2)]..boundaries[num_graphemes - kept / 2],
+    };
+    // Find the largest number of kept graphemes that fits.
+    let mut low = 0;
+    let mut high = num_graphemes.saturating_sub(1);
+    while low < high {
+        let middle = (low + high).div_ceil(2);
+        let candidate = replace_with_ellipsis(line, replaced_range(middle));
+        if line_width(measure_buffer, font_system, candidate) <= width {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    let range = replaced_range(low);
+    Some((replace_with_ellipsis(line, range.clone()), range))
+}
+
+// Returns a copy of the line with the specified byte range replaced by an ellipsis.
+// The ellipsis has the attributes of the first replaced character.
+fn replace_with_ellipsis(line: &BufferLine, range: Range<usize>) -> BufferLine {
+    let mut output = line.clone();
+    let mut replaced = output.split_off(range.start);
+    let suffix = replaced.split_off(range.len());
+    let ellipsis = BufferLine::new(
+        ELLIPSIS,
+        LineEnding::None,
+        AttrsList::new(&line.attrs_list().get_span(range.start)),
+        Shaping::Advanced,
+    );
+    output.append(ellipsis);
+    output.append(suffix);
+    output
+}
+
+// A mark displayed in place of a whitespace character when whitespace is visible.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WhitespaceMark {
+    // A dot in the middle of a space.
+    Dot(tiny_skia::Rect),
+    // A horizontal arrow spanning a tab.
+    Arrow {
+        start_x: f32,
+        end_x: f32,
+        y: f32,
+        head: f32,
+    },
+}
+
+fn whitespace_marks(buffer: &Buffer) -> Vec<WhitespaceMark> {
+    let mut marks = Vec::new();
+    for run in buffer.layout_runs() {
+        let center_y = (run.line_top + run.line_height / 2.0).round();
+        for glyph in run.glyphs {
+            let Some(text) = run.text.get(glyph.start..glyph.end) else {
+                continue;
+            };
+            let size = (glyph.font_size / 8.0).round().max(1.0);
+            if text == " " {
+                let x = (glyph.x + (glyph.w - size) / 2.0).round();
+                marks.extend(
+                    tiny_skia::Rect::from_xywh(x, center_y - (size / 2.0).floor(), size, size)
+                        .map(WhitespaceMark::Dot),
+                );
+            } else if text == "\t" {
+                let head = size * 3.0;
+                let start_x = glyph.x + size;
+                let end_x = glyph.x + glyph.w - size;
+                // Tabs that are too narrow for an arrow are not marked.
+                if end_x - start_x >= head {
+                    marks.push(WhitespaceMark::Arrow {
+                        start_x,
+                        end_x,
+                        y: center_y + 0.5,
+                        head,
+                    });
+                }
+            }
+        }
+    }
+    marks
+}
+
+fn password_text(text: &str) -> String {
+    iter::repeat_n(PASSWORD_CHAR, text.chars().count()).collect()
+}
+
+// Applies a change of the buffer containing `PASSWORD_CHAR`s to the actual text.
+// `inserted_text` is the actual text of the inserted item.
+fn apply_change_to_hidden_text(
+    hidden_text: &mut String,
+    change: &Change,
+    mut inserted_text: Option<&str>,
+) {
+    for item in &change.items {
+        let start = hidden_text_offset(hidden_text, item.start);
+        let len = item.text.chars().count();
+        if item.insert {
+            let text = inserted_text
+                .take()
+                .filter(|text| text.chars().count() == len)
+                .unwrap_or(&item.text);
+            hidden_text.insert_str(start, text);
+        } else {
+            let end = start + byte_index(&hidden_text[start..], len);
+            hidden_text.replace_range(start..end, "");
+        }
+    }
+}
+
+// Converts a cursor in the buffer containing `PASSWORD_CHAR`s to a byte offset in the actual text.
+fn hidden_text_offset(hidden_text: &str, cursor: Cursor) -> usize {
+    byte_index(hidden_text, cursor.index / PASSWORD_CHAR.len_utf8())
+}
+
+// Converts a byte offset in the actual text to a cursor in the buffer containing `PASSWORD_CHAR`s.
+fn masked_cursor(hidden_text: &str, offset: usize) -> Cursor {
+    let chars = hidden_text[..min(offset, hidden_text.len())]
+        .chars()
+        .count();
+    Cursor::new(0, chars * PASSWORD_CHAR.len_utf8())
+}
+
+// Returns the byte index of the character with the specified index,
+// or the length of the text if there are fewer characters.
+fn byte_index(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map_or(text.len(), |(index, _)| index)
+}
+
 fn unrestricted_text_size(
     buffer: &mut BorrowedWithFontSystem<'_, Buffer>,
     width: Option<f32>,
@@ -1441,7 +3664,136 @@ fn unrestricted_text_size(
     Size::new(width, height)
 }
 
-fn convert_color(color: Color) -> cosmic_text::Color {
+fn first_baseline(
+    buffer: &mut BorrowedWithFontSystem<'_, Buffer>,
+    width: f32,
+) -> Option<PhysicalPixels> {
+    let old_size = buffer.size();
+    buffer.set_size(Some(width), Some(MEASURE_MAX_SIZE));
+    buffer.set_scroll(cosmic_text::Scroll::default());
+    buffer.shape_until_scroll(false);
+    let baseline = buffer
+        .layout_runs()
+        .next()
+        .map(|layout_run| PhysicalPixels::from_i32(layout_run.line_y.round() as i32));
+    buffer.set_scroll(cosmic_text::Scroll::default());
+    buffer.set_size(old_size.0, old_size.1);
+    baseline
+}
+
+pub(crate) fn convert_color(color: Color) -> cosmic_text::Color {
     let c = color.to_color_u8();
     cosmic_text::Color::rgba(c.red(), c.green(), c.blue(), c.alpha())
 }
+
+#[test]
+fn hidden_text_changes() {
+    use cosmic_text::ChangeItem;
+
+    // Cursors in the masked buffer count bytes of `PASSWORD_CHAR`s.
+    let masked = |chars: usize| Cursor::new(0, chars * PASSWORD_CHAR.len_utf8());
+    let item = |start: usize, len: usize, insert: bool| ChangeItem {
+        start: masked(start),
+        end: masked(start + len),
+        text: password_text(&"x".repeat(len)),
+        insert,
+    };
+
+    let mut text = "пароль".to_string();
+    assert_eq!(hidden_text_offset(&text, masked(2)), "па".len());
+    assert_eq!(masked_cursor(&text, "па".len()), masked(2));
+
+    // Typing multibyte characters in the middle.
+    let change = Change {
+        items: vec![item(2, 2, true)],
+    };
+    apply_change_to_hidden_text(&mut text, &change, Some("ёж"));
+    assert_eq!(text, "паёжроль");
+    let cursor = change.items[0].end;
+    assert_eq!(hidden_text_offset(&text, cursor), "паёж".len());
+    assert_eq!(masked_cursor(&text, "паёж".len()), cursor);
+
+    // Deleting a range of characters.
+    let change = Change {
+        items: vec![item(1, 3, false)],
+    };
+    apply_change_to_hidden_text(&mut text, &change, None);
+    assert_eq!(text, "проль");
+    assert_eq!(hidden_text_offset(&text, change.items[0].start), "п".len());
+
+    // Replacing the selection deletes it and inserts the new text.
+    let change = Change {
+        items: vec![item(0, 2, false), item(0, 1, true)],
+    };
+    apply_change_to_hidden_text(&mut text, &change, Some("🔑"));
+    assert_eq!(text, "🔑оль");
+    assert_eq!(masked_cursor(&text, "🔑".len()), masked(1));
+
+    // Offsets past the end are clamped.
+    assert_eq!(hidden_text_offset(&text, masked(10)), text.len());
+    assert_eq!(masked_cursor(&text, 100), masked(4));
+}
+
+#[test]
+fn visible_whitespace() {
+    use cosmic_text::{fontdb, Family, Metrics};
+
+    let mut db = fontdb::Database::new();
+    db.load_font_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tester/assets/fonts/NotoSans-Regular.ttf"
+    ))
+    .unwrap();
+    let mut font_system = FontSystem::new_with_locale_and_db("en-US".into(), db);
+    let mut marks = |tab_width: u16| {
+        let mut buffer = Buffer::new(&mut font_system, Metrics::new(16.0, 20.0));
+        let mut buffer = buffer.borrow_with(&mut font_system);
+        buffer.set_tab_width(tab_width);
+        buffer.set_text(
+            "a b\tc\n\t",
+            &Attrs::new().family(Family::Name("Noto Sans")),
+            Shaping::Advanced,
+        );
+        buffer.shape_until_scroll(false);
+        whitespace_marks(&buffer)
+    };
+
+    let narrow = marks(4);
+    let [WhitespaceMark::Dot(dot), WhitespaceMark::Arrow {
+        start_x,
+        end_x,
+        y,
+        head,
+    }, WhitespaceMark::Arrow {
+        start_x: second_start_x,
+        end_x: second_end_x,
+        y: second_y,
+        ..
+    }] = narrow[..]
+    else {
+        panic!("unexpected marks: {narrow:?}");
+    };
+    // The dot is a square in the middle of the space on the first line.
+    assert_eq!(dot.width(), 2.0);
+    assert_eq!(dot.height(), 2.0);
+    assert_eq!(dot.top() + 1.0, 10.0);
+    assert!(start_x > dot.right());
+    // Arrows are drawn on the center lines and leave a gap at both ends of the tab.
+    assert_eq!((y, second_y), (10.5, 30.5));
+    assert_eq!(head, 6.0);
+    assert_eq!(second_start_x, 2.0);
+    // The tab on the first line extends to the second tab stop.
+    let tab_stop = second_end_x + 2.0;
+    assert!((end_x + 2.0 - tab_stop * 2.0).abs() < 0.01);
+
+    // Tab stops are twice as far apart with the doubled tab width.
+    let wide = marks(8);
+    let [_, _, WhitespaceMark::Arrow {
+        end_x: wide_end_x, ..
+    }] = wide[..]
+    else {
+        panic!("unexpected marks: {wide:?}");
+    };
+    assert_eq!(wide[..2], narrow[..2]);
+    assert!((wide_end_x + 2.0 - tab_stop * 2.0).abs() < 0.01);
+}
@@ -91,6 +91,10 @@ impl PhysicalPixels {
         self.0
     }
 
+    pub const fn abs(self) -> Self {
+        Self(self.0.abs())
+    }
+
     pub fn mul_f32_round(self, scale: f32) -> Self {
         Self(((self.0 as f32) * scale).round() as i32)
     }
@@ -289,6 +293,21 @@ impl Rect {
         }
     }
 
+    /// Returns a rect that is larger by `amount` on each side.
+    #[must_use]
+    pub fn expand(&self, amount: PhysicalPixels) -> Self {
+        Self {
+            top_left: Point {
+                x: self.top_left.x - amount,
+                y: self.top_left.y - amount,
+            },
+            size: Size {
+                x: max(self.size.x + amount * 2, 0.ppx()),
+                y: max(self.size.y + amount * 2, 0.ppx()),
+            },
+        }
+    }
+
     // TODO: naming with "x" and "y" for all methods?
 
     pub fn top_left(&self) -> Point {
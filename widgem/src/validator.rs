@@ -0,0 +1,616 @@
+//! Validation of text entered in a [TextInput](crate::widgets::TextInput).
+
+use {
+    anyhow::{bail, Context as _, Result},
+    regex_automata::{
+        hybrid::dfa::{Cache, DFA},
+        Anchored, Input, MatchKind,
+    },
+    std::{cell::RefCell, fmt::Debug, str::FromStr},
+    tracing::warn,
+};
+
+/// Result of validating a text.
+///
+/// The variants are ordered from the worst to the best, so the combined state of
+/// multiple checks is the minimum of their states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ValidationState {
+    /// The text is invalid and can't become valid by adding more characters.
+    /// The input rejects edits that would produce an invalid text.
+    Invalid,
+    /// The text is not valid yet, but it's a plausible intermediate value
+    /// (e.g. an empty string or a `-` sign in a number input).
+    Intermediate,
+    /// The text is valid.
+    Acceptable,
+}
+
+/// Checks text entered in an input.
+pub trait Validator: Debug {
+    fn validate(&self, text: &str) -> ValidationState;
+}
+
+/// Accepts integers within the specified range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntValidator {
+    min: i64,
+    max: i64,
+}
+
+impl IntValidator {
+    pub fn new(min: i64, max: i64) -> Self {
+        Self { min, max }
+    }
+
+    pub fn min(&self) -> i64 {
+        self.min
+    }
+
+    pub fn max(&self) -> i64 {
+        self.max
+    }
+}
+
+impl Default for IntValidator {
+    fn default() -> Self {
+        Self::new(i64::MIN, i64::MAX)
+    }
+}
+
+impl Validator for IntValidator {
+    fn validate(&self, text: &str) -> ValidationState {
+        let Some((is_negative, digits)) = split_sign(text, self.min < 0) else {
+            return ValidationState::Invalid;
+        };
+        if digits.is_empty() {
+            return ValidationState::Intermediate;
+        }
+        if !digits.bytes().all(|c| c.is_ascii_digit()) {
+            return ValidationState::Invalid;
+        }
+        let Ok(value) = text.parse::<i64>() else {
+            return ValidationState::Invalid;
+        };
+        if (self.min..=self.max).contains(&value) {
+            return ValidationState::Acceptable;
+        }
+        // Adding more digits increases the absolute value, so the text
+        // can only become valid if it's closer to zero than the range.
+        let bound = if is_negative { self.min } else { self.max };
+        let can_grow = if is_negative {
+            value > self.max
+        } else {
+            value < self.min
+        };
+        if can_grow && digits.len() < bound.unsigned_abs().to_string().len() {
+            ValidationState::Intermediate
+        } else {
+            ValidationState::Invalid
+        }
+    }
+}
+
+/// Accepts decimal numbers within the specified range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DoubleValidator {
+    min: f64,
+    max: f64,
+    decimals: Option<usize>,
+}
+
+impl DoubleValidator {
+    pub fn new(min: f64, max: f64) -> Self {
+        Self {
+            min,
+            max,
+            decimals: None,
+        }
+    }
+
+    pub fn min(&self) -> f64 {
+        self.min
+    }
+
+    pub fn max(&self) -> f64 {
+        self.max
+    }
+
+    /// Limits the number of digits after the decimal point.
+    pub fn with_decimals(mut self, decimals: Option<usize>) -> Self {
+        self.decimals = decimals;
+        self
+    }
+
+    pub fn decimals(&self) -> Option<usize> {
+        self.decimals
+    }
+}
+
+impl Default for DoubleValidator {
+    fn default() -> Self {
+        Self::new(f64::MIN, f64::MAX)
+    }
+}
+
+impl Validator for DoubleValidator {
+    fn validate(&self, text: &str) -> ValidationState {
+        let Some((is_negative, number)) = split_sign(text, self.min < 0.0) else {
+            return ValidationState::Invalid;
+        };
+        let (integer, fraction) = match number.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (number, None),
+        };
+        let is_digits = |s: &str| s.bytes().all(|c| c.is_ascii_digit());
+        if !is_digits(integer) || !fraction.is_none_or(is_digits) {
+            return ValidationState::Invalid;
+        }
+        if let (Some(decimals), Some(fraction)) = (self.decimals, fraction) {
+            if fraction.len() > decimals {
+                return ValidationState::Invalid;
+            }
+        }
+        if integer.is_empty() && fraction.is_none_or(str::is_empty) {
+            return ValidationState::Intermediate;
+        }
+        let Ok(value) = text.parse::<f64>() else {
+            return ValidationState::Invalid;
+        };
+        if value >= self.min && value <= self.max {
+            return ValidationState::Acceptable;
+        }
+        // Adding more characters increases the absolute value, so compare it with the range
+        // of absolute values of the same sign.
+        let (min, max) = if is_negative {
+            (-self.max, -self.min)
+        } else {
+            (self.min, self.max)
+        };
+        if can_reach(value.abs(), fraction, self.decimals, min, max) {
+            ValidationState::Intermediate
+        } else {
+            ValidationState::Invalid
+        }
+    }
+}
+
+// Returns true if appending digits to a non-negative number can produce a value within
+// `min..=max`. `fraction` is the part after the decimal point, if there is one.
+fn can_reach(
+    value: f64,
+    fraction: Option<&str>,
+    decimals: Option<usize>,
+    min: f64,
+    max: f64,
+) -> bool {
+    // True if `start..end` and `min..=max` intersect.
+    let intersects = |start: f64, end: f64| start <= max && end > min;
+    if let Some(fraction) = fraction {
+        let can_add_decimals = decimals.is_none_or(|decimals| fraction.len() < decimals);
+        return can_add_decimals && intersects(value, value + 10f64.powi(-(fraction.len() as i32)));
+    }
+    if decimals != Some(0) && intersects(value, value + 1.0) {
+        return true;
+    }
+    let mut scale = 10.0;
+    while value * scale <= max && scale.is_finite() {
+        if intersects(value * scale, (value + 1.0) * scale) {
+            return true;
+        }
+        scale *= 10.0;
+    }
+    false
+}
+
+// Returns the sign and the rest of the number, or `None` if the sign is not allowed.
+fn split_sign(text: &str, allow_negative: bool) -> Option<(bool, &str)> {
+    if let Some(rest) = text.strip_prefix('-') {
+        allow_negative.then_some((true, rest))
+    } else {
+        Some((false, text.strip_prefix('+').unwrap_or(text)))
+    }
+}
+
+/// Accepts text that fully matches a regular expression.
+///
+/// Text that is a prefix of a possible match is considered intermediate.
+#[derive(Debug)]
+pub struct RegexValidator {
+    pattern: String,
+    dfa: DFA,
+    cache: RefCell<Cache>,
+}
+
+impl RegexValidator {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let dfa = DFA::builder()
+            .configure(
+                DFA::config()
+                    .match_kind(MatchKind::All)
+                    .unicode_word_boundary(true),
+            )
+            .build(&format!(r"(?:{pattern})\z"))
+            .with_context(|| format!("invalid regex: {pattern:?}"))?;
+        Ok(Self {
+            pattern: pattern.into(),
+            cache: RefCell::new(dfa.create_cache()),
+            dfa,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    fn try_validate(&self, text: &str) -> Result<ValidationState> {
+        let cache = &mut *self.cache.borrow_mut();
+        let mut state = self
+            .dfa
+            .start_state_forward(cache, &Input::new(text).anchored(Anchored::Yes))?;
+        for byte in text.bytes() {
+            state = self.dfa.next_state(cache, state, byte)?;
+            if state.is_dead() {
+                return Ok(ValidationState::Invalid);
+            }
+            if state.is_quit() {
+                bail!("regex search stopped at a non-ASCII character");
+            }
+        }
+        state = self.dfa.next_eoi_state(cache, state)?;
+        Ok(if state.is_match() {
+            ValidationState::Acceptable
+        } else {
+            ValidationState::Intermediate
+        })
+    }
+}
+
+impl Validator for RegexValidator {
+    fn validate(&self, text: &str) -> ValidationState {
+        self.try_validate(text).unwrap_or_else(|err| {
+            warn!(
+                "failed to validate text with regex {:?}: {err:?}",
+                self.pattern
+            );
+            ValidationState::Intermediate
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MaskItem {
+    Literal(char),
+    Char { class: CharClass, required: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Digit,
+    Letter,
+    Alphanumeric,
+    Any,
+}
+
+impl CharClass {
+    fn matches(self, c: char) -> bool {
+        match self {
+            CharClass::Digit => c.is_ascii_digit(),
+            CharClass::Letter => c.is_alphabetic(),
+            CharClass::Alphanumeric => c.is_alphanumeric(),
+            CharClass::Any => !c.is_whitespace(),
+        }
+    }
+}
+
+/// Fixed format of an input, e.g. `"99/99/9999"` for a date.
+///
+/// Each character of the text corresponds to a character of the mask, except for
+/// optional characters that can be omitted:
+///
+/// - `9`: a digit (required);
+/// - `0`: a digit (optional);
+/// - `A`: a letter (required);
+/// - `a`: a letter (optional);
+/// - `N`: a letter or a digit (required);
+/// - `n`: a letter or a digit (optional);
+/// - `X`: any non-blank character (required);
+/// - `x`: any non-blank character (optional);
+/// - `\`: makes the next character a literal;
+/// - any other character is a literal that is inserted automatically while typing.
+///
+/// The text is acceptable if all required characters are present.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputMask {
+    mask: String,
+    items: Vec<MaskItem>,
+}
+
+impl InputMask {
+    pub fn new(mask: &str) -> Result<Self> {
+        let mut items = Vec::new();
+        let mut chars = mask.chars();
+        while let Some(c) = chars.next() {
+            let (class, required) = match c {
+                '9' => (CharClass::Digit, true),
+                '0' => (CharClass::Digit, false),
+                'A' => (CharClass::Letter, true),
+                'a' => (CharClass::Letter, false),
+                'N' => (CharClass::Alphanumeric, true),
+                'n' => (CharClass::Alphanumeric, false),
+                'X' => (CharClass::Any, true),
+                'x' => (CharClass::Any, false),
+                '\\' => {
+                    let Some(literal) = chars.next() else {
+                        bail!("input mask ends with an escape character: {mask:?}");
+                    };
+                    items.push(MaskItem::Literal(literal));
+                    continue;
+                }
+                _ => {
+                    items.push(MaskItem::Literal(c));
+                    continue;
+                }
+            };
+            items.push(MaskItem::Char { class, required });
+        }
+        Ok(Self {
+            mask: mask.into(),
+            items,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.mask
+    }
+
+    /// Adds literals to `text` that is about to be inserted after `before`,
+    /// so that typing `12122000` with the mask `99/99/9999` produces `12/12/2000`.
+    /// Literals at the end of the mask are added once the last editable character is typed.
+    pub(crate) fn expand_insertion(&self, before: &str, text: &str) -> String {
+        let mut output = String::new();
+        let mut states = self.run(before);
+        for c in text.chars() {
+            while !states.iter().any(|&state| self.accepts(state, c)) {
+                let Some(literal) = states
+                    .iter()
+                    .find_map(|&state| match self.items.get(state) {
+                        Some(MaskItem::Literal(literal)) => Some(*literal),
+                        _ => None,
+                    })
+                else {
+                    break;
+                };
+                output.push(literal);
+                states = self.step(&states, literal);
+            }
+            output.push(c);
+            states = self.step(&states, c);
+        }
+        // Complete the text if only literals are left.
+        if !output.is_empty() {
+            while let [state] = states[..] {
+                let Some(MaskItem::Literal(literal)) = self.items.get(state) else {
+                    break;
+                };
+                output.push(*literal);
+                states = self.step(&states, *literal);
+            }
+        }
+        output
+    }
+
+    // The mask is matched as a nondeterministic automaton: a state is the index of
+    // the next mask item, and optional items can be skipped. This allows text like `ab-9`
+    // to match `AA-00\9`, which is impossible if characters are matched by position.
+
+    fn accepts(&self, state: usize, c: char) -> bool {
+        match self.items.get(state) {
+            None => false,
+            Some(MaskItem::Literal(literal)) => *literal == c,
+            Some(MaskItem::Char { class, .. }) => class.matches(c),
+        }
+    }
+
+    // Adds states reachable by skipping optional items. Returns sorted unique states.
+    fn with_skipped(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < states.len() {
+            let state = states[i];
+            if let Some(MaskItem::Char {
+                required: false, ..
+            }) = self.items.get(state)
+            {
+                if !states.contains(&(state + 1)) {
+                    states.push(state + 1);
+                }
+            }
+            i += 1;
+        }
+        states.sort_unstable();
+        states
+    }
+
+    fn step(&self, states: &[usize], c: char) -> Vec<usize> {
+        let next = states
+            .iter()
+            .filter(|&&state| self.accepts(state, c))
+            .map(|state| state + 1)
+            .collect();
+        self.with_skipped(next)
+    }
+
+    // Returns states after matching `text`. The result is empty if `text` doesn't match.
+    fn run(&self, text: &str) -> Vec<usize> {
+        text.chars().fold(self.with_skipped(vec![0]), |states, c| {
+            self.step(&states, c)
+        })
+    }
+}
+
+impl FromStr for InputMask {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl Validator for InputMask {
+    fn validate(&self, text: &str) -> ValidationState {
+        let states = self.run(text);
+        if states.is_empty() {
+            ValidationState::Invalid
+        } else if states.contains(&self.items.len()) {
+            ValidationState::Acceptable
+        } else {
+            ValidationState::Intermediate
+        }
+    }
+}
+
+#[test]
+fn int_validator() {
+    use ValidationState::*;
+
+    let validator = IntValidator::new(-50, 1200);
+    for (text, expected) in [
+        ("", Intermediate),
+        ("-", Intermediate),
+        ("+", Intermediate),
+        ("0", Acceptable),
+        ("1200", Acceptable),
+        ("+12", Acceptable),
+        ("-50", Acceptable),
+        ("-51", Invalid),
+        ("1201", Invalid),
+        ("12000", Invalid),
+        ("1a", Invalid),
+        ("1.5", Invalid),
+    ] {
+        assert_eq!(validator.validate(text), expected, "text: {text:?}");
+    }
+
+    let validator = IntValidator::new(100, 999);
+    assert_eq!(validator.validate("5"), Intermediate);
+    assert_eq!(validator.validate("50"), Intermediate);
+    assert_eq!(validator.validate("500"), Acceptable);
+    assert_eq!(validator.validate("5000"), Invalid);
+    assert_eq!(validator.validate("-5"), Invalid);
+}
+
+#[test]
+fn double_validator() {
+    use ValidationState::*;
+
+    let validator = DoubleValidator::new(-10.0, 100.0).with_decimals(Some(2));
+    for (text, expected) in [
+        ("", Intermediate),
+        ("-", Intermediate),
+        (".", Intermediate),
+        ("-.", Intermediate),
+        ("1.", Acceptable),
+        (".5", Acceptable),
+        ("99.99", Acceptable),
+        ("-10", Acceptable),
+        ("1.234", Invalid),
+        ("1..2", Invalid),
+        ("1e5", Invalid),
+        ("-20", Invalid),
+        ("-200", Invalid),
+        ("1000", Invalid),
+        ("-0.5", Acceptable),
+    ] {
+        assert_eq!(validator.validate(text), expected, "text: {text:?}");
+    }
+
+    let validator = DoubleValidator::new(0.0, 1.0);
+    assert_eq!(validator.validate("-0.5"), Invalid);
+    assert_eq!(validator.validate("0.123456"), Acceptable);
+
+    // Text out of the range is intermediate only if more characters can make it valid.
+    let validator = DoubleValidator::new(100.0, 999.0);
+    assert_eq!(validator.validate("5"), Intermediate);
+    assert_eq!(validator.validate("50"), Intermediate);
+    assert_eq!(validator.validate("5."), Invalid);
+    assert_eq!(validator.validate("5000"), Invalid);
+    let validator = DoubleValidator::new(0.5, 0.75).with_decimals(Some(2));
+    assert_eq!(validator.validate("0"), Intermediate);
+    assert_eq!(validator.validate("0."), Intermediate);
+    assert_eq!(validator.validate("0.7"), Acceptable);
+    assert_eq!(validator.validate("0.4"), Invalid);
+    assert_eq!(validator.validate("0.8"), Invalid);
+    assert_eq!(validator.validate("0.76"), Invalid);
+    assert_eq!(validator.validate("1"), Invalid);
+    let validator = DoubleValidator::new(-20.0, -10.0);
+    assert_eq!(validator.validate("-1"), Intermediate);
+    assert_eq!(validator.validate("-5"), Invalid);
+    assert_eq!(validator.validate("5"), Invalid);
+}
+
+#[test]
+fn regex_validator() {
+    use ValidationState::*;
+
+    let validator = RegexValidator::new(r"[a-z]+@[a-z]+\.(com|org)").unwrap();
+    for (text, expected) in [
+        ("", Intermediate),
+        ("user", Intermediate),
+        ("user@", Intermediate),
+        ("user@example.c", Intermediate),
+        ("user@example.com", Acceptable),
+        ("user@example.org", Acceptable),
+        ("user@example.net", Invalid),
+        ("user@example.com.", Invalid),
+        ("User", Invalid),
+        ("@", Invalid),
+    ] {
+        assert_eq!(validator.validate(text), expected, "text: {text:?}");
+    }
+
+    // The whole text must match, and prefixes of longer matches are intermediate.
+    let validator = RegexValidator::new(r"\d{3}|\d{5}").unwrap();
+    assert_eq!(validator.validate("12"), Intermediate);
+    assert_eq!(validator.validate("123"), Acceptable);
+    assert_eq!(validator.validate("1234"), Intermediate);
+    assert_eq!(validator.validate("12345"), Acceptable);
+    assert_eq!(validator.validate("123456"), Invalid);
+
+    assert!(RegexValidator::new("(").is_err());
+}
+
+#[test]
+fn input_mask() {
+    use ValidationState::*;
+
+    let mask = InputMask::new(r"99/99/9999").unwrap();
+    assert_eq!(mask.validate(""), Intermediate);
+    assert_eq!(mask.validate("12/1"), Intermediate);
+    assert_eq!(mask.validate("12/12/2000"), Acceptable);
+    assert_eq!(mask.validate("12-12-2000"), Invalid);
+    assert_eq!(mask.validate("12/12/20000"), Invalid);
+    assert_eq!(mask.expand_insertion("", "12122000"), "12/12/2000");
+    assert_eq!(mask.expand_insertion("12", "/1"), "/1");
+
+    let mask = InputMask::new(r"AA-00\9").unwrap();
+    assert_eq!(mask.validate("ab-"), Intermediate);
+    assert_eq!(mask.validate("ab-x"), Invalid);
+    assert_eq!(mask.validate("ab-1"), Intermediate);
+    assert_eq!(mask.validate("a1"), Invalid);
+    assert_eq!(mask.validate("ab-12"), Intermediate);
+    assert_eq!(mask.validate("ab-129"), Acceptable);
+    // Optional characters can be omitted.
+    assert_eq!(mask.validate("ab-9"), Acceptable);
+    assert_eq!(mask.validate("ab-19"), Acceptable);
+    assert_eq!(mask.validate("ab-1299"), Invalid);
+    assert_eq!(mask.validate("ab-123"), Invalid);
+    assert_eq!(mask.expand_insertion("ab", "12"), "-129");
+    assert_eq!(mask.expand_insertion("ab-1", "9"), "9");
+
+    let mask = InputMask::new(r"90/90/9999").unwrap();
+    assert_eq!(mask.validate("1/2/2000"), Acceptable);
+    assert_eq!(mask.validate("1/12/2000"), Acceptable);
+    assert_eq!(mask.validate("1/123"), Invalid);
+    assert_eq!(mask.expand_insertion("1", "/2/2000"), "/2/2000");
+    assert!(InputMask::new(r"99\").is_err());
+}
@@ -3,18 +3,21 @@ use {
     crate::{
         callback::Callback,
         child_key::ChildKey,
+        draw::{CachedBoxShadow, DrawEvent},
         event::{Event, FocusReason},
         items::{
             with_index::{Items, ItemsMut},
             with_key::{ItemsWithKey, ItemsWithKeyMut},
         },
-        layout::{Layout, LayoutItemOptions, SizeHint},
+        layout::{
+            GridConfig, Layout, LayoutDebugInfo, LayoutDirection, LayoutItemOptions, SizeHint,
+        },
         shared_window::{SharedWindow, WindowId},
         shortcut::{Shortcut, ShortcutId, ShortcutScope},
         style::{
-            common::{BaseComputedStyle, ComputedElementStyle},
-            css::{PseudoClass, StyleSelector},
-            load_css,
+            common::{BaseComputedStyle, ComputedBorderStyle, ComputedElementStyle},
+            css::{ChildPosition, PseudoClass, StyleSelector},
+            inline::{InlineStyle, StyleProperty},
         },
         system::OrWarn,
         types::{PhysicalPixels, Point, Rect, Size},
@@ -24,7 +27,6 @@ use {
     anyhow::{Context, Result},
     derivative::Derivative,
     itertools::Itertools,
-    lightningcss::stylesheet::StyleSheet,
     std::{
         borrow::Cow,
         cell::RefCell,
@@ -34,6 +36,7 @@ use {
         ops::{Bound, Deref, DerefMut},
         rc::Rc,
     },
+    tiny_skia::Color,
     tracing::{error, warn},
     winit::window::CursorIcon,
 };
@@ -60,6 +63,10 @@ struct WidgetCreationContext {
     window: Option<SharedWindow>,
     app: App,
     parent_scale: f32,
+    parent_layout_direction: LayoutDirection,
+    parent_style_selector: Option<Rc<StyleSelector>>,
+    previous_sibling_style_selector: Option<Rc<StyleSelector>>,
+    child_position: Option<ChildPosition>,
     is_parent_enabled: bool,
     is_window_root: bool,
 }
@@ -187,6 +194,10 @@ auto_bitflags! {
         // true by default, but set to false if the widget
         // doesn't implement `handle_declare_children_request`
         has_declare_children_override,
+        // true if the widget is currently highlighted by the widget inspector
+        inspector_highlighted,
+        // true if the widget has focus that was received from keyboard
+        focus_visible,
     }
 }
 
@@ -195,12 +206,13 @@ struct Cache {
     size_hint_x: HashMap<Option<PhysicalPixels>, SizeHint>,
     // TODO: limit count
     size_hint_y: HashMap<PhysicalPixels, SizeHint>,
-}
-
-#[derive(Debug)]
-struct CustomStyle {
-    code: String,
-    style_sheet: StyleSheet<'static, 'static>,
+    baseline: HashMap<(PhysicalPixels, PhysicalPixels), Option<PhysicalPixels>>,
+    // Rects of the children computed by the default layout for each size of the widget.
+    layout: HashMap<(PhysicalPixels, PhysicalPixels), HashMap<RawWidgetId, Option<Rect>>>,
+    // Only collected if the layout debug overlay is enabled.
+    layout_debug_info: Option<LayoutDebugInfo>,
+    // One entry for each of `base_style.box_shadows`.
+    box_shadows: Vec<Option<CachedBoxShadow>>,
 }
 
 /// The first building block of a widget.
@@ -229,10 +241,14 @@ pub struct WidgetBase {
     parent_scale: f32,
     self_scale: Option<f32>,
 
+    parent_layout_direction: LayoutDirection,
+    self_layout_direction: Option<LayoutDirection>,
+
     // Present if the widget is not hidden, and only after layout.
     geometry: Option<WidgetGeometry>,
 
     layout: Layout,
+    grid_config: GridConfig,
 
     #[derivative(Debug = "ignore")]
     children: BTreeMap<ChildKey, Box<dyn Widget>>,
@@ -248,7 +264,6 @@ pub struct WidgetBase {
     shortcuts: HashMap<ShortcutId, Shortcut>,
     style_selector: StyleSelector,
     base_style: Rc<BaseComputedStyle>,
-    style: Option<CustomStyle>,
 
     cache: RefCell<Cache>,
 }
@@ -296,16 +311,15 @@ fn get_computed_style<T: ComputedElementStyle>(
     app: &App,
     element: &StyleSelector,
     scale: f32,
-    custom_style: Option<&CustomStyle>,
 ) -> Rc<T> {
-    app.style()
-        .get(element, scale, custom_style.map(|s| &s.style_sheet))
+    app.style().get(element, scale)
 }
 
 // Various private impls.
 impl WidgetBase {
     pub(crate) fn new_root<T: Widget>(app: App) -> WidgetBaseOf<T> {
         let id = RawWidgetId::new_unique();
+        let layout_direction = app.layout_direction();
         Self::new(WidgetCreationContext {
             parent_id: None,
             address: WidgetAddress::root(id),
@@ -313,6 +327,10 @@ impl WidgetBase {
             app,
             // Scale doesn't matter for root widget. Window will set scale for its content.
             parent_scale: 1.0,
+            parent_layout_direction: layout_direction,
+            parent_style_selector: None,
+            previous_sibling_style_selector: None,
+            child_position: None,
             is_parent_enabled: true,
             is_window_root: false,
         })
@@ -325,7 +343,12 @@ impl WidgetBase {
 
         let type_name = T::type_name();
         let style_selector = StyleSelector::new(last_path_part(type_name).into())
-            .with_pseudo_class(PseudoClass::Enabled);
+            .with_pseudo_class(PseudoClass::Enabled)
+            .with_context(
+                ctx.parent_style_selector,
+                ctx.previous_sibling_style_selector,
+                ctx.child_position,
+            );
         let self_scale = if ctx.is_window_root {
             Some(default_scale(&ctx.app))
         } else {
@@ -335,7 +358,6 @@ impl WidgetBase {
             &ctx.app,
             &style_selector,
             self_scale.unwrap_or(ctx.parent_scale),
-            None,
         );
         let mut common = Self {
             id,
@@ -360,6 +382,8 @@ impl WidgetBase {
             app: ctx.app,
             parent_scale: ctx.parent_scale,
             self_scale,
+            parent_layout_direction: ctx.parent_layout_direction,
+            self_layout_direction: None,
             geometry: None,
             cursor_icon: CursorIcon::Default,
             children: BTreeMap::new(),
@@ -367,9 +391,9 @@ impl WidgetBase {
             event_filters: HashMap::new(),
             shortcuts: HashMap::new(),
             style_selector,
-            style: None,
             base_style: common_style,
             layout: Layout::default(),
+            grid_config: GridConfig::default(),
             cache: RefCell::new(Cache::default()),
         };
 
@@ -425,25 +449,72 @@ impl WidgetBase {
     ) -> WidgetCreationContext {
         WidgetCreationContext {
             parent_id: Some(self.id),
-            address: self.address.clone().join(key, new_id),
+            address: self.address.clone().join(key.clone(), new_id),
             is_window_root: root_of_window.is_some(),
             window: root_of_window.or_else(|| self.window.clone()),
             app: self.app.create_app_handle(),
             parent_scale: self.scale(),
+            parent_layout_direction: self.layout_direction(),
+            parent_style_selector: Some(Rc::new(self.style_selector.clone())),
+            previous_sibling_style_selector: if self.app.style().has_sibling_combinators() {
+                self.children
+                    .range(..&key)
+                    .next_back()
+                    .map(|(_, sibling)| Rc::new(sibling.base().style_selector.clone()))
+            } else {
+                None
+            },
+            child_position: if self.app.style().has_structural_pseudo_classes() {
+                Some(ChildPosition {
+                    index: self.children.range(..&key).count(),
+                    count: self.children.len() + usize::from(!self.children.contains_key(&key)),
+                })
+            } else {
+                None
+            },
             is_parent_enabled: self.is_enabled(),
         }
     }
 
+    // Updates the ancestor and sibling information in the style selectors of the children.
+    // Children receive a `StyleChangeEvent` after that, so they will update their own children.
+    fn update_children_style_context(&mut self) {
+        let parent = Rc::new(self.style_selector.clone());
+        let track_siblings = self.app.style().has_sibling_combinators();
+        let track_positions = self.app.style().has_structural_pseudo_classes();
+        let count = self.children.len();
+        let mut previous_sibling = None;
+        for (index, child) in self.children.values_mut().enumerate() {
+            let selector = &mut child.base_mut().style_selector;
+            let position = track_positions.then_some(ChildPosition { index, count });
+            selector.set_context(Some(parent.clone()), previous_sibling.take(), position);
+            if track_siblings {
+                previous_sibling = Some(Rc::new(selector.clone()));
+            }
+        }
+    }
+
+    // Following siblings may be affected by sibling combinators and all siblings may be affected
+    // by structural pseudo-classes, so they need to be restyled as well.
+    fn siblings_changed(&mut self) {
+        if self.app.style().depends_on_siblings() {
+            self.request_style_change_event();
+        }
+    }
+
     /// Processes the event before it's dispatched to the widget.
     ///
     /// Returns `true` if the event is consumed and shouldn't be dispatched to the widget.
     pub(crate) fn before_event(&mut self, event: &Event) -> bool {
         match &event {
-            Event::FocusIn(_) => {
+            Event::FocusIn(event) => {
                 self.flags.insert(Flags::focused);
+                self.flags
+                    .set(Flags::focus_visible, event.reason() == FocusReason::Tab);
             }
             Event::FocusOut(_) => {
                 self.flags.remove(Flags::focused);
+                self.flags.remove(Flags::focus_visible);
             }
             Event::WindowFocusChange(_) => {}
             Event::MouseInput(event) => {
@@ -493,21 +564,35 @@ impl WidgetBase {
             }
             Event::StyleChange(_) => {
                 self.refresh_common_style();
+                self.update_children_style_context();
             }
             Event::Draw(event) => {
                 let Some(size) = self.size_or_err().or_warn() else {
                     return false;
                 };
 
+                let rect = Rect::from_pos_size(Point::default(), size);
+                let border = self.drawn_border();
+                let shadows = &self.base_style.box_shadows;
+                let mut cache = self.cache.borrow_mut();
+                cache
+                    .box_shadows
+                    .resize_with(shadows.len(), Default::default);
+                for (shadow, cached) in shadows.iter().zip(&mut cache.box_shadows) {
+                    event.draw_box_shadow(rect, &border.radius, shadow, cached);
+                }
+                drop(cache);
                 event.stroke_and_fill_rounded_rect(
-                    Rect::from_pos_size(Point::default(), size),
-                    &self.base_style.border,
+                    rect,
+                    &border,
                     self.base_style.background.as_ref(),
                 );
             }
             Event::AccessibilityAction(event) => {
+                // Actions on child nodes added by the widget are handled by the widget itself.
+                let is_own_node = event.target == accesskit::NodeId::from(self.id());
                 if let accesskit::Action::Focus = event.action {
-                    if self.is_focusable() {
+                    if self.is_focusable() && is_own_node {
                         self.set_focus(FocusReason::Accessibility);
                         return true;
                     }
@@ -669,31 +754,84 @@ impl WidgetBase {
     }
 
     pub(crate) fn style(&self) -> Option<&str> {
-        self.style.as_ref().map(|s| s.code.as_str())
+        self.style_selector.inline_style()?.code()
     }
 
     fn request_style_change_event(&mut self) {
         if let Some(window) = &self.window {
-            window.request_style_change_event(self.address.clone());
+            // If sibling combinators are in use, restyle the parent so that the following
+            // siblings are updated too.
+            let parent_address =
+                if self.app.style().has_sibling_combinators() && !self.is_window_root() {
+                    self.address.parent()
+                } else {
+                    None
+                };
+            window
+                .request_style_change_event(parent_address.unwrap_or_else(|| self.address.clone()));
         }
         self.update();
     }
 
+    /// Sets the inline style of the widget.
+    ///
+    /// `style` can either be a complete style sheet (e.g. `Button { color: red; }`) or
+    /// a list of declarations applied to the widget itself (e.g. `color: red; padding: 2px;`).
+    /// The inline style takes priority over the app's style. Inherited properties (`color` and
+    /// font properties) of the inline style also apply to the widget's descendants.
     pub fn set_style(&mut self, style: &str) -> &mut Self {
         if self.style() == Some(style) {
             return self;
         }
 
+        let mut inline = self.inline_style();
         // TODO: return error
-        self.style = load_css(style).or_warn().map(|style_sheet| CustomStyle {
-            style_sheet,
-            code: style.into(),
-        });
-        self.request_style_change_event();
+        if inline.set_code(Some(style)).or_warn().is_none() {
+            inline.set_code(None).or_warn();
+        }
+        self.set_inline_style(inline);
+        self
+    }
 
+    /// Sets a style property of the widget, replacing the previous value of the same property.
+    ///
+    /// Properties set this way take priority over the app's style and the style set with
+    /// [set_style](Self::set_style).
+    pub fn set_style_property(&mut self, property: StyleProperty) -> &mut Self {
+        if self
+            .style_selector
+            .inline_style()
+            .is_some_and(|inline| inline.properties().contains(&property))
+        {
+            return self;
+        }
+        let mut inline = self.inline_style();
+        inline.set_property(property);
+        self.set_inline_style(inline);
         self
     }
 
+    /// Removes all style properties set with [set_style_property](Self::set_style_property).
+    pub fn clear_style_properties(&mut self) -> &mut Self {
+        let mut inline = self.inline_style();
+        inline.clear_properties();
+        self.set_inline_style(inline);
+        self
+    }
+
+    fn inline_style(&self) -> InlineStyle {
+        self.style_selector
+            .inline_style()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn set_inline_style(&mut self, inline: InlineStyle) {
+        let inline = (!inline.is_empty()).then(|| Rc::new(inline));
+        self.style_selector.set_inline_style(inline);
+        self.request_style_change_event();
+    }
+
     pub fn add_class(&mut self, class: Cow<'static, str>) -> &mut Self {
         if self.style_selector.has_class(&class) {
             return self;
@@ -757,11 +895,7 @@ impl WidgetBase {
     }
 
     pub fn compute_style<T: ComputedElementStyle>(&self) -> Rc<T> {
-        self.app.style().get(
-            &self.style_selector,
-            self.scale(),
-            self.style.as_ref().map(|s| &s.style_sheet),
-        )
+        self.app.style().get(&self.style_selector, self.scale())
     }
 
     /// True if this widget is a root widget of an OS window.
@@ -832,6 +966,14 @@ impl WidgetBase {
         self.flags.contains(Flags::focused) && self.is_enabled()
     }
 
+    /// Returns `true` if the widget has focus that was received from keyboard
+    /// (e.g. by pressing Tab).
+    ///
+    /// Such widgets have the `:focus-visible` pseudo-class while their window is focused.
+    pub fn is_focus_visible(&self) -> bool {
+        self.is_focused() && self.flags.contains(Flags::focus_visible)
+    }
+
     /// Returns `true` if the widget's OS window is focused.
     pub fn is_window_focused(&self) -> bool {
         self.window.as_ref().is_some_and(|w| w.is_focused())
@@ -959,6 +1101,60 @@ impl WidgetBase {
         self.self_scale
     }
 
+    /// Horizontal direction of the widget's layout.
+    ///
+    /// By default, the direction is inherited from the parent widget. The direction of the root widget
+    /// is set using [AppBuilder::with_layout_direction](crate::AppBuilder::with_layout_direction).
+    ///
+    /// Layout direction can be changed for any widget
+    /// with [WidgetExt::set_layout_direction](crate::WidgetExt::set_layout_direction).
+    /// This value propagates to all child widgets.
+    pub fn layout_direction(&self) -> LayoutDirection {
+        self.self_layout_direction
+            .unwrap_or(self.parent_layout_direction)
+    }
+
+    /// Returns the value set with
+    /// [WidgetExt::set_layout_direction](crate::WidgetExt::set_layout_direction), if any.
+    ///
+    /// It returns `None` if the direction is inherited from the parent widget.
+    pub fn self_layout_direction(&self) -> Option<LayoutDirection> {
+        self.self_layout_direction
+    }
+
+    pub fn set_layout_direction(&mut self, direction: Option<LayoutDirection>) -> &mut Self {
+        if self.self_layout_direction == direction {
+            return self;
+        }
+        let old_direction = self.layout_direction();
+        self.self_layout_direction = direction;
+        if old_direction != self.layout_direction() {
+            self.layout_direction_changed();
+        }
+        self
+    }
+
+    fn parent_layout_direction_changed(&mut self, direction: LayoutDirection) {
+        if self.parent_layout_direction == direction {
+            return;
+        }
+        let old_direction = self.layout_direction();
+        self.parent_layout_direction = direction;
+        if old_direction != self.layout_direction() {
+            self.layout_direction_changed();
+        }
+    }
+
+    fn layout_direction_changed(&mut self) {
+        let direction = self.layout_direction();
+        for child in self.children.values_mut() {
+            child.base_mut().parent_layout_direction_changed(direction);
+        }
+        self.size_hint_changed();
+        // Widgets may need to update their content, e.g. swap arrow icons.
+        self.request_style_change_event();
+    }
+
     /// Check if the accessibility node hasn't been disabled for this widget.
     ///
     /// This value corresponds to the value set by [set_accessibility_node_enabled](Self::set_accessibility_node_enabled).
@@ -1184,6 +1380,10 @@ impl WidgetBase {
             Box::new(initializer.init(WidgetBase::new::<WI::Output>(ctx))?),
         );
         self.size_hint_changed();
+        if self.child_key_after(&key).is_some() || self.app.style().has_structural_pseudo_classes()
+        {
+            self.siblings_changed();
+        }
         Ok(self.children.get_mut(&key).unwrap().downcast_mut().unwrap())
     }
 
@@ -1331,6 +1531,7 @@ impl WidgetBase {
     pub fn remove_child(&mut self, key: impl Into<ChildKey>) -> Result<(), WidgetNotFound> {
         self.children.remove(&key.into()).ok_or(WidgetNotFound)?;
         self.size_hint_changed();
+        self.siblings_changed();
         Ok(())
     }
 
@@ -1508,6 +1709,7 @@ impl WidgetBase {
     /// Layout strategy determines how child widgets are positioned within the widget.
     pub fn set_layout(&mut self, layout: Layout) -> &mut Self {
         self.layout = layout;
+        self.size_hint_changed();
         self
     }
 
@@ -1548,6 +1750,50 @@ impl WidgetBase {
         self.size_hint_changed();
         self
     }
+
+    /// Assign a range of columns and rows to this widget in the parent widget's grid.
+    ///
+    /// The widget will occupy `span_x` columns starting from `x` and `span_y` rows
+    /// starting from `y`.
+    ///
+    /// This setting only takes effect if the parent's layout is [Layout::ExplicitGrid]
+    /// or [Layout::AutoGrid].
+    pub fn set_grid_cell_span(&mut self, x: i32, y: i32, span_x: i32, span_y: i32) -> &mut Self {
+        let mut options = self.layout_item_options.clone();
+        options.set_grid_cell_span(x, y, span_x, span_y);
+        self.set_layout_item_options(options)
+    }
+
+    /// Set the number of columns and rows occupied by this widget when its cell
+    /// is assigned automatically.
+    ///
+    /// This setting only takes effect if the parent's layout is [Layout::AutoGrid].
+    pub fn set_grid_span(&mut self, span_x: i32, span_y: i32) -> &mut Self {
+        let mut options = self.layout_item_options.clone();
+        options.set_grid_span(span_x, span_y);
+        self.set_layout_item_options(options)
+    }
+
+    /// Returns grid configuration of this widget.
+    ///
+    /// This configuration influences the layout of child widgets.
+    pub fn grid_config(&self) -> &GridConfig {
+        &self.grid_config
+    }
+
+    /// Set grid configuration of this widget.
+    ///
+    /// Grid configuration allows to override spacing from the style, set minimal sizes
+    /// of individual rows and columns and specify how extra space is distributed
+    /// between them.
+    pub fn set_grid_config(&mut self, config: GridConfig) -> &mut Self {
+        if self.grid_config == config {
+            return self;
+        }
+        self.grid_config = config;
+        self.size_hint_changed();
+        self
+    }
     // TODO: setters for other layout item options (alignment, is_fixed)?
 
     // TODO: automate size_hint_changed?
@@ -1573,6 +1819,8 @@ impl WidgetBase {
         let mut cache = self.cache.borrow_mut();
         cache.size_hint_x.clear();
         cache.size_hint_y.clear();
+        cache.baseline.clear();
+        cache.layout.clear();
     }
 
     pub(crate) fn size_hint_x_cache(&self, size_y: Option<PhysicalPixels>) -> Option<SizeHint> {
@@ -1590,6 +1838,118 @@ impl WidgetBase {
     pub(crate) fn set_size_hint_y_cache(&self, size_x: PhysicalPixels, value: SizeHint) {
         self.cache.borrow_mut().size_hint_y.insert(size_x, value);
     }
+
+    pub(crate) fn set_layout_debug_info(&self, info: LayoutDebugInfo) {
+        self.cache.borrow_mut().layout_debug_info = Some(info);
+    }
+
+    // Draws outlines of the widget, its padding and the gaps between rows and columns.
+    pub(crate) fn draw_layout_debug_overlay(&self, event: &DrawEvent) {
+        let Some(size) = self.size() else {
+            return;
+        };
+        let rect = Rect::from_pos_size(Point::default(), size);
+        if let Some(info) = &self.cache.borrow().layout_debug_info {
+            let content = info.content_rect;
+            let padding_rects = [
+                Rect::from_x1y1x2y2(rect.left(), rect.top(), rect.right(), content.top()),
+                Rect::from_x1y1x2y2(rect.left(), content.bottom(), rect.right(), rect.bottom()),
+                Rect::from_x1y1x2y2(rect.left(), content.top(), content.left(), content.bottom()),
+                Rect::from_x1y1x2y2(
+                    content.right(),
+                    content.top(),
+                    rect.right(),
+                    content.bottom(),
+                ),
+            ];
+            for padding_rect in padding_rects {
+                if !padding_rect.is_empty() {
+                    event.fill_rect(padding_rect, Color::from_rgba8(0, 160, 0, 40));
+                }
+            }
+            for gap in &info.gaps {
+                if !gap.is_empty() {
+                    event.fill_rect(*gap, Color::from_rgba8(255, 140, 0, 60));
+                }
+            }
+        }
+        if !rect.is_empty() {
+            event.stroke_rect(rect, Color::from_rgba8(220, 0, 0, 160));
+        }
+    }
+
+    pub(crate) fn set_inspector_highlighted(&mut self, value: bool) {
+        if self.flags.contains(Flags::inspector_highlighted) != value {
+            self.flags.set(Flags::inspector_highlighted, value);
+            self.update();
+        }
+    }
+
+    pub(crate) fn draw_outline(&self, event: &DrawEvent) {
+        let Some(outline) = &self.base_style.outline else {
+            return;
+        };
+        let Some(size) = self.size() else {
+            return;
+        };
+        let rect = Rect::from_pos_size(Point::default(), size);
+        event.draw_outline(rect, &self.drawn_border().radius, outline);
+    }
+
+    // Left and right sides of the border are swapped in right-to-left layouts,
+    // like the contents of the widget.
+    fn drawn_border(&self) -> Cow<'_, ComputedBorderStyle> {
+        if self.layout_direction() == LayoutDirection::RightToLeft {
+            Cow::Owned(self.base_style.border.mirrored())
+        } else {
+            Cow::Borrowed(&self.base_style.border)
+        }
+    }
+
+    // Draws a translucent box over the widget if it's highlighted by the widget inspector.
+    pub(crate) fn draw_inspector_highlight(&self, event: &DrawEvent) {
+        if !self.flags.contains(Flags::inspector_highlighted) {
+            return;
+        }
+        let Some(size) = self.size() else {
+            return;
+        };
+        let rect = Rect::from_pos_size(Point::default(), size);
+        if !rect.is_empty() {
+            event.fill_rect(rect, Color::from_rgba8(60, 130, 230, 70));
+            event.stroke_rect(rect, Color::from_rgba8(30, 90, 200, 220));
+        }
+    }
+
+    pub(crate) fn baseline_cache(&self, size: Size) -> Option<Option<PhysicalPixels>> {
+        self.cache
+            .borrow()
+            .baseline
+            .get(&(size.x(), size.y()))
+            .copied()
+    }
+
+    pub(crate) fn set_baseline_cache(&self, size: Size, value: Option<PhysicalPixels>) {
+        self.cache
+            .borrow_mut()
+            .baseline
+            .insert((size.x(), size.y()), value);
+    }
+
+    pub(crate) fn layout_cache(&self, size: Size) -> Option<HashMap<RawWidgetId, Option<Rect>>> {
+        self.cache
+            .borrow()
+            .layout
+            .get(&(size.x(), size.y()))
+            .cloned()
+    }
+
+    pub(crate) fn set_layout_cache(&self, size: Size, value: HashMap<RawWidgetId, Option<Rect>>) {
+        self.cache
+            .borrow_mut()
+            .layout
+            .insert((size.x(), size.y()), value);
+    }
 }
 
 /// <h2>Shortcuts and event filters</h2>
@@ -2,10 +2,10 @@ use {
     crate::{
         callback::Callback,
         event::{Event, LayoutEvent, StyleChangeEvent},
-        layout::{Layout, SizeHint, FALLBACK_SIZE_HINTS},
-        style::css::PseudoClass,
+        layout::{Alignment, GridConfig, Layout, LayoutDirection, SizeHint, FALLBACK_SIZE_HINTS},
+        style::{css::PseudoClass, inline::StyleProperty},
         system::{LayoutState, OrWarn},
-        types::PhysicalPixels,
+        types::{PhysicalPixels, Size},
         RawWidgetId, ScrollToRectRequest, Widget, WidgetGeometry, WidgetId,
     },
     anyhow::Result,
@@ -40,6 +40,16 @@ pub trait WidgetExt: Widget {
         self
     }
 
+    fn set_style_property(&mut self, property: StyleProperty) -> &mut Self {
+        self.base_mut().set_style_property(property);
+        self
+    }
+
+    fn clear_style_properties(&mut self) -> &mut Self {
+        self.base_mut().clear_style_properties();
+        self
+    }
+
     fn set_labelled_by(&mut self, label_id: RawWidgetId) -> &mut Self {
         self.base_mut().set_labelled_by(label_id);
         self
@@ -103,16 +113,26 @@ pub trait WidgetExt: Widget {
                 self.remove_pseudo_class(PseudoClass::Hover);
             }
             Event::FocusIn(_) => {
-                self.set_pseudo_class(PseudoClass::Focus, self.base().is_window_focused());
+                let is_window_focused = self.base().is_window_focused();
+                self.set_pseudo_class(PseudoClass::Focus, is_window_focused);
+                self.set_pseudo_class(
+                    PseudoClass::FocusVisible,
+                    is_window_focused && self.base().is_focus_visible(),
+                );
             }
             Event::FocusOut(_) => {
                 self.remove_pseudo_class(PseudoClass::Focus);
+                self.remove_pseudo_class(PseudoClass::FocusVisible);
             }
             Event::WindowFocusChange(event) => {
                 self.set_pseudo_class(
                     PseudoClass::Focus,
                     event.is_window_focused && self.base().is_focused(),
                 );
+                self.set_pseudo_class(
+                    PseudoClass::FocusVisible,
+                    event.is_window_focused && self.base().is_focus_visible(),
+                );
             }
             _ => (),
         }
@@ -159,11 +179,19 @@ pub trait WidgetExt: Widget {
             Event::Draw(event) => {
                 for child in self.base_mut().children_mut() {
                     if let Some(rect_in_parent) = child.base().rect_in_parent() {
-                        if let Some(child_event) = event.map_to_child(rect_in_parent) {
+                        let overflow = child.base().base_style().visual_overflow();
+                        if let Some(child_event) =
+                            event.map_to_child_with_overflow(rect_in_parent, overflow)
+                        {
                             child.dispatch(child_event.into());
                         }
                     }
                 }
+                self.base().draw_outline(&event);
+                if self.base().app().config().debug_layout {
+                    self.base().draw_layout_debug_overlay(&event);
+                }
+                self.base().draw_inspector_highlight(&event);
             }
             Event::WindowFocusChange(event) => {
                 for child in self.base_mut().children_mut() {
@@ -313,6 +341,19 @@ pub trait WidgetExt: Widget {
         }
     }
 
+    /// Returns the position of the first text baseline of this widget, given its size.
+    ///
+    /// See [Widget::handle_baseline_request].
+    fn baseline(&mut self, size: Size) -> Option<PhysicalPixels> {
+        if let Some(cached) = self.base().baseline_cache(size) {
+            cached
+        } else {
+            let r = self.handle_baseline_request(size).or_warn().flatten();
+            self.base().set_baseline_cache(size, r);
+            r
+        }
+    }
+
     fn add_class(&mut self, class: Cow<'static, str>) -> &mut Self {
         self.base_mut().add_class(class);
         self
@@ -369,6 +410,15 @@ pub trait WidgetExt: Widget {
         self
     }
 
+    /// Set horizontal direction of the layout for this widget and its children.
+    ///
+    /// If `None` is specified, the direction is inherited from the parent widget.
+    /// See also [WidgetBase::layout_direction](crate::WidgetBase::layout_direction).
+    fn set_layout_direction(&mut self, direction: Option<LayoutDirection>) -> &mut Self {
+        self.base_mut().set_layout_direction(direction);
+        self
+    }
+
     /// Assign column `x` and row `y` to this widget in the parent widget's grid.
     ///
     /// Same as [WidgetBase::set_grid_cell](crate::WidgetBase::set_grid_cell).
@@ -377,6 +427,22 @@ pub trait WidgetExt: Widget {
         self
     }
 
+    /// Assign a range of columns and rows to this widget in the parent widget's grid.
+    ///
+    /// Same as [WidgetBase::set_grid_cell_span](crate::WidgetBase::set_grid_cell_span).
+    fn set_grid_cell_span(&mut self, x: i32, y: i32, span_x: i32, span_y: i32) -> &mut Self {
+        self.base_mut().set_grid_cell_span(x, y, span_x, span_y);
+        self
+    }
+
+    /// Set the number of columns and rows occupied by this widget in an automatic grid.
+    ///
+    /// Same as [WidgetBase::set_grid_span](crate::WidgetBase::set_grid_span).
+    fn set_grid_span(&mut self, span_x: i32, span_y: i32) -> &mut Self {
+        self.base_mut().set_grid_span(span_x, span_y);
+        self
+    }
+
     fn set_size_x_fixed(&mut self, fixed: Option<bool>) -> &mut Self {
         let mut options = self.base().layout_item_options().clone();
         options.set_x_fixed(fixed);
@@ -390,6 +456,22 @@ pub trait WidgetExt: Widget {
         self
     }
 
+    /// Set alignment of this widget within its cell in the parent widget's grid.
+    fn set_x_alignment(&mut self, alignment: Option<Alignment>) -> &mut Self {
+        let mut options = self.base().layout_item_options().clone();
+        options.set_x_alignment(alignment);
+        self.base_mut().set_layout_item_options(options);
+        self
+    }
+
+    /// Set alignment of this widget within its cell in the parent widget's grid.
+    fn set_y_alignment(&mut self, alignment: Option<Alignment>) -> &mut Self {
+        let mut options = self.base().layout_item_options().clone();
+        options.set_y_alignment(alignment);
+        self.base_mut().set_layout_item_options(options);
+        self
+    }
+
     fn set_geometry(&mut self, geometry: Option<WidgetGeometry>) -> &mut Self {
         if self.base().geometry() == geometry.as_ref() {
             return self;
@@ -429,6 +511,12 @@ pub trait WidgetExt: Widget {
         self
     }
 
+    /// Same as [WidgetBase::set_grid_config](crate::WidgetBase::set_grid_config).
+    fn set_grid_config(&mut self, config: GridConfig) -> &mut Self {
+        self.base_mut().set_grid_config(config);
+        self
+    }
+
     fn boxed(self) -> Box<dyn Widget>
     where
         Self: Sized,
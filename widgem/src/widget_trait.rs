@@ -9,7 +9,7 @@ use {
             WindowFocusChangeEvent,
         },
         layout::{self, default_layout, default_size_hint_y, SizeHint},
-        types::PhysicalPixels,
+        types::{PhysicalPixels, Size},
         ScrollToRectRequest, WindowRectRequest, WindowRectResponse,
     },
     anyhow::Result,
@@ -477,6 +477,23 @@ pub trait Widget: Any {
         Ok(default_size_hint_y(self, size_x))
     }
 
+    /// Calculates the position of the first text baseline of this widget, given its size.
+    ///
+    /// The value is measured from the top of the widget. It's used by the parent's layout
+    /// to align items with [Alignment::Baseline](crate::layout::Alignment::Baseline).
+    /// The value is cached until [size_hint_changed](crate::WidgetBase::size_hint_changed) is called.
+    ///
+    /// You should not call this function directly. Use
+    /// [baseline](crate::WidgetExt::baseline) to get the current baseline.
+    ///
+    /// The default implementation calls [`default_baseline`](crate::layout::default_baseline)`(self, size)`
+    /// which uses the baseline of the first child that has one.
+    ///
+    /// Implement this function if your widget draws text without using child widgets.
+    fn handle_baseline_request(&mut self, size: Size) -> Result<Option<PhysicalPixels>> {
+        Ok(layout::default_baseline(self, size))
+    }
+
     // TODO: track accesskit state and don't update nodes if it's disabled
 
     /// Calculates the accessibility node representing this widget.
@@ -1,5 +1,6 @@
 mod button;
 mod column;
+mod find_bar;
 mod image;
 mod label;
 mod menu;
@@ -15,6 +16,7 @@ mod window;
 pub use self::{
     button::Button,
     column::Column,
+    find_bar::FindBar,
     image::Image,
     label::Label,
     menu::{Menu, MenuAction},
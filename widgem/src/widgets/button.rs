@@ -7,21 +7,16 @@ use {
         },
         impl_widget_base,
         layout::Layout,
-        style::{
-            common::ComputedElementStyle,
-            css::{convert_content_url, convert_zoom, PseudoClass, StyleSelector},
-            Styles,
-        },
+        style::{common::ComputedIconStyle, css::PseudoClass},
         text::TextHandler,
         timer::TimerId,
         widget_initializer::WidgetInitializer,
         widgets::Image,
-        Pixmap, Widget, WidgetBaseOf, WidgetExt,
+        Widget, WidgetBaseOf, WidgetExt,
     },
-    accesskit::{Action, Role},
+    accesskit::{Action, Role, Toggled},
     anyhow::Result,
     std::{fmt::Display, rc::Rc},
-    tracing::warn,
     widgem_macros::impl_with,
     winit::{
         event::MouseButton,
@@ -33,13 +28,16 @@ pub struct Button {
     auto_repeat: bool,
     is_mouse_leave_sensitive: bool,
     trigger_on_press: bool,
+    is_checkable: bool,
+    is_checked: bool,
     on_triggered: Callbacks<()>,
+    on_toggled: Callbacks<bool>,
     is_pressed: bool,
     was_pressed_but_moved_out: bool,
     auto_repeat_delay_timer: Option<TimerId>,
     auto_repeat_interval: Option<TimerId>,
     base: WidgetBaseOf<Self>,
-    style: Rc<ComputedButtonStyle>,
+    style: Rc<ComputedIconStyle>,
 }
 
 #[impl_with]
@@ -94,6 +92,51 @@ impl Button {
         self
     }
 
+    /// Makes the button checkable. A checkable button toggles its checked state
+    /// each time it's triggered.
+    ///
+    /// If the button becomes non-checkable, it's unchecked.
+    pub fn set_checkable(&mut self, value: bool) -> &mut Self {
+        self.is_checkable = value;
+        if !value {
+            self.set_checked(false);
+        }
+        self
+    }
+
+    pub fn is_checkable(&self) -> bool {
+        self.is_checkable
+    }
+
+    /// Sets the checked state of a checkable button.
+    ///
+    /// Checked buttons have the `:checked` pseudo-class.
+    /// Has no effect if the button is not checkable.
+    pub fn set_checked(&mut self, value: bool) -> &mut Self {
+        self.set_checked_internal(value, true)
+    }
+
+    fn set_checked_internal(&mut self, value: bool, from_setter: bool) -> &mut Self {
+        let value = value && self.is_checkable;
+        if self.is_checked == value {
+            return self;
+        }
+        self.is_checked = value;
+        self.set_pseudo_class(PseudoClass::Checked, value);
+        self.on_toggled.invoke(value, from_setter);
+        self
+    }
+
+    pub fn is_checked(&self) -> bool {
+        self.is_checked
+    }
+
+    /// Adds a callback that is called when the checked state of the button changes.
+    pub fn on_toggled(&mut self, callback: Callback<bool>) -> &mut Self {
+        self.on_toggled.add(callback);
+        self
+    }
+
     // TODO: set_icon should preferably work with SVG icons
     // pub fn set_icon(&mut self, icon: Option<Rc<Pixmap>>) {
     //     self.icon = icon;
@@ -107,6 +150,9 @@ impl Button {
     }
 
     pub fn trigger(&mut self) {
+        if self.is_checkable {
+            self.set_checked_internal(!self.is_checked, false);
+        }
         self.on_triggered.invoke((), false);
     }
 
@@ -195,7 +241,10 @@ impl WidgetInitializer for Initializer {
             auto_repeat: false,
             is_mouse_leave_sensitive: true,
             trigger_on_press: false,
+            is_checkable: false,
+            is_checked: false,
             on_triggered: Callbacks::default(),
+            on_toggled: Callbacks::default(),
             is_pressed: false,
             was_pressed_but_moved_out: false,
             base,
@@ -284,6 +333,13 @@ impl Widget for Button {
         let mut node = accesskit::Node::new(Role::Button);
         node.set_label(self.text_widget().text().as_str());
         node.add_action(Action::Click);
+        if self.is_checkable {
+            node.set_toggled(if self.is_checked {
+                Toggled::True
+            } else {
+                Toggled::False
+            });
+        }
         Ok(Some(node))
     }
 
@@ -296,25 +352,3 @@ impl Widget for Button {
         Ok(())
     }
 }
-
-#[derive(Debug, Clone, Default)]
-struct ComputedButtonStyle {
-    pub icon: Option<Pixmap>,
-}
-
-impl ComputedElementStyle for ComputedButtonStyle {
-    fn new(style: &Styles, element: &StyleSelector, scale: f32) -> ComputedButtonStyle {
-        let properties = style.find_rules(|s| element.matches(s));
-
-        let scale = scale * convert_zoom(&properties);
-        let mut icon = None;
-        if let Some(url) = convert_content_url(&properties) {
-            //println!("icon url: {url:?}");
-            match style.load_pixmap(&url, scale) {
-                Ok(pixmap) => icon = Some(pixmap),
-                Err(err) => warn!("failed to load icon: {err:?}"),
-            }
-        }
-        Self { icon }
-    }
-}
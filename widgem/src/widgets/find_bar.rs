@@ -0,0 +1,322 @@
+use {
+    crate::{
+        callback::{Callback, Callbacks},
+        event::{Event, FocusReason, KeyboardInputEvent},
+        impl_widget_base,
+        layout::Layout,
+        shortcut::standard_shortcuts,
+        system::OrWarn,
+        text::FindQuery,
+        widget_initializer::{self, WidgetInitializer},
+        widgets::{Button, TextInput},
+        RawWidgetId, Widget, WidgetBaseOf, WidgetExt,
+    },
+    anyhow::Result,
+    std::fmt::Display,
+    winit::keyboard::{Key, NamedKey},
+};
+
+const INDEX_FIND_INPUT: u64 = 0;
+const INDEX_MATCH_CASE: u64 = 1;
+const INDEX_WHOLE_WORD: u64 = 2;
+const INDEX_REGEX: u64 = 3;
+const INDEX_PREVIOUS: u64 = 4;
+const INDEX_NEXT: u64 = 5;
+const INDEX_REPLACE_INPUT: u64 = 6;
+const INDEX_REPLACE: u64 = 7;
+const INDEX_REPLACE_ALL: u64 = 8;
+const INDEX_CLOSE: u64 = 9;
+
+mod names {
+    pub const MATCH_CASE: &str = "Aa";
+    pub const WHOLE_WORD: &str = "W";
+    pub const REGEX: &str = ".*";
+    pub const PREVIOUS: &str = "Previous";
+    pub const NEXT: &str = "Next";
+    pub const REPLACE: &str = "Replace";
+    pub const REPLACE_ALL: &str = "Replace all";
+    pub const CLOSE: &str = "Close";
+}
+
+#[derive(Debug, Clone, Copy)]
+enum KeyAction {
+    FindNext,
+    FindPrevious,
+    Close,
+}
+
+/// A row of controls for searching and replacing text.
+///
+/// The find bar doesn't search by itself. Connect its callbacks to the widget
+/// that contains the text (e.g. [TextArea::find](crate::widgets::TextArea::find)).
+/// [TextArea](crate::widgets::TextArea) can create a built-in find bar with
+/// [set_find_bar_enabled](crate::widgets::TextArea::set_find_bar_enabled).
+///
+/// `Enter` and `F3` in the find input select the next match, `Shift+F3` selects
+/// the previous match, and `Escape` closes the find bar.
+pub struct FindBar {
+    base: WidgetBaseOf<Self>,
+    on_query_changed: Callbacks<FindQuery>,
+    on_find_next: Callbacks<()>,
+    on_find_previous: Callbacks<()>,
+    on_replace: Callbacks<String>,
+    on_replace_all: Callbacks<String>,
+    on_closed: Callbacks<()>,
+}
+
+impl FindBar {
+    fn new(mut base: WidgetBaseOf<Self>) -> Result<Self> {
+        base.set_layout(Layout::HorizontalFirst);
+        base.add_class("find_bar".into());
+
+        let text_changed = base
+            .callback(|this: &mut Self, _: String| {
+                this.query_changed();
+                Ok(())
+            })
+            .with_send_signals_on_setter_calls(true);
+        let option_toggled = base.callback(|this: &mut Self, _: bool| {
+            this.query_changed();
+            Ok(())
+        });
+        let key_pressed = base.callback(|this: &mut Self, action| {
+            match action {
+                KeyAction::FindNext => this.on_find_next.invoke((), false),
+                KeyAction::FindPrevious => this.on_find_previous.invoke((), false),
+                KeyAction::Close => this.close(),
+            }
+            Ok(())
+        });
+        let owner_id = base.id().raw();
+
+        let find_input = base
+            .set_child(INDEX_FIND_INPUT, TextInput::init())?
+            .add_class("find_input".into())
+            .set_placeholder("Find")
+            .on_text_changed(text_changed);
+        install_key_filter(find_input, owner_id, key_pressed.clone(), true);
+
+        for (index, name) in [
+            (INDEX_MATCH_CASE, names::MATCH_CASE),
+            (INDEX_WHOLE_WORD, names::WHOLE_WORD),
+            (INDEX_REGEX, names::REGEX),
+        ] {
+            base.set_child(index, Button::init(name.into()))?
+                .set_checkable(true)
+                .set_focusable(false)
+                .on_toggled(option_toggled.clone());
+        }
+
+        let find_previous = base.callback(|this: &mut Self, _| {
+            this.on_find_previous.invoke((), false);
+            Ok(())
+        });
+        base.set_child(INDEX_PREVIOUS, Button::init(names::PREVIOUS.into()))?
+            .on_triggered(find_previous);
+        let find_next = base.callback(|this: &mut Self, _| {
+            this.on_find_next.invoke((), false);
+            Ok(())
+        });
+        base.set_child(INDEX_NEXT, Button::init(names::NEXT.into()))?
+            .on_triggered(find_next);
+
+        let replace_input = base
+            .set_child(INDEX_REPLACE_INPUT, TextInput::init())?
+            .add_class("replace_input".into())
+            .set_placeholder("Replace");
+        install_key_filter(replace_input, owner_id, key_pressed, false);
+
+        let replace = base.callback(|this: &mut Self, _| {
+            let replacement = this.replacement();
+            this.on_replace.invoke(replacement, false);
+            Ok(())
+        });
+        base.set_child(INDEX_REPLACE, Button::init(names::REPLACE.into()))?
+            .on_triggered(replace);
+        let replace_all = base.callback(|this: &mut Self, _| {
+            let replacement = this.replacement();
+            this.on_replace_all.invoke(replacement, false);
+            Ok(())
+        });
+        base.set_child(INDEX_REPLACE_ALL, Button::init(names::REPLACE_ALL.into()))?
+            .on_triggered(replace_all);
+
+        let close = base.callback(|this: &mut Self, _| {
+            this.close();
+            Ok(())
+        });
+        base.set_child(INDEX_CLOSE, Button::init(names::CLOSE.into()))?
+            .add_class("find_bar_close".into())
+            .on_triggered(close);
+
+        Ok(Self {
+            base,
+            on_query_changed: Callbacks::default(),
+            on_find_next: Callbacks::default(),
+            on_find_previous: Callbacks::default(),
+            on_replace: Callbacks::default(),
+            on_replace_all: Callbacks::default(),
+            on_closed: Callbacks::default(),
+        })
+    }
+
+    pub fn init() -> impl WidgetInitializer<Output = Self> {
+        widget_initializer::from_fallible_new(Self::new)
+    }
+
+    fn find_input(&self) -> Result<&TextInput> {
+        self.base.get_child(INDEX_FIND_INPUT)
+    }
+
+    fn find_input_mut(&mut self) -> Result<&mut TextInput> {
+        self.base.get_child_mut(INDEX_FIND_INPUT)
+    }
+
+    fn is_checked(&self, index: u64) -> bool {
+        self.base
+            .get_child::<Button>(index)
+            .or_warn()
+            .is_some_and(|button| button.is_checked())
+    }
+
+    fn query_changed(&mut self) {
+        let query = self.query();
+        self.on_query_changed.invoke(query, false);
+    }
+
+    /// Returns the query specified by the find input and the option buttons.
+    pub fn query(&self) -> FindQuery {
+        let pattern = self
+            .find_input()
+            .or_warn()
+            .map(|input| input.text())
+            .unwrap_or_default();
+        FindQuery::new(pattern)
+            .with_case_insensitive(!self.is_checked(INDEX_MATCH_CASE))
+            .with_whole_word(self.is_checked(INDEX_WHOLE_WORD))
+            .with_regex(self.is_checked(INDEX_REGEX))
+    }
+
+    /// Sets the text of the find input. The new query is reported to `on_query_changed` callbacks.
+    pub fn set_pattern(&mut self, pattern: impl Display) -> &mut Self {
+        if let Some(input) = self.find_input_mut().or_warn() {
+            input.set_text(pattern);
+        }
+        self
+    }
+
+    /// Returns the text of the replace input.
+    pub fn replacement(&self) -> String {
+        self.base
+            .get_child::<TextInput>(INDEX_REPLACE_INPUT)
+            .or_warn()
+            .map(|input| input.text())
+            .unwrap_or_default()
+    }
+
+    /// Shows or hides the replace input and buttons. They are visible by default.
+    pub fn set_replace_visible(&mut self, visible: bool) -> &mut Self {
+        for index in [INDEX_REPLACE_INPUT, INDEX_REPLACE, INDEX_REPLACE_ALL] {
+            if let Some(child) = self.base.get_dyn_child_mut(index).or_warn() {
+                child.set_visible(visible);
+            }
+        }
+        self
+    }
+
+    /// Moves the keyboard focus to the find input.
+    pub fn focus_find_input(&mut self) -> &mut Self {
+        if let Some(input) = self.find_input_mut().or_warn() {
+            input.base().set_focus(FocusReason::Tab);
+        }
+        self
+    }
+
+    /// Hides the find bar.
+    pub fn close(&mut self) {
+        self.base.set_visible(false);
+        self.on_closed.invoke((), false);
+    }
+
+    /// Adds a callback that is called when the find input text or any of the options change.
+    pub fn on_query_changed(&mut self, callback: Callback<FindQuery>) -> &mut Self {
+        self.on_query_changed.add(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the user requests the next match.
+    pub fn on_find_next(&mut self, callback: Callback<()>) -> &mut Self {
+        self.on_find_next.add(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the user requests the previous match.
+    pub fn on_find_previous(&mut self, callback: Callback<()>) -> &mut Self {
+        self.on_find_previous.add(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the user requests to replace the current match.
+    ///
+    /// The callback receives the text of the replace input.
+    pub fn on_replace(&mut self, callback: Callback<String>) -> &mut Self {
+        self.on_replace.add(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the user requests to replace all matches.
+    ///
+    /// The callback receives the text of the replace input.
+    pub fn on_replace_all(&mut self, callback: Callback<String>) -> &mut Self {
+        self.on_replace_all.add(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the find bar is closed.
+    pub fn on_closed(&mut self, callback: Callback<()>) -> &mut Self {
+        self.on_closed.add(callback);
+        self
+    }
+}
+
+impl Widget for FindBar {
+    impl_widget_base!();
+}
+
+fn key_action(event: &KeyboardInputEvent, enter_finds_next: bool) -> Option<KeyAction> {
+    if !event.info.state.is_pressed() {
+        return None;
+    }
+    let shortcuts = standard_shortcuts();
+    if shortcuts.find_previous.matches(event) {
+        Some(KeyAction::FindPrevious)
+    } else if shortcuts.find_next.matches(event) {
+        Some(KeyAction::FindNext)
+    } else if event.info.logical_key == Key::Named(NamedKey::Escape) {
+        Some(KeyAction::Close)
+    } else if enter_finds_next && event.info.logical_key == Key::Named(NamedKey::Enter) {
+        Some(KeyAction::FindNext)
+    } else {
+        None
+    }
+}
+
+fn install_key_filter(
+    input: &mut TextInput,
+    owner_id: RawWidgetId,
+    callback: Callback<KeyAction>,
+    enter_finds_next: bool,
+) {
+    input
+        .base_mut()
+        .install_event_filter(owner_id, move |event| {
+            let Event::KeyboardInput(event) = event else {
+                return Ok(false);
+            };
+            let Some(action) = key_action(&event, enter_finds_next) else {
+                return Ok(false);
+            };
+            callback.invoke(action);
+            Ok(true)
+        });
+}
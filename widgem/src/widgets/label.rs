@@ -1,18 +1,40 @@
 use {
     crate::{
-        event::{FocusReason, MouseInputEvent},
+        accessibility::new_accessibility_node_id,
+        callback::Callback,
+        event::{
+            AccessibilityActionEvent, FocusInEvent, FocusOutEvent, FocusReason, KeyboardInputEvent,
+            LayoutEvent, MouseInputEvent,
+        },
         impl_widget_base,
-        text::TextHandler,
+        layout::default_layout,
+        text::{ElideMode, RichText, TextHandler},
         widget_initializer::{self, WidgetInitializer},
         RawWidgetId, Widget, WidgetBaseOf,
     },
-    accesskit::Role,
-    std::fmt::Display,
-    winit::event::{ElementState, MouseButton},
+    accesskit::{NodeId, Role},
+    anyhow::Result,
+    cosmic_text::Wrap,
+    std::{cmp::min, fmt::Display},
+    winit::{
+        event::{ElementState, MouseButton},
+        keyboard::{Key, KeyCode, NamedKey, PhysicalKey},
+    },
 };
 
+/// Displays plain or formatted text.
+///
+/// Rich text can contain links. A label with links can be focused, and its links
+/// can be activated with the mouse or the keyboard (arrow keys move between links,
+/// `Enter` and `Space` activate the focused link).
+///
+/// The text can be made [selectable](Self::set_selectable), [wrapped](Self::set_word_wrap)
+/// or [elided](Self::set_elide_mode) when the label is narrower than the text.
 pub struct Label {
     base: WidgetBaseOf<Self>,
+    is_selectable: bool,
+    // Accessibility nodes of the links.
+    link_node_ids: Vec<NodeId>,
 }
 
 impl Label {
@@ -20,15 +42,21 @@ impl Label {
         let id = base.id().raw();
         let text_style = base.compute_style();
         base.set_child(0, TextHandler::init(text, text_style))?
-            .set_host_id(id);
-        Ok(Label { base })
+            .set_host_id(id)
+            .set_read_only(true)
+            .set_cursor_visible_when_read_only(false)
+            .set_wrap(Wrap::None);
+        Ok(Label {
+            base,
+            is_selectable: false,
+            link_node_ids: Vec::new(),
+        })
     }
 
     pub fn init(text: String) -> impl WidgetInitializer<Output = Self> {
         widget_initializer::from_fallible_new_and_set(Self::new, Self::set_text, text)
     }
 
-    #[allow(dead_code)]
     fn text_widget(&self) -> &TextHandler {
         self.base.get_child::<TextHandler>(0).unwrap()
     }
@@ -39,11 +67,141 @@ impl Label {
 
     pub fn set_text(&mut self, text: impl Display) -> &mut Self {
         self.text_widget_mut().set_text(text);
+        self.text_changed();
+        self
+    }
+
+    /// Displays formatted text.
+    ///
+    /// The text can be created from a simple HTML markup with [RichText::from_html].
+    /// Links are focusable and can be activated by the user (see [on_link_activated](Self::on_link_activated)).
+    pub fn set_rich_text(&mut self, text: &RichText) -> &mut Self {
+        self.text_widget_mut().set_rich_text(text);
+        self.text_changed();
+        self
+    }
+
+    pub fn rich_text(&self) -> RichText {
+        self.text_widget().rich_text()
+    }
+
+    /// Adds a callback that is called when the user activates a link.
+    /// The callback receives the link target (the `href` attribute).
+    pub fn on_link_activated(&mut self, callback: Callback<String>) -> &mut Self {
+        self.text_widget_mut().on_link_activated(callback);
+        self
+    }
+
+    /// Allows the user to select the text with the mouse or the keyboard and copy it.
+    pub fn set_selectable(&mut self, selectable: bool) -> &mut Self {
+        if self.is_selectable == selectable {
+            return self;
+        }
+        self.is_selectable = selectable;
+        self.text_widget_mut().set_editable(selectable);
+        self.update_supports_focus();
+        self
+    }
+
+    pub fn is_selectable(&self) -> bool {
+        self.is_selectable
+    }
+
+    /// Wraps the text at word boundaries when the label is narrower than the text.
+    ///
+    /// Word wrap is disabled by default.
+    pub fn set_word_wrap(&mut self, word_wrap: bool) -> &mut Self {
+        self.text_widget_mut().set_word_wrap(word_wrap);
+        self.base.size_hint_changed();
+        self
+    }
+
+    pub fn is_word_wrap(&self) -> bool {
+        self.text_widget().is_word_wrap()
+    }
+
+    /// Sets how the text is shortened when the label is narrower than its preferred width.
+    ///
+    /// Elision only applies if [word wrap](Self::set_word_wrap) is disabled.
+    /// The default mode is [ElideMode::None].
+    pub fn set_elide_mode(&mut self, mode: ElideMode) -> &mut Self {
+        self.text_widget_mut().set_elide_mode(mode);
         self.base.size_hint_changed();
-        self.base.update();
         self
     }
 
+    pub fn elide_mode(&self) -> ElideMode {
+        self.text_widget().elide_mode()
+    }
+
+    /// Returns the selected part of the text.
+    pub fn selected_text(&mut self) -> Option<String> {
+        self.text_widget_mut().selected_text()
+    }
+
+    fn update_supports_focus(&mut self) {
+        let has_links = !self.text_widget().links().is_empty();
+        self.base
+            .set_supports_focus(self.is_selectable || has_links);
+    }
+
+    fn text_changed(&mut self) {
+        self.update_supports_focus();
+        let has_links = !self.text_widget().links().is_empty();
+        if has_links && self.base.is_focused() {
+            self.text_widget_mut().set_focused_link(Some(0));
+        }
+        self.base.size_hint_changed();
+        self.base.update();
+        self.update_link_nodes();
+    }
+
+    fn focus_link(&mut self, index: usize) {
+        self.text_widget_mut().set_focused_link(Some(index));
+        if !self.base.is_focused() {
+            self.base.set_focus(FocusReason::Accessibility);
+        }
+    }
+
+    fn link_index(&self, node_id: NodeId) -> Option<usize> {
+        self.link_node_ids.iter().position(|id| *id == node_id)
+    }
+
+    // Updates accessibility nodes of the links. Called when the text or the layout changes.
+    fn update_link_nodes(&mut self) {
+        let Some(window) = self.base.window() else {
+            return;
+        };
+        let links = self.text_widget().links().to_vec();
+        while self.link_node_ids.len() < links.len() {
+            let id = new_accessibility_node_id();
+            // Key 0 is used by the text widget.
+            let key = self.link_node_ids.len() as u32 + 1;
+            window.add_accessibility_node(Some(self.base.id().into()), id, key.into());
+            self.link_node_ids.push(id);
+        }
+        while self.link_node_ids.len() > links.len() {
+            if let Some(id) = self.link_node_ids.pop() {
+                window.remove_accessibility_node(Some(self.base.id().into()), id);
+            }
+        }
+        let text = self.text_widget().text();
+        let text_rect = self.text_widget().base().rect_in_window();
+        for (index, (id, (range, target))) in self.link_node_ids.iter().zip(links).enumerate() {
+            let mut node = accesskit::Node::new(Role::Link);
+            node.set_value(text.get(range).unwrap_or_default());
+            node.set_url(target);
+            node.add_action(accesskit::Action::Click);
+            node.add_action(accesskit::Action::Focus);
+            if let (Some(text_rect), Some(link_rect)) =
+                (text_rect, self.text_widget().link_rect(index))
+            {
+                node.set_bounds(link_rect.translate(text_rect.top_left()).into());
+            }
+            window.accessibility_node_updated(*id, Some(node));
+        }
+    }
+
     pub fn set_target(&mut self, target_id: RawWidgetId) -> &mut Self {
         let Some(window) = self.base.window() else {
             return self;
@@ -71,6 +229,12 @@ impl Label {
 impl Widget for Label {
     impl_widget_base!();
 
+    fn handle_layout(&mut self, _event: LayoutEvent) -> Result<()> {
+        default_layout(self);
+        self.update_link_nodes();
+        Ok(())
+    }
+
     fn handle_accessibility_node_request(&mut self) -> anyhow::Result<Option<accesskit::Node>> {
         let mut node = accesskit::Node::new(Role::Label);
         node.set_value(self.text_widget().text().as_str());
@@ -93,11 +257,90 @@ impl Widget for Label {
         }
         Ok(false)
     }
+
+    fn handle_keyboard_input(&mut self, event: KeyboardInputEvent) -> Result<bool> {
+        if self.is_selectable
+            && self
+                .text_widget_mut()
+                .handle_host_keyboard_input(event.clone())?
+        {
+            return Ok(true);
+        }
+        if !event.info.state.is_pressed() {
+            return Ok(false);
+        }
+        let num_links = self.text_widget().links().len();
+        if num_links == 0 {
+            return Ok(false);
+        }
+        let focused_link = self.text_widget().focused_link();
+        let next_link = match event.info.logical_key {
+            Key::Named(NamedKey::ArrowRight | NamedKey::ArrowDown) => {
+                focused_link.map_or(0, |index| min(index + 1, num_links - 1))
+            }
+            Key::Named(NamedKey::ArrowLeft | NamedKey::ArrowUp) => {
+                focused_link.map_or(0, |index| index.saturating_sub(1))
+            }
+            _ => {
+                let is_activation = event.info.physical_key == PhysicalKey::Code(KeyCode::Space)
+                    || event.info.logical_key == Key::Named(NamedKey::Space)
+                    || event.info.logical_key == Key::Named(NamedKey::Enter);
+                if is_activation && !event.info.repeat {
+                    if let Some(index) = focused_link {
+                        self.text_widget_mut().activate_link(index);
+                        return Ok(true);
+                    }
+                }
+                return Ok(false);
+            }
+        };
+        self.text_widget_mut().set_focused_link(Some(next_link));
+        Ok(true)
+    }
+
+    fn handle_focus_in(&mut self, event: FocusInEvent) -> Result<()> {
+        if self.is_selectable {
+            self.text_widget_mut().handle_host_focus_in(event.reason)?;
+        }
+        if self.text_widget().focused_link().is_none() && !self.text_widget().links().is_empty() {
+            self.text_widget_mut().set_focused_link(Some(0));
+        }
+        Ok(())
+    }
+
+    fn handle_focus_out(&mut self, _event: FocusOutEvent) -> Result<()> {
+        if self.is_selectable {
+            self.text_widget_mut().handle_host_focus_out()?;
+        }
+        self.text_widget_mut().set_focused_link(None);
+        Ok(())
+    }
+
+    fn handle_accessibility_action(&mut self, event: AccessibilityActionEvent) -> Result<bool> {
+        let Some(index) = self.link_index(event.target()) else {
+            return Ok(false);
+        };
+        match event.action() {
+            accesskit::Action::Click => {
+                self.focus_link(index);
+                self.text_widget_mut().activate_link(index);
+                Ok(true)
+            }
+            accesskit::Action::Focus => {
+                self.focus_link(index);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
 }
 
 impl Drop for Label {
     fn drop(&mut self) {
         if let Some(window) = self.base.window() {
+            while let Some(id) = self.link_node_ids.pop() {
+                window.remove_accessibility_node(Some(self.base.id().into()), id);
+            }
             let id = self.base.id().raw();
             if let Some(old_target_id) = window.label_to_target(id) {
                 window.remove_label_link(id, old_target_id);
@@ -3,21 +3,21 @@ use {
         callback::{Callback, Callbacks},
         event::{
             ActivateEvent, KeyboardInputEvent, LayoutEvent, MouseInputEvent, MouseLeaveEvent,
-            MouseMoveEvent, WindowFocusChangeEvent,
+            MouseMoveEvent, StyleChangeEvent, WindowFocusChangeEvent,
         },
         impl_widget_base,
         items::{
             with_index::{Items, ItemsMut},
             with_key::{ItemsWithKey, ItemsWithKeyMut},
         },
-        layout::{default_layout, default_size_hint_x, default_size_hint_y},
+        layout::{default_layout, default_size_hint_x, default_size_hint_y, Layout},
         shared_window::X11WindowType,
-        style::css::PseudoClass,
+        style::{common::ComputedIconStyle, css::PseudoClass},
         system::OrWarn,
         text::{TextHandler, TextStyle},
         types::{PhysicalPixels, Point},
         widget_initializer::{self, WidgetInitializer},
-        widgets::{Column, ScrollArea},
+        widgets::{Column, Image, ScrollArea},
         ChildKey, Widget, WidgetBase, WidgetBaseOf, WidgetExt, WindowRectRequest,
         WindowRectResponse,
     },
@@ -397,7 +397,8 @@ pub struct MenuAction {
 }
 
 impl MenuAction {
-    fn new(base: WidgetBaseOf<Self>, text: String) -> Self {
+    fn new(mut base: WidgetBaseOf<Self>, text: String) -> Self {
+        base.set_layout(Layout::HorizontalFirst);
         MenuAction {
             base,
             text,
@@ -428,14 +429,24 @@ impl Widget for MenuAction {
     impl_widget_base!();
 
     fn handle_declare_children_request(&mut self) -> anyhow::Result<()> {
+        let icon = self.base.compute_style::<ComputedIconStyle>().icon.clone();
         let text_style = self.base.compute_style::<TextStyle>();
-        self.base
-            .children_mut()
+        let mut children = self.base.children_mut();
+        let image = children.set_next_item(Image::init(None))?;
+        image.set_visible(icon.is_some());
+        image.set_prescaled(true);
+        image.set_pixmap(icon);
+        children
             .set_next_item(TextHandler::init(self.text.clone(), text_style))?
             .set_multiline(false);
         Ok(())
     }
 
+    fn handle_style_change(&mut self, _event: StyleChangeEvent) -> anyhow::Result<()> {
+        self.base.update();
+        Ok(())
+    }
+
     // Menu items are not really expanding.
     // However, the menu's OS window is sometimes slightly larger than requested.
     // In that case we want menu items to take all available space.
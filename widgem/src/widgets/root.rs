@@ -1,20 +1,102 @@
-use crate::{
-    impl_widget_base,
-    items::{
-        with_index::{Items, ItemsMut},
-        with_key::{ItemsWithKey, ItemsWithKeyMut},
+use {
+    crate::{
+        impl_widget_base,
+        inspector::InspectorState,
+        items::{
+            with_index::{Items, ItemsMut},
+            with_key::{ItemsWithKey, ItemsWithKeyMut},
+        },
+        style::Style,
+        widget_initializer::WidgetInitializer,
+        ChildKey, Widget, WidgetBase, WidgetBaseOf,
     },
-    widget_initializer::WidgetInitializer,
-    ChildKey, Widget, WidgetBase, WidgetBaseOf,
+    anyhow::Result,
+    std::{
+        io,
+        time::{Duration, SystemTime},
+    },
+    tracing::warn,
 };
 
+const STYLE_FILE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
 pub struct RootWidget {
     base: WidgetBaseOf<Self>,
+    inspector: Option<InspectorState>,
+    // Modification time of the style file that failed to load.
+    failed_style_file_modified: Option<SystemTime>,
+    // Set when the style file was not found, so that it's only reported once.
+    is_style_file_missing: bool,
 }
 
 impl RootWidget {
     pub fn new(base: WidgetBaseOf<Self>) -> Self {
-        Self { base }
+        Self {
+            base,
+            inspector: None,
+            failed_style_file_modified: None,
+            is_style_file_missing: false,
+        }
+    }
+
+    // Periodically checks if the style file has been modified and reloads it.
+    pub(crate) fn watch_style_file(&mut self) {
+        let callback = self
+            .base
+            .callback(|this, _| this.reload_style_file_if_modified());
+        self.base
+            .app()
+            .add_interval(STYLE_FILE_CHECK_INTERVAL, callback);
+    }
+
+    fn reload_style_file_if_modified(&mut self) -> Result<()> {
+        let style = self.base.app().style();
+        let Some(path) = style.file_path() else {
+            return Ok(());
+        };
+        let metadata = match fs_err::metadata(path) {
+            Ok(metadata) => metadata,
+            // The file may be temporarily missing while an editor saves it.
+            // The current style stays in use until the file reappears.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                if !self.is_style_file_missing {
+                    warn!("style file {:?} not found", path);
+                    self.is_style_file_missing = true;
+                }
+                return Ok(());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        self.is_style_file_missing = false;
+        let modified = metadata.modified()?;
+        if Some(modified) == style.file_modified_time()
+            || Some(modified) == self.failed_style_file_modified
+        {
+            return Ok(());
+        }
+        match Style::load_from_file(path) {
+            Ok(new_style) => {
+                self.failed_style_file_modified = None;
+                self.base.app().set_style(new_style);
+            }
+            Err(err) => {
+                warn!("failed to reload style from {:?}: {:?}", path, err);
+                self.failed_style_file_modified = Some(modified);
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn inspector_state(&self) -> Option<&InspectorState> {
+        self.inspector.as_ref()
+    }
+
+    pub(crate) fn inspector_state_mut(&mut self) -> Option<&mut InspectorState> {
+        self.inspector.as_mut()
+    }
+
+    pub(crate) fn set_inspector_state(&mut self, state: Option<InspectorState>) {
+        self.inspector = state;
     }
 
     pub fn set_main_content<WI: WidgetInitializer>(
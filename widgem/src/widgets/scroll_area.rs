@@ -2,7 +2,7 @@ use {
     crate::{
         event::{LayoutEvent, MouseScrollEvent},
         impl_widget_base,
-        layout::{default_layout, Layout, SizeHint},
+        layout::{default_layout, Layout, LayoutDirection, SizeHint},
         system::OrWarn,
         types::{Axis, LpxSuffix, PhysicalPixels, PpxSuffix, Rect},
         widget_initializer::{self, WidgetInitializer},
@@ -182,6 +182,15 @@ impl ScrollArea {
     //     }
     // }
 
+    // Returns -1 if increasing the horizontal scroll value moves the content to the right.
+    fn direction_x(&self) -> i32 {
+        if self.base.layout_direction() == LayoutDirection::RightToLeft {
+            -1
+        } else {
+            1
+        }
+    }
+
     fn relayout(&mut self) -> Result<()> {
         let geometry = self.base.geometry_or_err()?.clone();
 
@@ -357,8 +366,14 @@ impl ScrollArea {
             } else {
                 content_size_hint_y.preferred()
             };
+            // In right-to-left layout, the start of the content is aligned to the right edge.
+            let content_x = if self.base.layout_direction() == LayoutDirection::RightToLeft {
+                viewport_rect.size_x() - content_size_x + PhysicalPixels::from_i32(value_x)
+            } else {
+                PhysicalPixels::from_i32(-value_x)
+            };
             let content_rect = Rect::from_xywh(
-                PhysicalPixels::from_i32(-value_x),
+                content_x,
                 PhysicalPixels::from_i32(-value_y),
                 content_size_x,
                 content_size_y,
@@ -591,12 +606,13 @@ impl Widget for ScrollArea {
 
     fn handle_mouse_scroll(&mut self, event: MouseScrollEvent) -> Result<bool> {
         let delta = event.unified_delta(&self.base);
+        let direction_x = self.direction_x();
 
         let scroll_x = self
             .base
             .get_child_mut::<ScrollBar>(INDEX_SCROLL_BAR_X)
             .unwrap();
-        let new_value_x = scroll_x.value() - delta.x.round() as i32;
+        let new_value_x = scroll_x.value() - direction_x * delta.x.round() as i32;
         scroll_x.set_value(new_value_x.clamp(
             *scroll_x.value_range().start(),
             *scroll_x.value_range().end(),
@@ -645,8 +661,9 @@ impl Widget for ScrollArea {
         };
 
         if dx.to_i32() != 0 {
+            let direction_x = self.direction_x();
             let scroll_bar_x = self.base.get_child_mut::<ScrollBar>(INDEX_SCROLL_BAR_X)?;
-            scroll_bar_x.set_value(scroll_bar_x.value() + dx.to_i32());
+            scroll_bar_x.set_value(scroll_bar_x.value() + direction_x * dx.to_i32());
         }
 
         if dy.to_i32() != 0 {
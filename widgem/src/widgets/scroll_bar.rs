@@ -3,10 +3,10 @@ use {
         callback::{Callback, Callbacks},
         event::{
             Event, FocusInEvent, FocusOutEvent, FocusReason, KeyboardInputEvent, LayoutEvent,
-            MouseScrollEvent,
+            MouseScrollEvent, StyleChangeEvent,
         },
         impl_widget_base,
-        layout::{default_layout, Layout, SizeHint},
+        layout::{default_layout, Layout, LayoutDirection, SizeHint},
         system::OrWarn,
         types::{Axis, PhysicalPixels, Point, PpxSuffix, Rect, Size},
         widget_initializer::{self, WidgetInitializer},
@@ -30,6 +30,10 @@ use {
 pub struct ScrollBar {
     base: WidgetBaseOf<Self>,
     axis: Axis,
+    // True for horizontal scroll bars in right-to-left layout.
+    // The start of the range is on the right side.
+    is_mirrored: bool,
+    // Distance from the start of the pager to the start of the grip.
     current_grip_pos: PhysicalPixels,
     max_slider_pos: PhysicalPixels,
     grip_size: Size,
@@ -119,6 +123,7 @@ impl ScrollBar {
         let mut this = ScrollBar {
             base,
             axis,
+            is_mirrored: false,
             current_grip_pos: 0.ppx(),
             max_slider_pos: 0.ppx(),
             grip_size: Size::default(),
@@ -243,20 +248,33 @@ impl ScrollBar {
 
     fn set_axis_inner(&mut self, axis: Axis) -> &mut Self {
         self.axis = axis;
+        self.is_mirrored =
+            axis == Axis::X && self.base.layout_direction() == LayoutDirection::RightToLeft;
         self.set_layout(match axis {
             Axis::X => Layout::HorizontalFirst,
             Axis::Y => Layout::VerticalFirst,
         });
         match axis {
             Axis::X => {
+                let is_mirrored = self.is_mirrored;
                 let decrease = self.base.get_child_mut::<Button>(INDEX_DECREASE).unwrap();
-                decrease.set_text(names::SCROLL_LEFT);
-                decrease.add_class("scroll_left".into());
+                decrease.set_text(if is_mirrored {
+                    names::SCROLL_RIGHT
+                } else {
+                    names::SCROLL_LEFT
+                });
+                decrease.set_class("scroll_left".into(), !is_mirrored);
+                decrease.set_class("scroll_right".into(), is_mirrored);
                 decrease.remove_class("scroll_up".into());
 
                 let increase = self.base.get_child_mut::<Button>(INDEX_INCREASE).unwrap();
-                increase.set_text(names::SCROLL_RIGHT);
-                increase.add_class("scroll_right".into());
+                increase.set_text(if is_mirrored {
+                    names::SCROLL_LEFT
+                } else {
+                    names::SCROLL_RIGHT
+                });
+                increase.set_class("scroll_right".into(), !is_mirrored);
+                increase.set_class("scroll_left".into(), is_mirrored);
                 increase.remove_class("scroll_down".into());
 
                 let grip = self
@@ -273,11 +291,13 @@ impl ScrollBar {
                 let decrease = self.base.get_child_mut::<Button>(INDEX_DECREASE).unwrap();
                 decrease.set_text(names::SCROLL_UP);
                 decrease.remove_class("scroll_left".into());
+                decrease.remove_class("scroll_right".into());
                 decrease.add_class("scroll_up".into());
 
                 let increase = self.base.get_child_mut::<Button>(INDEX_INCREASE).unwrap();
                 increase.set_text(names::SCROLL_DOWN);
                 increase.remove_class("scroll_right".into());
+                increase.remove_class("scroll_left".into());
                 increase.add_class("scroll_down".into());
 
                 let grip = self
@@ -321,7 +341,12 @@ impl ScrollBar {
         if let Some((start_mouse_pos, start_slider_pos)) = self.slider_grab_pos {
             match self.axis {
                 Axis::X => {
-                    let new_pos = start_slider_pos - start_mouse_pos.x() + pos_in_window.x();
+                    let mouse_delta = if self.is_mirrored {
+                        start_mouse_pos.x() - pos_in_window.x()
+                    } else {
+                        pos_in_window.x() - start_mouse_pos.x()
+                    };
+                    let new_pos = start_slider_pos + mouse_delta;
                     self.current_grip_pos = new_pos.clamp(0.ppx(), self.max_slider_pos);
                     let new_value = if self.max_slider_pos == 0.ppx() {
                         *self.value_range.start()
@@ -370,7 +395,12 @@ impl ScrollBar {
         };
         self.pager_direction = match self.axis {
             Axis::X => {
-                if grip_rect_in_window.right() < pos_in_window.x() {
+                let is_after_grip = if self.is_mirrored {
+                    grip_rect_in_window.left() > pos_in_window.x()
+                } else {
+                    grip_rect_in_window.right() < pos_in_window.x()
+                };
+                if is_after_grip {
                     1
                 } else {
                     -1
@@ -409,6 +439,9 @@ impl ScrollBar {
 
         if self.pager_direction > 0 {
             let condition = match self.axis {
+                Axis::X if self.is_mirrored => {
+                    grip_rect_in_window.left() > self.pager_mouse_pos_in_window.x()
+                }
                 Axis::X => grip_rect_in_window.right() < self.pager_mouse_pos_in_window.x(),
                 Axis::Y => grip_rect_in_window.bottom() < self.pager_mouse_pos_in_window.y(),
             };
@@ -417,6 +450,9 @@ impl ScrollBar {
             }
         } else {
             let condition = match self.axis {
+                Axis::X if self.is_mirrored => {
+                    grip_rect_in_window.right() < self.pager_mouse_pos_in_window.x()
+                }
                 Axis::X => grip_rect_in_window.left() > self.pager_mouse_pos_in_window.x(),
                 Axis::Y => grip_rect_in_window.top() > self.pager_mouse_pos_in_window.y(),
             };
@@ -528,6 +564,9 @@ impl ScrollBar {
     fn update_grip_pos(&mut self) {
         self.current_grip_pos = self.value_to_slider_pos();
         let shift = match self.axis {
+            Axis::X if self.is_mirrored => {
+                Point::new(self.max_slider_pos - self.current_grip_pos, 0.ppx())
+            }
             Axis::X => Point::new(self.current_grip_pos, 0.ppx()),
             Axis::Y => Point::new(0.ppx(), self.current_grip_pos),
         };
@@ -662,6 +701,16 @@ impl Widget for ScrollBar {
         Ok(())
     }
 
+    fn handle_style_change(&mut self, _event: StyleChangeEvent) -> Result<()> {
+        let is_mirrored =
+            self.axis == Axis::X && self.base.layout_direction() == LayoutDirection::RightToLeft;
+        if self.is_mirrored != is_mirrored {
+            self.set_axis_inner(self.axis);
+            self.update_grip_pos();
+        }
+        Ok(())
+    }
+
     fn handle_mouse_scroll(&mut self, event: MouseScrollEvent) -> Result<bool> {
         //println!("handle_mouse_scroll: event={:?}", event);
         let delta = event.unified_delta(&self.base);
@@ -692,11 +741,19 @@ impl Widget for ScrollBar {
                     Ok(true)
                 }
                 NamedKey::ArrowLeft => {
-                    self.decrease_internal(false);
+                    if self.is_mirrored {
+                        self.increase_internal(false);
+                    } else {
+                        self.decrease_internal(false);
+                    }
                     Ok(true)
                 }
                 NamedKey::ArrowRight => {
-                    self.increase_internal(false);
+                    if self.is_mirrored {
+                        self.decrease_internal(false);
+                    } else {
+                        self.increase_internal(false);
+                    }
                     Ok(true)
                 }
                 NamedKey::ArrowUp => {
@@ -1,37 +1,273 @@
 use {
     crate::{
+        callback::{Callback, Callbacks},
         event::{
-            AccessibilityActionEvent, FocusInEvent, FocusOutEvent, FocusReason, InputMethodEvent,
-            KeyboardInputEvent, StyleChangeEvent,
+            AccessibilityActionEvent, DrawEvent, FocusInEvent, FocusOutEvent, FocusReason,
+            InputMethodEvent, KeyboardInputEvent, LayoutEvent, StyleChangeEvent,
         },
         impl_widget_base,
-        layout::{default_size_hint_x, default_size_hint_y, SizeHint},
+        layout::{default_layout, default_size_hint_x, default_size_hint_y, SizeHint},
+        shortcut::standard_shortcuts,
         style::{
             common::ComputedElementStyle,
-            css::{convert_font, convert_height, convert_width, PseudoClass, StyleSelector},
-            defaults::{DEFAULT_MIN_WIDTH_EM, DEFAULT_PREFERRED_WIDTH_EM},
+            css::{
+                convert_background_color, convert_font, convert_height, convert_main_color,
+                convert_padding, convert_width, PseudoClass, StyleSelector,
+            },
+            defaults::{self, DEFAULT_MIN_WIDTH_EM, DEFAULT_PREFERRED_WIDTH_EM},
             Styles,
         },
         system::OrWarn,
-        text::TextHandler,
-        types::PhysicalPixels,
+        text::{
+            editor::draw_run_glyphs, text_handler::convert_color, EditHistoryState, FindQuery,
+            LinePosition, RichText, TextFormat, TextHandler, TextStyle,
+        },
+        types::{PhysicalPixels, Point, PpxSuffix},
         widget_initializer::{self, WidgetInitializer},
-        widgets::{Row, ScrollArea},
+        widgets::{FindBar, Row, ScrollArea},
         ChildKey, Widget, WidgetBaseOf, WidgetExt,
     },
     accesskit::ActionData,
-    anyhow::{bail, Result},
-    cosmic_text::Wrap,
-    std::{fmt::Display, rc::Rc},
+    anyhow::{bail, Context as _, Result},
+    cosmic_text::{Buffer, Shaping, Wrap},
+    itertools::Itertools,
+    std::{cmp::max, fmt::Display, ops::Range, rc::Rc},
+    tiny_skia::{Color, Paint, Pixmap, Shader, Transform},
     tracing::warn,
-    winit::window::CursorIcon,
+    winit::{
+        keyboard::{Key, NamedKey},
+        window::CursorIcon,
+    },
 };
 
+const KEY_FIND_BAR: &str = "find_bar";
+
+const INDEX_GUTTER: u32 = 0;
+const INDEX_TEXT_HANDLER: u32 = 1;
+
+// Line numbers are padded to at least this number of digits.
+const MIN_LINE_NUMBER_DIGITS: usize = 2;
+
+// Displays line numbers to the left of the text.
+struct Gutter {
+    base: WidgetBaseOf<Self>,
+    text_style: Rc<TextStyle>,
+    style: Rc<TextAreaStyle>,
+    lines: Vec<LinePosition>,
+    current_line: usize,
+    is_current_line_highlighted: bool,
+    // Shaped line numbers. Recreated when the number of lines or the font changes.
+    numbers: Option<Buffer>,
+    // Width of the widest line number.
+    number_width: f32,
+    pixmap: Option<Pixmap>,
+}
+
+impl Gutter {
+    fn new(base: WidgetBaseOf<Self>, text_style: Rc<TextStyle>, style: Rc<TextAreaStyle>) -> Self {
+        let mut this = Self {
+            base,
+            text_style,
+            style,
+            lines: Vec::new(),
+            current_line: 0,
+            is_current_line_highlighted: false,
+            numbers: None,
+            number_width: 0.0,
+            pixmap: None,
+        };
+        this.update_numbers();
+        this
+    }
+
+    fn init(
+        text_style: Rc<TextStyle>,
+        style: Rc<TextAreaStyle>,
+    ) -> impl WidgetInitializer<Output = Self> {
+        widget_initializer::from_new(move |base| {
+            Gutter::new(base, text_style.clone(), style.clone())
+        })
+    }
+
+    fn set_styles(&mut self, text_style: Rc<TextStyle>, style: Rc<TextAreaStyle>) {
+        if self.text_style != text_style {
+            self.text_style = text_style;
+            self.numbers = None;
+            self.update_numbers();
+        }
+        self.style = style;
+        self.pixmap = None;
+        self.base.size_hint_changed();
+        self.base.update();
+    }
+
+    fn set_lines(&mut self, lines: &[LinePosition]) {
+        if self.lines == lines {
+            return;
+        }
+        if self.lines.last().map(|line| line.bottom) != lines.last().map(|line| line.bottom) {
+            self.base.size_hint_changed();
+        }
+        self.lines = lines.to_vec();
+        self.update_numbers();
+        self.pixmap = None;
+        self.base.update();
+    }
+
+    fn set_current_line(&mut self, current_line: usize, is_current_line_highlighted: bool) {
+        if self.current_line == current_line
+            && self.is_current_line_highlighted == is_current_line_highlighted
+        {
+            return;
+        }
+        self.current_line = current_line;
+        self.is_current_line_highlighted = is_current_line_highlighted;
+        self.pixmap = None;
+        self.base.update();
+    }
+
+    fn update_numbers(&mut self) {
+        let count = max(self.lines.len(), 1);
+        if self
+            .numbers
+            .as_ref()
+            .is_some_and(|numbers| numbers.lines.len() == count)
+        {
+            return;
+        }
+        let digits = max(count.to_string().len(), MIN_LINE_NUMBER_DIGITS);
+        let text_style = &self.text_style;
+        let attrs = self.base.app().font_attrs(&text_style.font);
+        let (numbers, number_width) = self.base.app().with_font_system(|font_system| {
+            let mut buffer = Buffer::new(font_system, text_style.font_metrics);
+            let mut borrowed = buffer.borrow_with(font_system);
+            borrowed.set_wrap(Wrap::None);
+            borrowed.set_text(&"0".repeat(digits), &attrs.as_attrs(), Shaping::Advanced);
+            let number_width = borrowed
+                .layout_runs()
+                .map(|run| run.line_w)
+                .fold(0.0, f32::max);
+            let text = (1..=count).join("\n");
+            borrowed.set_text(&text, &attrs.as_attrs(), Shaping::Advanced);
+            borrowed.shape_until_scroll(false);
+            (buffer, number_width)
+        });
+        if self.number_width != number_width {
+            self.number_width = number_width;
+            self.base.size_hint_changed();
+        }
+        self.numbers = Some(numbers);
+    }
+
+    fn size_x(&self) -> PhysicalPixels {
+        PhysicalPixels::from_i32(self.number_width.ceil() as i32) + self.style.gutter_padding_x * 2
+    }
+
+    fn draw_pixmap(&self, pixmap: &mut Pixmap) {
+        if let Some(background) = self.style.gutter_background {
+            pixmap.fill(background);
+        }
+        if self.is_current_line_highlighted {
+            let rect = self.lines.get(self.current_line).and_then(|line| {
+                tiny_skia::Rect::from_ltrb(0.0, line.top, pixmap.width() as f32, line.bottom)
+            });
+            if let Some(rect) = rect {
+                let paint = Paint {
+                    shader: Shader::SolidColor(self.text_style.current_line_background),
+                    ..Paint::default()
+                };
+                pixmap.fill_rect(rect, &paint, Transform::default(), None);
+            }
+        }
+        let Some(numbers) = &self.numbers else {
+            return;
+        };
+        let right = (self.size_x() - self.style.gutter_padding_x).to_i32() as f32;
+        self.base
+            .app()
+            .with_font_system_and_swash_cache(|font_system, swash_cache| {
+                for run in numbers.layout_runs() {
+                    let Some(line) = self.lines.get(run.line_i) else {
+                        continue;
+                    };
+                    // The number of the current line has the same color as the text.
+                    let color = convert_color(if run.line_i == self.current_line {
+                        self.text_style.text_color
+                    } else {
+                        self.style.gutter_text_color
+                    });
+                    draw_run_glyphs(
+                        font_system,
+                        swash_cache,
+                        &run,
+                        (right - run.line_w, line.baseline),
+                        0,
+                        |_| color,
+                        &mut |x, y, w, h, c| {
+                            let paint = Paint {
+                                shader: Shader::SolidColor(Color::from_rgba8(
+                                    c.r(),
+                                    c.g(),
+                                    c.b(),
+                                    c.a(),
+                                )),
+                                anti_alias: false,
+                                ..Paint::default()
+                            };
+                            if let Some(rect) =
+                                tiny_skia::Rect::from_xywh(x as f32, y as f32, w as f32, h as f32)
+                            {
+                                pixmap.fill_rect(rect, &paint, Transform::default(), None);
+                            }
+                        },
+                    );
+                }
+            });
+    }
+}
+
+impl Widget for Gutter {
+    impl_widget_base!();
+
+    fn handle_size_hint_x_request(&mut self, _size_y: Option<PhysicalPixels>) -> Result<SizeHint> {
+        let size = self.size_x();
+        Ok(SizeHint::new_fixed(size, size))
+    }
+
+    fn handle_size_hint_y_request(&mut self, _size_x: PhysicalPixels) -> Result<SizeHint> {
+        let size = self.lines.last().map_or(0.ppx(), |line| {
+            PhysicalPixels::from_i32(line.bottom.ceil() as i32)
+        });
+        Ok(SizeHint::new_fixed(size, size))
+    }
+
+    fn handle_draw(&mut self, event: DrawEvent) -> Result<()> {
+        let size = self.base.size_or_err()?;
+        let size_x = max(1, size.x().to_i32()) as u32;
+        let size_y = max(1, size.y().to_i32()) as u32;
+        if self
+            .pixmap
+            .as_ref()
+            .is_none_or(|pixmap| pixmap.width() != size_x || pixmap.height() != size_y)
+        {
+            let mut pixmap = Pixmap::new(size_x, size_y).context("failed to create pixmap")?;
+            self.draw_pixmap(&mut pixmap);
+            self.pixmap = Some(pixmap);
+        }
+        if let Some(pixmap) = &self.pixmap {
+            event.draw_pixmap(Point::default(), pixmap.as_ref(), Default::default());
+        }
+        Ok(())
+    }
+}
+
 pub struct TextArea {
     base: WidgetBaseOf<Self>,
     style: Rc<TextAreaStyle>,
     expand_to_fit_content_x: bool,
     expand_to_fit_content_y: bool,
+    on_editing_finished: Callbacks<()>,
+    is_find_bar_enabled: bool,
 }
 
 impl TextArea {
@@ -40,25 +276,49 @@ impl TextArea {
         base.set_supports_focus(true);
         base.set_cursor_icon(CursorIcon::Text);
         let host_id = base.id();
-        let text_style = base.compute_style();
-        base.set_main_child(ScrollArea::init())?
+        let text_style: Rc<TextStyle> = base.compute_style();
+        let style: Rc<TextAreaStyle> = base.compute_style();
+        let text_changed = base
+            .callback(|this: &mut Self, _: String| this.update_gutter())
+            .with_send_signals_on_setter_calls(true);
+        let cursor_position_changed = base
+            .callback(|this: &mut Self, _: usize| this.update_gutter_current_line())
+            .with_send_signals_on_setter_calls(true);
+        let wrapper = base
+            .set_main_child(ScrollArea::init())?
             .set_size_x_fixed(Some(false))
             .set_size_y_fixed(Some(false))
             .set_allow_expand_x(false)
             .set_content(Row::init())?
-            .add_class("text_area_text_wrapper".into())
-            .contents_mut()
-            .set_next_item(TextHandler::init(String::new(), text_style))?
+            .add_class("text_area_text_wrapper".into());
+        wrapper
+            .base_mut()
+            .set_child(
+                INDEX_GUTTER,
+                Gutter::init(text_style.clone(), style.clone()),
+            )?
+            .set_visible(false)
+            .set_size_y_fixed(Some(false));
+        wrapper
+            .base_mut()
+            .set_child(
+                INDEX_TEXT_HANDLER,
+                TextHandler::init(String::new(), text_style),
+            )?
             .set_multiline(true)
             .set_editable(true)
             .set_wrap(Wrap::WordOrGlyph)
             .set_host_id(host_id.into())
+            .on_text_changed(text_changed)
+            .on_cursor_position_changed(cursor_position_changed)
             .set_size_x_fixed(Some(false))
             .set_size_y_fixed(Some(false));
         Ok(TextArea {
-            style: base.compute_style(),
+            style,
             expand_to_fit_content_x: false,
             expand_to_fit_content_y: false,
+            on_editing_finished: Callbacks::default(),
+            is_find_bar_enabled: false,
             base,
         })
     }
@@ -68,22 +328,62 @@ impl TextArea {
         widget_initializer::from_fallible_new(Self::new)
     }
 
-    // fn text_handler(&self) -> anyhow::Result<&TextHandler> {
-    //     self.base
-    //         .get_child::<ScrollArea>(ChildKey::main())?
-    //         .content::<Row>()?
-    //         .base()
-    //         .get_child(0u32)
-    // }
+    fn text_handler(&self) -> anyhow::Result<&TextHandler> {
+        self.base
+            .get_child::<ScrollArea>(ChildKey::main())?
+            .content::<Row>()?
+            .base()
+            .get_child(INDEX_TEXT_HANDLER)
+    }
 
     fn text_handler_mut(&mut self) -> anyhow::Result<&mut TextHandler> {
         self.base
             .get_child_mut::<ScrollArea>(ChildKey::main())?
             .content_mut::<Row>()?
             .base_mut()
-            .get_child_mut(0u32)
+            .get_child_mut(INDEX_TEXT_HANDLER)
+    }
+
+    fn gutter(&self) -> anyhow::Result<&Gutter> {
+        self.base
+            .get_child::<ScrollArea>(ChildKey::main())?
+            .content::<Row>()?
+            .base()
+            .get_child(INDEX_GUTTER)
     }
 
+    fn gutter_mut(&mut self) -> anyhow::Result<&mut Gutter> {
+        self.base
+            .get_child_mut::<ScrollArea>(ChildKey::main())?
+            .content_mut::<Row>()?
+            .base_mut()
+            .get_child_mut(INDEX_GUTTER)
+    }
+
+    // Passes the current positions of the lines to the line number gutter.
+    fn update_gutter(&mut self) -> Result<()> {
+        if !self.are_line_numbers_visible() {
+            return Ok(());
+        }
+        let lines = self.text_handler_mut()?.line_positions().to_vec();
+        self.gutter_mut()?.set_lines(&lines);
+        self.update_gutter_current_line()
+    }
+
+    // Passes the line containing the cursor to the line number gutter.
+    fn update_gutter_current_line(&mut self) -> Result<()> {
+        if !self.are_line_numbers_visible() {
+            return Ok(());
+        }
+        let handler = self.text_handler()?;
+        let current_line = handler.cursor().line;
+        let is_current_line_highlighted = handler.is_current_line_highlighted();
+        self.gutter_mut()?
+            .set_current_line(current_line, is_current_line_highlighted);
+        Ok(())
+    }
+
+    /// Replaces the text. The undo history is cleared.
     pub fn set_text(&mut self, text: impl Display) {
         let Some(handler) = self.text_handler_mut().or_warn() else {
             return;
@@ -91,6 +391,352 @@ impl TextArea {
         handler.set_text(text);
     }
 
+    /// Inserts `text` at the end and moves the cursor there.
+    ///
+    /// The text can be appended even if the text area is [read-only](Self::set_read_only),
+    /// e.g. to display log output.
+    pub fn append(&mut self, text: &str) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.append(text);
+        }
+        self
+    }
+
+    /// Replaces the text with formatted text. The undo history is cleared.
+    pub fn set_rich_text(&mut self, text: &RichText) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.set_rich_text(text);
+        }
+        self
+    }
+
+    /// Returns the text with its formatting.
+    pub fn rich_text(&self) -> RichText {
+        self.text_handler()
+            .or_warn()
+            .map(|handler| handler.rich_text())
+            .unwrap_or_default()
+    }
+
+    /// Returns the format of the character at the specified byte offset in the text.
+    pub fn format_at(&self, offset: usize) -> TextFormat {
+        self.text_handler()
+            .or_warn()
+            .map(|handler| handler.format_at(offset))
+            .unwrap_or_default()
+    }
+
+    /// Sets the format of the specified byte range of the text.
+    pub fn set_format(&mut self, range: Range<usize>, format: &TextFormat) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.set_format(range, format);
+        }
+        self
+    }
+
+    /// Makes the selected text bold or, if all of it is already bold, removes bold.
+    pub fn toggle_bold(&mut self) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.toggle_bold();
+        }
+        self
+    }
+
+    /// Makes the selected text italic or, if all of it is already italic, removes italic.
+    pub fn toggle_italic(&mut self) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.toggle_italic();
+        }
+        self
+    }
+
+    /// Underlines the selected text or, if all of it is already underlined, removes underline.
+    pub fn toggle_underline(&mut self) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.toggle_underline();
+        }
+        self
+    }
+
+    /// Enables the standard shortcuts that change formatting of the selected text
+    /// (`Ctrl+B`, `Ctrl+I` and `Ctrl+U`). Disabled by default.
+    pub fn set_rich_text_editable(&mut self, editable: bool) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.set_rich_text_editable(editable);
+        }
+        self
+    }
+
+    pub fn is_rich_text_editable(&self) -> bool {
+        self.text_handler()
+            .or_warn()
+            .is_some_and(|handler| handler.is_rich_text_editable())
+    }
+
+    /// Reverts the last edit of the text.
+    pub fn undo(&mut self) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.undo();
+        }
+        self
+    }
+
+    /// Repeats the last edit reverted by [undo](Self::undo).
+    pub fn redo(&mut self) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.redo();
+        }
+        self
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.text_handler()
+            .or_warn()
+            .is_some_and(|handler| handler.can_undo())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.text_handler()
+            .or_warn()
+            .is_some_and(|handler| handler.can_redo())
+    }
+
+    /// Adds a callback that is called when availability of undo or redo changes.
+    pub fn on_history_changed(&mut self, callback: Callback<EditHistoryState>) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.on_history_changed(callback);
+        }
+        self
+    }
+
+    /// Highlights all matches of the query in the text. Returns the number of matches.
+    ///
+    /// The highlighting is updated automatically when the text changes. Its color can be
+    /// changed with the `::target-text` CSS pseudo-element.
+    /// Returns an error if the query contains an invalid regular expression.
+    pub fn find(&mut self, query: FindQuery) -> Result<usize> {
+        self.text_handler_mut()?.find(query)
+    }
+
+    /// Removes the find query and the highlighting of its matches.
+    pub fn clear_find(&mut self) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.clear_find();
+        }
+        self
+    }
+
+    pub fn find_query(&self) -> Option<FindQuery> {
+        self.text_handler()
+            .or_warn()
+            .and_then(|handler| handler.find_query().cloned())
+    }
+
+    /// Returns the byte ranges of the text that match the find query.
+    pub fn find_matches(&self) -> Vec<Range<usize>> {
+        self.text_handler()
+            .or_warn()
+            .map(|handler| handler.find_matches().to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Selects the next match after the cursor and scrolls to it, wrapping around
+    /// at the end of the text. Returns false if there are no matches.
+    pub fn find_next(&mut self) -> bool {
+        self.text_handler_mut()
+            .or_warn()
+            .is_some_and(|handler| handler.find_next())
+    }
+
+    /// Selects the previous match before the cursor and scrolls to it, wrapping around
+    /// at the start of the text. Returns false if there are no matches.
+    pub fn find_previous(&mut self) -> bool {
+        self.text_handler_mut()
+            .or_warn()
+            .is_some_and(|handler| handler.find_previous())
+    }
+
+    /// Replaces the selected match and selects the next one.
+    /// Returns false if there are no matches.
+    ///
+    /// If the query is a regular expression, `replacement` can refer to its capture groups
+    /// (e.g. `$1` or `${name}`). The replacement can be reverted with a single [undo](Self::undo).
+    pub fn replace(&mut self, replacement: &str) -> bool {
+        self.text_handler_mut()
+            .or_warn()
+            .is_some_and(|handler| handler.replace(replacement))
+    }
+
+    /// Replaces all matches. Returns the number of replaced matches.
+    ///
+    /// If the query is a regular expression, `replacement` can refer to its capture groups
+    /// (e.g. `$1` or `${name}`). The replacement can be reverted with a single [undo](Self::undo).
+    pub fn replace_all(&mut self, replacement: &str) -> usize {
+        self.text_handler_mut()
+            .or_warn()
+            .map(|handler| handler.replace_all(replacement))
+            .unwrap_or(0)
+    }
+
+    /// Enables the built-in [FindBar] that is displayed below the text when
+    /// the user presses `Ctrl+F`. Disabled by default.
+    ///
+    /// `F3` and `Shift+F3` select the next and the previous match, and `Escape` closes the find bar.
+    pub fn set_find_bar_enabled(&mut self, enabled: bool) -> &mut Self {
+        if self.is_find_bar_enabled == enabled {
+            return self;
+        }
+        self.is_find_bar_enabled = enabled;
+        if !enabled {
+            self.base.remove_child(KEY_FIND_BAR).or_warn();
+            self.clear_find();
+            return self;
+        }
+        let query_changed = self.base.callback(|this, query: FindQuery| {
+            this.find_from_bar(query);
+            Ok(())
+        });
+        let find_next = self.base.callback(|this, _| {
+            this.find_next();
+            Ok(())
+        });
+        let find_previous = self.base.callback(|this, _| {
+            this.find_previous();
+            Ok(())
+        });
+        let replace = self.base.callback(|this, replacement: String| {
+            this.replace(&replacement);
+            Ok(())
+        });
+        let replace_all = self.base.callback(|this, replacement: String| {
+            this.replace_all(&replacement);
+            Ok(())
+        });
+        let closed = self.base.callback(|this, _| {
+            this.clear_find();
+            this.base.set_focus(FocusReason::Tab);
+            Ok(())
+        });
+        if let Some(find_bar) = self.base.set_child(KEY_FIND_BAR, FindBar::init()).or_warn() {
+            find_bar
+                .set_visible(false)
+                .set_size_y_fixed(Some(true))
+                .on_query_changed(query_changed)
+                .on_find_next(find_next)
+                .on_find_previous(find_previous)
+                .on_replace(replace)
+                .on_replace_all(replace_all)
+                .on_closed(closed);
+        }
+        self
+    }
+
+    pub fn is_find_bar_enabled(&self) -> bool {
+        self.is_find_bar_enabled
+    }
+
+    /// Shows the built-in find bar (if it's [enabled](Self::set_find_bar_enabled))
+    /// and focuses its find input. The selected text is used as the initial query.
+    pub fn show_find_bar(&mut self) -> &mut Self {
+        let selected_text = self
+            .text_handler_mut()
+            .or_warn()
+            .and_then(|handler| handler.selected_text())
+            .filter(|text| !text.contains('\n'));
+        let Ok(find_bar) = self.base.get_child_mut::<FindBar>(KEY_FIND_BAR) else {
+            return self;
+        };
+        find_bar.set_visible(true).focus_find_input();
+        let query = find_bar.query();
+        if let Some(text) = selected_text.filter(|text| *text != query.pattern) {
+            // The find bar reports the new query with `on_query_changed`.
+            find_bar.set_pattern(text);
+        } else {
+            self.find_from_bar(query);
+        }
+        self
+    }
+
+    fn find_from_bar(&mut self, query: FindQuery) {
+        if self.find(query).is_err() {
+            // The user is still typing the regular expression.
+            self.clear_find();
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.text_handler()
+            .or_warn()
+            .map(|handler| handler.text())
+            .unwrap_or_default()
+    }
+
+    /// Prevents the user from modifying the text.
+    ///
+    /// The text can still be focused, selected, copied and navigated with the keyboard.
+    /// The text area has the `:read-only` pseudo-class in this mode.
+    pub fn set_read_only(&mut self, read_only: bool) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.set_read_only(read_only);
+        }
+        self.base.set_pseudo_class(PseudoClass::ReadOnly, read_only);
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.text_handler()
+            .or_warn()
+            .is_some_and(|handler| handler.is_read_only())
+    }
+
+    /// Shows or hides the text cursor in [read-only](Self::set_read_only) mode.
+    /// The cursor is visible by default.
+    pub fn set_cursor_visible_when_read_only(&mut self, visible: bool) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.set_cursor_visible_when_read_only(visible);
+        }
+        self
+    }
+
+    /// Adds a callback that is called when the text changes.
+    pub fn on_text_changed(&mut self, callback: Callback<String>) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.on_text_changed(callback);
+        }
+        self
+    }
+
+    /// Adds a callback that is called when the cursor moves.
+    ///
+    /// The callback receives the byte offset of the cursor in the text.
+    pub fn on_cursor_position_changed(&mut self, callback: Callback<usize>) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.on_cursor_position_changed(callback);
+        }
+        self
+    }
+
+    /// Adds a callback that is called when the selection changes.
+    ///
+    /// The callback receives the byte range of the selected text or `None` if
+    /// nothing is selected.
+    pub fn on_selection_changed(&mut self, callback: Callback<Option<Range<usize>>>) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.on_selection_changed(callback);
+        }
+        self
+    }
+
+    /// Adds a callback that is called when the text area loses focus after the user
+    /// has modified the text.
+    ///
+    /// The callback is not called if the text wasn't modified since the last time it was called
+    /// or since the text was replaced with [set_text](Self::set_text).
+    pub fn on_editing_finished(&mut self, callback: Callback<()>) -> &mut Self {
+        self.on_editing_finished.add(callback);
+        self
+    }
+
     pub fn set_expand_to_fit_content_x(&mut self, value: bool) -> &mut Self {
         if self.expand_to_fit_content_x != value {
             self.expand_to_fit_content_x = value;
@@ -112,6 +758,72 @@ impl TextArea {
             .set_expand_to_fit_content_y(value)
     }
 
+    /// Shows line numbers to the left of the text. They are hidden by default.
+    ///
+    /// The line numbers scroll together with the text. Their appearance is specified
+    /// by the `TextArea::gutter` CSS pseudo-element (`color`, `background` and `padding`).
+    pub fn set_line_numbers_visible(&mut self, visible: bool) -> &mut Self {
+        if let Some(gutter) = self.gutter_mut().or_warn() {
+            gutter.set_visible(visible);
+        }
+        self.update_gutter().or_warn();
+        self
+    }
+
+    pub fn are_line_numbers_visible(&self) -> bool {
+        self.gutter()
+            .or_warn()
+            .is_some_and(|gutter| gutter.base().is_self_visible())
+    }
+
+    /// Fills the background of the line that contains the cursor. Disabled by default.
+    ///
+    /// The color is specified by the `TextArea::current-line` CSS pseudo-element.
+    pub fn set_current_line_highlighted(&mut self, highlighted: bool) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.set_current_line_highlighted(highlighted);
+        }
+        self.update_gutter().or_warn();
+        self
+    }
+
+    pub fn is_current_line_highlighted(&self) -> bool {
+        self.text_handler()
+            .or_warn()
+            .is_some_and(|handler| handler.is_current_line_highlighted())
+    }
+
+    /// Sets the distance between tab stops, in the number of space characters. The default is 8.
+    pub fn set_tab_width(&mut self, tab_width: u16) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.set_tab_width(tab_width);
+        }
+        self.update_gutter().or_warn();
+        self
+    }
+
+    pub fn tab_width(&self) -> u16 {
+        self.text_handler()
+            .or_warn()
+            .map_or(0, |handler| handler.tab_width())
+    }
+
+    /// Displays spaces as dots and tabs as arrows. Disabled by default.
+    ///
+    /// The color is specified by the `TextArea::whitespace` CSS pseudo-element.
+    pub fn set_whitespace_visible(&mut self, visible: bool) -> &mut Self {
+        if let Some(handler) = self.text_handler_mut().or_warn() {
+            handler.set_whitespace_visible(visible);
+        }
+        self
+    }
+
+    pub fn is_whitespace_visible(&self) -> bool {
+        self.text_handler()
+            .or_warn()
+            .is_some_and(|handler| handler.is_whitespace_visible())
+    }
+
     pub fn set_wrap(&mut self, wrap: Wrap) -> &mut Self {
         if let Some(scroll_area) = self
             .base
@@ -123,6 +835,7 @@ impl TextArea {
         if let Some(handler) = self.text_handler_mut().or_warn() {
             handler.set_wrap(wrap);
         }
+        self.update_gutter().or_warn();
         self
     }
 }
@@ -135,12 +848,27 @@ impl Widget for TextArea {
     }
 
     fn handle_focus_out(&mut self, _event: FocusOutEvent) -> Result<()> {
-        self.text_handler_mut()?.handle_host_focus_out()
+        let handler = self.text_handler_mut()?;
+        handler.handle_host_focus_out()?;
+        if handler.take_edited() {
+            self.on_editing_finished.invoke((), false);
+        }
+        Ok(())
     }
 
     fn handle_style_change(&mut self, _event: StyleChangeEvent) -> Result<()> {
         self.style = self.base.compute_style();
-        Ok(())
+        let text_style: Rc<TextStyle> = self.base.compute_style();
+        self.text_handler_mut()?.set_text_style(text_style.clone());
+        let style = self.style.clone();
+        self.gutter_mut()?.set_styles(text_style, style);
+        self.update_gutter()
+    }
+
+    fn handle_layout(&mut self, _event: LayoutEvent) -> Result<()> {
+        default_layout(self);
+        // Lines may be wrapped differently if the width has changed.
+        self.update_gutter()
     }
 
     fn handle_size_hint_x_request(&mut self, size_y: Option<PhysicalPixels>) -> Result<SizeHint> {
@@ -166,6 +894,26 @@ impl Widget for TextArea {
     }
 
     fn handle_keyboard_input(&mut self, event: KeyboardInputEvent) -> Result<bool> {
+        if self.is_find_bar_enabled && event.info.state.is_pressed() {
+            let shortcuts = standard_shortcuts();
+            if shortcuts.find.matches(&event) {
+                self.show_find_bar();
+                return Ok(true);
+            } else if shortcuts.find_previous.matches(&event) {
+                self.find_previous();
+                return Ok(true);
+            } else if shortcuts.find_next.matches(&event) {
+                self.find_next();
+                return Ok(true);
+            } else if event.info.logical_key == Key::Named(NamedKey::Escape) {
+                if let Ok(find_bar) = self.base.get_child_mut::<FindBar>(KEY_FIND_BAR) {
+                    if find_bar.base().is_self_visible() {
+                        find_bar.close();
+                        return Ok(true);
+                    }
+                }
+            }
+        }
         self.text_handler_mut()?.handle_host_keyboard_input(event)
     }
 
@@ -185,6 +933,9 @@ impl Widget for TextArea {
                 Ok(true)
             }
             accesskit::Action::SetValue => {
+                if self.is_read_only() {
+                    return Ok(false);
+                }
                 let value: String = match event.data {
                     Some(ActionData::Value(value)) => value.into(),
                     Some(ActionData::NumericValue(value)) => value.to_string(),
@@ -215,6 +966,9 @@ pub struct TextAreaStyle {
     pub preferred_width: PhysicalPixels,
     pub min_height: PhysicalPixels,
     pub preferred_height: PhysicalPixels,
+    pub gutter_text_color: Color,
+    pub gutter_background: Option<Color>,
+    pub gutter_padding_x: PhysicalPixels,
 }
 
 impl ComputedElementStyle for TextAreaStyle {
@@ -223,8 +977,8 @@ impl ComputedElementStyle for TextAreaStyle {
             .clone()
             .with_pseudo_class(PseudoClass::Custom("min".into()));
 
-        let properties = style.find_rules(|s| element.matches(s));
-        let font = convert_font(&properties, Some(&style.root_font_style()));
+        let properties = style.find_rules_for_element(element);
+        let font = convert_font(&properties, Some(&style.inherited_font(element)));
         let preferred_width = convert_width(&properties, scale, font.font_size)
             .or_warn()
             .flatten()
@@ -240,7 +994,7 @@ impl ComputedElementStyle for TextAreaStyle {
                 (font.font_size * DEFAULT_PREFERRED_WIDTH_EM).to_physical(scale)
             });
 
-        let min_properties = style.find_rules(|s| element_min.matches(s));
+        let min_properties = style.find_rules_for_element(&element_min);
         let min_width = convert_width(&min_properties, scale, font.font_size)
             .or_warn()
             .flatten()
@@ -256,11 +1010,18 @@ impl ComputedElementStyle for TextAreaStyle {
                 (font.font_size * DEFAULT_MIN_WIDTH_EM).to_physical(scale)
             });
 
+        let gutter_element = element.clone().with_pseudo_element("gutter".into());
+        let gutter_properties = style.find_rules_for_element(&gutter_element);
+
         Self {
             min_width,
             preferred_width,
             min_height,
             preferred_height,
+            gutter_text_color: convert_main_color(&gutter_properties)
+                .unwrap_or_else(defaults::gutter_text_color),
+            gutter_background: convert_background_color(&gutter_properties),
+            gutter_padding_x: convert_padding(&gutter_properties, scale, font.font_size).x(),
         }
     }
 }
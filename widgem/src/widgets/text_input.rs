@@ -1,5 +1,6 @@
 use {
     crate::{
+        callback::{Callback, Callbacks},
         event::{
             AccessibilityActionEvent, FocusInEvent, FocusOutEvent, FocusReason, InputMethodEvent,
             KeyboardInputEvent, LayoutEvent, StyleChangeEvent,
@@ -13,16 +14,20 @@ use {
             Styles,
         },
         system::OrWarn,
-        text::TextHandler,
+        text::{EchoMode, EditHistoryState, TextHandler},
         types::{PhysicalPixels, Point, PpxSuffix, Rect, Size},
+        validator::{InputMask, ValidationState, Validator},
         widget_initializer::{self, WidgetInitializer},
         ScrollToRectRequest, Widget, WidgetBaseOf, WidgetExt, WidgetGeometry,
     },
     accesskit::ActionData,
     anyhow::{bail, Result},
-    std::{cmp::max, fmt::Display, rc::Rc},
+    std::{cmp::max, fmt::Display, ops::Range, rc::Rc},
     tracing::warn,
-    winit::window::CursorIcon,
+    winit::{
+        keyboard::{Key, NamedKey},
+        window::CursorIcon,
+    },
 };
 
 struct Viewport {
@@ -51,11 +56,29 @@ impl Widget for Viewport {
         );
         Ok(SizeHint::new_fixed(size, size))
     }
+
+    fn handle_baseline_request(&mut self, _size: Size) -> Result<Option<PhysicalPixels>> {
+        let text_widget = self.base.get_child_mut::<TextHandler>(0)?;
+        // The text widget always has its preferred size (see `TextInput::adjust_scroll`).
+        let size_x = text_widget.size_hint_x(None).preferred();
+        let size_y = text_widget.size_hint_y(size_x).preferred();
+        let offset_y = text_widget
+            .base()
+            .rect_in_parent()
+            .map_or(0.ppx(), |rect| rect.top());
+        Ok(text_widget
+            .baseline(Size::new(size_x, size_y))
+            .map(|baseline| offset_y + baseline))
+    }
 }
 
 pub struct TextInput {
     base: WidgetBaseOf<Self>,
     style: Rc<TextInputStyle>,
+    on_return_pressed: Callbacks<()>,
+    on_editing_finished: Callbacks<()>,
+    // Set when the user presses Enter or the input loses focus. Reset when the text changes.
+    is_editing_finished: bool,
 }
 
 impl TextInput {
@@ -64,6 +87,26 @@ impl TextInput {
         base.set_cursor_icon(CursorIcon::Text);
         let host_id = base.id();
         let text_style = base.compute_style();
+        let validation_state_changed = base
+            .callback(|this: &mut Self, _: ValidationState| {
+                this.update_invalid_pseudo_class();
+                Ok(())
+            })
+            .with_send_signals_on_setter_calls(true);
+        let text_changed = base
+            .callback(|this: &mut Self, _: String| {
+                this.is_editing_finished = false;
+                this.update_invalid_pseudo_class();
+                Ok(())
+            })
+            .with_send_signals_on_setter_calls(true);
+        let placeholder_shown_changed = base
+            .callback(|this: &mut Self, is_shown: bool| {
+                this.base
+                    .set_pseudo_class(PseudoClass::PlaceholderShown, is_shown);
+                Ok(())
+            })
+            .with_send_signals_on_setter_calls(true);
         let viewport = base.set_child(0, Viewport::init())?;
         viewport.base_mut().set_receives_all_mouse_events(true);
         viewport.base_mut().set_cursor_icon(CursorIcon::Text);
@@ -73,11 +116,17 @@ impl TextInput {
             .set_child(0, TextHandler::init(String::new(), text_style))?
             .set_multiline(false)
             .set_editable(true)
-            .set_host_id(host_id.into());
+            .set_host_id(host_id.into())
+            .on_validation_state_changed(validation_state_changed)
+            .on_text_changed(text_changed)
+            .on_placeholder_shown_changed(placeholder_shown_changed);
         editor.base_mut().set_receives_all_mouse_events(true);
         Ok(TextInput {
             style: base.compute_style(),
             base,
+            on_return_pressed: Callbacks::default(),
+            on_editing_finished: Callbacks::default(),
+            is_editing_finished: false,
         })
     }
 
@@ -104,10 +153,213 @@ impl TextInput {
             .unwrap()
     }
 
+    /// Replaces the text. The undo history is cleared.
     pub fn set_text(&mut self, text: impl Display) {
         self.text_widget_mut().set_text(text);
     }
 
+    pub fn text(&self) -> String {
+        self.text_widget().text()
+    }
+
+    /// Adds a callback that is called when the text changes.
+    pub fn on_text_changed(&mut self, callback: Callback<String>) -> &mut Self {
+        self.text_widget_mut().on_text_changed(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the cursor moves.
+    ///
+    /// The callback receives the byte offset of the cursor in the text.
+    pub fn on_cursor_position_changed(&mut self, callback: Callback<usize>) -> &mut Self {
+        self.text_widget_mut().on_cursor_position_changed(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the selection changes.
+    ///
+    /// The callback receives the byte range of the selected text or `None` if
+    /// nothing is selected.
+    pub fn on_selection_changed(&mut self, callback: Callback<Option<Range<usize>>>) -> &mut Self {
+        self.text_widget_mut().on_selection_changed(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the user presses Enter.
+    pub fn on_return_pressed(&mut self, callback: Callback<()>) -> &mut Self {
+        self.on_return_pressed.add(callback);
+        self
+    }
+
+    /// Adds a callback that is called when the user presses Enter or
+    /// the input loses focus after editing the text.
+    ///
+    /// The callback is called once per editing session: it's not called if the user
+    /// hasn't modified the text since the last time it was called or since the text was
+    /// replaced with [set_text](Self::set_text). In particular, pressing Enter without
+    /// modifying the text only triggers [on_return_pressed](Self::on_return_pressed).
+    pub fn on_editing_finished(&mut self, callback: Callback<()>) -> &mut Self {
+        self.on_editing_finished.add(callback);
+        self
+    }
+
+    fn finish_editing(&mut self) {
+        self.is_editing_finished = true;
+        self.update_invalid_pseudo_class();
+        if self.text_widget_mut().take_edited() {
+            self.on_editing_finished.invoke((), false);
+        }
+    }
+
+    // Intermediate text is not styled as an error while the user is still typing it.
+    fn update_invalid_pseudo_class(&mut self) {
+        let is_invalid = match self.validation_state() {
+            ValidationState::Invalid => true,
+            ValidationState::Intermediate => self.is_editing_finished,
+            ValidationState::Acceptable => false,
+        };
+        self.base.set_pseudo_class(PseudoClass::Invalid, is_invalid);
+    }
+
+    /// Prevents the user from modifying the text.
+    ///
+    /// The text can still be focused, selected, copied and navigated with the keyboard.
+    /// The input has the `:read-only` pseudo-class in this mode.
+    pub fn set_read_only(&mut self, read_only: bool) -> &mut Self {
+        self.text_widget_mut().set_read_only(read_only);
+        self.base.set_pseudo_class(PseudoClass::ReadOnly, read_only);
+        self
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.text_widget().is_read_only()
+    }
+
+    /// Shows or hides the text cursor in [read-only](Self::set_read_only) mode.
+    /// The cursor is visible by default.
+    pub fn set_cursor_visible_when_read_only(&mut self, visible: bool) -> &mut Self {
+        self.text_widget_mut()
+            .set_cursor_visible_when_read_only(visible);
+        self
+    }
+
+    /// Sets how the text is displayed.
+    ///
+    /// In [password](EchoMode::Password) mode, each character is displayed as a bullet,
+    /// the text can't be copied and the undo history is disabled.
+    pub fn set_echo_mode(&mut self, echo_mode: EchoMode) -> &mut Self {
+        self.text_widget_mut().set_echo_mode(echo_mode);
+        self
+    }
+
+    pub fn echo_mode(&self) -> EchoMode {
+        self.text_widget().echo_mode()
+    }
+
+    /// Displays the actual text in [password](EchoMode::Password) mode.
+    ///
+    /// Use it to implement a "show password" toggle.
+    pub fn set_password_revealed(&mut self, revealed: bool) -> &mut Self {
+        self.text_widget_mut().set_password_revealed(revealed);
+        self
+    }
+
+    pub fn is_password_revealed(&self) -> bool {
+        self.text_widget().is_password_revealed()
+    }
+
+    /// Sets the text displayed while the input is empty and not focused.
+    ///
+    /// The placeholder is drawn with the color specified by the `::placeholder` style rule.
+    /// The input has the `:placeholder-shown` pseudo-class while the placeholder is displayed.
+    pub fn set_placeholder(&mut self, placeholder: impl Display) -> &mut Self {
+        self.text_widget_mut().set_placeholder(placeholder);
+        self
+    }
+
+    pub fn placeholder(&self) -> &str {
+        self.text_widget().placeholder()
+    }
+
+    /// Sets the validator that checks the text entered by the user.
+    ///
+    /// Edits that would make the text [invalid](ValidationState::Invalid) are rejected.
+    /// The input has the `:invalid` pseudo-class while the text is
+    /// [invalid](ValidationState::Invalid). [Intermediate](ValidationState::Intermediate) text
+    /// only gets the `:invalid` pseudo-class after the user presses Enter or the input
+    /// loses focus, and loses it as soon as the text changes.
+    pub fn set_validator(&mut self, validator: Option<Rc<dyn Validator>>) -> &mut Self {
+        self.text_widget_mut().set_validator(validator);
+        self
+    }
+
+    pub fn validator(&self) -> Option<&Rc<dyn Validator>> {
+        self.text_widget().validator()
+    }
+
+    /// Sets the format of the text entered by the user, e.g. `"99/99/9999"`.
+    ///
+    /// See [InputMask] for the syntax.
+    pub fn set_input_mask(&mut self, mask: Option<InputMask>) -> &mut Self {
+        self.text_widget_mut().set_input_mask(mask);
+        self
+    }
+
+    pub fn input_mask(&self) -> Option<&InputMask> {
+        self.text_widget().input_mask()
+    }
+
+    /// Limits the number of characters the user can enter.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) -> &mut Self {
+        self.text_widget_mut().set_max_length(max_length);
+        self
+    }
+
+    pub fn max_length(&self) -> Option<usize> {
+        self.text_widget().max_length()
+    }
+
+    /// Returns the result of validating the current text
+    /// with the validator and the input mask.
+    pub fn validation_state(&self) -> ValidationState {
+        self.text_widget().validation_state()
+    }
+
+    /// Adds a callback that is called when the [validation state](Self::validation_state) changes.
+    pub fn on_validation_state_changed(
+        &mut self,
+        callback: Callback<ValidationState>,
+    ) -> &mut Self {
+        self.text_widget_mut().on_validation_state_changed(callback);
+        self
+    }
+
+    /// Reverts the last edit of the text.
+    pub fn undo(&mut self) -> &mut Self {
+        self.text_widget_mut().undo();
+        self
+    }
+
+    /// Repeats the last edit reverted by [undo](Self::undo).
+    pub fn redo(&mut self) -> &mut Self {
+        self.text_widget_mut().redo();
+        self
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.text_widget().can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.text_widget().can_redo()
+    }
+
+    /// Adds a callback that is called when availability of undo or redo changes.
+    pub fn on_history_changed(&mut self, callback: Callback<EditHistoryState>) -> &mut Self {
+        self.text_widget_mut().on_history_changed(callback);
+        self
+    }
+
     fn adjust_scroll(&mut self) {
         let Some(editor_viewport_rect) =
             self.base.get_dyn_child(0).unwrap().base().rect_in_parent()
@@ -180,7 +432,9 @@ impl Widget for TextInput {
     }
 
     fn handle_focus_out(&mut self, _event: FocusOutEvent) -> Result<()> {
-        self.text_widget_mut().handle_host_focus_out()
+        self.text_widget_mut().handle_host_focus_out()?;
+        self.finish_editing();
+        Ok(())
     }
 
     fn handle_layout(&mut self, _event: LayoutEvent) -> Result<()> {
@@ -202,6 +456,13 @@ impl Widget for TextInput {
     }
 
     fn handle_keyboard_input(&mut self, event: KeyboardInputEvent) -> Result<bool> {
+        if event.info.state.is_pressed()
+            && !event.info.repeat
+            && event.info.logical_key == Key::Named(NamedKey::Enter)
+        {
+            self.on_return_pressed.invoke((), false);
+            self.finish_editing();
+        }
         self.text_widget_mut().handle_host_keyboard_input(event)
     }
 
@@ -232,6 +493,9 @@ impl Widget for TextInput {
                 Ok(true)
             }
             accesskit::Action::SetValue => {
+                if self.is_read_only() {
+                    return Ok(false);
+                }
                 let value: String = match event.data {
                     Some(ActionData::Value(value)) => value.into(),
                     Some(ActionData::NumericValue(value)) => value.to_string(),
@@ -271,8 +535,8 @@ impl ComputedElementStyle for TextInputStyle {
             .clone()
             .with_pseudo_class(PseudoClass::Custom("min".into()));
 
-        let properties = style.find_rules(|s| element.matches(s));
-        let font = convert_font(&properties, Some(&style.root_font_style()));
+        let properties = style.find_rules_for_element(element);
+        let font = convert_font(&properties, Some(&style.inherited_font(element)));
         let preferred_width = convert_width(&properties, scale, font.font_size)
             .or_warn()
             .flatten()
@@ -281,7 +545,7 @@ impl ComputedElementStyle for TextInputStyle {
                 (font.font_size * DEFAULT_PREFERRED_WIDTH_EM).to_physical(scale)
             });
 
-        let min_properties = style.find_rules(|s| element_min.matches(s));
+        let min_properties = style.find_rules_for_element(&element_min);
         let min_width = convert_width(&min_properties, scale, font.font_size)
             .or_warn()
             .flatten()
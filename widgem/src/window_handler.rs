@@ -270,6 +270,9 @@ impl<'a> WindowHandler<'a> {
                 }
                 //self.inner.set_ime_position(PhysicalPosition::new(10, 10));
             }
+            WindowEvent::ThemeChanged(theme) => {
+                self.root_widget.base().app().system_theme_changed(theme);
+            }
             WindowEvent::Focused(is_focused) => {
                 trace!("window focus {:?} {}", self.window.id(), is_focused);
                 if self.window.focus_changed(is_focused) {
@@ -516,9 +519,24 @@ impl<'a> WindowHandler<'a> {
             warn!("cannot dispatch accessibility request to virtual root: {request:?}");
             return;
         }
-        if let Ok(widget) = get_widget_by_id_mut(self.root_widget, request.target.into()) {
+        // Nodes that don't belong to a widget are handled by the closest ancestor widget.
+        let mut widget_node_id = request.target;
+        while self
+            .root_widget
+            .base()
+            .app()
+            .address(widget_node_id.into())
+            .is_none()
+        {
+            let Some(parent) = self.window.accessibility_node_parent(widget_node_id) else {
+                break;
+            };
+            widget_node_id = parent;
+        }
+        if let Ok(widget) = get_widget_by_id_mut(self.root_widget, widget_node_id.into()) {
             let accepted = widget.dispatch(
                 AccessibilityActionEvent {
+                    target: request.target,
                     action: request.action,
                     data: request.data.clone(),
                 }